@@ -0,0 +1,93 @@
+//! Opaque page cursors for `/v1/trades`.
+//!
+//! A cursor names a page boundary ([`PageCursor`]) together with the sort
+//! order it was issued for, encoded as URL-safe base64 so clients treat it
+//! as a token rather than something to construct.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hl_indexer::pagination::{FillKey, PageCursor, SortOrder};
+
+use crate::error::ApiError;
+
+/// Version prefix, so the format can change without misreading old cursors.
+const VERSION: &str = "v1";
+
+/// Encode a page boundary for `order`.
+pub(crate) fn encode(order: SortOrder, cursor: PageCursor) -> String {
+    let (side, key) = match cursor {
+        PageCursor::After(key) => ("after", key),
+        PageCursor::Before(key) => ("before", key),
+    };
+    let raw = format!(
+        "{}:{}:{}:{}:{}",
+        VERSION,
+        order.as_str(),
+        side,
+        key.timestamp_ms,
+        key.trade_id
+    );
+    URL_SAFE_NO_PAD.encode(raw)
+}
+
+/// Decode a cursor produced by [`encode`].
+pub(crate) fn decode(cursor: &str) -> Result<(SortOrder, PageCursor), ApiError> {
+    parse(cursor).ok_or_else(|| ApiError::BadRequest("invalid cursor".to_string()))
+}
+
+fn parse(cursor: &str) -> Option<(SortOrder, PageCursor)> {
+    let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+    let mut parts = raw.split(':');
+    if parts.next()? != VERSION {
+        return None;
+    }
+    let order = SortOrder::parse(parts.next()?)?;
+    let side = parts.next()?;
+    let key = FillKey {
+        timestamp_ms: parts.next()?.parse().ok()?,
+        trade_id: parts.next()?.parse().ok()?,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    let cursor = match side {
+        "after" => PageCursor::After(key),
+        "before" => PageCursor::Before(key),
+        _ => return None,
+    };
+    Some((order, cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let key = FillKey {
+            timestamp_ms: 1768576560527,
+            trade_id: 461128571856302,
+        };
+        for order in [SortOrder::Asc, SortOrder::Desc] {
+            for cursor in [PageCursor::After(key), PageCursor::Before(key)] {
+                let encoded = encode(order, cursor);
+                assert!(encoded
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_".contains(c)));
+                assert_eq!(decode(&encoded).unwrap(), (order, cursor));
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_garbage() {
+        for cursor in [
+            "",
+            "not base64!",
+            "djE6YXNj",
+            &URL_SAFE_NO_PAD.encode("v2:asc:after:1:2"),
+        ] {
+            assert!(matches!(decode(cursor), Err(ApiError::BadRequest(_))));
+        }
+    }
+}
@@ -7,6 +7,7 @@ use axum::{
 };
 use serde::Serialize;
 use thiserror::Error;
+use utoipa::ToSchema;
 
 /// API errors that can be returned to clients.
 #[derive(Debug, Error)]
@@ -19,6 +20,10 @@ pub enum ApiError {
     #[error("not found: {0}")]
     NotFound(String),
 
+    /// The server is at capacity for this kind of request.
+    #[error("service unavailable: {0}")]
+    Unavailable(String),
+
     /// Internal server error.
     #[error("internal error: {0}")]
     Internal(String),
@@ -29,9 +34,12 @@ pub enum ApiError {
 }
 
 /// Error response body.
-#[derive(Debug, Serialize)]
-struct ErrorResponse {
+#[derive(Debug, Serialize, ToSchema)]
+pub(crate) struct ErrorResponse {
+    /// Error code: `bad_request`, `not_found`, `unavailable`,
+    /// `upstream_unavailable`, `indexer_error` or `internal_error`.
     error: String,
+    /// Human-readable explanation, if any.
     #[serde(skip_serializing_if = "Option::is_none")]
     details: Option<String>,
 }
@@ -41,10 +49,27 @@ impl IntoResponse for ApiError {
         let (status, error, details) = match &self {
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "bad_request", Some(msg.clone())),
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, "not_found", Some(msg.clone())),
+            ApiError::Unavailable(msg) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "unavailable",
+                Some(msg.clone()),
+            ),
             ApiError::Internal(msg) => {
                 tracing::error!("Internal error: {}", msg);
                 (StatusCode::INTERNAL_SERVER_ERROR, "internal_error", None)
             }
+            ApiError::Indexer(
+                e @ (hl_indexer::IndexerError::InvalidFilter(_)
+                | hl_indexer::IndexerError::InvalidPage(_)),
+            ) => (StatusCode::BAD_REQUEST, "bad_request", Some(e.to_string())),
+            ApiError::Indexer(e) if e.is_upstream_failure() => {
+                tracing::warn!("Upstream unavailable: {}", e);
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "upstream_unavailable",
+                    Some(e.to_string()),
+                )
+            }
             ApiError::Indexer(e) => {
                 tracing::error!("Indexer error: {}", e);
                 (
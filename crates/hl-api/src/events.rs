@@ -0,0 +1,119 @@
+//! Internal bus of live updates for the streaming endpoints.
+//!
+//! Producers publish [`Event`]s as data changes: the fill forwarder for fills
+//! the WebSocket collector receives and the PnL they lead to, the leaderboard
+//! refresher for every snapshot that ranks differently than the one before.
+//! Each streaming client holds its own subscription and picks out the events
+//! it asked for.
+
+use hl_indexer::leaderboard::LeaderboardConfig;
+use hl_indexer::snapshot::LeaderboardSnapshot;
+use hl_indexer::{convert_fills, PnLSummary, UserFill};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::task::JoinHandle;
+
+use crate::state::AppState;
+
+/// Events a slow subscriber may fall behind by before it skips the oldest.
+const BUS_CAPACITY: usize = 1024;
+
+/// A change published on the bus. Users are lowercase.
+#[derive(Debug)]
+pub(crate) enum Event {
+    /// New fills of a user, oldest first.
+    Fills { user: String, fills: Vec<UserFill> },
+    /// A user's all-time PnL after new fills.
+    PnL { user: String, summary: PnLSummary },
+    /// A leaderboard snapshot whose entries differ from the previous one.
+    Leaderboard {
+        config: LeaderboardConfig,
+        snapshot: Arc<LeaderboardSnapshot>,
+    },
+}
+
+/// Broadcast channel carrying [`Event`]s to every subscriber.
+pub(crate) struct EventBus {
+    sender: broadcast::Sender<Arc<Event>>,
+}
+
+impl EventBus {
+    pub(crate) fn new() -> Self {
+        Self {
+            sender: broadcast::channel(BUS_CAPACITY).0,
+        }
+    }
+
+    /// Send an event to all current subscribers.
+    pub(crate) fn publish(&self, event: Event) {
+        // No subscribers just means nobody is streaming right now
+        let _ = self.sender.send(Arc::new(event));
+    }
+
+    /// Receive every event published from now on.
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<Arc<Event>> {
+        self.sender.subscribe()
+    }
+}
+
+/// Spawn the task that publishes fills received by the WebSocket collector,
+/// and the PnL they result in, on the event bus.
+///
+/// In API mode the collector receives nothing and the task stays idle.
+pub fn spawn_fill_forwarder(state: Arc<AppState>) -> JoinHandle<()> {
+    // Subscribe now so fills collected before the task first runs are kept
+    let mut new_fills = state.indexer.subscribe_new_fills();
+    tokio::spawn(async move {
+        loop {
+            let collected = match new_fills.recv().await {
+                Ok(collected) => collected,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Fill forwarder skipped {} batches of fills", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+
+            let user = collected.user.to_lowercase();
+            let mut fills = convert_fills(&collected.fills);
+            fills.sort_by_key(|f| (f.timestamp_ms, f.trade_id));
+            state.events.publish(Event::Fills {
+                user: user.clone(),
+                fills,
+            });
+
+            match state.indexer.get_user_pnl(&user, None, None, None).await {
+                Ok(summary) => state.events.publish(Event::PnL { user, summary }),
+                Err(e) => tracing::warn!("Failed to update PnL of {}: {}", user, e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_subscribers_see_events_published_after_subscribing() {
+        let bus = EventBus::new();
+        bus.publish(Event::Fills {
+            user: "0xa".to_string(),
+            fills: Vec::new(),
+        });
+
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        bus.publish(Event::Fills {
+            user: "0xb".to_string(),
+            fills: Vec::new(),
+        });
+
+        for receiver in [&mut first, &mut second] {
+            let event = receiver.recv().await.unwrap();
+            assert!(matches!(&*event, Event::Fills { user, .. } if user == "0xb"));
+            assert!(receiver.try_recv().is_err());
+        }
+    }
+}
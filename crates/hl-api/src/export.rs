@@ -0,0 +1,671 @@
+//! Bulk exports as CSV or newline-delimited JSON.
+//!
+//! - `GET /v1/export/trades` - every trade matching the `/v1/trades` filters
+//! - `GET /v1/export/pnl` - the per-asset PnL breakdown
+//! - `GET /v1/export/roundtrips` - positions from open to flat
+//! - `GET /v1/export/leaderboard` - the leaderboard's entries
+//!
+//! The format is chosen by `format=csv|ndjson`, then by the `Accept` header
+//! (`text/csv` or `application/x-ndjson`, weighed by their `q` values), and
+//! defaults to CSV. Each export
+//! has fixed columns, given by [`ExportRow::COLUMNS`]: the CSV header and the
+//! keys of every NDJSON record, in that order. Decimals are written exactly
+//! as computed, as strings.
+//!
+//! User exports fetch fills a page of [`CHUNK_ROWS`] at a time and write
+//! each page's rows before fetching the next, so an export of hundreds of
+//! thousands of fills is never held in memory: trades are written as they
+//! are fetched, round-trips as they settle, and the PnL breakdown is summed
+//! page by page. Builder-only trades are the exception, since builder
+//! attribution needs the whole window at once.
+//!
+//! Unlike `/v1/trades` and `/v1/pnl`, user exports do not fall back to
+//! previously fetched data. If the upstream API fails before the first page
+//! they return `503`; if it fails later the body ends early.
+
+use axum::body::Body;
+use axum::extract::{Query, State};
+use axum::http::header::{ACCEPT, CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Response};
+use futures::stream::{self, BoxStream};
+use futures::{StreamExt, TryStreamExt};
+use hl_indexer::filter::TradeFilter;
+use hl_indexer::pagination::{PageRequest, SortOrder};
+use hl_indexer::roundtrip::RoundTripTracker;
+use hl_indexer::IndexerError;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::error::{ApiError, ErrorResponse};
+use crate::handlers::{
+    leaderboard_config, leaderboard_response, page_position, served_snapshot, trade_filter,
+    validate_user,
+};
+use crate::state::AppState;
+use crate::types::{
+    AssetPnLResponse, ExportQuery, LeaderboardExportRow, LeaderboardQuery, PnLQuery,
+    RoundTripResponse, TradeResponse, TradesQuery,
+};
+use hl_types::{Asset, AssetPnL, UserFill};
+
+/// Fills fetched per page, and rows rendered per chunk of the response body.
+const CHUNK_ROWS: usize = 1000;
+
+/// Batches of rows making up an export.
+type Rows<R> = BoxStream<'static, Result<Vec<R>, IndexerError>>;
+
+/// Output format of an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    /// The format asked for by `format`, else by the `Accept` header.
+    fn negotiate(query: &ExportQuery, headers: &HeaderMap) -> Result<Self, ApiError> {
+        if let Some(format) = query.format.as_deref() {
+            return match format.to_lowercase().as_str() {
+                "csv" => Ok(ExportFormat::Csv),
+                "ndjson" => Ok(ExportFormat::Ndjson),
+                _ => Err(ApiError::BadRequest(format!(
+                    "invalid format '{}': must be 'csv' or 'ndjson'",
+                    format
+                ))),
+            };
+        }
+
+        let Some(accept) = headers.get(ACCEPT).and_then(|value| value.to_str().ok()) else {
+            return Ok(ExportFormat::Csv);
+        };
+        // Highest weight wins, then the range listed first, then CSV
+        let csv = ExportFormat::Csv.weight(accept);
+        let ndjson = ExportFormat::Ndjson.weight(accept);
+        let prefers_ndjson = match (csv, ndjson) {
+            (_, None) => false,
+            (None, Some((q, _))) => q > 0.0,
+            (Some((csv_q, csv_at)), Some((q, at))) => q > csv_q || (q == csv_q && at < csv_at),
+        };
+        Ok(if prefers_ndjson {
+            ExportFormat::Ndjson
+        } else {
+            ExportFormat::Csv
+        })
+    }
+
+    /// The `q` value the `Accept` header gives this format and the index of
+    /// the media range it comes from, or `None` if no range matches.
+    ///
+    /// The most specific matching range counts: an exact media type before
+    /// `text/*` or `application/*`, before `*/*`.
+    fn weight(self, accept: &str) -> Option<(f32, usize)> {
+        let (media_types, wildcard) = match self {
+            ExportFormat::Csv => (&["text/csv"][..], "text/*"),
+            ExportFormat::Ndjson => (
+                &["application/x-ndjson", "application/ndjson"][..],
+                "application/*",
+            ),
+        };
+
+        let mut best: Option<(u8, f32, usize)> = None;
+        for (index, range) in accept.split(',').enumerate() {
+            let mut params = range.split(';');
+            let media_range = params.next().unwrap_or_default().trim().to_lowercase();
+            let specificity = if media_types.contains(&media_range.as_str()) {
+                2
+            } else if media_range == wildcard {
+                1
+            } else if media_range == "*/*" {
+                0
+            } else {
+                continue;
+            };
+            let q = params
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if best.is_none_or(|(most_specific, _, _)| specificity > most_specific) {
+                best = Some((specificity, q, index));
+            }
+        }
+        best.map(|(_, q, index)| (q, index))
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+
+    /// Render rows without a header.
+    fn render<R: Serialize>(self, rows: &[R]) -> Vec<u8> {
+        match self {
+            ExportFormat::Csv => {
+                let mut writer = csv_writer();
+                for row in rows {
+                    writer.serialize(row).expect("export rows are flat");
+                }
+                writer.into_inner().expect("writing to memory cannot fail")
+            }
+            ExportFormat::Ndjson => {
+                let mut out = Vec::new();
+                for row in rows {
+                    serde_json::to_writer(&mut out, row).expect("export rows serialize to JSON");
+                    out.push(b'\n');
+                }
+                out
+            }
+        }
+    }
+}
+
+fn csv_writer() -> csv::Writer<Vec<u8>> {
+    csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new())
+}
+
+/// A flat record of an export.
+pub(crate) trait ExportRow: Serialize + Send + 'static {
+    /// Field names in serialization order.
+    const COLUMNS: &'static [&'static str];
+}
+
+impl ExportRow for TradeResponse {
+    const COLUMNS: &'static [&'static str] = &[
+        "asset",
+        "timestamp_ms",
+        "price",
+        "size",
+        "side",
+        "fee",
+        "closed_pnl",
+        "trade_id",
+        "order_id",
+        "crossed",
+        "direction",
+    ];
+}
+
+impl ExportRow for AssetPnLResponse {
+    const COLUMNS: &'static [&'static str] = &[
+        "asset",
+        "realized_pnl",
+        "fees",
+        "net_pnl",
+        "fill_count",
+        "volume",
+    ];
+}
+
+impl ExportRow for RoundTripResponse {
+    const COLUMNS: &'static [&'static str] = &[
+        "asset",
+        "direction",
+        "opened_at_ms",
+        "closed_at_ms",
+        "fill_count",
+        "max_size",
+        "volume",
+        "fees",
+        "realized_pnl",
+        "net_pnl",
+    ];
+}
+
+impl ExportRow for LeaderboardExportRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "rank",
+        "user",
+        "metricValue",
+        "volume",
+        "realizedPnl",
+        "returnPct",
+        "tradeCount",
+        "builderFillCount",
+        "tainted",
+        "startingEquity",
+        "netDeposits",
+        "flaggedVolume",
+        "flaggedFillCount",
+        "previousRank",
+        "rankChange24h",
+    ];
+}
+
+/// GET /v1/export/trades - Export a user's trades.
+///
+/// Takes the `/v1/trades` parameters except `limit` and `cursor`: the export
+/// holds every matching trade, oldest first unless `order=desc`.
+#[utoipa::path(
+    get,
+    path = "/v1/export/trades",
+    summary = "Export a user's trades",
+    tag = "export",
+    params(ExportQuery, TradesQuery),
+    responses(
+        (
+            status = 200,
+            description = "Trades, one per row",
+            content((String = "text/csv"), (TradeResponse = "application/x-ndjson")),
+        ),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 503, description = "Upstream API unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn trades(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(export): Query<ExportQuery>,
+    Query(query): Query<TradesQuery>,
+) -> Result<Response, ApiError> {
+    let format = ExportFormat::negotiate(&export, &headers)?;
+    validate_user(&query.user)?;
+    if query.limit.is_some() || query.cursor.is_some() {
+        return Err(ApiError::BadRequest(
+            "exports are not paged: remove limit and cursor".to_string(),
+        ));
+    }
+    let order = match query.order {
+        Some(_) => page_position(&query)?.0,
+        None => SortOrder::Asc,
+    };
+    let filter = trade_filter(&query)?;
+
+    let fills = fill_batches(
+        state,
+        &query.user,
+        query.from_ms,
+        query.to_ms,
+        filter,
+        order,
+    );
+    let rows = started(fills)
+        .await?
+        .map_ok(|fills| fills.into_iter().map(TradeResponse::from).collect())
+        .boxed();
+
+    Ok(export_response("trades", format, rows))
+}
+
+/// GET /v1/export/pnl - Export a user's per-asset PnL.
+#[utoipa::path(
+    get,
+    path = "/v1/export/pnl",
+    summary = "Export a user's PnL by asset",
+    tag = "export",
+    params(ExportQuery, PnLQuery),
+    responses(
+        (
+            status = 200,
+            description = "One row per asset, by symbol",
+            content((String = "text/csv"), (AssetPnLResponse = "application/x-ndjson")),
+        ),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 503, description = "Upstream API unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn pnl(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(export): Query<ExportQuery>,
+    Query(query): Query<PnLQuery>,
+) -> Result<Response, ApiError> {
+    let format = ExportFormat::negotiate(&export, &headers)?;
+    validate_user(&query.user)?;
+    let filter =
+        TradeFilter::new().with_assets(parse_assets(query.assets.as_deref()).into_iter().flatten());
+
+    let mut fills = fill_batches(
+        state,
+        &query.user,
+        query.from_ms,
+        query.to_ms,
+        filter,
+        SortOrder::Asc,
+    );
+    let mut by_asset: HashMap<Asset, AssetPnL> = HashMap::new();
+    while let Some(batch) = fills.try_next().await? {
+        for fill in &batch {
+            by_asset
+                .entry(fill.asset.clone())
+                .or_insert_with(|| AssetPnL::new(fill.asset.clone()))
+                .add_fill(fill);
+        }
+    }
+    let mut rows: Vec<AssetPnLResponse> = by_asset.values().map(Into::into).collect();
+    rows.sort_by(|a, b| a.asset.cmp(&b.asset));
+
+    Ok(export_response("pnl", format, in_memory(rows)))
+}
+
+/// GET /v1/export/roundtrips - Export a user's round-trips.
+///
+/// A round-trip is a position in one asset from the fill that opened it to
+/// the fill that brought it back to flat; a fill that reverses the position
+/// closes one and opens the next. Positions are taken to be flat at
+/// `from_ms`, and ones still open are exported with an empty `closed_at_ms`.
+#[utoipa::path(
+    get,
+    path = "/v1/export/roundtrips",
+    summary = "Export a user's round-trips",
+    tag = "export",
+    params(ExportQuery, PnLQuery),
+    responses(
+        (
+            status = 200,
+            description = "Round-trips by opening time",
+            content((String = "text/csv"), (RoundTripResponse = "application/x-ndjson")),
+        ),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 503, description = "Upstream API unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn round_trips(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(export): Query<ExportQuery>,
+    Query(query): Query<PnLQuery>,
+) -> Result<Response, ApiError> {
+    let format = ExportFormat::negotiate(&export, &headers)?;
+    validate_user(&query.user)?;
+    let filter =
+        TradeFilter::new().with_assets(parse_assets(query.assets.as_deref()).into_iter().flatten());
+
+    let fills = fill_batches(
+        state,
+        &query.user,
+        query.from_ms,
+        query.to_ms,
+        filter,
+        SortOrder::Asc,
+    );
+    let fills = started(fills).await?;
+    // Round-trips settled by each page, then the rest once fills run out
+    let trips = stream::try_unfold(
+        (fills, Some(RoundTripTracker::new())),
+        |(mut fills, tracker)| async move {
+            let Some(mut tracker) = tracker else {
+                return Ok(None);
+            };
+            Ok(Some(match fills.try_next().await? {
+                Some(batch) => {
+                    for fill in &batch {
+                        tracker.add_fill(fill);
+                    }
+                    (tracker.take_settled(), (fills, Some(tracker)))
+                }
+                None => (tracker.finish(), (fills, None)),
+            }))
+        },
+    );
+    let rows = trips
+        .map_ok(|trips| trips.into_iter().map(RoundTripResponse::from).collect())
+        .boxed();
+
+    Ok(export_response("roundtrips", format, rows))
+}
+
+/// GET /v1/export/leaderboard - Export the competition leaderboard.
+///
+/// Takes the `/v1/leaderboard` parameters and exports the same entries.
+#[utoipa::path(
+    get,
+    path = "/v1/export/leaderboard",
+    summary = "Export the competition leaderboard",
+    tag = "export",
+    params(ExportQuery, LeaderboardQuery),
+    responses(
+        (
+            status = 200,
+            description = "Leaderboard entries by rank",
+            content((String = "text/csv"), (LeaderboardExportRow = "application/x-ndjson")),
+        ),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "No snapshot retained for `asOfMs`", body = ErrorResponse),
+        (status = 503, description = "Upstream API down and nothing cached, or too many leaderboards tracked", body = ErrorResponse),
+    )
+)]
+pub async fn leaderboard(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(export): Query<ExportQuery>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Response, ApiError> {
+    let format = ExportFormat::negotiate(&export, &headers)?;
+    let config = leaderboard_config(&state, &query)?;
+    let (snapshot, stale) = served_snapshot(&state, &config, query.as_of_ms).await?;
+    let response = leaderboard_response(&state, &config, &query, &snapshot, stale).await;
+
+    let rows = response
+        .entries
+        .into_iter()
+        .map(LeaderboardExportRow::from)
+        .collect();
+
+    Ok(export_response("leaderboard", format, in_memory(rows)))
+}
+
+/// Parse a comma-separated asset list.
+fn parse_assets(assets: Option<&str>) -> Option<Vec<Asset>> {
+    assets.map(|s| {
+        s.split(',')
+            .map(|sym| Asset::from_symbol(sym.trim()))
+            .collect()
+    })
+}
+
+/// Fetch a user's fills matching `filter`, a page of [`CHUNK_ROWS`] at a
+/// time, in `order`.
+///
+/// Builder-only fills come as one batch, since they are attributed over the
+/// whole window whatever the page.
+fn fill_batches(
+    state: Arc<AppState>,
+    user: &str,
+    from_ms: Option<i64>,
+    to_ms: Option<i64>,
+    filter: TradeFilter,
+    order: SortOrder,
+) -> Rows<UserFill> {
+    let limit = if filter.builder_only {
+        usize::MAX
+    } else {
+        CHUNK_ROWS
+    };
+    let user = user.to_string();
+    stream::try_unfold(Some(PageRequest::first(order, limit)), move |request| {
+        let (state, user, filter) = (state.clone(), user.clone(), filter.clone());
+        async move {
+            let Some(request) = request else {
+                return Ok(None);
+            };
+            let page = state
+                .indexer
+                .get_user_fills_page(&user, from_ms, to_ms, &filter, &request)
+                .await?;
+            let next = page.next_cursor().map(|cursor| PageRequest {
+                cursor: Some(cursor),
+                ..request
+            });
+            Ok(Some((page.fills, next)))
+        }
+    })
+    .boxed()
+}
+
+/// Fetch the first batch of `rows` before answering, so an upstream
+/// failure is still a `503` rather than an empty export.
+async fn started<R: Send + 'static>(mut rows: Rows<R>) -> Result<Rows<R>, ApiError> {
+    let first = rows.try_next().await?;
+    Ok(stream::iter(first.map(Ok)).chain(rows).boxed())
+}
+
+/// Rows already computed, as a single batch.
+fn in_memory<R: Send + 'static>(rows: Vec<R>) -> Rows<R> {
+    stream::iter([Ok(rows)]).boxed()
+}
+
+/// Stream `rows` as a `name.csv` or `name.ndjson` attachment.
+fn export_response<R: ExportRow>(name: &str, format: ExportFormat, rows: Rows<R>) -> Response {
+    let header = (format == ExportFormat::Csv).then(|| {
+        let mut writer = csv_writer();
+        writer
+            .write_record(R::COLUMNS)
+            .expect("writing to memory cannot fail");
+        Ok::<_, IndexerError>(writer.into_inner().expect("writing to memory cannot fail"))
+    });
+    let rows = rows
+        .map_ok(|batch| stream::iter(batch).chunks(CHUNK_ROWS).map(Ok))
+        .try_flatten()
+        .map_ok(move |chunk| format.render(&chunk))
+        .inspect_err(|e| tracing::warn!("Export ended early: {}", e));
+    let body = Body::from_stream(stream::iter(header).chain(rows));
+
+    let disposition = format!("attachment; filename=\"{}.{}\"", name, format.extension());
+    (
+        [(CONTENT_TYPE, format.content_type().to_string())],
+        [(CONTENT_DISPOSITION, disposition)],
+        body,
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use rust_decimal::Decimal;
+
+    /// Serialize `row` with a serde-derived header and check it against
+    /// the declared columns.
+    fn assert_columns<R: ExportRow>(row: R) {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.serialize(&row).unwrap();
+        let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(csv.lines().next().unwrap(), R::COLUMNS.join(","));
+
+        // NDJSON records have the same fields (map keys come back sorted)
+        let json = serde_json::to_value(&row).unwrap();
+        let mut keys: Vec<&str> = json
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut columns = R::COLUMNS.to_vec();
+        keys.sort_unstable();
+        columns.sort_unstable();
+        assert_eq!(keys, columns);
+    }
+
+    #[test]
+    fn test_columns_match_serialization() {
+        let trade = hl_types::UserFill {
+            asset: Asset::from_symbol("BTC"),
+            timestamp_ms: 1,
+            price: Decimal::ONE,
+            size: Decimal::ONE,
+            side: hl_types::Side::Buy,
+            fee: Decimal::ZERO,
+            closed_pnl: Decimal::ZERO,
+            trade_id: 1,
+            order_id: 1,
+            crossed: true,
+            direction: "Open Long".to_string(),
+            liquidated_user: None,
+        };
+        assert_columns(TradeResponse::from(trade));
+        assert_columns(AssetPnLResponse::from(&hl_types::AssetPnL::new(
+            Asset::from_symbol("BTC"),
+        )));
+        assert_columns(RoundTripResponse {
+            asset: "BTC".to_string(),
+            direction: "long".to_string(),
+            opened_at_ms: 1,
+            closed_at_ms: None,
+            fill_count: 1,
+            max_size: Decimal::ONE,
+            volume: Decimal::ONE,
+            fees: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+            net_pnl: Decimal::ZERO,
+        });
+        assert_columns(LeaderboardExportRow {
+            rank: 1,
+            user: "0xabc".to_string(),
+            metric_value: Decimal::ONE,
+            volume: Decimal::ONE,
+            realized_pnl: Decimal::ZERO,
+            return_pct: None,
+            trade_count: 1,
+            builder_fill_count: 0,
+            tainted: false,
+            starting_equity: None,
+            net_deposits: None,
+            flagged_volume: Decimal::ZERO,
+            flagged_fill_count: 0,
+            previous_rank: None,
+            rank_change_24h: None,
+        });
+    }
+
+    #[test]
+    fn test_negotiate_format() {
+        let query = |format: Option<&str>| ExportQuery {
+            format: format.map(str::to_string),
+        };
+        let accept = |value: &'static str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(ACCEPT, HeaderValue::from_static(value));
+            headers
+        };
+        let none = HeaderMap::new();
+
+        assert_eq!(
+            ExportFormat::negotiate(&query(None), &none).unwrap(),
+            ExportFormat::Csv
+        );
+        assert_eq!(
+            ExportFormat::negotiate(&query(Some("NDJSON")), &accept("text/csv")).unwrap(),
+            ExportFormat::Ndjson
+        );
+        for (header, format) in [
+            ("application/x-ndjson", ExportFormat::Ndjson),
+            // Weights beat the order ranges are listed in
+            (
+                "text/html, application/x-ndjson;q=0.9, text/csv",
+                ExportFormat::Csv,
+            ),
+            ("text/csv;q=0.5, application/x-ndjson", ExportFormat::Ndjson),
+            // Equal weights go to the range listed first
+            ("application/ndjson, text/csv", ExportFormat::Ndjson),
+            ("text/csv, application/x-ndjson", ExportFormat::Csv),
+            // An exact media type outranks a wildcard matching it
+            ("text/csv;q=0, */*", ExportFormat::Ndjson),
+            ("application/*;q=0.2, text/*;q=0.1", ExportFormat::Ndjson),
+            ("*/*", ExportFormat::Csv),
+            // Nothing acceptable falls back to CSV
+            ("application/x-ndjson;q=0", ExportFormat::Csv),
+            ("text/html", ExportFormat::Csv),
+        ] {
+            assert_eq!(
+                ExportFormat::negotiate(&query(None), &accept(header)).unwrap(),
+                format,
+                "{}",
+                header
+            );
+        }
+        assert!(ExportFormat::negotiate(&query(Some("xlsx")), &none).is_err());
+    }
+}
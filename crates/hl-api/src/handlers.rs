@@ -1,23 +1,30 @@
 //! Route handlers for the API endpoints.
 
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     Json,
 };
-use chrono::{Duration, TimeZone, Utc};
-use std::sync::Arc;
-
-use crate::error::ApiError;
-use crate::state::AppState;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::cursor;
+use crate::error::{ApiError, ErrorResponse};
+use crate::refresher::{builder_client, date_of, refresh_leaderboard};
+use crate::stale::Freshness;
+use crate::state::{parse_address_list, AppState};
 use crate::types::{
-    AssetPnLResponse, HealthResponse, LeaderboardEntryResponse, LeaderboardQuery,
-    LeaderboardResponse, PnLQuery, PnLResponse, TradeResponse, TradesQuery, TradesResponse,
-};
-use hl_builder_data::{BuilderDataClient, FillEnricher};
-use hl_indexer::leaderboard::{
-    calculate_leaderboard, rank_leaderboard, FillEnricherChecker, LeaderboardConfig, NoBuilderChecker,
+    AssetPnLResponse, BuilderAnalyticsQuery, BuilderAnalyticsResponse, HealthResponse,
+    LeaderboardEntryResponse, LeaderboardHistoryPoint, LeaderboardHistoryQuery,
+    LeaderboardHistoryResponse, LeaderboardQuery, LeaderboardResponse, PnLQuery, PnLResponse,
+    TradeResponse, TradesQuery, TradesResponse, UserLeaderboardHistory,
 };
-use hl_types::Asset;
+use futures::StreamExt;
+use hl_builder_data::{BucketSize, BuilderAnalyzer, BuilderDataError, FillFilter, DEFAULT_TOP_N};
+use hl_indexer::filter::TradeFilter;
+use hl_indexer::leaderboard::LeaderboardConfig;
+use hl_indexer::pagination::{PageCursor, PageRequest, SortOrder};
+use hl_indexer::snapshot::LeaderboardSnapshot;
+use hl_types::{Asset, FillDirection, PnLSummary, Side};
 
 /// Default limit for trades query.
 const DEFAULT_TRADES_LIMIT: usize = 100;
@@ -26,79 +33,223 @@ const DEFAULT_TRADES_LIMIT: usize = 100;
 const MAX_TRADES_LIMIT: usize = 1000;
 
 /// GET /health - Health check endpoint.
-pub async fn health() -> Json<HealthResponse> {
+#[utoipa::path(
+    get,
+    path = "/health",
+    summary = "Health check",
+    tag = "health",
+    responses((status = 200, description = "Service is up", body = HealthResponse))
+)]
+pub async fn health(State(state): State<Arc<AppState>>) -> Json<HealthResponse> {
     Json(HealthResponse {
         status: "ok".to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
+        upstream: state.indexer.upstream_state().as_str().to_string(),
     })
 }
 
 /// GET /v1/trades - Fetch user trades/fills.
+///
+/// Trades are paged with opaque cursors: pass a response's `next_cursor` or
+/// `prev_cursor` back as `cursor` to move through the history in either
+/// direction. Filters are applied before paging. If the upstream API fails,
+/// the page last served for the same parameters is returned with
+/// `stale: true`.
+#[utoipa::path(
+    get,
+    path = "/v1/trades",
+    summary = "Fetch user trades",
+    tag = "trades",
+    params(TradesQuery),
+    responses(
+        (status = 200, description = "A page of trades", body = TradesResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 503, description = "Upstream API down and nothing cached", body = ErrorResponse),
+    )
+)]
 pub async fn get_trades(
     State(state): State<Arc<AppState>>,
     Query(query): Query<TradesQuery>,
 ) -> Result<Json<TradesResponse>, ApiError> {
-    // Validate user address
-    if query.user.is_empty() {
-        return Err(ApiError::BadRequest("user address is required".to_string()));
-    }
-
-    if !query.user.starts_with("0x") {
-        return Err(ApiError::BadRequest(
-            "user address must start with 0x".to_string(),
-        ));
-    }
-
-    // Fetch fills from indexer
-    let fills = state
-        .indexer
-        .get_user_fills(&query.user, query.from_ms, query.to_ms)
-        .await?;
-
-    // Filter by asset if specified
-    let fills = if let Some(ref asset_filter) = query.asset {
-        let target_asset = Asset::from_symbol(asset_filter);
-        fills
-            .into_iter()
-            .filter(|f| f.asset == target_asset)
-            .collect()
-    } else {
-        fills
-    };
+    validate_user(&query.user)?;
 
-    // Apply limit
     let limit = query
         .limit
         .unwrap_or(DEFAULT_TRADES_LIMIT)
         .min(MAX_TRADES_LIMIT);
+    let (order, cursor) = page_position(&query)?;
+    let request = PageRequest {
+        order,
+        cursor,
+        limit,
+    };
+
+    let filter = trade_filter(&query)?;
 
-    let total_count = fills.len();
-    let has_more = total_count > limit;
+    // Fetch the page from the indexer
+    let result = state
+        .indexer
+        .get_user_fills_page(&query.user, query.from_ms, query.to_ms, &filter, &request)
+        .await;
+    let key = (
+        query.user.to_lowercase(),
+        query.from_ms,
+        query.to_ms,
+        filter,
+        request,
+    );
+    let (page, freshness) = state.last_trades.resolve(key, result)?;
 
-    let trades: Vec<TradeResponse> = fills.into_iter().take(limit).map(Into::into).collect();
+    let next_cursor = page.next_cursor().map(|c| cursor::encode(order, c));
+    let prev_cursor = page.prev_cursor().map(|c| cursor::encode(order, c));
+    let trades: Vec<TradeResponse> = page.fills.into_iter().map(Into::into).collect();
 
     Ok(Json(TradesResponse {
         count: trades.len(),
         trades,
-        has_more,
+        has_more: page.has_next,
+        order: order.as_str().to_string(),
+        next_cursor,
+        prev_cursor,
+        stale: freshness.stale,
+        data_age_ms: freshness.data_age_ms,
     }))
 }
 
-/// GET /v1/pnl - Calculate PnL for a user.
-pub async fn get_pnl(
-    State(state): State<Arc<AppState>>,
-    Query(query): Query<PnLQuery>,
-) -> Result<Json<PnLResponse>, ApiError> {
-    // Validate user address
-    if query.user.is_empty() {
+/// Check the `user` parameter of a per-user request.
+pub(crate) fn validate_user(user: &str) -> Result<(), ApiError> {
+    if user.is_empty() {
         return Err(ApiError::BadRequest("user address is required".to_string()));
     }
-
-    if !query.user.starts_with("0x") {
+    if !user.starts_with("0x") {
         return Err(ApiError::BadRequest(
             "user address must start with 0x".to_string(),
         ));
     }
+    Ok(())
+}
+
+/// Fill filter of a trades request.
+///
+/// `asset` and `assets` are combined; either may be used alone.
+pub(crate) fn trade_filter(query: &TradesQuery) -> Result<TradeFilter, ApiError> {
+    let mut filter = TradeFilter::new()
+        .with_builder_only(query.builder_only)
+        .with_nonzero_pnl(query.nonzero_pnl);
+
+    let assets = query.asset.iter().chain(&query.assets);
+    let mut symbols: Vec<&str> = assets.flat_map(|s| s.split(',')).map(str::trim).collect();
+    symbols.retain(|s| !s.is_empty());
+    // Keep a stable order so equivalent requests share a last known good page
+    symbols.sort_unstable();
+    symbols.dedup();
+    filter = filter.with_assets(symbols.into_iter().map(Asset::from_symbol));
+
+    if let Some(side) = query.side.as_deref() {
+        let side = match side.to_lowercase().as_str() {
+            "buy" => Side::Buy,
+            "sell" => Side::Sell,
+            _ => {
+                return Err(ApiError::BadRequest(format!(
+                    "invalid side '{}': must be 'buy' or 'sell'",
+                    side
+                )))
+            }
+        };
+        filter = filter.with_side(side);
+    }
+
+    if let Some(direction) = query.direction.as_deref() {
+        let direction = FillDirection::parse(direction).ok_or_else(|| {
+            ApiError::BadRequest(format!(
+                "invalid direction '{}': must be 'open', 'close', 'flip', 'liquidation', or 'other'",
+                direction
+            ))
+        })?;
+        filter = filter.with_direction(direction);
+    }
+
+    if let Some(liquidity) = query.liquidity.as_deref() {
+        let crossed = match liquidity.to_lowercase().as_str() {
+            "taker" => true,
+            "maker" => false,
+            _ => {
+                return Err(ApiError::BadRequest(format!(
+                    "invalid liquidity '{}': must be 'maker' or 'taker'",
+                    liquidity
+                )))
+            }
+        };
+        filter = filter.with_crossed(crossed);
+    }
+
+    if let (Some(min), Some(max)) = (query.min_notional, query.max_notional) {
+        if min > max {
+            return Err(ApiError::BadRequest(
+                "min_notional must not exceed max_notional".to_string(),
+            ));
+        }
+    }
+    filter = filter.with_notional(query.min_notional, query.max_notional);
+
+    if let Some(order_id) = query.order_id {
+        filter = filter.with_order_id(order_id);
+    }
+
+    Ok(filter)
+}
+
+/// Sort order and page boundary of a trades request.
+///
+/// A cursor carries the order it was issued for; `order` may be omitted
+/// alongside it but must not contradict it.
+pub(crate) fn page_position(
+    query: &TradesQuery,
+) -> Result<(SortOrder, Option<PageCursor>), ApiError> {
+    let order = query
+        .order
+        .as_deref()
+        .map(|s| {
+            SortOrder::parse(s).ok_or_else(|| {
+                ApiError::BadRequest(format!("invalid order '{}': must be 'asc' or 'desc'", s))
+            })
+        })
+        .transpose()?;
+
+    let Some(encoded) = query.cursor.as_deref() else {
+        return Ok((order.unwrap_or_default(), None));
+    };
+    let (cursor_order, cursor) = cursor::decode(encoded)?;
+    if order.is_some_and(|order| order != cursor_order) {
+        return Err(ApiError::BadRequest(format!(
+            "cursor was issued for order '{}'",
+            cursor_order.as_str()
+        )));
+    }
+    Ok((cursor_order, Some(cursor)))
+}
+
+/// GET /v1/pnl - Calculate PnL for a user.
+///
+/// Falls back to the last PnL computed for the same parameters, with
+/// `stale: true`, if the upstream API fails.
+#[utoipa::path(
+    get,
+    path = "/v1/pnl",
+    summary = "Calculate a user's PnL",
+    tag = "pnl",
+    params(PnLQuery),
+    responses(
+        (status = 200, description = "PnL summary", body = PnLResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 503, description = "Upstream API down and nothing cached", body = ErrorResponse),
+    )
+)]
+pub async fn get_pnl(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PnLQuery>,
+) -> Result<Json<PnLResponse>, ApiError> {
+    validate_user(&query.user)?;
 
     // Parse asset filter if provided
     let assets: Option<Vec<Asset>> = query.assets.as_ref().map(|s| {
@@ -108,7 +259,7 @@ pub async fn get_pnl(
     });
 
     // Get PnL from indexer
-    let summary = state
+    let result = state
         .indexer
         .get_user_pnl(
             &query.user,
@@ -116,32 +267,387 @@ pub async fn get_pnl(
             query.to_ms,
             assets.as_deref(),
         )
-        .await?;
+        .await;
+    let key = (
+        query.user.to_lowercase(),
+        query.from_ms,
+        query.to_ms,
+        query.assets.clone(),
+    );
+    let (summary, freshness) = state.last_pnl.resolve(key, result)?;
+
+    Ok(Json(pnl_response(
+        query.user,
+        &summary,
+        query.from_ms,
+        query.to_ms,
+        freshness,
+    )))
+}
 
-    // Convert to response
+/// Render a PnL summary as served by `/v1/pnl`.
+pub(crate) fn pnl_response(
+    user: String,
+    summary: &PnLSummary,
+    from_ms: Option<i64>,
+    to_ms: Option<i64>,
+    freshness: Freshness,
+) -> PnLResponse {
     let by_asset: Vec<AssetPnLResponse> = summary
         .by_asset
         .values()
         .map(Into::into)
         .collect();
 
-    Ok(Json(PnLResponse {
-        user: query.user,
+    PnLResponse {
+        user,
         realized_pnl: summary.realized_pnl,
         total_fees: summary.total_fees,
         net_pnl: summary.net_pnl,
         fill_count: summary.fill_count,
         by_asset,
-        from_ms: query.from_ms,
-        to_ms: query.to_ms,
-    }))
+        from_ms,
+        to_ms,
+        stale: freshness.stale,
+        data_age_ms: freshness.data_age_ms,
+    }
 }
 
+/// Milliseconds in 24 hours, the baseline for rank movement.
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
 /// GET /v1/leaderboard - Get competition leaderboard.
+///
+/// Serves the latest precomputed snapshot. The first request for a new
+/// parameter combination computes it synchronously; after that the background
+/// refresher keeps it up to date. With `asOfMs`, serves the retained snapshot
+/// that was current at that time instead.
+///
+/// If the latest refresh failed (e.g. the upstream API is down), the previous
+/// snapshot is still served, with `stale: true`.
+#[utoipa::path(
+    get,
+    path = "/v1/leaderboard",
+    summary = "Get the competition leaderboard",
+    tag = "leaderboard",
+    params(LeaderboardQuery),
+    responses(
+        (status = 200, description = "Ranked leaderboard", body = LeaderboardResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "No snapshot retained for `asOfMs`", body = ErrorResponse),
+        (status = 503, description = "Upstream API down and nothing cached, or too many leaderboards tracked", body = ErrorResponse),
+    )
+)]
 pub async fn get_leaderboard(
     State(state): State<Arc<AppState>>,
     Query(query): Query<LeaderboardQuery>,
 ) -> Result<Json<LeaderboardResponse>, ApiError> {
+    let config = leaderboard_config(&state, &query)?;
+    let (snapshot, stale) = served_snapshot(&state, &config, query.as_of_ms).await?;
+
+    Ok(Json(
+        leaderboard_response(&state, &config, &query, &snapshot, stale).await,
+    ))
+}
+
+/// The snapshot a leaderboard request is served from, and whether it is stale.
+///
+/// With `as_of_ms`, the retained snapshot that was current at that time;
+/// otherwise the latest one.
+pub(crate) async fn served_snapshot(
+    state: &AppState,
+    config: &LeaderboardConfig,
+    as_of_ms: Option<u64>,
+) -> Result<(Arc<LeaderboardSnapshot>, bool), ApiError> {
+    match as_of_ms {
+        Some(as_of_ms) => {
+            let snapshot = state
+                .leaderboards
+                .as_of(config, as_of_ms)
+                .await
+                .ok_or_else(|| {
+                    ApiError::NotFound(format!(
+                        "no leaderboard snapshot recorded at or before {}",
+                        as_of_ms
+                    ))
+                })?;
+            Ok((snapshot, false))
+        }
+        None => {
+            let snapshot = latest_snapshot(state, config).await?;
+            Ok((snapshot, state.leaderboards.is_stale(config).await))
+        }
+    }
+}
+
+/// Render a snapshot of the leaderboard selected by `config` and `query`.
+pub(crate) async fn leaderboard_response(
+    state: &AppState,
+    config: &LeaderboardConfig,
+    query: &LeaderboardQuery,
+    snapshot: &LeaderboardSnapshot,
+    stale: bool,
+) -> LeaderboardResponse {
+    // Reported like /v1/trades and /v1/pnl: an age only when stale
+    let freshness = if stale {
+        Freshness::stale_since(snapshot.computed_at_ms)
+    } else {
+        Freshness::FRESH
+    };
+
+    // Rank movement is relative to the snapshot current a day earlier
+    let baseline = state
+        .leaderboards
+        .as_of(config, snapshot.computed_at_ms.saturating_sub(DAY_MS))
+        .await;
+
+    // Convert to response types
+    let entries: Vec<LeaderboardEntryResponse> = snapshot
+        .entries
+        .iter()
+        .cloned()
+        .map(|entry| LeaderboardEntryResponse::from(entry).with_baseline(baseline.as_deref()))
+        .collect();
+
+    LeaderboardResponse {
+        filtered_users: entries.len(),
+        entries,
+        metric: config.metric.as_str().to_string(),
+        from_ms: query.from_ms,
+        to_ms: query.to_ms,
+        coin: query.coin.clone(),
+        builder_only: config.builder_only,
+        total_users: snapshot.total_users,
+        computed_at_ms: snapshot.computed_at_ms,
+        snapshot_version: snapshot.version,
+        stale: freshness.stale,
+        data_age_ms: freshness.data_age_ms,
+    }
+}
+
+/// GET /v1/competitions/{id}/leaderboard/history - Rank and metric time series.
+///
+/// Accepts the same parameters as `/v1/leaderboard` to select the leaderboard,
+/// plus `sinceMs`, `untilMs` and `user` to narrow the series.
+///
+/// History is thinned to one snapshot per `LEADERBOARD_HISTORY_INTERVAL_SECS`
+/// (15 minutes by default) and kept for `LEADERBOARD_HISTORY_RETENTION_DAYS`
+/// (30 by default). A leaderboard only accumulates history while it is
+/// tracked: from its first request until it goes unread for
+/// `LEADERBOARD_IDLE_TIMEOUT_SECS`. After that its history is still served
+/// until it ages out. History survives restarts only when
+/// `LEADERBOARD_HISTORY_DIR` is set; otherwise it starts over on every deploy.
+#[utoipa::path(
+    get,
+    path = "/v1/competitions/{id}/leaderboard/history",
+    summary = "Leaderboard rank and metric history",
+    tag = "leaderboard",
+    params(
+        ("id" = String, Path, description = "Competition ID (`COMPETITION_ID`)"),
+        LeaderboardQuery,
+        LeaderboardHistoryQuery,
+    ),
+    responses(
+        (status = 200, description = "Per-user rank series", body = LeaderboardHistoryResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Unknown competition", body = ErrorResponse),
+        (status = 503, description = "Too many leaderboards tracked", body = ErrorResponse),
+    )
+)]
+pub async fn get_leaderboard_history(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<String>,
+    Query(query): Query<LeaderboardQuery>,
+    Query(history_query): Query<LeaderboardHistoryQuery>,
+) -> Result<Json<LeaderboardHistoryResponse>, ApiError> {
+    if competition_id != state.competition_config.competition_id {
+        return Err(ApiError::NotFound(format!(
+            "competition '{}' not found",
+            competition_id
+        )));
+    }
+
+    let config = leaderboard_config(&state, &query)?;
+
+    // Start tracking this leaderboard so history accumulates from now on
+    let latest = latest_snapshot(&state, &config).await?;
+
+    let snapshots = state
+        .leaderboards
+        .history(&config, history_query.since_ms, history_query.until_ms)
+        .await
+        .unwrap_or_default();
+
+    let user_filter = history_query.user.map(|u| u.to_lowercase());
+    let mut series: HashMap<String, Vec<LeaderboardHistoryPoint>> = HashMap::new();
+    for snapshot in &snapshots {
+        for entry in &snapshot.entries {
+            if user_filter.as_ref().is_some_and(|u| *u != entry.user) {
+                continue;
+            }
+            series
+                .entry(entry.user.clone())
+                .or_default()
+                .push(LeaderboardHistoryPoint {
+                    computed_at_ms: snapshot.computed_at_ms,
+                    snapshot_version: snapshot.version,
+                    rank: entry.rank,
+                    metric_value: entry.metric_value,
+                });
+        }
+    }
+
+    // Order users by their current standing, then by address
+    let mut users: Vec<UserLeaderboardHistory> = series
+        .into_iter()
+        .map(|(user, points)| UserLeaderboardHistory { user, points })
+        .collect();
+    users.sort_by(|a, b| {
+        let rank_a = latest.rank_of(&a.user).unwrap_or(usize::MAX);
+        let rank_b = latest.rank_of(&b.user).unwrap_or(usize::MAX);
+        rank_a.cmp(&rank_b).then_with(|| a.user.cmp(&b.user))
+    });
+
+    Ok(Json(LeaderboardHistoryResponse {
+        competition_id,
+        metric: config.metric.as_str().to_string(),
+        snapshot_count: snapshots.len(),
+        users,
+    }))
+}
+
+/// Longest time range accepted by the builder analytics endpoint.
+const MAX_ANALYTICS_RANGE_DAYS: i64 = 90;
+
+/// Longest lookback accepted by the builder analytics endpoint.
+const MAX_ANALYTICS_LOOKBACK_DAYS: u32 = 90;
+
+/// Maximum number of top assets/users returned by builder analytics.
+const MAX_ANALYTICS_TOP: usize = 100;
+
+/// GET /v1/builder/{address}/analytics - Builder revenue analytics.
+///
+/// Aggregates the builder's fills from the daily archives into fee revenue,
+/// volume and new-versus-returning users per day or hour, plus top assets
+/// and users by volume. Only builders configured on this server (the
+/// competition's target builders or the indexer's builders) are served.
+#[utoipa::path(
+    get,
+    path = "/v1/builder/{address}/analytics",
+    summary = "Builder revenue analytics",
+    tag = "builder",
+    params(
+        ("address" = String, Path, description = "Builder address"),
+        BuilderAnalyticsQuery,
+    ),
+    responses(
+        (status = 200, description = "Builder analytics", body = BuilderAnalyticsResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Builder is not configured on this server", body = ErrorResponse),
+    )
+)]
+pub async fn get_builder_analytics(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+    Query(query): Query<BuilderAnalyticsQuery>,
+) -> Result<Json<BuilderAnalyticsResponse>, ApiError> {
+    let builder = address.to_lowercase();
+    if !builder.starts_with("0x") {
+        return Err(ApiError::BadRequest(
+            "builder address must start with 0x".to_string(),
+        ));
+    }
+    // Archives are only downloaded for builders this server is set up for
+    let configured = state
+        .competition_config
+        .target_builders
+        .iter()
+        .chain(&state.indexer.config().builder_addresses)
+        .any(|addr| addr.eq_ignore_ascii_case(&builder));
+    if !configured {
+        return Err(ApiError::NotFound(format!(
+            "builder {} is not configured",
+            builder
+        )));
+    }
+
+    let bucket = BucketSize::from_str(&query.bucket).ok_or_else(|| {
+        ApiError::BadRequest(format!(
+            "invalid bucket '{}': must be 'day' or 'hour'",
+            query.bucket
+        ))
+    })?;
+
+    let day_ms = DAY_MS as i64;
+    let to_ms = query
+        .to_ms
+        .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    let out_of_range = || ApiError::BadRequest("fromMs or toMs is out of range".to_string());
+    let from_ms = match query.from_ms {
+        Some(from_ms) => from_ms,
+        None => to_ms.checked_sub(7 * day_ms).ok_or_else(out_of_range)?,
+    };
+    if from_ms > to_ms {
+        return Err(ApiError::BadRequest(
+            "fromMs must not be after toMs".to_string(),
+        ));
+    }
+    if to_ms.checked_sub(from_ms).ok_or_else(out_of_range)? > MAX_ANALYTICS_RANGE_DAYS * day_ms {
+        return Err(ApiError::BadRequest(format!(
+            "time range must not exceed {} days",
+            MAX_ANALYTICS_RANGE_DAYS
+        )));
+    }
+    if query.lookback_days > MAX_ANALYTICS_LOOKBACK_DAYS {
+        return Err(ApiError::BadRequest(format!(
+            "lookbackDays must not exceed {}",
+            MAX_ANALYTICS_LOOKBACK_DAYS
+        )));
+    }
+    let top = query.top.unwrap_or(DEFAULT_TOP_N).min(MAX_ANALYTICS_TOP);
+
+    let lookback_from_ms = from_ms
+        .checked_sub(i64::from(query.lookback_days) * day_ms)
+        .ok_or_else(out_of_range)?;
+    let client = builder_client(&state, &builder, &FillFilter::new())?;
+
+    // Aggregate a day at a time so the whole range is never held at once.
+    // Archives cover whole days; fills before the range only seed known users.
+    let mut analysis = BuilderAnalyzer::new(bucket).with_top_n(top).start();
+    let mut days = client.fetch_fills_by_date(date_of(lookback_from_ms)?, date_of(to_ms)?);
+    while let Some((date, result)) = days.next().await {
+        let mut fills = match result {
+            Ok((fills, _)) => fills,
+            Err(BuilderDataError::NotFound { .. }) => continue,
+            Err(e) => {
+                return Err(ApiError::Internal(format!(
+                    "failed to fetch fills of builder {} for {}: {}",
+                    builder, date, e
+                )))
+            }
+        };
+        fills.sort_by_key(|f| f.time);
+        for fill in fills.iter().filter(|f| f.timestamp_ms() <= to_ms) {
+            if fill.timestamp_ms() >= from_ms {
+                analysis.add(fill);
+            } else if query.lookback_days > 0 {
+                analysis.add_known_user(&fill.user);
+            }
+        }
+    }
+
+    let analytics = analysis.finish();
+
+    Ok(Json(BuilderAnalyticsResponse::new(
+        builder, from_ms, to_ms, analytics,
+    )))
+}
+
+/// Validate leaderboard query parameters and build the matching config.
+pub(crate) fn leaderboard_config(
+    state: &AppState,
+    query: &LeaderboardQuery,
+) -> Result<LeaderboardConfig, ApiError> {
     // Check if competition is configured
     if !state.competition_config.is_configured() {
         return Err(ApiError::BadRequest(
@@ -157,149 +663,104 @@ pub async fn get_leaderboard(
         ))
     })?;
 
-    // Validate returnPct requires from_ms
-    if matches!(metric, hl_indexer::leaderboard::LeaderboardMetric::ReturnPct) {
-        if query.from_ms.is_none() {
-            return Err(ApiError::BadRequest(
-                "from_ms is required for returnPct metric".to_string(),
-            ));
-        }
-        if query.max_start_capital.is_none() {
-            return Err(ApiError::BadRequest(
-                "maxStartCapital is required for returnPct metric".to_string(),
-            ));
-        }
+    // Validate returnPct requires from_ms (starting equity is measured there)
+    if matches!(
+        metric,
+        hl_indexer::leaderboard::LeaderboardMetric::ReturnPct
+    ) && query.from_ms.is_none()
+    {
+        return Err(ApiError::BadRequest(
+            "from_ms is required for returnPct metric".to_string(),
+        ));
     }
 
     // Determine builder_only mode
     let builder_only = query.builder_only || state.competition_config.builder_only;
 
-    // Build leaderboard config
-    let config = LeaderboardConfig {
-        target_builder: state.competition_config.target_builder.clone(),
+    // Restrict attribution to a subset of the configured builders if asked
+    let configured_builders = &state.competition_config.target_builders;
+    let target_builders = match query.builders.as_deref() {
+        Some(list) => {
+            let requested = parse_address_list(list);
+            if let Some(unknown) = requested.iter().find(|b| !configured_builders.contains(b)) {
+                return Err(ApiError::BadRequest(format!(
+                    "builder '{}' is not configured for this competition",
+                    unknown
+                )));
+            }
+            // Keep configured order so equivalent requests share a snapshot
+            configured_builders
+                .iter()
+                .filter(|b| requested.contains(b))
+                .cloned()
+                .collect()
+        }
+        None => configured_builders.clone(),
+    };
+
+    Ok(LeaderboardConfig {
+        target_builders,
         builder_only,
         max_start_capital: query.max_start_capital,
         coin: query.coin.clone(),
         from_ms: query.from_ms,
         to_ms: query.to_ms,
         metric,
-    };
-
-    // Calculate leaderboard based on whether builder is configured
-    let (stats, builder_fills_loaded) = if let Some(ref builder_addr) = state.competition_config.target_builder {
-        // Fetch builder fills for the date range
-        let enricher = fetch_builder_fills(builder_addr, query.from_ms, query.to_ms).await?;
-        let fills_count = enricher.total_fills();
-        let checker = FillEnricherChecker::new(enricher);
-
-        tracing::info!("Loaded {} builder fills for leaderboard", fills_count);
-
-        let stats = calculate_leaderboard(
-            &state.indexer,
-            &state.competition_config.competition_users,
-            &config,
-            &checker,
-        )
-        .await?;
-
-        (stats, fills_count)
-    } else {
-        // No builder configured, use no-op checker
-        let checker = NoBuilderChecker;
-
-        let stats = calculate_leaderboard(
-            &state.indexer,
-            &state.competition_config.competition_users,
-            &config,
-            &checker,
-        )
-        .await?;
-
-        (stats, 0)
-    };
-
-    let total_users = stats.len();
-
-    // Rank and filter
-    let ranked = rank_leaderboard(stats, metric, builder_only);
-    let filtered_users = ranked.len();
-
-    // Convert to response types
-    let entries: Vec<LeaderboardEntryResponse> = ranked.into_iter().map(Into::into).collect();
-
-    tracing::info!(
-        "Leaderboard: {} total users, {} after filtering, {} builder fills",
-        total_users,
-        filtered_users,
-        builder_fills_loaded
-    );
-
-    Ok(Json(LeaderboardResponse {
-        entries,
-        metric: metric.as_str().to_string(),
-        from_ms: query.from_ms,
-        to_ms: query.to_ms,
-        coin: query.coin,
-        builder_only,
-        total_users,
-        filtered_users,
-    }))
+        exclude_flagged_volume: query
+            .exclude_flagged_volume
+            .unwrap_or(state.competition_config.exclude_flagged_volume),
+    })
 }
 
-/// Fetch builder fills for a date range.
+/// Serve the cached snapshot, computing it only on first request.
 ///
-/// Builder data is organized by date, so we fetch all dates in the range.
-async fn fetch_builder_fills(
-    builder_addr: &str,
-    from_ms: Option<i64>,
-    to_ms: Option<i64>,
-) -> Result<FillEnricher, ApiError> {
-    let client = BuilderDataClient::new(builder_addr).map_err(|e| {
-        ApiError::BadRequest(format!("invalid builder address '{}': {}", builder_addr, e))
-    })?;
+/// Concurrent first requests for the same config share one computation, and
+/// a new config is refused while the store is full.
+pub(crate) async fn latest_snapshot(
+    state: &AppState,
+    config: &LeaderboardConfig,
+) -> Result<Arc<LeaderboardSnapshot>, ApiError> {
+    if let Some(snapshot) = state.leaderboards.get(config).await {
+        return Ok(snapshot);
+    }
 
-    // Determine date range
-    let now = Utc::now();
-    let from_date = from_ms
-        .map(|ms| Utc.timestamp_millis_opt(ms).unwrap().date_naive())
-        .unwrap_or_else(|| (now - Duration::days(7)).date_naive());
-
-    let to_date = to_ms
-        .map(|ms| Utc.timestamp_millis_opt(ms).unwrap().date_naive())
-        .unwrap_or_else(|| now.date_naive());
-
-    // Collect fills from all dates in range
-    let mut all_fills = Vec::new();
-    let mut current_date = from_date;
-
-    while current_date <= to_date {
-        match client.fetch_fills(current_date).await {
-            Ok(fills) => {
-                tracing::debug!(
-                    "Fetched {} builder fills for date {}",
-                    fills.len(),
-                    current_date
-                );
-                all_fills.extend(fills);
-            }
-            Err(e) => {
-                // Log but don't fail - data might not exist for all dates
-                tracing::debug!(
-                    "No builder fills for date {} ({}), continuing",
-                    current_date,
-                    e
-                );
+    let pending = lock(&state.first_computes).get(config).cloned();
+    let compute = match pending {
+        Some(compute) => compute,
+        None => {
+            let pending_count = lock(&state.first_computes).len();
+            if !state.leaderboards.has_room(pending_count + 1).await {
+                return Err(ApiError::Unavailable(
+                    "too many leaderboard parameter combinations are being tracked; \
+                     retry later or use the default parameters"
+                        .to_string(),
+                ));
             }
+            lock(&state.first_computes)
+                .entry(config.clone())
+                .or_default()
+                .clone()
         }
-        current_date += Duration::days(1);
-    }
+    };
 
-    tracing::info!(
-        "Total builder fills fetched: {} (from {} to {})",
-        all_fills.len(),
-        from_date,
-        to_date
-    );
+    let result = {
+        let _computing = compute.lock().await;
+        match state.leaderboards.get(config).await {
+            Some(snapshot) => Ok(snapshot),
+            None => refresh_leaderboard(state, config).await,
+        }
+    };
+
+    let mut first_computes = lock(&state.first_computes);
+    if first_computes
+        .get(config)
+        .is_some_and(|current| Arc::ptr_eq(current, &compute))
+    {
+        first_computes.remove(config);
+    }
+    result
+}
 
-    Ok(FillEnricher::new(all_fills))
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
 }
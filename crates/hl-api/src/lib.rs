@@ -5,7 +5,21 @@
 //! - `GET /health` - Health check
 //! - `GET /v1/trades` - Fetch user trades/fills
 //! - `GET /v1/pnl` - Calculate PnL for a user
-//! - `GET /v1/leaderboard` - Get competition leaderboard
+//! - `GET /v1/leaderboard` - Get competition leaderboard (served from snapshots
+//!   kept fresh by [`spawn_leaderboard_refresher`])
+//! - `GET /v1/competitions/{id}/leaderboard/history` - Rank and metric history
+//! - `GET /v1/builder/{address}/analytics` - Builder fee revenue, volume and users
+//! - `GET /v1/stream/trades`, `GET /v1/stream/pnl` and
+//!   `GET /v1/competitions/{id}/leaderboard/stream` - Live updates as
+//!   server-sent events, fed by [`spawn_fill_forwarder`] and the refresher
+//! - `GET /v1/export/trades`, `/v1/export/pnl`, `/v1/export/roundtrips` and
+//!   `/v1/export/leaderboard` - Streamed CSV or NDJSON exports
+//! - `GET /openapi.json` and `GET /docs` - OpenAPI document ([`ApiDoc`]) and
+//!   a browsable version of it
+//!
+//! When the Hyperliquid API fails, trades, PnL and leaderboards are served
+//! from the last data fetched successfully, flagged `stale` with its age.
+//! Requests with nothing to fall back on get `503 upstream_unavailable`.
 //!
 //! # Example
 //!
@@ -25,12 +39,22 @@
 //! }
 //! ```
 
+mod cursor;
 mod error;
+mod events;
+mod export;
 mod handlers;
+mod openapi;
+mod refresher;
+mod stale;
 mod state;
+mod stream;
 mod types;
 
 pub use error::ApiError;
+pub use events::spawn_fill_forwarder;
+pub use openapi::ApiDoc;
+pub use refresher::{spawn_leaderboard_refresher, RefresherConfig};
 pub use state::{AppState, CompetitionConfig};
 pub use types::*;
 
@@ -50,6 +74,34 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/v1/trades", get(handlers::get_trades))
         .route("/v1/pnl", get(handlers::get_pnl))
         .route("/v1/leaderboard", get(handlers::get_leaderboard))
+        .route(
+            "/v1/competitions/:id/leaderboard/history",
+            get(handlers::get_leaderboard_history),
+        )
+        .route(
+            "/v1/builder/:address/analytics",
+            get(handlers::get_builder_analytics),
+        )
+        // Live updates (server-sent events)
+        .route("/v1/stream/trades", get(stream::trades))
+        .route("/v1/stream/pnl", get(stream::pnl))
+        .route(
+            "/v1/competitions/:id/leaderboard/stream",
+            get(stream::leaderboard),
+        )
+        // Bulk exports (CSV or NDJSON)
+        .route("/v1/export/trades", get(export::trades))
+        .route("/v1/export/pnl", get(export::pnl))
+        .route("/v1/export/roundtrips", get(export::round_trips))
+        .route("/v1/export/leaderboard", get(export::leaderboard))
+        // API description
+        .route("/openapi.json", get(openapi::spec))
+        .route("/docs", get(openapi::docs))
+        .route("/docs/swagger-ui.css", get(openapi::swagger_ui_css))
+        .route(
+            "/docs/swagger-ui-bundle.js",
+            get(openapi::swagger_ui_bundle),
+        )
         // Add state and middleware
         .with_state(state)
         .layer(TraceLayer::new_for_http())
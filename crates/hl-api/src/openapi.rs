@@ -0,0 +1,100 @@
+//! OpenAPI description of the API.
+//!
+//! [`ApiDoc`] is derived from the handlers' `#[utoipa::path]` attributes and
+//! the request and response types, so it cannot fall out of step with them.
+//! It is served at `/openapi.json`, with a browsable version at `/docs`.
+//! The Swagger UI assets for that page are vendored under
+//! `crates/hl-api/assets/swagger-ui` (release 5.17.14, with its license)
+//! and served by the API itself, so the page loads no third-party scripts.
+//!
+//! A copy is committed as `crates/hl-api/openapi.json` for generating
+//! clients; `tests/openapi_test.rs` fails when that copy is out of date.
+
+use axum::http::header;
+use axum::response::{Html, IntoResponse};
+use axum::Json;
+use std::sync::OnceLock;
+use utoipa::openapi::OpenApi as OpenApiDocument;
+use utoipa::OpenApi;
+
+use crate::types::LaggedEvent;
+use crate::{export, handlers, stream};
+
+/// Page rendering `/openapi.json` with Swagger UI.
+const DOCS_PAGE: &str = include_str!("docs.html");
+
+/// Swagger UI's stylesheet.
+const SWAGGER_UI_CSS: &str = include_str!("../assets/swagger-ui/swagger-ui.css");
+
+/// Swagger UI's script.
+const SWAGGER_UI_BUNDLE: &str = include_str!("../assets/swagger-ui/swagger-ui-bundle.js");
+
+/// How long browsers may cache the vendored assets, which only change with
+/// the binary.
+const ASSET_CACHE_CONTROL: &str = "public, max-age=86400";
+
+/// The API's OpenAPI 3 document.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Hyperliquid Trade Ledger API",
+        description = "Trades, PnL, competition leaderboards and builder analytics for Hyperliquid users."
+    ),
+    paths(
+        handlers::health,
+        handlers::get_trades,
+        handlers::get_pnl,
+        handlers::get_leaderboard,
+        handlers::get_leaderboard_history,
+        handlers::get_builder_analytics,
+        stream::trades,
+        stream::pnl,
+        stream::leaderboard,
+        export::trades,
+        export::pnl,
+        export::round_trips,
+        export::leaderboard,
+    ),
+    components(schemas(LaggedEvent)),
+    tags(
+        (name = "health", description = "Service status"),
+        (name = "trades", description = "User fills"),
+        (name = "pnl", description = "Realized PnL"),
+        (name = "leaderboard", description = "Competition rankings"),
+        (name = "builder", description = "Builder revenue analytics"),
+        (name = "stream", description = "Live updates as server-sent events"),
+        (name = "export", description = "Bulk exports as CSV or NDJSON"),
+    )
+)]
+pub struct ApiDoc;
+
+/// GET /openapi.json - The OpenAPI document.
+pub async fn spec() -> Json<&'static OpenApiDocument> {
+    static SPEC: OnceLock<OpenApiDocument> = OnceLock::new();
+    Json(SPEC.get_or_init(ApiDoc::openapi))
+}
+
+/// GET /docs - Interactive API documentation.
+pub async fn docs() -> Html<&'static str> {
+    Html(DOCS_PAGE)
+}
+
+/// GET /docs/swagger-ui.css - Stylesheet of the documentation page.
+pub async fn swagger_ui_css() -> impl IntoResponse {
+    asset("text/css; charset=utf-8", SWAGGER_UI_CSS)
+}
+
+/// GET /docs/swagger-ui-bundle.js - Script of the documentation page.
+pub async fn swagger_ui_bundle() -> impl IntoResponse {
+    asset("text/javascript; charset=utf-8", SWAGGER_UI_BUNDLE)
+}
+
+fn asset(content_type: &'static str, body: &'static str) -> impl IntoResponse {
+    (
+        [
+            (header::CONTENT_TYPE, content_type),
+            (header::CACHE_CONTROL, ASSET_CACHE_CONTROL),
+        ],
+        body,
+    )
+}
@@ -0,0 +1,387 @@
+//! Background leaderboard computation.
+//!
+//! Leaderboards are computed off the request path and stored as versioned
+//! snapshots in [`AppState::leaderboards`]. The `/v1/leaderboard` handler only
+//! computes synchronously the first time a new parameter combination is
+//! requested; after that, the refresher keeps the snapshot up to date on an
+//! interval and whenever the WebSocket collector stores new fills.
+
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use hl_builder_data::{BuilderDataClient, BuilderFill, FillEnricher, FillFilter};
+use hl_indexer::leaderboard::{
+    calculate_leaderboard_with_detector, rank_leaderboard, FillEnricherChecker, LeaderboardConfig,
+    LeaderboardMetric, NoBuilderChecker,
+};
+use hl_indexer::snapshot::LeaderboardSnapshot;
+use hl_indexer::wash::WashTradeDetector;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
+
+use crate::error::ApiError;
+use crate::events::Event;
+use crate::state::AppState;
+
+/// Default interval between scheduled refreshes.
+const DEFAULT_REFRESH_INTERVAL_SECS: u64 = 60;
+
+/// Default delay used to coalesce bursts of fills into a single refresh.
+const DEFAULT_MIN_REFRESH_GAP_SECS: u64 = 5;
+
+/// Default time after which an unread leaderboard stops being refreshed.
+const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 60 * 60;
+
+/// Configuration for the background leaderboard refresher.
+#[derive(Debug, Clone)]
+pub struct RefresherConfig {
+    /// Interval between scheduled refreshes.
+    pub interval: std::time::Duration,
+
+    /// Delay after a new-fill notification before refreshing, so that a
+    /// burst of fills triggers one refresh instead of many.
+    pub min_refresh_gap: std::time::Duration,
+
+    /// Leaderboards not read for this long are dropped instead of refreshed.
+    pub idle_timeout: std::time::Duration,
+}
+
+impl Default for RefresherConfig {
+    fn default() -> Self {
+        Self {
+            interval: std::time::Duration::from_secs(DEFAULT_REFRESH_INTERVAL_SECS),
+            min_refresh_gap: std::time::Duration::from_secs(DEFAULT_MIN_REFRESH_GAP_SECS),
+            idle_timeout: std::time::Duration::from_secs(DEFAULT_IDLE_TIMEOUT_SECS),
+        }
+    }
+}
+
+impl RefresherConfig {
+    /// Load refresher configuration from environment variables.
+    ///
+    /// Environment variables:
+    /// - `LEADERBOARD_REFRESH_SECS`: Seconds between scheduled refreshes (default: 60)
+    /// - `LEADERBOARD_MIN_REFRESH_GAP_SECS`: Debounce for fill-triggered refreshes (default: 5)
+    /// - `LEADERBOARD_IDLE_TIMEOUT_SECS`: Stop refreshing unread leaderboards after this long (default: 3600)
+    pub fn from_env() -> Self {
+        let secs = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .map(std::time::Duration::from_secs)
+                .unwrap_or(std::time::Duration::from_secs(default))
+        };
+
+        Self {
+            interval: secs("LEADERBOARD_REFRESH_SECS", DEFAULT_REFRESH_INTERVAL_SECS),
+            min_refresh_gap: secs(
+                "LEADERBOARD_MIN_REFRESH_GAP_SECS",
+                DEFAULT_MIN_REFRESH_GAP_SECS,
+            ),
+            idle_timeout: secs("LEADERBOARD_IDLE_TIMEOUT_SECS", DEFAULT_IDLE_TIMEOUT_SECS),
+        }
+    }
+}
+
+/// Spawn the background task that keeps leaderboard snapshots fresh.
+///
+/// The task warms the default leaderboard immediately, then refreshes every
+/// tracked leaderboard on `config.interval` and after new fills arrive.
+pub fn spawn_leaderboard_refresher(
+    state: Arc<AppState>,
+    config: RefresherConfig,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        if !state.competition_config.is_configured() {
+            tracing::info!("Leaderboard refresher idle: competition not configured");
+            return;
+        }
+
+        // The default leaderboard is always kept so its history stays complete
+        let default_config = default_leaderboard_config(&state);
+        state.leaderboards.pin(default_config.clone()).await;
+        if let Err(e) = refresh_leaderboard(&state, &default_config).await {
+            tracing::warn!("Failed to warm default leaderboard: {}", e);
+        }
+
+        let mut fill_updates = state.indexer.subscribe_fill_updates();
+        let mut fills_closed = false;
+        let mut ticker = tokio::time::interval(config.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick completes immediately; the default was just warmed.
+        ticker.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                changed = fill_updates.changed(), if !fills_closed => {
+                    if changed.is_err() {
+                        fills_closed = true;
+                        continue;
+                    }
+                    // Coalesce bursts of fills into a single refresh
+                    tokio::time::sleep(config.min_refresh_gap).await;
+                    fill_updates.borrow_and_update();
+                    tracing::debug!("Refreshing leaderboards after new fills");
+                }
+            }
+
+            refresh_all(&state, &config).await;
+        }
+    })
+}
+
+/// Refresh every tracked leaderboard, evicting ones nobody reads anymore.
+async fn refresh_all(state: &AppState, config: &RefresherConfig) {
+    let evicted = state
+        .leaderboards
+        .evict_idle(config.idle_timeout.as_millis() as u64)
+        .await;
+    if evicted > 0 {
+        tracing::debug!("Evicted {} idle leaderboard snapshots", evicted);
+    }
+
+    for leaderboard_config in state.leaderboards.tracked_configs().await {
+        // Keep serving the previous snapshot, flagged stale, if a refresh fails
+        if let Err(e) = refresh_leaderboard(state, &leaderboard_config).await {
+            tracing::warn!("Leaderboard refresh failed: {}", e);
+            state
+                .leaderboards
+                .mark_refresh_failed(&leaderboard_config)
+                .await;
+        }
+    }
+}
+
+/// The leaderboard served for a request with no query parameters.
+fn default_leaderboard_config(state: &AppState) -> LeaderboardConfig {
+    LeaderboardConfig {
+        target_builders: state.competition_config.target_builders.clone(),
+        builder_only: state.competition_config.builder_only,
+        max_start_capital: None,
+        coin: None,
+        from_ms: None,
+        to_ms: None,
+        metric: LeaderboardMetric::Volume,
+        exclude_flagged_volume: state.competition_config.exclude_flagged_volume,
+    }
+}
+
+/// Wash trading detector covering the competition's participants and
+/// configured linked address groups.
+fn wash_trade_detector(state: &AppState) -> WashTradeDetector {
+    let config = &state.competition_config;
+    config.linked_addresses.iter().fold(
+        WashTradeDetector::new().with_participants(&config.competition_users),
+        |detector, group| detector.with_linked_group(group),
+    )
+}
+
+/// Compute a leaderboard and publish it as the latest snapshot for `config`.
+///
+/// Streaming clients are notified if the ranking differs from the previous
+/// snapshot.
+pub(crate) async fn refresh_leaderboard(
+    state: &AppState,
+    config: &LeaderboardConfig,
+) -> Result<Arc<LeaderboardSnapshot>, ApiError> {
+    let users = &state.competition_config.competition_users;
+    let detector = wash_trade_detector(state);
+
+    // Calculate leaderboard based on whether builder is configured
+    let (stats, builder_fills_loaded) = if !config.target_builders.is_empty() {
+        // Fetch fills of every target builder for the date range
+        let enricher =
+            fetch_builder_fills(state, &config.target_builders, config.from_ms, config.to_ms)
+                .await?;
+        let fills_count = enricher.total_fills();
+        let checker = FillEnricherChecker::new(enricher);
+
+        tracing::info!("Loaded {} builder fills for leaderboard", fills_count);
+
+        let stats =
+            calculate_leaderboard_with_detector(&state.indexer, users, config, &checker, &detector)
+                .await?;
+        (stats, fills_count)
+    } else {
+        // No builder configured, use no-op checker
+        let checker = NoBuilderChecker;
+
+        let stats =
+            calculate_leaderboard_with_detector(&state.indexer, users, config, &checker, &detector)
+                .await?;
+        (stats, 0)
+    };
+
+    let total_users = stats.len();
+    let ranked = rank_leaderboard(stats, config.metric, config.builder_only);
+    let previous = state.leaderboards.peek(config).await;
+    let snapshot = state
+        .leaderboards
+        .publish(config.clone(), ranked, total_users)
+        .await;
+
+    let changed = previous.is_none_or(|previous| {
+        previous.entries != snapshot.entries || previous.total_users != snapshot.total_users
+    });
+    if changed {
+        state.events.publish(Event::Leaderboard {
+            config: config.clone(),
+            snapshot: snapshot.clone(),
+        });
+    }
+
+    tracing::info!(
+        "Leaderboard v{}: {} total users, {} builder fills",
+        snapshot.version,
+        total_users,
+        builder_fills_loaded
+    );
+
+    Ok(snapshot)
+}
+
+/// Fetch fills of all given builders for a date range and index them in a
+/// single enricher.
+async fn fetch_builder_fills(
+    state: &AppState,
+    builder_addrs: &[String],
+    from_ms: Option<i64>,
+    to_ms: Option<i64>,
+) -> Result<FillEnricher, ApiError> {
+    // Only competition users' builder fills are ever matched, so without
+    // any users there is nothing worth downloading
+    let users = &state.competition_config.competition_users;
+    if users.is_empty() {
+        return Ok(FillEnricher::new(Vec::new()));
+    }
+    let filter = FillFilter::new().with_users(users);
+    let fills = load_builder_fills(state, builder_addrs, from_ms, to_ms, &filter).await?;
+    Ok(FillEnricher::new(fills))
+}
+
+/// Client for the archives of builder `addr`, keeping only rows passing
+/// `filter`.
+///
+/// Set up like the indexer's own clients: same stats endpoint, cache and
+/// recording, skipping malformed rows rather than failing the day.
+pub(crate) fn builder_client(
+    state: &AppState,
+    addr: &str,
+    filter: &FillFilter,
+) -> Result<BuilderDataClient, ApiError> {
+    let config = state.indexer.config();
+    let client = config
+        .builder_client(addr)
+        .map_err(|e| ApiError::BadRequest(format!("invalid builder address '{}': {}", addr, e)))?;
+    Ok(client.with_filter(filter.clone()))
+}
+
+/// Download fills of all given builders for a date range.
+///
+/// Builder data is organized by builder and date, so we fetch every date in
+/// the range for each builder, several dates at a time. Without `from_ms`
+/// the last 7 days are used. Archives go through the indexer's builder cache
+/// when one is configured.
+///
+/// Fails if any date could not be fetched after retries; dates without data
+/// are skipped. Only rows passing `filter` are parsed and kept.
+pub(crate) async fn load_builder_fills(
+    state: &AppState,
+    builder_addrs: &[String],
+    from_ms: Option<i64>,
+    to_ms: Option<i64>,
+    filter: &FillFilter,
+) -> Result<Vec<BuilderFill>, ApiError> {
+    let clients = builder_addrs
+        .iter()
+        .map(|addr| builder_client(state, addr, filter))
+        .collect::<Result<Vec<_>, ApiError>>()?;
+
+    // Determine date range
+    let now = Utc::now();
+    let from_date = match from_ms {
+        Some(ms) => date_of(ms)?,
+        None => (now - Duration::days(7)).date_naive(),
+    };
+    let to_date = match to_ms {
+        Some(ms) => date_of(ms)?,
+        None => now.date_naive(),
+    };
+
+    // Collect fills from all builders and dates in range. Dates without an
+    // archive are expected; anything else fails the load rather than
+    // silently producing a leaderboard with fills missing.
+    let mut all_fills = Vec::new();
+
+    for client in &clients {
+        let report = client
+            .fetch_fills_range_report(from_date, to_date)
+            .await
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+
+        if !report.is_complete() {
+            let dates: Vec<String> = report
+                .failed
+                .iter()
+                .map(|(date, _)| date.to_string())
+                .collect();
+            let (_, first_error) = &report.failed[0];
+            return Err(ApiError::Internal(format!(
+                "failed to fetch fills of builder {} for {} date(s) ({}): {}",
+                client.builder_address(),
+                dates.len(),
+                dates.join(", "),
+                first_error
+            )));
+        }
+
+        tracing::debug!(
+            "Fetched {} fills for builder {} ({} dates, {} without data, {} rows skipped)",
+            report.fills.len(),
+            client.builder_address(),
+            report.fetched.len(),
+            report.missing.len(),
+            report.skipped_rows()
+        );
+        all_fills.extend(report.fills);
+    }
+
+    tracing::info!(
+        "Total builder fills fetched: {} (from {} to {})",
+        all_fills.len(),
+        from_date,
+        to_date
+    );
+
+    Ok(all_fills)
+}
+
+/// The UTC date of a millisecond timestamp.
+pub(crate) fn date_of(ms: i64) -> Result<NaiveDate, ApiError> {
+    Utc.timestamp_millis_opt(ms)
+        .single()
+        .map(|time| time.date_naive())
+        .ok_or_else(|| ApiError::BadRequest(format!("timestamp {} is out of range", ms)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = RefresherConfig::default();
+        assert_eq!(config.interval, std::time::Duration::from_secs(60));
+        assert_eq!(config.min_refresh_gap, std::time::Duration::from_secs(5));
+        assert_eq!(config.idle_timeout, std::time::Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_date_of() {
+        assert_eq!(
+            date_of(0).unwrap(),
+            NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()
+        );
+        assert!(matches!(date_of(i64::MAX), Err(ApiError::BadRequest(_))));
+    }
+}
@@ -0,0 +1,223 @@
+//! Last known good data, served when the upstream API is failing.
+//!
+//! Every successful upstream read behind `/v1/trades` and `/v1/pnl` is kept
+//! per request. If a later identical request fails because Hyperliquid is
+//! degraded, the handler answers with the kept data, flagged `stale` and
+//! with its age, instead of an error.
+
+use hl_indexer::IndexerError;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::error::ApiError;
+
+/// Requests remembered per store; the oldest is dropped beyond this.
+const DEFAULT_CAPACITY: usize = 256;
+
+/// How fresh a served response is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Freshness {
+    /// Whether the upstream failed and older data was served.
+    pub stale: bool,
+    /// How long ago the served data was fetched, in milliseconds.
+    pub data_age_ms: u64,
+}
+
+impl Freshness {
+    /// Data fetched for this request.
+    pub(crate) const FRESH: Self = Self {
+        stale: false,
+        data_age_ms: 0,
+    };
+
+    /// Data fetched at `fetched_at_ms` and served because a refresh failed.
+    pub(crate) fn stale_since(fetched_at_ms: u64) -> Self {
+        Self {
+            stale: true,
+            data_age_ms: now_ms().saturating_sub(fetched_at_ms),
+        }
+    }
+}
+
+struct Stored<V> {
+    value: V,
+    stored_at_ms: u64,
+    weight: usize,
+}
+
+/// The latest successful result per request key, bounded in size.
+///
+/// Both the number of entries and their total weight (e.g. fills held) are
+/// capped; the oldest entries are dropped to stay within either bound.
+pub(crate) struct LastKnownGood<K, V> {
+    capacity: usize,
+    max_weight: usize,
+    weigh: fn(&V) -> usize,
+    entries: Mutex<HashMap<K, Stored<V>>>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> LastKnownGood<K, V> {
+    pub(crate) fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            max_weight: usize::MAX,
+            weigh: |_| 1,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Cap the total `weigh`t of remembered values at `max_weight`.
+    ///
+    /// A single value heavier than the cap is not remembered.
+    pub(crate) fn with_max_weight(mut self, max_weight: usize, weigh: fn(&V) -> usize) -> Self {
+        self.max_weight = max_weight;
+        self.weigh = weigh;
+        self
+    }
+
+    /// Remember a successful `result` for `key`, or fall back to the last
+    /// remembered value if the upstream failed.
+    ///
+    /// Errors that are not upstream failures, and upstream failures with
+    /// nothing remembered, are returned as-is.
+    pub(crate) fn resolve(
+        &self,
+        key: K,
+        result: Result<V, IndexerError>,
+    ) -> Result<(V, Freshness), ApiError> {
+        match result {
+            Ok(value) => {
+                self.insert(key, value.clone());
+                Ok((value, Freshness::FRESH))
+            }
+            Err(e) if e.is_upstream_failure() => match self.get(&key) {
+                Some(stored) => {
+                    tracing::warn!("Serving stale data after upstream failure: {}", e);
+                    Ok((stored.value, Freshness::stale_since(stored.stored_at_ms)))
+                }
+                None => Err(e.into()),
+            },
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<K, Stored<V>>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn get(&self, key: &K) -> Option<Stored<V>> {
+        self.lock().get(key).map(|stored| Stored {
+            value: stored.value.clone(),
+            stored_at_ms: stored.stored_at_ms,
+            weight: stored.weight,
+        })
+    }
+
+    fn insert(&self, key: K, value: V) {
+        let weight = (self.weigh)(&value);
+        let mut entries = self.lock();
+        entries.remove(&key);
+        if weight > self.max_weight {
+            return;
+        }
+        let mut total: usize = entries.values().map(|stored| stored.weight).sum();
+        while entries.len() >= self.capacity || total + weight > self.max_weight {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, stored)| stored.stored_at_ms)
+                .map(|(key, _)| key.clone());
+            let Some(removed) = oldest.and_then(|oldest| entries.remove(&oldest)) else {
+                break;
+            };
+            total -= removed.weight;
+        }
+        let stored_at_ms = now_ms();
+        entries.insert(
+            key,
+            Stored {
+                value,
+                stored_at_ms,
+                weight,
+            },
+        );
+    }
+}
+
+/// Current time in milliseconds since epoch.
+fn now_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis().max(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hl_ingestion::IngestionError;
+
+    fn upstream_down() -> IndexerError {
+        IndexerError::Ingestion(IngestionError::Network("503".to_string()))
+    }
+
+    #[test]
+    fn test_serves_last_good_value_on_upstream_failure() {
+        let store = LastKnownGood::new();
+
+        let (value, freshness) = store.resolve("a", Ok(1)).unwrap();
+        assert_eq!((value, freshness), (1, Freshness::FRESH));
+
+        let (value, freshness) = store.resolve("a", Err(upstream_down())).unwrap();
+        assert_eq!(value, 1);
+        assert!(freshness.stale);
+
+        // Nothing remembered for this key
+        assert!(store.resolve("b", Err(upstream_down())).is_err());
+    }
+
+    #[test]
+    fn test_request_errors_are_not_masked() {
+        let store = LastKnownGood::new();
+        store.resolve("a", Ok(1)).unwrap();
+
+        let err = IndexerError::InvalidAddress("a".to_string());
+        assert!(matches!(
+            store.resolve("a", Err(err)),
+            Err(ApiError::Indexer(IndexerError::InvalidAddress(_)))
+        ));
+    }
+
+    #[test]
+    fn test_capacity_drops_oldest() {
+        let store = LastKnownGood::with_capacity(2);
+        store.resolve("a", Ok(1)).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        store.resolve("b", Ok(2)).unwrap();
+        store.resolve("c", Ok(3)).unwrap();
+
+        assert!(store.resolve("a", Err(upstream_down())).is_err());
+        assert_eq!(store.resolve("b", Err(upstream_down())).unwrap().0, 2);
+        assert_eq!(store.resolve("c", Err(upstream_down())).unwrap().0, 3);
+    }
+
+    #[test]
+    fn test_max_weight_drops_oldest() {
+        let store = LastKnownGood::new().with_max_weight(5, |value: &Vec<u8>| value.len());
+        store.resolve("a", Ok(vec![1, 1])).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        store.resolve("b", Ok(vec![2, 2])).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        store.resolve("c", Ok(vec![3, 3])).unwrap();
+
+        assert!(store.resolve("a", Err(upstream_down())).is_err());
+        assert!(store.resolve("b", Err(upstream_down())).is_ok());
+        assert!(store.resolve("c", Err(upstream_down())).is_ok());
+
+        // Too heavy to remember at all
+        store.resolve("d", Ok(vec![4; 6])).unwrap();
+        assert!(store.resolve("d", Err(upstream_down())).is_err());
+        assert!(store.resolve("c", Err(upstream_down())).is_ok());
+    }
+}
@@ -1,26 +1,81 @@
 //! Application state for the API server.
 
-use hl_indexer::Indexer;
+use hl_indexer::filter::TradeFilter;
+use hl_indexer::leaderboard::LeaderboardConfig;
+use hl_indexer::pagination::{FillPage, PageRequest};
+use hl_indexer::snapshot::{
+    SnapshotStore, DEFAULT_HISTORY_INTERVAL_MS, DEFAULT_HISTORY_RETENTION_MS, DEFAULT_MAX_TRACKED,
+};
+use hl_indexer::{Indexer, PnLSummary};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::events::EventBus;
+use crate::stale::LastKnownGood;
+
+/// User (lowercase), time window, filter and page of a trades request.
+pub(crate) type TradesKey = (String, Option<i64>, Option<i64>, TradeFilter, PageRequest);
+
+/// User (lowercase), time window and asset filter of a PnL request.
+pub(crate) type PnLKey = (String, Option<i64>, Option<i64>, Option<String>);
+
+/// Fills remembered across all trades responses kept for upstream outages.
+const MAX_STALE_FILLS: usize = 50_000;
+
+/// Competition ID used when `COMPETITION_ID` is not set.
+pub const DEFAULT_COMPETITION_ID: &str = "default";
 
 /// Configuration for trading competitions.
 #[derive(Debug, Clone)]
 pub struct CompetitionConfig {
-    /// Target builder address (must be lowercase).
-    pub target_builder: Option<String>,
+    /// Identifier used in `/v1/competitions/{id}/...` routes.
+    pub competition_id: String,
+
+    /// Target builder addresses (lowercase). Fills routed by any of them
+    /// count as builder fills.
+    pub target_builders: Vec<String>,
 
     /// Whether to enforce builder-only mode by default.
     pub builder_only: bool,
 
     /// List of user addresses participating in the competition.
     pub competition_users: Vec<String>,
+
+    /// Groups of addresses known to belong to the same person.
+    pub linked_addresses: Vec<Vec<String>>,
+
+    /// Whether wash trading volume is excluded from leaderboards by default.
+    pub exclude_flagged_volume: bool,
+
+    /// Minimum spacing between retained leaderboard history snapshots.
+    pub history_interval_ms: u64,
+
+    /// How long leaderboard history is retained.
+    pub history_retention_ms: u64,
+
+    /// Directory leaderboard history is persisted to, so it survives
+    /// restarts. History is kept in memory only when unset.
+    pub history_dir: Option<PathBuf>,
+
+    /// Maximum number of leaderboard parameter combinations kept up to date
+    /// at once.
+    pub max_tracked_leaderboards: usize,
 }
 
 impl Default for CompetitionConfig {
     fn default() -> Self {
         Self {
-            target_builder: None,
+            competition_id: DEFAULT_COMPETITION_ID.to_string(),
+            target_builders: Vec::new(),
             builder_only: false,
             competition_users: Vec::new(),
+            linked_addresses: Vec::new(),
+            exclude_flagged_volume: false,
+            history_interval_ms: DEFAULT_HISTORY_INTERVAL_MS,
+            history_retention_ms: DEFAULT_HISTORY_RETENTION_MS,
+            history_dir: None,
+            max_tracked_leaderboards: DEFAULT_MAX_TRACKED,
         }
     }
 }
@@ -29,13 +84,28 @@ impl CompetitionConfig {
     /// Create a new CompetitionConfig from environment variables.
     ///
     /// Environment variables:
-    /// - `TARGET_BUILDER`: Builder address (will be lowercased)
+    /// - `COMPETITION_ID`: Competition identifier (default: "default")
+    /// - `TARGET_BUILDER`: Comma-separated builder addresses (will be lowercased)
     /// - `BUILDER_ONLY`: "true" to enable builder-only mode
     /// - `COMPETITION_USERS`: Comma-separated list of user addresses
+    /// - `LINKED_ADDRESSES`: Groups of linked addresses, separated by `;`, each a
+    ///   comma-separated list (e.g. `0xa,0xb;0xc,0xd`)
+    /// - `EXCLUDE_FLAGGED_VOLUME`: "true" to drop wash trading volume by default
+    /// - `LEADERBOARD_HISTORY_INTERVAL_SECS`: Spacing of retained history snapshots (default: 900)
+    /// - `LEADERBOARD_HISTORY_RETENTION_DAYS`: How long history is kept (default: 30)
+    /// - `LEADERBOARD_HISTORY_DIR`: Directory to persist history in, under a
+    ///   subdirectory named after the competition ID (default: memory only)
+    /// - `LEADERBOARD_MAX_TRACKED`: Most leaderboard parameter combinations kept at once (default: 64)
     pub fn from_env() -> Self {
-        let target_builder = std::env::var("TARGET_BUILDER")
+        let competition_id = std::env::var("COMPETITION_ID")
             .ok()
-            .map(|s| s.to_lowercase());
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_COMPETITION_ID.to_string());
+
+        let target_builders = std::env::var("TARGET_BUILDER")
+            .ok()
+            .map(|s| parse_address_list(&s))
+            .unwrap_or_default();
 
         let builder_only = std::env::var("BUILDER_ONLY")
             .map(|s| s.to_lowercase() == "true")
@@ -43,18 +113,51 @@ impl CompetitionConfig {
 
         let competition_users = std::env::var("COMPETITION_USERS")
             .ok()
-            .map(|s| {
-                s.split(',')
-                    .map(|addr| addr.trim().to_lowercase())
-                    .filter(|addr| !addr.is_empty())
-                    .collect()
-            })
+            .map(|s| parse_address_list(&s))
+            .unwrap_or_default();
+
+        let linked_addresses = std::env::var("LINKED_ADDRESSES")
+            .ok()
+            .map(|s| parse_linked_addresses(&s))
             .unwrap_or_default();
 
+        let exclude_flagged_volume = std::env::var("EXCLUDE_FLAGGED_VOLUME")
+            .map(|s| s.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let history_interval_ms = std::env::var("LEADERBOARD_HISTORY_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .map(|secs| secs * 1000)
+            .unwrap_or(DEFAULT_HISTORY_INTERVAL_MS);
+
+        let history_retention_ms = std::env::var("LEADERBOARD_HISTORY_RETENTION_DAYS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .map(|days| days * 24 * 60 * 60 * 1000)
+            .unwrap_or(DEFAULT_HISTORY_RETENTION_MS);
+
+        let history_dir = std::env::var("LEADERBOARD_HISTORY_DIR")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .map(|dir| PathBuf::from(dir).join(&competition_id));
+
+        let max_tracked_leaderboards = std::env::var("LEADERBOARD_MAX_TRACKED")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_MAX_TRACKED);
+
         Self {
-            target_builder,
+            competition_id,
+            target_builders,
             builder_only,
             competition_users,
+            linked_addresses,
+            exclude_flagged_volume,
+            history_interval_ms,
+            history_retention_ms,
+            history_dir,
+            max_tracked_leaderboards,
         }
     }
 
@@ -65,7 +168,7 @@ impl CompetitionConfig {
 
     /// Check if builder-only mode is enabled.
     pub fn is_builder_only(&self) -> bool {
-        self.builder_only && self.target_builder.is_some()
+        self.builder_only && !self.target_builders.is_empty()
     }
 
     /// Get the number of competition users.
@@ -74,6 +177,28 @@ impl CompetitionConfig {
     }
 }
 
+/// Parse a comma-separated list of addresses, lowercased.
+pub(crate) fn parse_address_list(value: &str) -> Vec<String> {
+    let mut addresses: Vec<String> = Vec::new();
+    for addr in value.split(',').map(|addr| addr.trim().to_lowercase()) {
+        if !addr.is_empty() && !addresses.contains(&addr) {
+            addresses.push(addr);
+        }
+    }
+    addresses
+}
+
+/// Parse `;`-separated groups of comma-separated addresses.
+///
+/// Groups with fewer than two addresses link nothing and are dropped.
+fn parse_linked_addresses(value: &str) -> Vec<Vec<String>> {
+    value
+        .split(';')
+        .map(parse_address_list)
+        .filter(|group| group.len() > 1)
+        .collect()
+}
+
 /// Shared application state.
 pub struct AppState {
     /// The indexer for fetching and processing data.
@@ -81,6 +206,22 @@ pub struct AppState {
 
     /// Competition configuration.
     pub competition_config: CompetitionConfig,
+
+    /// Latest leaderboard snapshots, kept fresh by the background refresher.
+    pub leaderboards: SnapshotStore,
+
+    /// Leaderboards being computed for their first request, so concurrent
+    /// requests for the same config wait for one computation.
+    pub(crate) first_computes: Mutex<HashMap<LeaderboardConfig, Arc<tokio::sync::Mutex<()>>>>,
+
+    /// Last page of trades served per request, for when the upstream API fails.
+    pub(crate) last_trades: LastKnownGood<TradesKey, FillPage>,
+
+    /// Last PnL served per request, for when the upstream API fails.
+    pub(crate) last_pnl: LastKnownGood<PnLKey, PnLSummary>,
+
+    /// Live updates for streaming clients.
+    pub(crate) events: EventBus,
 }
 
 impl AppState {
@@ -89,14 +230,61 @@ impl AppState {
         Self {
             indexer,
             competition_config: CompetitionConfig::default(),
+            leaderboards: SnapshotStore::new(),
+            first_computes: Mutex::new(HashMap::new()),
+            last_trades: LastKnownGood::new()
+                .with_max_weight(MAX_STALE_FILLS, |page: &FillPage| page.fills.len()),
+            last_pnl: LastKnownGood::new(),
+            events: EventBus::new(),
         }
     }
 
     /// Create a new application state with indexer and competition config.
     pub fn with_config(indexer: Indexer, competition_config: CompetitionConfig) -> Self {
+        let mut leaderboards = SnapshotStore::new()
+            .with_history(
+                competition_config.history_interval_ms,
+                competition_config.history_retention_ms,
+            )
+            .with_max_tracked(competition_config.max_tracked_leaderboards);
+        if let Some(dir) = &competition_config.history_dir {
+            leaderboards = leaderboards.with_history_dir(dir);
+        }
         Self {
             indexer,
             competition_config,
+            leaderboards,
+            first_computes: Mutex::new(HashMap::new()),
+            last_trades: LastKnownGood::new()
+                .with_max_weight(MAX_STALE_FILLS, |page: &FillPage| page.fills.len()),
+            last_pnl: LastKnownGood::new(),
+            events: EventBus::new(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_address_list() {
+        assert_eq!(
+            parse_address_list(" 0xA,0xb,,0xa "),
+            vec!["0xa".to_string(), "0xb".to_string()]
+        );
+        assert!(parse_address_list("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_linked_addresses() {
+        let groups = parse_linked_addresses("0xA, 0xb;0xc;;0xD,0xe,");
+        assert_eq!(
+            groups,
+            vec![
+                vec!["0xa".to_string(), "0xb".to_string()],
+                vec!["0xd".to_string(), "0xe".to_string()],
+            ]
+        );
+    }
+}
@@ -0,0 +1,258 @@
+//! Server-sent event streams of live updates.
+//!
+//! Each connection subscribes to the [event bus](crate::events) and is sent
+//! the events it asked for, as JSON:
+//!
+//! - `GET /v1/stream/trades?user=` - a `trades` event for each batch of the
+//!   user's new fills
+//! - `GET /v1/stream/pnl?user=` - a `pnl` event with the user's all-time PnL
+//!   after each batch
+//! - `GET /v1/competitions/{id}/leaderboard/stream` - the current leaderboard,
+//!   then a `leaderboard` event whenever its ranking changes. Accepts the
+//!   `/v1/leaderboard` parameters except `asOfMs`.
+//!
+//! A client that reads too slowly misses the oldest updates and is sent a
+//! `lagged` event saying how many; it should refetch the regular endpoint.
+
+use axum::extract::{Path, Query, State};
+use axum::response::sse::{Event as SseEvent, KeepAlive, Sse};
+use futures::Stream;
+use serde::Serialize;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+
+use crate::error::{ApiError, ErrorResponse};
+use crate::events::Event;
+use crate::handlers::{
+    latest_snapshot, leaderboard_config, leaderboard_response, pnl_response, validate_user,
+};
+use crate::stale::Freshness;
+use crate::state::AppState;
+use crate::types::{
+    LaggedEvent, LeaderboardQuery, LeaderboardResponse, PnLResponse, StreamQuery, TradesEvent,
+};
+
+/// Events queued per connection while the client is reading.
+const CONNECTION_BUFFER: usize = 64;
+
+/// How often a streamed leaderboard is marked as read, so the refresher
+/// keeps it up to date for as long as someone is listening.
+const KEEP_TRACKED_INTERVAL: Duration = Duration::from_secs(60);
+
+/// GET /v1/stream/trades - Stream a user's new fills.
+#[utoipa::path(
+    get,
+    path = "/v1/stream/trades",
+    operation_id = "stream_trades",
+    summary = "Stream a user's new trades",
+    tag = "stream",
+    params(StreamQuery),
+    responses(
+        (
+            status = 200,
+            description = "`trades` events, plus `lagged` events (`LaggedEvent`) if the client falls behind",
+            content_type = "text/event-stream",
+            body = TradesEvent,
+        ),
+        (status = 400, description = "Invalid user", body = ErrorResponse),
+    )
+)]
+pub async fn trades(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>, ApiError> {
+    let user = stream_user(&query)?;
+    let events = forward(&state, move |event| match event {
+        Event::Fills { user: of, fills } if *of == user => Some(json_event(
+            "trades",
+            &TradesEvent {
+                user: user.clone(),
+                trades: fills.iter().cloned().map(Into::into).collect(),
+            },
+        )),
+        _ => None,
+    });
+    Ok(sse_response(events))
+}
+
+/// GET /v1/stream/pnl - Stream a user's PnL as new fills arrive.
+#[utoipa::path(
+    get,
+    path = "/v1/stream/pnl",
+    operation_id = "stream_pnl",
+    summary = "Stream a user's PnL",
+    tag = "stream",
+    params(StreamQuery),
+    responses(
+        (
+            status = 200,
+            description = "`pnl` events, plus `lagged` events (`LaggedEvent`) if the client falls behind",
+            content_type = "text/event-stream",
+            body = PnLResponse,
+        ),
+        (status = 400, description = "Invalid user", body = ErrorResponse),
+    )
+)]
+pub async fn pnl(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>, ApiError> {
+    let user = stream_user(&query)?;
+    let events = forward(&state, move |event| match event {
+        Event::PnL { user: of, summary } if *of == user => Some(json_event(
+            "pnl",
+            &pnl_response(user.clone(), summary, None, None, Freshness::FRESH),
+        )),
+        _ => None,
+    });
+    Ok(sse_response(events))
+}
+
+/// GET /v1/competitions/{id}/leaderboard/stream - Stream leaderboard changes.
+#[utoipa::path(
+    get,
+    path = "/v1/competitions/{id}/leaderboard/stream",
+    operation_id = "stream_leaderboard",
+    summary = "Stream leaderboard changes",
+    tag = "stream",
+    params(
+        ("id" = String, Path, description = "Competition ID (`COMPETITION_ID`)"),
+        LeaderboardQuery,
+    ),
+    responses(
+        (
+            status = 200,
+            description = "`leaderboard` events, plus `lagged` events (`LaggedEvent`) if the client falls behind",
+            content_type = "text/event-stream",
+            body = LeaderboardResponse,
+        ),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Unknown competition", body = ErrorResponse),
+        (status = 503, description = "Too many leaderboards tracked", body = ErrorResponse),
+    )
+)]
+pub async fn leaderboard(
+    State(state): State<Arc<AppState>>,
+    Path(competition_id): Path<String>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Sse<impl Stream<Item = Result<SseEvent, Infallible>>>, ApiError> {
+    if competition_id != state.competition_config.competition_id {
+        return Err(ApiError::NotFound(format!(
+            "competition '{}' not found",
+            competition_id
+        )));
+    }
+    if query.as_of_ms.is_some() {
+        return Err(ApiError::BadRequest(
+            "asOfMs cannot be streamed; use /v1/leaderboard".to_string(),
+        ));
+    }
+    let config = leaderboard_config(&state, &query)?;
+
+    // Subscribe before reading the current snapshot so no change is missed
+    let mut events = state.events.subscribe();
+    let snapshot = latest_snapshot(&state, &config).await?;
+    let stale = state.leaderboards.is_stale(&config).await;
+    let current = leaderboard_response(&state, &config, &query, &snapshot, stale).await;
+
+    let (sender, receiver) = mpsc::channel(CONNECTION_BUFFER);
+    sender
+        .try_send(json_event("leaderboard", &current).id(snapshot.version.to_string()))
+        .expect("a new connection has room for one event");
+
+    tokio::spawn(async move {
+        let mut sent_version = snapshot.version;
+        let mut keep_tracked = tokio::time::interval(KEEP_TRACKED_INTERVAL);
+        loop {
+            let received = tokio::select! {
+                _ = sender.closed() => return,
+                _ = keep_tracked.tick() => {
+                    state.leaderboards.get(&config).await;
+                    continue;
+                }
+                received = events.recv() => received,
+            };
+            let event = match received {
+                Ok(event) => match &*event {
+                    Event::Leaderboard {
+                        config: of,
+                        snapshot,
+                    } if *of == config && snapshot.version > sent_version => {
+                        sent_version = snapshot.version;
+                        let response =
+                            leaderboard_response(&state, &config, &query, snapshot, false).await;
+                        json_event("leaderboard", &response).id(snapshot.version.to_string())
+                    }
+                    _ => continue,
+                },
+                Err(RecvError::Lagged(skipped)) => lagged_event(skipped),
+                Err(RecvError::Closed) => return,
+            };
+            if sender.send(event).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    Ok(sse_response(receiver))
+}
+
+/// Validate the user of a stream request, lowercased to match bus events.
+fn stream_user(query: &StreamQuery) -> Result<String, ApiError> {
+    validate_user(&query.user)?;
+    Ok(query.user.to_lowercase())
+}
+
+/// Send the bus events `render` picks out to a new connection until the
+/// client disconnects.
+fn forward<F>(state: &AppState, mut render: F) -> mpsc::Receiver<SseEvent>
+where
+    F: FnMut(&Event) -> Option<SseEvent> + Send + 'static,
+{
+    let mut events = state.events.subscribe();
+    let (sender, receiver) = mpsc::channel(CONNECTION_BUFFER);
+    tokio::spawn(async move {
+        loop {
+            let received = tokio::select! {
+                _ = sender.closed() => return,
+                received = events.recv() => received,
+            };
+            let event = match received {
+                Ok(event) => match render(&event) {
+                    Some(event) => event,
+                    None => continue,
+                },
+                Err(RecvError::Lagged(skipped)) => lagged_event(skipped),
+                Err(RecvError::Closed) => return,
+            };
+            if sender.send(event).await.is_err() {
+                return;
+            }
+        }
+    });
+    receiver
+}
+
+/// Serve a connection's queued events, with keep-alive comments in between.
+fn sse_response(
+    events: mpsc::Receiver<SseEvent>,
+) -> Sse<impl Stream<Item = Result<SseEvent, Infallible>>> {
+    let stream = futures::stream::unfold(events, |mut events| async move {
+        events.recv().await.map(|event| (Ok(event), events))
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+fn json_event(name: &str, data: &impl Serialize) -> SseEvent {
+    SseEvent::default()
+        .event(name)
+        .json_data(data)
+        .expect("API responses serialize to JSON")
+}
+
+fn lagged_event(skipped: u64) -> SseEvent {
+    json_event("lagged", &LaggedEvent { skipped })
+}
@@ -1,11 +1,15 @@
 //! API request and response types.
 
 use hl_indexer::leaderboard::LeaderboardMetric;
+use hl_indexer::snapshot::LeaderboardSnapshot;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::{IntoParams, ToSchema};
 
 /// Query parameters for fetching user trades/fills.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct TradesQuery {
     /// User address (required).
     pub user: String,
@@ -15,12 +19,37 @@ pub struct TradesQuery {
     pub to_ms: Option<i64>,
     /// Filter by asset symbol (e.g., "BTC", "ETH").
     pub asset: Option<String>,
+    /// Filter by asset symbols (comma-separated), in addition to `asset`.
+    pub assets: Option<String>,
     /// Maximum number of results to return.
     pub limit: Option<usize>,
+    /// Sort order: "desc" (newest first, default) or "asc".
+    pub order: Option<String>,
+    /// Page cursor from a previous response's `next_cursor` or `prev_cursor`.
+    pub cursor: Option<String>,
+    /// Filter by side: "buy" or "sell".
+    pub side: Option<String>,
+    /// Filter by direction: "open", "close", "flip", "liquidation" or "other".
+    pub direction: Option<String>,
+    /// Filter by liquidity: "maker" or "taker".
+    pub liquidity: Option<String>,
+    /// Minimum notional value (price × size).
+    pub min_notional: Option<Decimal>,
+    /// Maximum notional value (price × size).
+    pub max_notional: Option<Decimal>,
+    /// Filter by order ID.
+    pub order_id: Option<u64>,
+    /// Only return fills attributed to a configured builder.
+    #[serde(default)]
+    pub builder_only: bool,
+    /// Only return fills that realized PnL.
+    #[serde(default)]
+    pub nonzero_pnl: bool,
 }
 
 /// Query parameters for fetching PnL.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct PnLQuery {
     /// User address (required).
     pub user: String,
@@ -33,7 +62,7 @@ pub struct PnLQuery {
 }
 
 /// A single trade/fill in the API response.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TradeResponse {
     /// Asset symbol.
     pub asset: String,
@@ -81,18 +110,29 @@ impl From<hl_types::UserFill> for TradeResponse {
 }
 
 /// Response containing a list of trades.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TradesResponse {
     /// List of trades.
     pub trades: Vec<TradeResponse>,
     /// Total count (may be limited by query).
     pub count: usize,
-    /// Whether more results exist beyond the limit.
+    /// Whether more results follow this page (see `next_cursor`).
     pub has_more: bool,
+    /// Sort order of `trades`.
+    pub order: String,
+    /// Cursor for the following page, if any.
+    pub next_cursor: Option<String>,
+    /// Cursor for the preceding page, if any.
+    pub prev_cursor: Option<String>,
+    /// Whether the upstream API failed and previously fetched trades were
+    /// served instead.
+    pub stale: bool,
+    /// Age of the served data in milliseconds (0 unless stale).
+    pub data_age_ms: u64,
 }
 
 /// Per-asset PnL breakdown in the API response.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AssetPnLResponse {
     /// Asset symbol.
     pub asset: String,
@@ -122,7 +162,7 @@ impl From<&hl_types::AssetPnL> for AssetPnLResponse {
 }
 
 /// PnL summary response.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PnLResponse {
     /// User address.
     pub user: String,
@@ -140,20 +180,106 @@ pub struct PnLResponse {
     pub from_ms: Option<i64>,
     /// Query time range end (if specified).
     pub to_ms: Option<i64>,
+    /// Whether the upstream API failed and a previously computed PnL was
+    /// served instead.
+    pub stale: bool,
+    /// Age of the served data in milliseconds (0 unless stale).
+    pub data_age_ms: u64,
 }
 
 /// Health check response.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct HealthResponse {
     /// Service status.
     pub status: String,
     /// Service version.
     pub version: String,
+    /// Circuit breaker state in front of the Hyperliquid API: `closed`,
+    /// `open` or `half_open`.
+    pub upstream: String,
+}
+
+/// Query parameters for a user's trade and PnL streams.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StreamQuery {
+    /// User address (required).
+    pub user: String,
+}
+
+/// Data of a `trades` stream event: fills the user just made.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TradesEvent {
+    /// User address (lowercase).
+    pub user: String,
+    /// The new trades, oldest first.
+    pub trades: Vec<TradeResponse>,
+}
+
+/// Data of a `lagged` stream event.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LaggedEvent {
+    /// Number of updates the client missed because it read too slowly.
+    pub skipped: u64,
+}
+
+/// Query parameter selecting the format of an export.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    /// "csv" or "ndjson". Defaults to the `Accept` header, then CSV.
+    pub format: Option<String>,
+}
+
+/// One round-trip (a position from open to flat) in an export.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RoundTripResponse {
+    /// Asset symbol.
+    pub asset: String,
+    /// Position direction: "long" or "short".
+    pub direction: String,
+    /// Opening fill timestamp (milliseconds since epoch).
+    pub opened_at_ms: u64,
+    /// Closing fill timestamp, or null while the position is open.
+    pub closed_at_ms: Option<u64>,
+    /// Number of fills.
+    pub fill_count: usize,
+    /// Largest absolute position size reached.
+    pub max_size: Decimal,
+    /// Notional volume traded.
+    pub volume: Decimal,
+    /// Fees paid.
+    pub fees: Decimal,
+    /// Realized PnL.
+    pub realized_pnl: Decimal,
+    /// Net PnL (realized - fees).
+    pub net_pnl: Decimal,
+}
+
+impl From<hl_indexer::roundtrip::RoundTrip> for RoundTripResponse {
+    fn from(trip: hl_indexer::roundtrip::RoundTrip) -> Self {
+        Self {
+            asset: trip.asset.symbol().to_string(),
+            direction: match trip.side {
+                hl_types::Side::Buy => "long".to_string(),
+                hl_types::Side::Sell => "short".to_string(),
+            },
+            opened_at_ms: trip.opened_at_ms,
+            closed_at_ms: trip.closed_at_ms,
+            fill_count: trip.fill_count,
+            max_size: trip.max_size,
+            volume: trip.volume,
+            fees: trip.fees,
+            realized_pnl: trip.realized_pnl,
+            net_pnl: trip.net_pnl(),
+        }
+    }
 }
 
 /// Query parameters for the leaderboard endpoint.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
 pub struct LeaderboardQuery {
     /// Filter by coin/asset symbol (e.g., "BTC", "ETH").
     pub coin: Option<String>,
@@ -167,8 +293,17 @@ pub struct LeaderboardQuery {
     /// Filter to only show users who used the builder.
     #[serde(default)]
     pub builder_only: bool,
-    /// Maximum start capital for return percentage calculation.
+    /// Optional cap on the capital base for return percentage calculation;
+    /// also the capital assumed when a user's own capital cannot be
+    /// determined.
     pub max_start_capital: Option<Decimal>,
+    /// Serve the leaderboard as it was at this time (milliseconds since epoch).
+    pub as_of_ms: Option<u64>,
+    /// Comma-separated subset of the configured builders to attribute fills to
+    /// (defaults to all of them).
+    pub builders: Option<String>,
+    /// Remove volume flagged as wash trading (defaults to `EXCLUDE_FLAGGED_VOLUME`).
+    pub exclude_flagged_volume: Option<bool>,
 }
 
 fn default_metric() -> String {
@@ -183,7 +318,7 @@ impl LeaderboardQuery {
 }
 
 /// A single entry in the leaderboard.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct LeaderboardEntryResponse {
     /// Rank (1-indexed).
@@ -205,6 +340,35 @@ pub struct LeaderboardEntryResponse {
     pub builder_fill_count: usize,
     /// Whether the user is tainted (has non-builder fills during open positions).
     pub tainted: bool,
+    /// Equity at the start of the window, used as the return % capital base.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub starting_equity: Option<Decimal>,
+    /// Deposits minus withdrawals during the window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub net_deposits: Option<Decimal>,
+    /// Volume flagged as wash trading or self-dealing.
+    pub flagged_volume: Decimal,
+    /// Number of fills flagged as wash trading or self-dealing.
+    pub flagged_fill_count: usize,
+    /// Per-builder breakdown, keyed by builder address.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub builders: BTreeMap<String, BuilderStatsResponse>,
+    /// Rank in the snapshot from 24 hours before this one (if retained).
+    pub previous_rank: Option<usize>,
+    /// Places gained since `previous_rank` (positive means moved up).
+    #[serde(rename = "rankChange24h")]
+    pub rank_change_24h: Option<i64>,
+}
+
+impl LeaderboardEntryResponse {
+    /// Fill in rank movement relative to an earlier snapshot.
+    pub fn with_baseline(mut self, baseline: Option<&LeaderboardSnapshot>) -> Self {
+        self.previous_rank = baseline.and_then(|b| b.rank_of(&self.user));
+        self.rank_change_24h = self
+            .previous_rank
+            .map(|previous| previous as i64 - self.rank as i64);
+        self
+    }
 }
 
 impl From<hl_indexer::leaderboard::LeaderboardEntry> for LeaderboardEntryResponse {
@@ -219,12 +383,111 @@ impl From<hl_indexer::leaderboard::LeaderboardEntry> for LeaderboardEntryRespons
             trade_count: entry.trade_count,
             builder_fill_count: entry.builder_fill_count,
             tainted: entry.tainted,
+            starting_equity: entry.starting_equity,
+            net_deposits: entry.net_deposits,
+            flagged_volume: entry.flagged_volume,
+            flagged_fill_count: entry.flagged_fill_count,
+            builders: entry
+                .builder_breakdown
+                .into_iter()
+                .map(|(builder, stats)| (builder, stats.into()))
+                .collect(),
+            previous_rank: None,
+            rank_change_24h: None,
+        }
+    }
+}
+
+/// A leaderboard entry in an export: [`LeaderboardEntryResponse`] without
+/// the per-builder breakdown, with every column always present.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardExportRow {
+    /// Rank (1-indexed).
+    pub rank: usize,
+    /// User address.
+    pub user: String,
+    /// Value of the ranking metric.
+    pub metric_value: Decimal,
+    /// Total trading volume.
+    pub volume: Decimal,
+    /// Realized PnL.
+    pub realized_pnl: Decimal,
+    /// Return percentage (if applicable).
+    pub return_pct: Option<Decimal>,
+    /// Number of trades.
+    pub trade_count: usize,
+    /// Number of fills that went through the builder.
+    pub builder_fill_count: usize,
+    /// Whether the user is tainted.
+    pub tainted: bool,
+    /// Equity at the start of the window (if known).
+    pub starting_equity: Option<Decimal>,
+    /// Deposits minus withdrawals during the window (if known).
+    pub net_deposits: Option<Decimal>,
+    /// Volume flagged as wash trading or self-dealing.
+    pub flagged_volume: Decimal,
+    /// Number of fills flagged as wash trading or self-dealing.
+    pub flagged_fill_count: usize,
+    /// Rank in the snapshot from 24 hours before (if retained).
+    pub previous_rank: Option<usize>,
+    /// Places gained since `previousRank`.
+    #[serde(rename = "rankChange24h")]
+    pub rank_change_24h: Option<i64>,
+}
+
+impl From<LeaderboardEntryResponse> for LeaderboardExportRow {
+    fn from(entry: LeaderboardEntryResponse) -> Self {
+        Self {
+            rank: entry.rank,
+            user: entry.user,
+            metric_value: entry.metric_value,
+            volume: entry.volume,
+            realized_pnl: entry.realized_pnl,
+            return_pct: entry.return_pct,
+            trade_count: entry.trade_count,
+            builder_fill_count: entry.builder_fill_count,
+            tainted: entry.tainted,
+            starting_equity: entry.starting_equity,
+            net_deposits: entry.net_deposits,
+            flagged_volume: entry.flagged_volume,
+            flagged_fill_count: entry.flagged_fill_count,
+            previous_rank: entry.previous_rank,
+            rank_change_24h: entry.rank_change_24h,
+        }
+    }
+}
+
+/// A user's trading through one builder.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BuilderStatsResponse {
+    /// Number of fills routed by the builder.
+    pub fill_count: usize,
+    /// Notional volume routed by the builder.
+    pub volume: Decimal,
+    /// Exchange fees paid on those fills.
+    pub fees: Decimal,
+    /// Builder fees paid on those fills.
+    pub builder_fees: Decimal,
+    /// Realized PnL on those fills.
+    pub realized_pnl: Decimal,
+}
+
+impl From<hl_indexer::leaderboard::BuilderStats> for BuilderStatsResponse {
+    fn from(stats: hl_indexer::leaderboard::BuilderStats) -> Self {
+        Self {
+            fill_count: stats.fill_count,
+            volume: stats.volume,
+            fees: stats.fees,
+            builder_fees: stats.builder_fees,
+            realized_pnl: stats.realized_pnl,
         }
     }
 }
 
 /// Leaderboard response.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct LeaderboardResponse {
     /// Leaderboard entries.
@@ -246,4 +509,277 @@ pub struct LeaderboardResponse {
     pub total_users: usize,
     /// Number of users after taint filtering (if builder_only).
     pub filtered_users: usize,
+    /// When the served snapshot was computed (milliseconds since epoch).
+    pub computed_at_ms: u64,
+    /// Version of the served snapshot.
+    pub snapshot_version: u64,
+    /// Whether the latest refresh failed and an older snapshot was served.
+    pub stale: bool,
+    /// Time since the served snapshot was computed, in milliseconds
+    /// (0 unless stale).
+    pub data_age_ms: u64,
+}
+
+/// Query parameters for leaderboard history.
+///
+/// Accepted alongside the regular [`LeaderboardQuery`] parameters, which
+/// select which leaderboard the history is for.
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct LeaderboardHistoryQuery {
+    /// Only include snapshots computed at or after this time.
+    pub since_ms: Option<u64>,
+    /// Only include snapshots computed at or before this time.
+    pub until_ms: Option<u64>,
+    /// Only include this user's series.
+    pub user: Option<String>,
+}
+
+/// A user's position in one historical snapshot.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardHistoryPoint {
+    /// When the snapshot was computed (milliseconds since epoch).
+    pub computed_at_ms: u64,
+    /// Version of the snapshot.
+    pub snapshot_version: u64,
+    /// Rank in the snapshot.
+    pub rank: usize,
+    /// Value of the ranking metric.
+    pub metric_value: Decimal,
+}
+
+/// Rank and metric time series for one user.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserLeaderboardHistory {
+    /// User address.
+    pub user: String,
+    /// Points in chronological order.
+    pub points: Vec<LeaderboardHistoryPoint>,
+}
+
+/// Leaderboard history response.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderboardHistoryResponse {
+    /// Competition identifier.
+    pub competition_id: String,
+    /// Metric used for ranking.
+    pub metric: String,
+    /// Number of snapshots in the requested range.
+    pub snapshot_count: usize,
+    /// Per-user series, ordered by rank in the latest snapshot.
+    pub users: Vec<UserLeaderboardHistory>,
+}
+
+/// Query parameters for the builder analytics endpoint.
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct BuilderAnalyticsQuery {
+    /// Start time in milliseconds since epoch (default: 7 days ago).
+    pub from_ms: Option<i64>,
+    /// End time in milliseconds since epoch (default: now).
+    pub to_ms: Option<i64>,
+    /// Bucket size: "day" or "hour".
+    #[serde(default = "default_bucket")]
+    pub bucket: String,
+    /// Number of top assets and users to return.
+    pub top: Option<usize>,
+    /// Days before `fromMs` to scan for users who already traded, so they
+    /// count as returning rather than new.
+    #[serde(default)]
+    pub lookback_days: u32,
+}
+
+fn default_bucket() -> String {
+    "day".to_string()
+}
+
+/// Aggregate builder activity.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivitySummaryResponse {
+    /// Builder fees collected.
+    pub fee_revenue: Decimal,
+    /// Notional volume.
+    pub volume: Decimal,
+    /// Number of fills.
+    pub fill_count: usize,
+    /// Distinct users.
+    pub unique_users: usize,
+    /// Users trading through the builder for the first time.
+    pub new_users: usize,
+    /// Users who had traded through the builder before.
+    pub returning_users: usize,
+}
+
+impl From<hl_builder_data::ActivitySummary> for ActivitySummaryResponse {
+    fn from(summary: hl_builder_data::ActivitySummary) -> Self {
+        Self {
+            fee_revenue: summary.fee_revenue,
+            volume: summary.volume,
+            fill_count: summary.fill_count,
+            unique_users: summary.unique_users,
+            new_users: summary.new_users,
+            returning_users: summary.returning_users,
+        }
+    }
+}
+
+/// Builder activity within one time bucket.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalyticsBucketResponse {
+    /// Bucket start in milliseconds since epoch.
+    pub start_ms: i64,
+    /// Activity in the bucket.
+    #[serde(flatten)]
+    pub summary: ActivitySummaryResponse,
+}
+
+/// Volume and revenue for a top asset or user.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RankedActivityResponse {
+    /// Asset symbol or user address.
+    pub key: String,
+    /// Notional volume.
+    pub volume: Decimal,
+    /// Builder fees collected.
+    pub fee_revenue: Decimal,
+    /// Number of fills.
+    pub fill_count: usize,
+}
+
+impl From<hl_builder_data::RankedActivity> for RankedActivityResponse {
+    fn from(activity: hl_builder_data::RankedActivity) -> Self {
+        Self {
+            key: activity.key,
+            volume: activity.volume,
+            fee_revenue: activity.fee_revenue,
+            fill_count: activity.fill_count,
+        }
+    }
+}
+
+/// Builder revenue analytics response.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BuilderAnalyticsResponse {
+    /// Builder address (lowercase).
+    pub builder: String,
+    /// Bucket size used.
+    pub bucket: String,
+    /// Start of the analyzed range (ms).
+    pub from_ms: i64,
+    /// End of the analyzed range (ms).
+    pub to_ms: i64,
+    /// Totals over the whole range.
+    pub totals: ActivitySummaryResponse,
+    /// Per-bucket activity in time order (empty buckets omitted).
+    pub buckets: Vec<AnalyticsBucketResponse>,
+    /// Assets with the most volume.
+    pub top_assets: Vec<RankedActivityResponse>,
+    /// Users with the most volume.
+    pub top_users: Vec<RankedActivityResponse>,
+}
+
+impl BuilderAnalyticsResponse {
+    /// Build the response from computed analytics.
+    pub fn new(
+        builder: String,
+        from_ms: i64,
+        to_ms: i64,
+        analytics: hl_builder_data::BuilderAnalytics,
+    ) -> Self {
+        Self {
+            builder,
+            bucket: analytics.bucket_size.as_str().to_string(),
+            from_ms,
+            to_ms,
+            totals: analytics.totals.into(),
+            buckets: analytics
+                .buckets
+                .into_iter()
+                .map(|bucket| AnalyticsBucketResponse {
+                    start_ms: bucket.start.timestamp_millis(),
+                    summary: bucket.summary.into(),
+                })
+                .collect(),
+            top_assets: analytics.top_assets.into_iter().map(Into::into).collect(),
+            top_users: analytics.top_users.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hl_indexer::leaderboard::LeaderboardEntry;
+
+    fn make_entry(user: &str, rank: usize) -> LeaderboardEntry {
+        LeaderboardEntry {
+            rank,
+            user: user.to_string(),
+            metric_value: Decimal::ZERO,
+            volume: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+            return_pct: None,
+            trade_count: 0,
+            builder_fill_count: 0,
+            tainted: false,
+            starting_equity: None,
+            net_deposits: None,
+            flagged_volume: Decimal::ZERO,
+            flagged_fill_count: 0,
+            builder_breakdown: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_rank_change_against_baseline() {
+        let baseline = LeaderboardSnapshot {
+            version: 1,
+            computed_at_ms: 0,
+            entries: vec![make_entry("0xaaa", 1), make_entry("0xbbb", 4)],
+            total_users: 2,
+        };
+
+        let moved_up =
+            LeaderboardEntryResponse::from(make_entry("0xbbb", 1)).with_baseline(Some(&baseline));
+        assert_eq!(moved_up.previous_rank, Some(4));
+        assert_eq!(moved_up.rank_change_24h, Some(3));
+
+        let new_user =
+            LeaderboardEntryResponse::from(make_entry("0xccc", 2)).with_baseline(Some(&baseline));
+        assert_eq!(new_user.previous_rank, None);
+        assert_eq!(new_user.rank_change_24h, None);
+
+        let json = serde_json::to_value(&moved_up).unwrap();
+        assert_eq!(json["previousRank"], 4);
+        assert_eq!(json["rankChange24h"], 3);
+    }
+
+    #[test]
+    fn test_analytics_bucket_is_flattened() {
+        let bucket = AnalyticsBucketResponse {
+            start_ms: 86_400_000,
+            summary: ActivitySummaryResponse {
+                fee_revenue: Decimal::new(15, 1),
+                volume: Decimal::from(3000),
+                fill_count: 3,
+                unique_users: 2,
+                new_users: 1,
+                returning_users: 1,
+            },
+        };
+
+        let json = serde_json::to_value(&bucket).unwrap();
+        assert_eq!(json["startMs"], 86_400_000);
+        assert_eq!(json["feeRevenue"], "1.5");
+        assert_eq!(json["newUsers"], 1);
+    }
 }
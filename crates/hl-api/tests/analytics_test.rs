@@ -0,0 +1,78 @@
+//! Builder analytics against a local mock of the Hyperliquid API.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use hl_api::{create_router, AppState, CompetitionConfig};
+use hl_indexer::{Endpoints, Indexer, IndexerConfig};
+use hl_mock_server::{MockHyperliquid, MockServer};
+use serde_json::Value;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const BUILDER: &str = "0x2868fc0d9786a740b491577a43502259efa78a39";
+
+fn router(server: &MockServer) -> axum::Router {
+    let config = IndexerConfig::mainnet().with_endpoints(Endpoints::custom(server.url()));
+    let competition = CompetitionConfig {
+        target_builders: vec![BUILDER.to_string()],
+        ..CompetitionConfig::default()
+    };
+    create_router(Arc::new(AppState::with_config(
+        Indexer::new(config),
+        competition,
+    )))
+}
+
+async fn get_json(router: &axum::Router, uri: &str) -> (StatusCode, Value) {
+    let response = router
+        .clone()
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_configured_builder_only() {
+    let server = MockHyperliquid::new().start().await.unwrap();
+    let router = router(&server);
+
+    let uri = format!(
+        "/v1/builder/{}/analytics?fromMs=1768003200000&toMs=1768089599999",
+        BUILDER.to_uppercase().replacen("0X", "0x", 1)
+    );
+    let (status, body) = get_json(&router, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["builder"], BUILDER);
+
+    let other = "/v1/builder/0x0000000000000000000000000000000000000001/analytics";
+    let (status, body) = get_json(&router, other).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(body["details"].as_str().unwrap().contains("not configured"));
+}
+
+#[tokio::test]
+async fn test_out_of_range_times_are_rejected() {
+    let server = MockHyperliquid::new().start().await.unwrap();
+    let router = router(&server);
+
+    for query in [
+        "toMs=9223372036854775807&fromMs=9223372036800000000",
+        "toMs=-9223372036854775807",
+        "fromMs=-9223372036854775807&toMs=-9223372036854775000&lookbackDays=1",
+        "fromMs=-9223372036854775807&toMs=9223372036854775807",
+    ] {
+        let uri = format!("/v1/builder/{}/analytics?{}", BUILDER, query);
+        let (status, body) = get_json(&router, &uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}: {}", query, body);
+        assert!(
+            body["details"].as_str().unwrap().contains("out of range"),
+            "{}",
+            body
+        );
+    }
+}
@@ -0,0 +1,257 @@
+//! CSV and NDJSON exports against a local mock of the Hyperliquid API.
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use hl_api::{create_router, AppState, CompetitionConfig};
+use hl_indexer::{Endpoints, Indexer, IndexerConfig};
+use hl_mock_server::{sample_fill, Fill, MockHyperliquid, MockServer, Side};
+use serde_json::Value;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const USER: &str = "0x00000000000000000000000000000000000000d4";
+
+/// 2,500 BTC buys, then an ETH long opened and closed in two fills.
+fn history() -> Vec<Fill> {
+    let mut fills: Vec<Fill> = (1..=2_500)
+        .map(|tid| sample_fill(tid * 1_000, tid))
+        .collect();
+
+    let mut open = sample_fill(3_000_000, 3_001);
+    open.coin = "ETH".to_string();
+    open.px = "3000.125".parse().unwrap();
+    open.sz = "2.50".parse().unwrap();
+    let mut close = sample_fill(3_001_000, 3_002);
+    close.coin = "ETH".to_string();
+    close.side = Side::Ask;
+    close.dir = "Close Long".to_string();
+    close.px = "3010.125".parse().unwrap();
+    close.sz = "2.50".parse().unwrap();
+    close.closed_pnl = "25.000".parse().unwrap();
+    fills.extend([open, close]);
+    fills
+}
+
+async fn start_server() -> MockServer {
+    MockHyperliquid::new()
+        .with_fills(USER, history())
+        .start()
+        .await
+        .unwrap()
+}
+
+fn router(server: &MockServer) -> axum::Router {
+    let config = IndexerConfig::mainnet().with_endpoints(Endpoints::custom(server.url()));
+    let competition = CompetitionConfig {
+        competition_users: vec![USER.to_string()],
+        ..CompetitionConfig::default()
+    };
+    create_router(Arc::new(AppState::with_config(
+        Indexer::new(config),
+        competition,
+    )))
+}
+
+/// Fetch an export, returning its status, content type and body.
+async fn get(
+    router: &axum::Router,
+    uri: &str,
+    accept: Option<&str>,
+) -> (StatusCode, String, String) {
+    let mut request = Request::get(uri);
+    if let Some(accept) = accept {
+        request = request.header(header::ACCEPT, accept);
+    }
+    let response = router
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .map(|v| v.to_str().unwrap().to_string())
+        .unwrap_or_default();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        content_type,
+        String::from_utf8(body.to_vec()).unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_trades_csv() {
+    let server = start_server().await;
+    let router = router(&server);
+
+    let uri = format!("/v1/export/trades?user={}&from_ms=0", USER);
+    let (status, content_type, body) = get(&router, &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "text/csv; charset=utf-8");
+
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(
+        lines[0],
+        "asset,timestamp_ms,price,size,side,fee,closed_pnl,trade_id,order_id,crossed,direction"
+    );
+    // Every fill, oldest first, across several chunks
+    assert_eq!(lines.len(), 1 + 2_502);
+    assert_eq!(
+        lines[1],
+        "BTC,1000,50000.0,0.01,buy,0.2,0.0,1,1,true,Open Long"
+    );
+    // Decimals keep the scale they were reported with
+    assert_eq!(
+        lines[2_502],
+        "ETH,3001000,3010.125,2.50,sell,0.2,25.000,3002,3002,true,Close Long"
+    );
+
+    let filtered = format!("{}&asset=ETH&order=desc", uri);
+    let (_, _, body) = get(&router, &filtered, None).await;
+    let trade_ids: Vec<&str> = body
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').nth(7).unwrap())
+        .collect();
+    assert_eq!(trade_ids, vec!["3002", "3001"]);
+}
+
+#[tokio::test]
+async fn test_ndjson_by_param_or_accept_header() {
+    let server = start_server().await;
+    let router = router(&server);
+
+    let uri = format!("/v1/export/trades?user={}&from_ms=2999999", USER);
+    let (status, content_type, by_param) =
+        get(&router, &format!("{}&format=ndjson", uri), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/x-ndjson");
+    let (_, _, by_header) = get(&router, &uri, Some("application/x-ndjson")).await;
+    assert_eq!(by_param, by_header);
+
+    let records: Vec<Value> = by_param
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["price"], "3000.125");
+    assert_eq!(records[1]["closed_pnl"], "25.000");
+    // Keys come in column order
+    assert!(by_param.starts_with("{\"asset\":\"ETH\",\"timestamp_ms\":3000000,"));
+}
+
+#[tokio::test]
+async fn test_pnl_and_round_trips() {
+    let server = start_server().await;
+    let router = router(&server);
+
+    let uri = format!("/v1/export/pnl?user={}&from_ms=0", USER);
+    let (status, _, body) = get(&router, &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "asset,realized_pnl,fees,net_pnl,fill_count,volume",
+            "BTC,0.0,500.0,-500.0,2500,1250000.000",
+            "ETH,25.000,0.4,24.600,2,15025.62500",
+        ]
+    );
+
+    let uri = format!("/v1/export/roundtrips?user={}&from_ms=0&format=csv", USER);
+    let (status, _, body) = get(&router, &uri, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(
+        lines[0],
+        "asset,direction,opened_at_ms,closed_at_ms,fill_count,max_size,volume,fees,realized_pnl,net_pnl"
+    );
+    // The BTC long is still open
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("BTC,long,1000,,2500,25.00,"));
+    assert_eq!(
+        lines[2],
+        "ETH,long,3000000,3001000,2,2.50,15025.62500,0.4,25.000,24.600"
+    );
+}
+
+#[tokio::test]
+async fn test_leaderboard_csv() {
+    let server = start_server().await;
+    let router = router(&server);
+
+    let (status, _, body) = get(&router, "/v1/export/leaderboard?metric=pnl", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(
+        lines[0],
+        "rank,user,metricValue,volume,realizedPnl,returnPct,tradeCount,builderFillCount,\
+         tainted,startingEquity,netDeposits,flaggedVolume,flaggedFillCount,previousRank,\
+         rankChange24h"
+    );
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].starts_with(&format!("1,{},", USER)));
+}
+
+#[tokio::test]
+async fn test_invalid_exports() {
+    let server = start_server().await;
+    let router = router(&server);
+
+    for (uri, details) in [
+        (
+            format!("/v1/export/trades?user={}&format=xlsx", USER),
+            "invalid format 'xlsx'",
+        ),
+        (
+            format!("/v1/export/trades?user={}&limit=10", USER),
+            "exports are not paged",
+        ),
+        (
+            format!("/v1/export/trades?user={}&side=long", USER),
+            "invalid side 'long'",
+        ),
+        ("/v1/export/pnl?user=abc".to_string(), "must start with 0x"),
+    ] {
+        let (status, content_type, body) = get(&router, &uri, None).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+        assert_eq!(content_type, "application/json");
+        let body: Value = serde_json::from_str(&body).unwrap();
+        let message = body["details"].as_str().unwrap();
+        assert!(message.contains(details), "{}: {}", uri, message);
+    }
+}
+
+#[tokio::test]
+async fn test_exports_fetch_pages_as_they_are_written() {
+    let server = start_server().await;
+    let router = router(&server);
+    let uri = format!("/v1/export/trades?user={}&from_ms=0", USER);
+    let request = || Request::get(&uri).body(Body::empty()).unwrap();
+
+    // Only the first page is fetched before the response starts
+    let response = router.clone().oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let before = server.info_request_count("userFillsByTime");
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(server.info_request_count("userFillsByTime") > before);
+    assert_eq!(body.iter().filter(|&&b| b == b'\n').count(), 1 + 2_502);
+
+    // An upstream failure on a later page ends the body early
+    let response = router.clone().oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    server.set_unavailable(true);
+    assert!(axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .is_err());
+
+    // ...and on the first page fails the export
+    let (status, _, _) = get(&router, &uri, None).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+}
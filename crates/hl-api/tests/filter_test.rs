@@ -0,0 +1,168 @@
+//! Fill filters on `/v1/trades` against a local mock of the Hyperliquid API.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use hl_api::{create_router, AppState};
+use hl_indexer::{Endpoints, Indexer, IndexerConfig};
+use hl_mock_server::{sample_fill, Fill, MockHyperliquid, MockServer};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const USER: &str = "0x00000000000000000000000000000000000000b2";
+
+/// Trade IDs 1..=40: every fourth fill is an ETH liquidation, the rest
+/// alternate between ETH and BTC opens and closes.
+fn history() -> Vec<Fill> {
+    (1..=40)
+        .map(|tid| {
+            let mut fill = sample_fill(tid * 1_000, tid);
+            if tid % 4 == 0 {
+                fill.coin = "ETH".to_string();
+                fill.dir = "Close Long".to_string();
+                fill.liquidation = serde_json::from_value(serde_json::json!({
+                    "liquidatedUser": USER,
+                    "markPx": "2990.0",
+                    "method": "market",
+                }))
+                .unwrap();
+                fill.px = Decimal::from(3000);
+                fill.sz = Decimal::from(2);
+                fill.closed_pnl = Decimal::from(-150);
+                fill.crossed = false;
+            } else if tid % 2 == 0 {
+                fill.coin = "ETH".to_string();
+                fill.dir = "Close Long".to_string();
+                fill.px = Decimal::from(3000);
+                fill.closed_pnl = Decimal::from(12);
+            }
+            fill.oid = tid / 2;
+            fill
+        })
+        .collect()
+}
+
+async fn start_server() -> MockServer {
+    MockHyperliquid::new()
+        .with_fills(USER, history())
+        .start()
+        .await
+        .unwrap()
+}
+
+fn router(server: &MockServer) -> axum::Router {
+    let config = IndexerConfig::mainnet().with_endpoints(Endpoints::custom(server.url()));
+    create_router(Arc::new(AppState::new(Indexer::new(config))))
+}
+
+async fn get_json(router: &axum::Router, uri: &str) -> (StatusCode, Value) {
+    let response = router
+        .clone()
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+fn trade_ids(body: &Value) -> Vec<u64> {
+    body["trades"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["trade_id"].as_u64().unwrap())
+        .collect()
+}
+
+async fn ids(router: &axum::Router, filters: &str) -> Vec<u64> {
+    let uri = format!("/v1/trades?user={}&order=asc&{}", USER, filters);
+    let (status, body) = get_json(router, &uri).await;
+    assert_eq!(status, StatusCode::OK, "{}: {}", filters, body);
+    trade_ids(&body)
+}
+
+#[tokio::test]
+async fn test_liquidations_on_eth_in_window() {
+    let server = start_server().await;
+    let router = router(&server);
+
+    let uri = format!(
+        "/v1/trades?user={}&asset=ETH&direction=liquidation&from_ms=10000&to_ms=30000&limit=2",
+        USER
+    );
+    let (status, body) = get_json(&router, &uri).await;
+    assert_eq!(status, StatusCode::OK);
+    // Filtering happens before paging, so the first page is full
+    assert_eq!(trade_ids(&body), vec![28, 24]);
+    assert_eq!(body["has_more"], true);
+
+    let cursor = body["next_cursor"].as_str().unwrap();
+    let (_, body) = get_json(&router, &format!("{}&cursor={}", uri, cursor)).await;
+    assert_eq!(trade_ids(&body), vec![20, 16]);
+    let cursor = body["next_cursor"].as_str().unwrap();
+    let (_, body) = get_json(&router, &format!("{}&cursor={}", uri, cursor)).await;
+    assert_eq!(trade_ids(&body), vec![12]);
+    assert!(body["next_cursor"].is_null());
+}
+
+#[tokio::test]
+async fn test_each_filter() {
+    let server = start_server().await;
+    let router = router(&server);
+
+    let btc: Vec<u64> = (1..=40).filter(|tid| tid % 2 == 1).collect();
+    assert_eq!(ids(&router, "assets=btc").await, btc);
+    assert_eq!(
+        ids(&router, "asset=BTC&assets=ETH&limit=1000").await.len(),
+        40
+    );
+    assert_eq!(
+        ids(&router, "direction=close&to_ms=10000").await,
+        vec![2, 6, 10]
+    );
+    assert_eq!(
+        ids(&router, "liquidity=maker&to_ms=12000").await,
+        vec![4, 8, 12]
+    );
+    assert_eq!(ids(&router, "side=sell").await, Vec::<u64>::new());
+    assert_eq!(ids(&router, "order_id=3").await, vec![6, 7]);
+    assert_eq!(
+        ids(&router, "nonzero_pnl=true&to_ms=4000").await,
+        vec![2, 4]
+    );
+
+    // BTC fills are 500 notional, ETH closes 30, liquidations 6000
+    assert_eq!(ids(&router, "min_notional=6000").await.len(), 10);
+    assert_eq!(ids(&router, "max_notional=30&to_ms=6000").await, vec![2, 6]);
+    assert_eq!(
+        ids(&router, "min_notional=100&max_notional=500&to_ms=4000").await,
+        vec![1, 3]
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_filters() {
+    let server = start_server().await;
+    let router = router(&server);
+
+    for (filters, details) in [
+        ("side=long", "invalid side 'long'"),
+        ("direction=liquidated", "invalid direction 'liquidated'"),
+        ("liquidity=both", "invalid liquidity 'both'"),
+        (
+            "min_notional=10&max_notional=5",
+            "min_notional must not exceed",
+        ),
+        ("builder_only=true", "at least one configured builder"),
+    ] {
+        let uri = format!("/v1/trades?user={}&{}", USER, filters);
+        let (status, body) = get_json(&router, &uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", filters);
+        let message = body["details"].as_str().unwrap();
+        assert!(message.contains(details), "{}: {}", filters, message);
+    }
+}
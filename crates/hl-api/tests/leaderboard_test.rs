@@ -0,0 +1,133 @@
+//! Computing leaderboards on first request.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use hl_api::{create_router, AppState, CompetitionConfig};
+use hl_indexer::{Endpoints, Indexer, IndexerConfig};
+use hl_mock_server::{sample_fill, MockHyperliquid, MockServer};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const USER: &str = "0x00000000000000000000000000000000000000b7";
+
+async fn start_server() -> MockServer {
+    MockHyperliquid::new()
+        .with_fills(USER, (1..=3).map(|i| sample_fill(i * 1_000, i)).collect())
+        .start()
+        .await
+        .unwrap()
+}
+
+fn router(server: &MockServer, max_tracked_leaderboards: usize) -> axum::Router {
+    let config = IndexerConfig::mainnet().with_endpoints(Endpoints::custom(server.url()));
+    let competition = CompetitionConfig {
+        competition_users: vec![USER.to_string()],
+        max_tracked_leaderboards,
+        ..CompetitionConfig::default()
+    };
+    create_router(Arc::new(AppState::with_config(
+        Indexer::new(config),
+        competition,
+    )))
+}
+
+async fn get_json(router: &axum::Router, uri: &str) -> (StatusCode, Value) {
+    let response = router
+        .clone()
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+async fn get_status(router: &axum::Router, uri: &str) -> StatusCode {
+    get_json(router, uri).await.0
+}
+
+#[tokio::test]
+async fn test_concurrent_first_requests_share_one_computation() {
+    let server = start_server().await;
+    let router = router(&server, 8);
+
+    let requests = (0..5).map(|_| get_json(&router, "/v1/leaderboard?metric=volume"));
+    let responses = futures::future::join_all(requests).await;
+
+    // One snapshot was computed and every request was served from it
+    let versions: Vec<&Value> = responses
+        .iter()
+        .map(|(status, body)| {
+            assert_eq!(*status, StatusCode::OK);
+            &body["snapshotVersion"]
+        })
+        .collect();
+    assert!(versions.iter().all(|version| **version == json!(1)));
+}
+
+#[tokio::test]
+async fn test_new_parameters_refused_when_full() {
+    let server = start_server().await;
+    let router = router(&server, 2);
+
+    for uri in [
+        "/v1/leaderboard?metric=volume",
+        "/v1/leaderboard?metric=pnl",
+    ] {
+        assert_eq!(get_status(&router, uri).await, StatusCode::OK);
+    }
+    assert_eq!(
+        get_status(&router, "/v1/leaderboard?metric=volume&coin=BTC").await,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+
+    // Tracked parameters are still served
+    assert_eq!(
+        get_status(&router, "/v1/leaderboard?metric=pnl").await,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn test_history_survives_restart() {
+    let server = start_server().await;
+    let dir = tempfile::tempdir().unwrap();
+    let state = || {
+        let config = IndexerConfig::mainnet().with_endpoints(Endpoints::custom(server.url()));
+        let competition = CompetitionConfig {
+            competition_users: vec![USER.to_string()],
+            history_interval_ms: 0,
+            history_dir: Some(dir.path().to_path_buf()),
+            ..CompetitionConfig::default()
+        };
+        Arc::new(AppState::with_config(Indexer::new(config), competition))
+    };
+
+    let before = create_router(state());
+    let (status, first) = get_json(&before, "/v1/leaderboard?metric=volume").await;
+    assert_eq!(status, StatusCode::OK);
+    let first_at = first["computedAtMs"].as_u64().unwrap();
+
+    let restarted = state();
+    assert_eq!(restarted.leaderboards.load_history().await, 1);
+    let after = create_router(restarted);
+
+    let (status, history) = get_json(
+        &after,
+        "/v1/competitions/default/leaderboard/history?metric=volume",
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(history["snapshotCount"], json!(2));
+
+    let (status, as_of) = get_json(
+        &after,
+        &format!("/v1/leaderboard?metric=volume&asOfMs={}", first_at),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(as_of["snapshotVersion"], json!(1));
+}
@@ -0,0 +1,167 @@
+//! Keeps the committed OpenAPI document in step with the code.
+//!
+//! After changing a route or an API type, regenerate it with:
+//!
+//! ```sh
+//! UPDATE_OPENAPI=1 cargo test -p hl-api --test openapi_test
+//! ```
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use hl_api::{create_router, ApiDoc, AppState};
+use hl_indexer::{Endpoints, Indexer, IndexerConfig};
+use hl_mock_server::MockHyperliquid;
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+use tower::ServiceExt;
+use utoipa::OpenApi;
+
+const SPEC_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
+/// Source of `create_router`, whose routes must all be documented.
+const ROUTER_SOURCE: &str = include_str!("../src/lib.rs");
+
+/// Routes serving the API description itself, which are not part of it.
+const UNDOCUMENTED_ROUTES: &[&str] = &[
+    "/openapi.json",
+    "/docs",
+    "/docs/swagger-ui.css",
+    "/docs/swagger-ui-bundle.js",
+];
+
+/// Paths passed to `.route(...)` in `create_router`, in OpenAPI syntax.
+fn routed_paths() -> Vec<String> {
+    ROUTER_SOURCE
+        .split(".route(")
+        .skip(1)
+        .map(|call| {
+            let path = call.split('"').nth(1).expect("route path literal");
+            path.split('/')
+                .map(|segment| match segment.strip_prefix(':') {
+                    Some(param) => format!("{{{}}}", param),
+                    None => segment.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .collect()
+}
+
+#[test]
+fn test_committed_spec_is_up_to_date() {
+    let generated = ApiDoc::openapi().to_pretty_json().unwrap() + "\n";
+    if std::env::var_os("UPDATE_OPENAPI").is_some() {
+        std::fs::write(SPEC_FILE, &generated).unwrap();
+        return;
+    }
+
+    let committed = std::fs::read_to_string(Path::new(SPEC_FILE)).unwrap_or_default();
+    assert!(
+        committed == generated,
+        "{} is out of date; regenerate it with \
+         `UPDATE_OPENAPI=1 cargo test -p hl-api --test openapi_test`",
+        SPEC_FILE
+    );
+}
+
+#[tokio::test]
+async fn test_documented_paths_are_routed() {
+    let server = MockHyperliquid::new().start().await.unwrap();
+    let config = IndexerConfig::mainnet().with_endpoints(Endpoints::custom(server.url()));
+    let router = create_router(Arc::new(AppState::new(Indexer::new(config))));
+
+    let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let paths = spec["paths"].as_object().unwrap();
+    assert!(paths.contains_key("/v1/trades"));
+
+    for (path, operations) in paths {
+        assert!(operations.get("get").is_some(), "{} has no GET", path);
+        let uri = path
+            .replace("{id}", "default")
+            .replace("{address}", "0x0000000000000000000000000000000000000001");
+        let response = router
+            .clone()
+            .oneshot(Request::get(&uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        // Unrouted paths get an empty 404; handlers explain theirs
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(
+            status != StatusCode::NOT_FOUND || !body.is_empty(),
+            "{} is documented but not routed",
+            path
+        );
+    }
+}
+
+#[test]
+fn test_routes_are_documented() {
+    let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let paths = spec["paths"].as_object().unwrap();
+
+    let routed = routed_paths();
+    assert!(routed.contains(&"/v1/competitions/{id}/leaderboard/history".to_string()));
+    for path in routed {
+        assert!(
+            paths.contains_key(&path) || UNDOCUMENTED_ROUTES.contains(&path.as_str()),
+            "{} is routed but has no #[utoipa::path]",
+            path
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_spec_and_docs_are_served() {
+    let server = MockHyperliquid::new().start().await.unwrap();
+    let config = IndexerConfig::mainnet().with_endpoints(Endpoints::custom(server.url()));
+    let router = create_router(Arc::new(AppState::new(Indexer::new(config))));
+
+    let response = router
+        .clone()
+        .oneshot(Request::get("/openapi.json").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let served: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(served, serde_json::to_value(ApiDoc::openapi()).unwrap());
+    assert!(served["openapi"].as_str().unwrap().starts_with("3."));
+
+    let response = router
+        .clone()
+        .oneshot(Request::get("/docs").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let page = std::str::from_utf8(&body).unwrap();
+    assert!(page.contains("/openapi.json"));
+    // Everything the page loads is served locally
+    assert!(!page.contains("https://"));
+
+    for (asset, content_type) in [
+        ("/docs/swagger-ui.css", "text/css"),
+        ("/docs/swagger-ui-bundle.js", "text/javascript"),
+    ] {
+        assert!(page.contains(asset));
+        let response = router
+            .clone()
+            .oneshot(Request::get(asset).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let served = response.headers()[axum::http::header::CONTENT_TYPE]
+            .to_str()
+            .unwrap();
+        assert!(served.starts_with(content_type), "{}: {}", asset, served);
+    }
+}
@@ -0,0 +1,166 @@
+//! Cursor pagination of `/v1/trades` against a local mock of the
+//! Hyperliquid API.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use hl_api::{create_router, AppState};
+use hl_indexer::{Endpoints, Indexer, IndexerConfig};
+use hl_mock_server::{sample_fill, MockHyperliquid, MockServer};
+use serde_json::Value;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const USER: &str = "0x00000000000000000000000000000000000000a1";
+
+/// 250 fills; every timestamp is shared by two fills, so pages split ties.
+async fn start_server() -> MockServer {
+    let fills = (1..=250).map(|tid| sample_fill(1_000 + tid / 2 * 1_000, tid));
+    MockHyperliquid::new()
+        .with_fills(USER, fills.collect())
+        .start()
+        .await
+        .unwrap()
+}
+
+fn router(server: &MockServer) -> axum::Router {
+    let config = IndexerConfig::mainnet().with_endpoints(Endpoints::custom(server.url()));
+    create_router(Arc::new(AppState::new(Indexer::new(config))))
+}
+
+async fn get_json(router: &axum::Router, uri: &str) -> (StatusCode, Value) {
+    let response = router
+        .clone()
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+fn trade_ids(body: &Value) -> Vec<u64> {
+    body["trades"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["trade_id"].as_u64().unwrap())
+        .collect()
+}
+
+fn with_cursor(uri: &str, cursor: Option<&str>) -> String {
+    match cursor {
+        Some(cursor) => format!("{}&cursor={}", uri, cursor),
+        None => uri.to_string(),
+    }
+}
+
+/// Follow `cursor_field` from the page at `cursor` until it runs out,
+/// collecting each page.
+async fn walk(
+    router: &axum::Router,
+    uri: &str,
+    cursor: Option<&str>,
+    cursor_field: &str,
+) -> Vec<Vec<u64>> {
+    let mut pages = Vec::new();
+    let (_, mut body) = get_json(router, &with_cursor(uri, cursor)).await;
+    loop {
+        pages.push(trade_ids(&body));
+        let Some(cursor) = body[cursor_field].as_str() else {
+            return pages;
+        };
+        let (status, next) = get_json(router, &with_cursor(uri, Some(cursor))).await;
+        assert_eq!(status, StatusCode::OK);
+        body = next;
+    }
+}
+
+#[tokio::test]
+async fn test_walk_all_trades_in_both_orders() {
+    let server = start_server().await;
+    let router = router(&server);
+
+    for order in ["desc", "asc"] {
+        let uri = format!(
+            "/v1/trades?user={}&from_ms=0&limit=100&order={}",
+            USER, order
+        );
+        let pages = walk(&router, &uri, None, "next_cursor").await;
+
+        let sizes: Vec<usize> = pages.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![100, 100, 50]);
+        let mut expected: Vec<u64> = (1..=250).collect();
+        if order == "desc" {
+            expected.reverse();
+        }
+        assert_eq!(pages.concat(), expected);
+    }
+}
+
+#[tokio::test]
+async fn test_prev_cursor_returns_to_earlier_pages() {
+    let server = start_server().await;
+    let router = router(&server);
+    let uri = format!("/v1/trades?user={}&from_ms=0&limit=100", USER);
+
+    let forward = walk(&router, &uri, None, "next_cursor").await;
+    let (_, first) = get_json(&router, &uri).await;
+    assert!(first["prev_cursor"].is_null());
+    assert_eq!(first["has_more"], true);
+
+    // Walk to the last page, then all the way back
+    let mut body = first;
+    for _ in 1..forward.len() {
+        let cursor = body["next_cursor"].as_str().unwrap().to_string();
+        body = get_json(&router, &with_cursor(&uri, Some(&cursor))).await.1;
+    }
+    assert_eq!(body["has_more"], false);
+    let cursor = body["prev_cursor"].as_str();
+    let backward = walk(&router, &uri, cursor, "prev_cursor").await;
+
+    let mut expected = forward.clone();
+    expected.pop();
+    expected.reverse();
+    assert_eq!(backward, expected);
+}
+
+#[tokio::test]
+async fn test_later_pages_fetch_narrower_windows() {
+    let server = start_server().await;
+    let router = router(&server);
+    let uri = format!("/v1/trades?user={}&from_ms=0&limit=100", USER);
+
+    let (_, first) = get_json(&router, &uri).await;
+    let last_timestamp = first["trades"][99]["timestamp_ms"].as_u64().unwrap();
+    let cursor = first["next_cursor"].as_str().unwrap();
+    get_json(&router, &with_cursor(&uri, Some(cursor))).await;
+
+    let requests = server.info_requests();
+    let last = requests.last().unwrap();
+    assert_eq!(last["type"], "userFillsByTime");
+    assert_eq!(last["endTime"].as_u64(), Some(last_timestamp));
+}
+
+#[tokio::test]
+async fn test_invalid_order_and_cursor() {
+    let server = start_server().await;
+    let router = router(&server);
+    let base = format!("/v1/trades?user={}&from_ms=0&limit=10", USER);
+
+    let (status, _) = get_json(&router, &format!("{}&order=sideways", base)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get_json(&router, &format!("{}&cursor=garbage", base)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let uri = format!("/v1/trades?user={}&from_ms=0&limit=0", USER);
+    assert_eq!(get_json(&router, &uri).await.0, StatusCode::BAD_REQUEST);
+
+    // A cursor cannot be reused with the opposite order
+    let (_, page) = get_json(&router, &format!("{}&order=asc", base)).await;
+    let cursor = page["next_cursor"].as_str().unwrap();
+    let uri = format!("{}&order=desc&cursor={}", base, cursor);
+    let (status, body) = get_json(&router, &uri).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["details"].as_str().unwrap().contains("asc"));
+}
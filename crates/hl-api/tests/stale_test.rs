@@ -0,0 +1,117 @@
+//! Serving last known good data while the Hyperliquid API is down.
+//!
+//! Outages are simulated with the local mock server, so these tests need no
+//! network access.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use hl_api::{
+    create_router, spawn_leaderboard_refresher, AppState, CompetitionConfig, RefresherConfig,
+};
+use hl_indexer::{Endpoints, Indexer, IndexerConfig};
+use hl_mock_server::{sample_fill, MockHyperliquid, MockServer};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+const USER: &str = "0x00000000000000000000000000000000000000a1";
+
+async fn start_server() -> MockServer {
+    MockHyperliquid::new()
+        .with_fills(USER, (1..=3).map(|i| sample_fill(i * 1_000, i)).collect())
+        .start()
+        .await
+        .unwrap()
+}
+
+fn app_state(server: &MockServer, competition: CompetitionConfig) -> Arc<AppState> {
+    let config = IndexerConfig::mainnet().with_endpoints(Endpoints::custom(server.url()));
+    Arc::new(AppState::with_config(Indexer::new(config), competition))
+}
+
+async fn get_json(router: axum::Router, uri: &str) -> (StatusCode, Value) {
+    let response = router
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_trades_and_pnl_served_stale_during_outage() {
+    let server = start_server().await;
+    let router = create_router(app_state(&server, CompetitionConfig::default()));
+    let trades = format!("/v1/trades?user={}&from_ms=0", USER);
+    let pnl = format!("/v1/pnl?user={}&from_ms=0", USER);
+
+    let (_, fresh_trades) = get_json(router.clone(), &trades).await;
+    let (_, fresh_pnl) = get_json(router.clone(), &pnl).await;
+    assert_eq!(fresh_trades["stale"], false);
+    assert_eq!(fresh_pnl["stale"], false);
+    assert_eq!(fresh_trades["data_age_ms"], 0);
+
+    server.set_unavailable(true);
+    tokio::time::sleep(Duration::from_millis(5)).await;
+
+    let (status, body) = get_json(router.clone(), &trades).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["stale"], true);
+    assert!(body["data_age_ms"].as_u64().unwrap() > 0);
+    assert_eq!(body["trades"], fresh_trades["trades"]);
+
+    let (status, body) = get_json(router.clone(), &pnl).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["stale"], true);
+    assert_eq!(body["net_pnl"], fresh_pnl["net_pnl"]);
+
+    // Nothing to fall back on for a request never served before
+    let uncached = format!("/v1/trades?user={}&from_ms=1", USER);
+    let (status, body) = get_json(router, &uncached).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["error"], "upstream_unavailable");
+}
+
+#[tokio::test]
+async fn test_leaderboard_keeps_last_snapshot_during_outage() {
+    let server = start_server().await;
+    let competition = CompetitionConfig {
+        competition_users: vec![USER.to_string()],
+        ..CompetitionConfig::default()
+    };
+    let state = app_state(&server, competition);
+    let router = create_router(state.clone());
+
+    let (_, fresh) = get_json(router.clone(), "/v1/leaderboard").await;
+    assert_eq!(fresh["stale"], false);
+    assert_eq!(fresh["dataAgeMs"], 0);
+    assert_eq!(fresh["entries"][0]["tradeCount"], 3);
+
+    server.set_unavailable(true);
+    let refresher = spawn_leaderboard_refresher(
+        state,
+        RefresherConfig {
+            interval: Duration::from_millis(20),
+            ..RefresherConfig::default()
+        },
+    );
+
+    let mut body = Value::Null;
+    for _ in 0..100 {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        body = get_json(router.clone(), "/v1/leaderboard").await.1;
+        if body["stale"] == true {
+            break;
+        }
+    }
+    refresher.abort();
+
+    assert_eq!(body["stale"], true);
+    assert_eq!(body["snapshotVersion"], fresh["snapshotVersion"]);
+    assert_eq!(body["entries"], fresh["entries"]);
+    assert!(body["dataAgeMs"].as_u64().unwrap() > 0);
+}
@@ -0,0 +1,203 @@
+//! Server-sent event streams against a local mock of the Hyperliquid API.
+
+use axum::body::{Body, BodyDataStream};
+use axum::http::{Request, StatusCode};
+use futures::StreamExt;
+use hl_api::{
+    create_router, spawn_fill_forwarder, spawn_leaderboard_refresher, AppState, CompetitionConfig,
+    RefresherConfig,
+};
+use hl_indexer::{Endpoints, FillSource, Indexer, IndexerConfig};
+use hl_mock_server::{sample_fill, MockHyperliquid, MockServer, WsEvent};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+const USER: &str = "0x00000000000000000000000000000000000000c3";
+
+/// Reads named events off an SSE response body.
+struct EventReader {
+    body: BodyDataStream,
+    buffer: String,
+}
+
+impl EventReader {
+    /// Wait for the next event, skipping keep-alive comments.
+    async fn next(&mut self) -> (String, Value) {
+        tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                if let Some(end) = self.buffer.find("\n\n") {
+                    let frame: String = self.buffer.drain(..end + 2).collect();
+                    let mut name = None;
+                    let mut data = None;
+                    for line in frame.lines() {
+                        if let Some(value) = line.strip_prefix("event: ") {
+                            name = Some(value.to_string());
+                        } else if let Some(value) = line.strip_prefix("data: ") {
+                            data = Some(serde_json::from_str(value).unwrap());
+                        }
+                    }
+                    if let (Some(name), Some(data)) = (name, data) {
+                        return (name, data);
+                    }
+                    continue;
+                }
+                let chunk = self.body.next().await.expect("stream open").unwrap();
+                self.buffer.push_str(std::str::from_utf8(&chunk).unwrap());
+            }
+        })
+        .await
+        .expect("event arrives")
+    }
+}
+
+async fn open(router: &axum::Router, uri: &str) -> EventReader {
+    let response = router
+        .clone()
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+    EventReader {
+        body: response.into_body().into_data_stream(),
+        buffer: String::new(),
+    }
+}
+
+fn app_state(
+    server: &MockServer,
+    config: IndexerConfig,
+    competition: CompetitionConfig,
+) -> Arc<AppState> {
+    let config = config.with_endpoints(Endpoints::custom(server.url()));
+    Arc::new(AppState::with_config(Indexer::new(config), competition))
+}
+
+#[tokio::test]
+async fn test_trades_and_pnl_stream_collected_fills() {
+    let mut closing = sample_fill(3_000, 3);
+    closing.closed_pnl = "40".parse().unwrap();
+    let server = MockHyperliquid::new()
+        .with_ws_script(
+            USER,
+            vec![
+                WsEvent::Fills(vec![sample_fill(2_000, 2), sample_fill(1_000, 1)]),
+                WsEvent::Fills(vec![sample_fill(2_000, 2), closing]),
+            ],
+        )
+        .start()
+        .await
+        .unwrap();
+    let state = app_state(
+        &server,
+        IndexerConfig::mainnet().with_fill_source(FillSource::WebSocket),
+        CompetitionConfig::default(),
+    );
+    let router = create_router(state.clone());
+    let forwarder = spawn_fill_forwarder(state.clone());
+
+    let mut trades = open(&router, &format!("/v1/stream/trades?user={}", USER)).await;
+    let mut pnl = open(&router, &format!("/v1/stream/pnl?user={}", USER)).await;
+    state.indexer.start_collecting(USER).await.unwrap();
+
+    // Fills arrive oldest first, and redelivered fills are not sent again
+    let (name, first) = trades.next().await;
+    assert_eq!(name, "trades");
+    assert_eq!(first["user"], USER);
+    let ids = |event: &Value| -> Vec<u64> {
+        event["trades"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["trade_id"].as_u64().unwrap())
+            .collect()
+    };
+    assert_eq!(ids(&first), vec![1, 2]);
+    assert_eq!(ids(&trades.next().await.1), vec![3]);
+
+    // Each batch is followed by the PnL of every fill collected so far,
+    // which may already include the next batch
+    let (name, mut summary) = pnl.next().await;
+    assert_eq!(name, "pnl");
+    assert!(summary["fill_count"].as_u64().unwrap() >= 2);
+    if summary["fill_count"] != 3 {
+        summary = pnl.next().await.1;
+    }
+    assert_eq!(summary["fill_count"], 3);
+    assert_eq!(summary["realized_pnl"], "40");
+
+    state.indexer.stop_collecting().await;
+    forwarder.abort();
+}
+
+#[tokio::test]
+async fn test_leaderboard_stream_sends_changes() {
+    let server = MockHyperliquid::new()
+        .with_fills(USER, vec![sample_fill(1_000, 1)])
+        .start()
+        .await
+        .unwrap();
+    let competition = CompetitionConfig {
+        competition_users: vec![USER.to_string()],
+        ..CompetitionConfig::default()
+    };
+    let state = app_state(&server, IndexerConfig::mainnet(), competition);
+    let router = create_router(state.clone());
+
+    let uri = "/v1/competitions/default/leaderboard/stream?metric=volume";
+    let mut leaderboard = open(&router, uri).await;
+    let (name, current) = leaderboard.next().await;
+    assert_eq!(name, "leaderboard");
+    assert_eq!(current["entries"][0]["tradeCount"], 1);
+
+    let refresher = spawn_leaderboard_refresher(
+        state,
+        RefresherConfig {
+            interval: Duration::from_millis(20),
+            ..RefresherConfig::default()
+        },
+    );
+    server.add_fills(USER, vec![sample_fill(2_000, 2)]);
+
+    // Refreshes that change nothing are not sent
+    let (name, changed) = leaderboard.next().await;
+    refresher.abort();
+    assert_eq!(name, "leaderboard");
+    assert_eq!(changed["entries"][0]["tradeCount"], 2);
+    assert!(
+        changed["snapshotVersion"].as_u64().unwrap() > current["snapshotVersion"].as_u64().unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_stream_requests() {
+    let server = MockHyperliquid::new().start().await.unwrap();
+    let state = app_state(
+        &server,
+        IndexerConfig::mainnet(),
+        CompetitionConfig::default(),
+    );
+    let router = create_router(state);
+
+    for (uri, status) in [
+        ("/v1/stream/trades?user=", StatusCode::BAD_REQUEST),
+        ("/v1/stream/pnl?user=abc", StatusCode::BAD_REQUEST),
+        (
+            "/v1/competitions/other/leaderboard/stream",
+            StatusCode::NOT_FOUND,
+        ),
+        (
+            "/v1/competitions/default/leaderboard/stream?asOfMs=1000",
+            StatusCode::BAD_REQUEST,
+        ),
+    ] {
+        let response = router
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), status, "{}", uri);
+    }
+}
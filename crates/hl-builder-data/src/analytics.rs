@@ -0,0 +1,431 @@
+//! Revenue analytics over a builder's fills.
+//!
+//! Aggregates [`BuilderFill`]s into fee revenue, volume and user counts,
+//! bucketed by day or hour, plus the top assets and users by volume.
+//!
+//! A user is *new* in the bucket containing their first fill and
+//! *returning* in every later bucket. Users seen before the analyzed fills
+//! (see [`BuilderAnalyzer::with_known_users`]) are never new.
+
+use crate::types::BuilderFill;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Default number of top assets and users to report.
+pub const DEFAULT_TOP_N: usize = 10;
+
+/// Width of an analytics time bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BucketSize {
+    /// Calendar day (UTC).
+    Day,
+    /// Clock hour (UTC).
+    Hour,
+}
+
+impl BucketSize {
+    /// Parse from string representation.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "day" | "daily" | "1d" => Some(Self::Day),
+            "hour" | "hourly" | "1h" => Some(Self::Hour),
+            _ => None,
+        }
+    }
+
+    /// Get string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Hour => "hour",
+        }
+    }
+
+    /// Bucket width.
+    pub fn duration(&self) -> Duration {
+        match self {
+            Self::Day => Duration::days(1),
+            Self::Hour => Duration::hours(1),
+        }
+    }
+
+    /// Start of the bucket containing `time`.
+    pub fn bucket_start(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        time.duration_trunc(self.duration()).unwrap_or(time)
+    }
+}
+
+/// Aggregate figures for a set of fills.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActivitySummary {
+    /// Builder fees collected.
+    pub fee_revenue: Decimal,
+
+    /// Notional volume.
+    pub volume: Decimal,
+
+    /// Number of fills.
+    pub fill_count: usize,
+
+    /// Distinct users.
+    pub unique_users: usize,
+
+    /// Users trading through the builder for the first time.
+    pub new_users: usize,
+
+    /// Users who had traded through the builder before.
+    pub returning_users: usize,
+}
+
+/// Activity within one time bucket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalyticsBucket {
+    /// Start of the bucket.
+    pub start: DateTime<Utc>,
+
+    /// Activity in the bucket.
+    pub summary: ActivitySummary,
+}
+
+/// Volume and revenue attributed to one asset or user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankedActivity {
+    /// Asset symbol or user address.
+    pub key: String,
+
+    /// Notional volume.
+    pub volume: Decimal,
+
+    /// Builder fees collected.
+    pub fee_revenue: Decimal,
+
+    /// Number of fills.
+    pub fill_count: usize,
+}
+
+/// Result of [`BuilderAnalyzer::analyze`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuilderAnalytics {
+    /// Bucket width used.
+    pub bucket_size: BucketSize,
+
+    /// Totals over all analyzed fills.
+    pub totals: ActivitySummary,
+
+    /// Per-bucket activity in time order. Buckets without fills are omitted.
+    pub buckets: Vec<AnalyticsBucket>,
+
+    /// Assets with the most volume, descending.
+    pub top_assets: Vec<RankedActivity>,
+
+    /// Users with the most volume, descending.
+    pub top_users: Vec<RankedActivity>,
+}
+
+/// Aggregates builder fills into [`BuilderAnalytics`].
+///
+/// # Example
+///
+/// ```rust
+/// use hl_builder_data::{BucketSize, BuilderAnalyzer};
+///
+/// let analytics = BuilderAnalyzer::new(BucketSize::Hour)
+///     .with_top_n(5)
+///     .analyze(&[]);
+/// assert!(analytics.buckets.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct BuilderAnalyzer {
+    bucket_size: BucketSize,
+    top_n: usize,
+    known_users: HashSet<String>,
+}
+
+impl BuilderAnalyzer {
+    /// Create an analyzer with the given bucket size.
+    pub fn new(bucket_size: BucketSize) -> Self {
+        Self {
+            bucket_size,
+            top_n: DEFAULT_TOP_N,
+            known_users: HashSet::new(),
+        }
+    }
+
+    /// Set how many top assets and users to report.
+    pub fn with_top_n(mut self, top_n: usize) -> Self {
+        self.top_n = top_n;
+        self
+    }
+
+    /// Treat these users as having traded before the analyzed fills.
+    pub fn with_known_users<I, S>(mut self, users: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.known_users
+            .extend(users.into_iter().map(|u| u.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Aggregate the given fills.
+    pub fn analyze(&self, fills: &[BuilderFill]) -> BuilderAnalytics {
+        let mut sorted: Vec<&BuilderFill> = fills.iter().collect();
+        sorted.sort_by_key(|f| f.time);
+
+        let mut analysis = self.start();
+        for fill in sorted {
+            analysis.add(fill);
+        }
+        analysis.finish()
+    }
+
+    /// Start an incremental aggregation, for fills too many to hold at once.
+    pub fn start(&self) -> BuilderAnalysis {
+        BuilderAnalysis {
+            bucket_size: self.bucket_size,
+            top_n: self.top_n,
+            seen: self.known_users.clone(),
+            totals: ActivitySummary::default(),
+            total_users: HashSet::new(),
+            buckets: BTreeMap::new(),
+            by_asset: HashMap::new(),
+            by_user: HashMap::new(),
+        }
+    }
+}
+
+/// Aggregation in progress, from [`BuilderAnalyzer::start`].
+///
+/// Fills must be added in time order for new and returning users to be
+/// counted correctly.
+///
+/// # Example
+///
+/// ```rust
+/// use hl_builder_data::{BucketSize, BuilderAnalyzer};
+///
+/// let mut analysis = BuilderAnalyzer::new(BucketSize::Day).start();
+/// analysis.add_known_user("0xabc");
+/// let analytics = analysis.finish();
+/// assert_eq!(analytics.totals.fill_count, 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BuilderAnalysis {
+    bucket_size: BucketSize,
+    top_n: usize,
+    seen: HashSet<String>,
+    totals: ActivitySummary,
+    total_users: HashSet<String>,
+    buckets: BTreeMap<DateTime<Utc>, (ActivitySummary, HashSet<String>)>,
+    by_asset: HashMap<String, RankedActivity>,
+    by_user: HashMap<String, RankedActivity>,
+}
+
+impl BuilderAnalysis {
+    /// Treat `user` as having traded before the fills added so far.
+    pub fn add_known_user(&mut self, user: &str) {
+        self.seen.insert(user.to_lowercase());
+    }
+
+    /// Add the next fill in time order.
+    pub fn add(&mut self, fill: &BuilderFill) {
+        let user = fill.user.to_lowercase();
+        let volume = fill.notional_value();
+        let start = self.bucket_size.bucket_start(fill.time);
+        let (summary, users) = self.buckets.entry(start).or_default();
+
+        summary.fee_revenue += fill.builder_fee;
+        summary.volume += volume;
+        summary.fill_count += 1;
+        if users.insert(user.clone()) {
+            if self.seen.insert(user.clone()) {
+                summary.new_users += 1;
+                self.totals.new_users += 1;
+            } else {
+                summary.returning_users += 1;
+            }
+        }
+
+        self.totals.fee_revenue += fill.builder_fee;
+        self.totals.volume += volume;
+        self.totals.fill_count += 1;
+        self.total_users.insert(user.clone());
+
+        record(&mut self.by_asset, fill.asset.symbol(), fill, volume);
+        record(&mut self.by_user, &user, fill, volume);
+    }
+
+    /// Finish the aggregation.
+    pub fn finish(self) -> BuilderAnalytics {
+        let mut totals = self.totals;
+        totals.unique_users = self.total_users.len();
+        totals.returning_users = totals.unique_users - totals.new_users;
+
+        BuilderAnalytics {
+            bucket_size: self.bucket_size,
+            totals,
+            buckets: self
+                .buckets
+                .into_iter()
+                .map(|(start, (mut summary, users))| {
+                    summary.unique_users = users.len();
+                    AnalyticsBucket { start, summary }
+                })
+                .collect(),
+            top_assets: top_n(self.by_asset, self.top_n),
+            top_users: top_n(self.by_user, self.top_n),
+        }
+    }
+}
+
+fn record(
+    activity: &mut HashMap<String, RankedActivity>,
+    key: &str,
+    fill: &BuilderFill,
+    volume: Decimal,
+) {
+    let entry = activity
+        .entry(key.to_string())
+        .or_insert_with(|| RankedActivity {
+            key: key.to_string(),
+            volume: Decimal::ZERO,
+            fee_revenue: Decimal::ZERO,
+            fill_count: 0,
+        });
+    entry.volume += volume;
+    entry.fee_revenue += fill.builder_fee;
+    entry.fill_count += 1;
+}
+
+/// Highest-volume entries, ties broken by key for stable output.
+fn top_n(activity: HashMap<String, RankedActivity>, n: usize) -> Vec<RankedActivity> {
+    let mut ranked: Vec<RankedActivity> = activity.into_values().collect();
+    ranked.sort_by(|a, b| b.volume.cmp(&a.volume).then_with(|| a.key.cmp(&b.key)));
+    ranked.truncate(n);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BuilderFillSide;
+    use chrono::TimeZone;
+    use hl_types::Asset;
+    use rust_decimal_macros::dec;
+
+    fn fill(user: &str, coin: &str, time_sec: i64, price: Decimal, fee: Decimal) -> BuilderFill {
+        BuilderFill {
+            time: Utc.timestamp_opt(time_sec, 0).unwrap(),
+            user: user.to_string(),
+            asset: Asset::from_symbol(coin),
+            side: BuilderFillSide::Bid,
+            price,
+            size: dec!(1),
+            crossed: true,
+            special_trade_type: "Na".to_string(),
+            time_in_force: "Gtc".to_string(),
+            is_trigger: false,
+            counterparty: "0x0".to_string(),
+            closed_pnl: Decimal::ZERO,
+            twap_id: 0,
+            builder_fee: fee,
+            builder: "0xb1".to_string(),
+            line: 0,
+        }
+    }
+
+    const DAY: i64 = 86_400;
+
+    #[test]
+    fn test_bucket_size_parsing() {
+        assert_eq!(BucketSize::from_str("day"), Some(BucketSize::Day));
+        assert_eq!(BucketSize::from_str("Hour"), Some(BucketSize::Hour));
+        assert_eq!(BucketSize::from_str("week"), None);
+    }
+
+    #[test]
+    fn test_daily_buckets_and_new_users() {
+        let fills = vec![
+            fill("0xa", "BTC", 10, dec!(100), dec!(1)),
+            fill("0xB", "ETH", 20, dec!(50), dec!(0.5)),
+            fill("0xa", "BTC", DAY + 10, dec!(200), dec!(2)),
+            fill("0xc", "BTC", DAY + 20, dec!(10), dec!(0.1)),
+        ];
+
+        let analytics = BuilderAnalyzer::new(BucketSize::Day).analyze(&fills);
+
+        assert_eq!(analytics.buckets.len(), 2);
+        let day1 = &analytics.buckets[0].summary;
+        assert_eq!(day1.unique_users, 2);
+        assert_eq!(day1.new_users, 2);
+        assert_eq!(day1.fee_revenue, dec!(1.5));
+
+        let day2 = &analytics.buckets[1];
+        assert_eq!(day2.start, Utc.timestamp_opt(DAY, 0).unwrap());
+        assert_eq!(day2.summary.new_users, 1);
+        assert_eq!(day2.summary.returning_users, 1);
+
+        assert_eq!(analytics.totals.volume, dec!(360));
+        assert_eq!(analytics.totals.unique_users, 3);
+        assert_eq!(analytics.totals.new_users, 3);
+        assert_eq!(analytics.totals.returning_users, 0);
+    }
+
+    #[test]
+    fn test_known_users_are_returning() {
+        let fills = vec![fill("0xa", "BTC", 10, dec!(100), dec!(1))];
+
+        let analytics = BuilderAnalyzer::new(BucketSize::Hour)
+            .with_known_users(["0xA"])
+            .analyze(&fills);
+
+        assert_eq!(analytics.totals.new_users, 0);
+        assert_eq!(analytics.totals.returning_users, 1);
+    }
+
+    #[test]
+    fn test_incremental_matches_batch() {
+        let fills = vec![
+            fill("0xa", "BTC", 10, dec!(100), dec!(1)),
+            fill("0xb", "ETH", DAY + 20, dec!(50), dec!(0.5)),
+            fill("0xc", "BTC", DAY + 30, dec!(10), dec!(0.1)),
+        ];
+        let analyzer = BuilderAnalyzer::new(BucketSize::Day).with_known_users(["0xb"]);
+
+        let mut analysis = BuilderAnalyzer::new(BucketSize::Day).start();
+        analysis.add(&fills[0]);
+        // Learned partway through, e.g. from an earlier lookback day
+        analysis.add_known_user("0xB");
+        analysis.add(&fills[1]);
+        analysis.add(&fills[2]);
+
+        assert_eq!(analysis.finish(), analyzer.analyze(&fills));
+    }
+
+    #[test]
+    fn test_top_assets_and_users() {
+        let fills = vec![
+            fill("0xa", "BTC", 10, dec!(100), dec!(1)),
+            fill("0xb", "ETH", 20, dec!(300), dec!(3)),
+            fill("0xa", "SOL", 30, dec!(50), dec!(0.5)),
+        ];
+
+        let analytics = BuilderAnalyzer::new(BucketSize::Day)
+            .with_top_n(2)
+            .analyze(&fills);
+
+        let assets: Vec<&str> = analytics
+            .top_assets
+            .iter()
+            .map(|a| a.key.as_str())
+            .collect();
+        assert_eq!(assets, vec!["ETH", "BTC"]);
+
+        assert_eq!(analytics.top_users[0].key, "0xb");
+        assert_eq!(analytics.top_users[1].volume, dec!(150));
+        assert_eq!(analytics.top_users[1].fill_count, 2);
+    }
+}
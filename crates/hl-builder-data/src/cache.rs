@@ -0,0 +1,617 @@
+//! Local on-disk cache for builder fill archives.
+//!
+//! Daily archives never change once a day is over, so there is no reason to
+//! download and parse them more than once. The cache directory is laid out
+//! as:
+//!
+//! ```text
+//! <dir>/objects/<sha256>.csv.lz4     raw archives, addressed by content hash
+//! <dir>/parsed/<sha256>.bin          parsed fills for that archive
+//! <dir>/refs/<server>/<network>/<builder>/<YYYYMMDD>
+//!                                     "<sha256> <fetched_at_ms>"
+//! ```
+//!
+//! Refs are kept per stats endpoint (see [`ArchiveCache::with_endpoint`]),
+//! so mainnet, testnet and other servers can share one directory without
+//! serving each other's archives.
+//!
+//! Refs fetched well after their day ended are permanent. Today's archive is
+//! still growing, and yesterday's may still be completed for a while, so any
+//! other ref is only trusted for a short TTL before the file is downloaded
+//! again.
+//! Identical downloads share one object, so re-fetching today's file only
+//! costs disk space when it actually changed.
+//!
+//! The cache is best-effort: read or write failures are logged and the
+//! client falls back to downloading.
+
+use crate::client::StatsEndpoint;
+use crate::types::{BuilderFill, BuilderFillSide};
+use chrono::{DateTime, NaiveDate, Utc};
+use hl_types::Asset;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// Default time a cached archive for today is trusted.
+pub const DEFAULT_TODAY_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// How long after the end of a UTC day its archive may still change.
+const FINAL_GRACE: Duration = Duration::from_secs(60 * 60);
+
+/// Version of the parsed-fill encoding. Bump when [`CachedFill`] changes so
+/// stale entries are re-parsed from the raw archive.
+const PARSED_FORMAT_VERSION: u32 = 2;
+
+/// On-disk cache for builder fill archives.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use hl_builder_data::{ArchiveCache, BuilderDataClient};
+/// use std::time::Duration;
+///
+/// let cache = ArchiveCache::new("/var/cache/hl-builder-fills")
+///     .with_today_ttl(Duration::from_secs(300));
+/// let client = BuilderDataClient::new("0x2868fc0d9786a740b491577a43502259efa78a39")
+///     .unwrap()
+///     .with_cache(cache);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ArchiveCache {
+    dir: PathBuf,
+    today_ttl: Duration,
+    endpoint: PathBuf,
+}
+
+/// A cached archive reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ArchiveRef {
+    hash: String,
+    fetched_at_ms: i64,
+}
+
+impl ArchiveCache {
+    /// Create a cache rooted at `dir`. The directory is created on first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            today_ttl: DEFAULT_TODAY_TTL,
+            endpoint: StatsEndpoint::default().cache_key(),
+        }
+    }
+
+    /// Keep entries for archives from `endpoint` (mainnet by default).
+    ///
+    /// Clients scope their cache to their own endpoint, so this only matters
+    /// when using the cache directly, e.g. to [`import`](Self::import)
+    /// archives for a testnet client.
+    pub fn with_endpoint(mut self, endpoint: &StatsEndpoint) -> Self {
+        self.endpoint = endpoint.cache_key();
+        self
+    }
+
+    /// Set how long a cached archive for the current (UTC) day is trusted.
+    pub fn with_today_ttl(mut self, ttl: Duration) -> Self {
+        self.today_ttl = ttl;
+        self
+    }
+
+    /// Get the cache directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Load cached fills for a builder and date, if present and fresh.
+    ///
+    /// Uses the parsed cache when possible, otherwise parses the raw archive
+    /// (and stores the parsed result for next time).
+    pub async fn load(&self, builder: &str, date: NaiveDate) -> Option<Vec<BuilderFill>> {
+        let archive_ref = self.read_ref(builder, date).await?;
+        if !self.is_fresh(&archive_ref, date, Utc::now()) {
+            return None;
+        }
+
+        if let Some(fills) = self.read_parsed(&archive_ref.hash).await {
+            return Some(fills);
+        }
+
+        let compressed = tokio::fs::read(self.object_path(&archive_ref.hash))
+            .await
+            .ok()?;
+        match crate::client::parse_archive(&compressed) {
+            Ok(fills) => {
+                self.write_parsed_logged(&archive_ref.hash, &fills).await;
+                Some(fills)
+            }
+            Err(e) => {
+                tracing::warn!("Corrupt cached archive {}: {}", archive_ref.hash, e);
+                None
+            }
+        }
+    }
+
+    /// Load cached parsed fills, without falling back to the raw archive.
+    pub(crate) async fn load_parsed(
+        &self,
+        builder: &str,
+        date: NaiveDate,
+    ) -> Option<Vec<BuilderFill>> {
+        let archive_ref = self.read_ref(builder, date).await?;
+        if !self.is_fresh(&archive_ref, date, Utc::now()) {
+            return None;
+        }
+        self.read_parsed(&archive_ref.hash).await
+    }
+
+    /// Load the raw (LZ4-compressed) archive for a builder and date, if
+    /// present and fresh.
+    pub async fn load_raw(&self, builder: &str, date: NaiveDate) -> Option<Vec<u8>> {
+        let archive_ref = self.read_ref(builder, date).await?;
+        if !self.is_fresh(&archive_ref, date, Utc::now()) {
+            return None;
+        }
+        tokio::fs::read(self.object_path(&archive_ref.hash))
+            .await
+            .ok()
+    }
+
+    /// Store a downloaded archive and its parsed fills.
+    pub async fn store(
+        &self,
+        builder: &str,
+        date: NaiveDate,
+        compressed: &[u8],
+        fills: &[BuilderFill],
+    ) {
+        self.store_inner(builder, date, compressed, Some(fills))
+            .await;
+    }
+
+    /// Store a downloaded archive without parsed fills, e.g. when only part
+    /// of it was parsed. The next [`load`](Self::load) parses it in full.
+    pub async fn store_raw(&self, builder: &str, date: NaiveDate, compressed: &[u8]) {
+        self.store_inner(builder, date, compressed, None).await;
+    }
+
+    async fn store_inner(
+        &self,
+        builder: &str,
+        date: NaiveDate,
+        compressed: &[u8],
+        fills: Option<&[BuilderFill]>,
+    ) {
+        let hash = hex::encode(Sha256::digest(compressed));
+        let result = async {
+            let object = self.object_path(&hash);
+            if tokio::fs::metadata(&object).await.is_err() {
+                write_atomic(&object, compressed).await?;
+            }
+            self.commit_ref(builder, date, &hash, fills).await
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!(
+                "Failed to cache builder fills for {} on {}: {}",
+                builder,
+                date,
+                e
+            );
+        }
+    }
+
+    /// Start storing an archive that is still being downloaded.
+    ///
+    /// Bytes written to the returned writer go to a temporary file; nothing
+    /// becomes visible until [`ArchiveWriter::commit`].
+    pub(crate) async fn writer(&self, builder: &str, date: NaiveDate) -> io::Result<ArchiveWriter> {
+        let tmp = tmp_path(&self.dir.join("objects").join("incoming"));
+        if let Some(parent) = tmp.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let file = tokio::fs::File::create(&tmp).await?;
+        Ok(ArchiveWriter {
+            cache: self.clone(),
+            builder: builder.to_string(),
+            date,
+            tmp,
+            file: Some(file),
+            hasher: Sha256::new(),
+        })
+    }
+
+    /// Store parsed fills for an archive object and point the ref at it.
+    async fn commit_ref(
+        &self,
+        builder: &str,
+        date: NaiveDate,
+        hash: &str,
+        fills: Option<&[BuilderFill]>,
+    ) -> io::Result<()> {
+        if let Some(fills) = fills {
+            self.write_parsed(hash, fills).await?;
+        }
+        let contents = format!("{} {}\n", hash, Utc::now().timestamp_millis());
+        write_atomic(&self.ref_path(builder, date), contents.as_bytes()).await
+    }
+
+    /// Whether a ref may be used: always if it was fetched once the day's
+    /// archive was complete, otherwise within the TTL.
+    fn is_fresh(&self, archive_ref: &ArchiveRef, date: NaiveDate, now: DateTime<Utc>) -> bool {
+        let final_from_ms = date
+            .succ_opt()
+            .and_then(|next| next.and_hms_opt(0, 0, 0))
+            .map(|end| end.and_utc().timestamp_millis() + FINAL_GRACE.as_millis() as i64);
+        if final_from_ms.is_some_and(|final_from_ms| archive_ref.fetched_at_ms >= final_from_ms) {
+            return true;
+        }
+        let age_ms = now.timestamp_millis() - archive_ref.fetched_at_ms;
+        age_ms >= 0 && (age_ms as u128) < self.today_ttl.as_millis()
+    }
+
+    async fn read_ref(&self, builder: &str, date: NaiveDate) -> Option<ArchiveRef> {
+        let contents = tokio::fs::read_to_string(self.ref_path(builder, date))
+            .await
+            .ok()?;
+        let mut parts = contents.split_whitespace();
+        let hash = parts.next()?.to_string();
+        let fetched_at_ms = parts.next()?.parse().ok()?;
+        Some(ArchiveRef {
+            hash,
+            fetched_at_ms,
+        })
+    }
+
+    async fn read_parsed(&self, hash: &str) -> Option<Vec<BuilderFill>> {
+        let bytes = tokio::fs::read(self.parsed_path(hash)).await.ok()?;
+        let parsed: ParsedArchive = bincode::deserialize(&bytes).ok()?;
+        if parsed.version != PARSED_FORMAT_VERSION {
+            return None;
+        }
+        parsed
+            .fills
+            .into_iter()
+            .map(CachedFill::into_fill)
+            .collect()
+    }
+
+    async fn write_parsed(&self, hash: &str, fills: &[BuilderFill]) -> io::Result<()> {
+        let parsed = ParsedArchive {
+            version: PARSED_FORMAT_VERSION,
+            fills: fills.iter().map(CachedFill::from_fill).collect(),
+        };
+        let bytes = bincode::serialize(&parsed).map_err(io::Error::other)?;
+        write_atomic(&self.parsed_path(hash), &bytes).await
+    }
+
+    async fn write_parsed_logged(&self, hash: &str, fills: &[BuilderFill]) {
+        if let Err(e) = self.write_parsed(hash, fills).await {
+            tracing::warn!("Failed to cache parsed fills for {}: {}", hash, e);
+        }
+    }
+
+    fn object_path(&self, hash: &str) -> PathBuf {
+        self.dir.join("objects").join(format!("{}.csv.lz4", hash))
+    }
+
+    fn parsed_path(&self, hash: &str) -> PathBuf {
+        self.dir.join("parsed").join(format!("{}.bin", hash))
+    }
+
+    fn ref_path(&self, builder: &str, date: NaiveDate) -> PathBuf {
+        self.dir
+            .join("refs")
+            .join(&self.endpoint)
+            .join(builder.to_lowercase())
+            .join(date.format("%Y%m%d").to_string())
+    }
+}
+
+/// An archive being written to the cache while it downloads.
+///
+/// Dropping the writer without committing discards what was written.
+pub(crate) struct ArchiveWriter {
+    cache: ArchiveCache,
+    builder: String,
+    date: NaiveDate,
+    tmp: PathBuf,
+    file: Option<tokio::fs::File>,
+    hasher: Sha256,
+}
+
+impl ArchiveWriter {
+    /// Append downloaded bytes.
+    pub(crate) async fn write(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.hasher.update(chunk);
+        match &mut self.file {
+            Some(file) => file.write_all(chunk).await,
+            None => Err(io::Error::other("archive writer already closed")),
+        }
+    }
+
+    /// Store the complete archive, with its parsed fills if given. Failures
+    /// are logged.
+    pub(crate) async fn commit(mut self, fills: Option<&[BuilderFill]>) {
+        let hash = hex::encode(std::mem::take(&mut self.hasher).finalize());
+        let result = async {
+            let mut file = self
+                .file
+                .take()
+                .expect("archive writer is open until committed");
+            file.flush().await?;
+            drop(file);
+            let object = self.cache.object_path(&hash);
+            if tokio::fs::metadata(&object).await.is_err() {
+                tokio::fs::rename(&self.tmp, &object).await?;
+            }
+            self.cache
+                .commit_ref(&self.builder, self.date, &hash, fills)
+                .await
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!(
+                "Failed to cache builder fills for {} on {}: {}",
+                self.builder,
+                self.date,
+                e
+            );
+        }
+    }
+}
+
+impl Drop for ArchiveWriter {
+    fn drop(&mut self) {
+        // Gone already if it was committed as a new object
+        let _ = std::fs::remove_file(&self.tmp);
+    }
+}
+
+/// Write a file via a temporary file and rename, so readers never see a
+/// partial write.
+async fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let tmp = tmp_path(path);
+    tokio::fs::write(&tmp, contents).await?;
+    tokio::fs::rename(&tmp, path).await
+}
+
+/// A unique temporary file name next to `path`.
+fn tmp_path(path: &Path) -> PathBuf {
+    static NEXT_TMP: AtomicU64 = AtomicU64::new(0);
+    path.with_extension(format!(
+        "tmp.{}.{}",
+        std::process::id(),
+        NEXT_TMP.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Parsed archive as stored on disk.
+#[derive(Serialize, Deserialize)]
+struct ParsedArchive {
+    version: u32,
+    fills: Vec<CachedFill>,
+}
+
+/// Compact binary form of a [`BuilderFill`].
+///
+/// Decimals are stored in their 16-byte native form and times as
+/// milliseconds, so the encoding is independent of serde string formats.
+#[derive(Serialize, Deserialize)]
+struct CachedFill {
+    time_ms: i64,
+    user: String,
+    coin: String,
+    is_buy: bool,
+    price: [u8; 16],
+    size: [u8; 16],
+    crossed: bool,
+    special_trade_type: String,
+    time_in_force: String,
+    is_trigger: bool,
+    counterparty: String,
+    closed_pnl: [u8; 16],
+    twap_id: u64,
+    builder_fee: [u8; 16],
+    line: u64,
+}
+
+impl CachedFill {
+    fn from_fill(fill: &BuilderFill) -> Self {
+        Self {
+            time_ms: fill.timestamp_ms(),
+            user: fill.user.clone(),
+            coin: fill.asset.symbol().to_string(),
+            is_buy: fill.side.is_buy(),
+            price: fill.price.serialize(),
+            size: fill.size.serialize(),
+            crossed: fill.crossed,
+            special_trade_type: fill.special_trade_type.clone(),
+            time_in_force: fill.time_in_force.clone(),
+            is_trigger: fill.is_trigger,
+            counterparty: fill.counterparty.clone(),
+            closed_pnl: fill.closed_pnl.serialize(),
+            twap_id: fill.twap_id,
+            builder_fee: fill.builder_fee.serialize(),
+            line: fill.line,
+        }
+    }
+
+    fn into_fill(self) -> Option<BuilderFill> {
+        Some(BuilderFill {
+            time: DateTime::from_timestamp_millis(self.time_ms)?,
+            user: self.user,
+            asset: Asset::from_symbol(&self.coin),
+            side: if self.is_buy {
+                BuilderFillSide::Bid
+            } else {
+                BuilderFillSide::Ask
+            },
+            price: Decimal::deserialize(self.price),
+            size: Decimal::deserialize(self.size),
+            crossed: self.crossed,
+            special_trade_type: self.special_trade_type,
+            time_in_force: self.time_in_force,
+            is_trigger: self.is_trigger,
+            counterparty: self.counterparty,
+            closed_pnl: Decimal::deserialize(self.closed_pnl),
+            twap_id: self.twap_id,
+            builder_fee: Decimal::deserialize(self.builder_fee),
+            builder: String::new(),
+            line: self.line,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeDelta, TimeZone};
+    use rust_decimal_macros::dec;
+
+    fn sample_fill() -> BuilderFill {
+        BuilderFill {
+            time: Utc.timestamp_millis_opt(1_768_000_000_123).unwrap(),
+            user: "0xabc".to_string(),
+            asset: Asset::from_symbol("SOL"),
+            side: BuilderFillSide::Ask,
+            price: dec!(135.880),
+            size: dec!(0.23),
+            crossed: true,
+            special_trade_type: "Na".to_string(),
+            time_in_force: "Alo".to_string(),
+            is_trigger: false,
+            counterparty: "0xdef".to_string(),
+            closed_pnl: dec!(-1.5),
+            twap_id: 7,
+            builder_fee: dec!(0.003125),
+            builder: String::new(),
+            line: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_and_load_past_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ArchiveCache::new(dir.path());
+        let date = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+        let fills = vec![sample_fill()];
+
+        assert!(cache.load("0xb1", date).await.is_none());
+        cache.store("0xB1", date, b"archive bytes", &fills).await;
+
+        let loaded = cache.load("0xb1", date).await.unwrap();
+        assert_eq!(loaded, fills);
+        // Decimal scale survives the round trip
+        assert_eq!(loaded[0].price.to_string(), "135.880");
+    }
+
+    #[tokio::test]
+    async fn test_endpoints_have_separate_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let mainnet = ArchiveCache::new(dir.path());
+        let testnet = mainnet.clone().with_endpoint(&StatsEndpoint::testnet());
+        let mirror = mainnet
+            .clone()
+            .with_endpoint(&StatsEndpoint::mainnet().with_base_url("http://127.0.0.1:8080"));
+        let date = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+
+        mainnet.store("0xb1", date, b"mainnet", &[]).await;
+
+        assert!(testnet.load_raw("0xb1", date).await.is_none());
+        assert!(mirror.load_raw("0xb1", date).await.is_none());
+        testnet.store("0xb1", date, b"testnet", &[]).await;
+        assert_eq!(mainnet.load_raw("0xb1", date).await.unwrap(), b"mainnet");
+        assert_eq!(testnet.load_raw("0xb1", date).await.unwrap(), b"testnet");
+        assert!(dir
+            .path()
+            .join("refs/stats-data.hyperliquid.xyz/Testnet/0xb1/20260110")
+            .exists());
+    }
+
+    #[tokio::test]
+    async fn test_identical_archives_share_an_object() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ArchiveCache::new(dir.path());
+        let day1 = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+        let day2 = NaiveDate::from_ymd_opt(2026, 1, 11).unwrap();
+
+        cache.store("0xb1", day1, b"same", &[]).await;
+        cache.store("0xb1", day2, b"same", &[]).await;
+
+        let objects = std::fs::read_dir(dir.path().join("objects"))
+            .unwrap()
+            .count();
+        assert_eq!(objects, 1);
+    }
+
+    #[test]
+    fn test_today_expires_after_ttl() {
+        let cache = ArchiveCache::new("/unused").with_today_ttl(Duration::from_secs(60));
+        let now = Utc.timestamp_millis_opt(1_768_000_000_000).unwrap();
+        let today = now.date_naive();
+        let yesterday = today.pred_opt().unwrap();
+
+        let recent = ArchiveRef {
+            hash: "h".to_string(),
+            fetched_at_ms: now.timestamp_millis() - 30_000,
+        };
+        let stale = ArchiveRef {
+            hash: "h".to_string(),
+            fetched_at_ms: now.timestamp_millis() - 120_000,
+        };
+
+        assert!(cache.is_fresh(&recent, today, now));
+        assert!(!cache.is_fresh(&stale, today, now));
+        assert!(cache.is_fresh(&stale, yesterday, now));
+    }
+
+    #[test]
+    fn test_past_day_fetched_before_it_was_complete_expires() {
+        let cache = ArchiveCache::new("/unused").with_today_ttl(Duration::from_secs(60));
+        let date = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+        let midnight = Utc.with_ymd_and_hms(2026, 1, 11, 0, 0, 0).unwrap();
+        let fetched_at = |offset: TimeDelta| ArchiveRef {
+            hash: "h".to_string(),
+            fetched_at_ms: (midnight + offset).timestamp_millis(),
+        };
+        let now = midnight + TimeDelta::days(2);
+
+        // During the day, and shortly after it ended
+        let during = fetched_at(TimeDelta::hours(-1));
+        let just_after = fetched_at(TimeDelta::minutes(5));
+        assert!(!cache.is_fresh(&during, date, now));
+        assert!(!cache.is_fresh(&just_after, date, now));
+        // Once the grace period is over the archive is final
+        let complete = fetched_at(TimeDelta::hours(2));
+        assert!(cache.is_fresh(&complete, date, now));
+    }
+
+    #[tokio::test]
+    async fn test_reparses_raw_archive_without_parsed_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ArchiveCache::new(dir.path());
+        let date = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+
+        // A valid LZ4 frame containing an empty CSV with just the header
+        let csv = b"time,user,coin,side,px,sz,crossed,special_trade_type,tif,is_trigger,counterparty,closed_pnl,twap_id,builder_fee\n";
+        let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+        std::io::Write::write_all(&mut encoder, csv).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        cache.store("0xb1", date, &compressed, &[]).await;
+        std::fs::remove_dir_all(dir.path().join("parsed")).unwrap();
+
+        assert_eq!(cache.load("0xb1", date).await, Some(Vec::new()));
+        assert!(dir.path().join("parsed").exists());
+    }
+}
@@ -1,13 +1,181 @@
 //! HTTP client for downloading builder fill data.
 
+use crate::cache::{ArchiveCache, ArchiveWriter};
 use crate::error::BuilderDataError;
-use crate::parser::parse_builder_fills;
+use crate::parser::{parse_builder_fills, FillReader, ParseMode, ParseReport};
+use crate::stream::{BuilderFillStream, FillFilter};
 use crate::types::BuilderFill;
 use chrono::NaiveDate;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
 use std::io::Read;
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Base URL for Hyperliquid stats data.
-const STATS_BASE_URL: &str = "https://stats-data.hyperliquid.xyz";
+pub const STATS_BASE_URL: &str = "https://stats-data.hyperliquid.xyz";
+
+/// Default number of dates fetched at once by range fetches.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Default number of retries per file after a transient failure.
+pub const DEFAULT_MAX_RETRIES: u32 = 2;
+
+/// Delay before the first retry; doubled for every further attempt.
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Fills of one date and how they parsed, as fetched by
+/// [`BuilderDataClient::fetch_fills_by_date`].
+pub type DateFetch = Result<(Vec<BuilderFill>, ParseReport), BuilderDataError>;
+
+/// Where builder fill archives are downloaded from.
+///
+/// Archives live at `<base_url>/<network>/builder_fills/<builder>/<YYYYMMDD>.csv.lz4`,
+/// where `network` is `Mainnet` or `Testnet`. The base URL can point at any
+/// server with the same layout, e.g. a local stand-in serving fixtures.
+///
+/// # Example
+///
+/// ```rust
+/// use hl_builder_data::StatsEndpoint;
+///
+/// let endpoint = StatsEndpoint::testnet().with_base_url("http://127.0.0.1:8080/");
+/// assert_eq!(endpoint.base_url(), "http://127.0.0.1:8080");
+/// assert_eq!(endpoint.network(), "Testnet");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsEndpoint {
+    base_url: String,
+    network: String,
+}
+
+impl StatsEndpoint {
+    /// Create an endpoint from a base URL and network path segment.
+    pub fn new(base_url: impl Into<String>, network: impl Into<String>) -> Self {
+        Self::mainnet()
+            .with_base_url(base_url)
+            .with_network(network)
+    }
+
+    /// Mainnet archives on the official stats server.
+    pub fn mainnet() -> Self {
+        Self {
+            base_url: STATS_BASE_URL.to_string(),
+            network: "Mainnet".to_string(),
+        }
+    }
+
+    /// Testnet archives on the official stats server.
+    pub fn testnet() -> Self {
+        Self::mainnet().with_network("Testnet")
+    }
+
+    /// Replace the base URL, keeping the network segment.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Replace the network path segment.
+    pub fn with_network(mut self, network: impl Into<String>) -> Self {
+        self.network = network.into();
+        self
+    }
+
+    /// Base URL without a trailing slash.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Network path segment (`Mainnet` or `Testnet`).
+    pub fn network(&self) -> &str {
+        &self.network
+    }
+
+    /// Directory under which a cache keeps refs for this endpoint, e.g.
+    /// `stats-data.hyperliquid.xyz/Mainnet`, so servers and networks never
+    /// share cache entries.
+    pub(crate) fn cache_key(&self) -> PathBuf {
+        let server = self
+            .base_url
+            .split_once("://")
+            .map_or(self.base_url.as_str(), |(_, rest)| rest);
+        PathBuf::from(path_segment(server)).join(path_segment(&self.network))
+    }
+
+    /// URL of `builder`'s archive for `date`.
+    pub fn archive_url(&self, builder: &str, date: NaiveDate) -> String {
+        format!(
+            "{}/{}/builder_fills/{}/{}.csv.lz4",
+            self.base_url,
+            self.network,
+            builder,
+            date.format("%Y%m%d")
+        )
+    }
+}
+
+impl Default for StatsEndpoint {
+    fn default() -> Self {
+        Self::mainnet()
+    }
+}
+
+/// `s` with every character but ASCII alphanumerics, `.` and `-` replaced,
+/// safe to use as a single path component.
+fn path_segment(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Outcome of fetching a date range, day by day.
+///
+/// Every date in the range ends up in exactly one of `fetched`, `missing`
+/// or `failed`.
+#[derive(Debug, Default)]
+pub struct RangeFetch {
+    /// Fills of all fetched dates, sorted by time.
+    pub fills: Vec<BuilderFill>,
+
+    /// Dates whose archive was downloaded (or served from the cache).
+    pub fetched: Vec<NaiveDate>,
+
+    /// Dates with no archive (the builder had no fills, or it is not
+    /// uploaded yet).
+    pub missing: Vec<NaiveDate>,
+
+    /// Dates that still failed after retrying, with the last error.
+    pub failed: Vec<(NaiveDate, BuilderDataError)>,
+
+    /// Parse report of each fetched date, in the same order as `fetched`.
+    pub reports: Vec<ParseReport>,
+}
+
+impl RangeFetch {
+    /// Rows skipped across all fetched dates (lenient mode only).
+    pub fn skipped_rows(&self) -> usize {
+        self.reports.iter().map(|r| r.skipped).sum()
+    }
+
+    /// Whether every date was either fetched or missing.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// The fills, or the error of the earliest failed date.
+    pub fn into_result(self) -> Result<Vec<BuilderFill>, BuilderDataError> {
+        match self.failed.into_iter().next() {
+            Some((_, e)) => Err(e),
+            None => Ok(self.fills),
+        }
+    }
+}
 
 /// Client for fetching builder fill data from Hyperliquid.
 ///
@@ -34,9 +202,18 @@ const STATS_BASE_URL: &str = "https://stats-data.hyperliquid.xyz";
 ///     Ok(())
 /// }
 /// ```
+#[derive(Debug, Clone)]
 pub struct BuilderDataClient {
     http_client: reqwest::Client,
     builder_address: String,
+    endpoint: StatsEndpoint,
+    cache: Option<ArchiveCache>,
+    filter: FillFilter,
+    parse_mode: ParseMode,
+    concurrency: usize,
+    max_retries: u32,
+    retry_delay: Duration,
+    offline: bool,
 }
 
 impl BuilderDataClient {
@@ -59,21 +236,86 @@ impl BuilderDataClient {
         Ok(Self {
             http_client: reqwest::Client::new(),
             builder_address: address,
+            endpoint: StatsEndpoint::default(),
+            cache: None,
+            filter: FillFilter::default(),
+            parse_mode: ParseMode::default(),
+            concurrency: DEFAULT_CONCURRENCY,
+            max_retries: DEFAULT_MAX_RETRIES,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            offline: false,
         })
     }
 
+    /// Download archives from `endpoint` instead of mainnet.
+    pub fn with_endpoint(mut self, endpoint: StatsEndpoint) -> Self {
+        self.cache = self.cache.map(|cache| cache.with_endpoint(&endpoint));
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// Cache downloaded archives on disk.
+    ///
+    /// Completed days are served from the cache without touching the
+    /// network; today's archive, or one fetched before its day was complete,
+    /// is re-downloaded once the cache's TTL has passed. Entries are kept
+    /// per endpoint, so the cache is always scoped to this client's one.
+    pub fn with_cache(mut self, cache: ArchiveCache) -> Self {
+        self.cache = Some(cache.with_endpoint(&self.endpoint));
+        self
+    }
+
+    /// Only keep fills passing `filter`.
+    ///
+    /// The filter is applied while parsing, so rows for other users or
+    /// assets are never held in memory.
+    pub fn with_filter(mut self, filter: FillFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Set how rows that fail to parse are handled (strict by default).
+    pub fn with_parse_mode(mut self, mode: ParseMode) -> Self {
+        self.parse_mode = mode;
+        self
+    }
+
+    /// Never download; serve dates from the cache only.
+    ///
+    /// Dates missing from the cache are `NotFound`, as if the builder had no
+    /// fills that day.
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Set how many dates a range fetch downloads at once (at least 1).
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set how often a file is retried after a transient failure, and the
+    /// delay before the first retry (doubled for each further retry).
+    pub fn with_retries(mut self, max_retries: u32, retry_delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_delay = retry_delay;
+        self
+    }
+
     /// Get the builder address (lowercase).
     pub fn builder_address(&self) -> &str {
         &self.builder_address
     }
 
+    /// Get the endpoint archives are downloaded from.
+    pub fn endpoint(&self) -> &StatsEndpoint {
+        &self.endpoint
+    }
+
     /// Build the URL for a specific date.
     fn build_url(&self, date: NaiveDate) -> String {
-        let date_str = date.format("%Y%m%d").to_string();
-        format!(
-            "{}/Mainnet/builder_fills/{}/{}.csv.lz4",
-            STATS_BASE_URL, self.builder_address, date_str
-        )
+        self.endpoint.archive_url(&self.builder_address, date)
     }
 
     /// Fetch fills for a specific date.
@@ -87,8 +329,149 @@ impl BuilderDataClient {
     /// - `NotFound` if no data exists for that date (403/404)
     /// - `Http` for network errors
     /// - `Decompression` if LZ4 decompression fails
-    /// - `CsvParse` if CSV parsing fails
+    /// - `CsvParse`, `InvalidRow` or `MissingColumns` if CSV parsing fails
     pub async fn fetch_fills(&self, date: NaiveDate) -> Result<Vec<BuilderFill>, BuilderDataError> {
+        let (fills, report) = self.fetch_fills_with_report(date).await?;
+        self.warn_skipped(date, &report);
+        Ok(fills)
+    }
+
+    /// Log rows that lenient parsing skipped.
+    fn warn_skipped(&self, date: NaiveDate, report: &ParseReport) {
+        if report.skipped == 0 {
+            return;
+        }
+        tracing::warn!(
+            "Skipped {} of {} builder fill rows for {} on {}{}",
+            report.skipped,
+            report.rows,
+            self.builder_address,
+            date.format("%Y-%m-%d"),
+            report
+                .errors
+                .first()
+                .map(|e| format!(" (first: {})", e))
+                .unwrap_or_default()
+        );
+    }
+
+    /// Fetch fills for a specific date along with a report of how the
+    /// archive parsed.
+    ///
+    /// The archive is decoded while it downloads, so only the fills passing
+    /// the client's filter are ever held in memory. In lenient mode the
+    /// report lists the rows that were skipped. Fills served from the parsed
+    /// cache always come from a clean parse.
+    pub async fn fetch_fills_with_report(
+        &self,
+        date: NaiveDate,
+    ) -> Result<(Vec<BuilderFill>, ParseReport), BuilderDataError> {
+        if let Some((mut fills, report)) = self.load_cached(date).await {
+            tracing::debug!(
+                "Loaded {} cached builder fills for {}",
+                fills.len(),
+                date.format("%Y-%m-%d")
+            );
+            self.tag_builder(&mut fills);
+            return Ok((fills, report));
+        }
+
+        let response = self.send(date).await?;
+        let (mut stream, download) = BuilderFillStream::from_response(
+            response,
+            self.filter.clone(),
+            self.parse_mode,
+            &self.builder_address,
+            self.cache_writer(date).await,
+        );
+        let mut fills = Vec::new();
+        while let Some(fill) = stream.try_next().await? {
+            fills.push(fill);
+        }
+        let report = stream.report().await.unwrap_or_default();
+
+        if let Ok(Some(writer)) = download.await {
+            // Only a complete, clean parse may be reused as is
+            let parsed = self.filter.is_empty() && report.is_clean();
+            writer.commit(parsed.then_some(fills.as_slice())).await;
+        }
+        tracing::info!(
+            "Parsed {} builder fills for {}",
+            fills.len(),
+            date.format("%Y-%m-%d")
+        );
+
+        Ok((fills, report))
+    }
+
+    /// Stream fills for a specific date while the archive downloads.
+    ///
+    /// Unlike [`fetch_fills`](Self::fetch_fills), fills are handed over as
+    /// they are decoded instead of being collected. A cached archive is
+    /// streamed from the cache, and downloads are added to it, but they are
+    /// not retried.
+    ///
+    /// # Errors
+    ///
+    /// - `NotFound` if no data exists for that date (403/404)
+    /// - `Http` if the request fails
+    ///
+    /// Errors while decoding are yielded by the stream.
+    pub async fn stream_fills(
+        &self,
+        date: NaiveDate,
+    ) -> Result<BuilderFillStream, BuilderDataError> {
+        if let Some(cache) = &self.cache {
+            if let Some(compressed) = cache.load_raw(&self.builder_address, date).await {
+                return Ok(BuilderFillStream::from_lz4_reader(
+                    std::io::Cursor::new(compressed),
+                    self.filter.clone(),
+                    self.parse_mode,
+                    &self.builder_address,
+                ));
+            }
+        }
+
+        let response = self.send(date).await?;
+        let (stream, download) = BuilderFillStream::from_response(
+            response,
+            self.filter.clone(),
+            self.parse_mode,
+            &self.builder_address,
+            self.cache_writer(date).await,
+        );
+        tokio::spawn(async move {
+            if let Ok(Some(writer)) = download.await {
+                writer.commit(None).await;
+            }
+        });
+        Ok(stream)
+    }
+
+    /// A writer for caching the archive for `date`, if there is a cache.
+    async fn cache_writer(&self, date: NaiveDate) -> Option<ArchiveWriter> {
+        let cache = self.cache.as_ref()?;
+        match cache.writer(&self.builder_address, date).await {
+            Ok(writer) => Some(writer),
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to cache builder fills for {} on {}: {}",
+                    self.builder_address,
+                    date,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    /// Request the archive for `date`, mapping 403/404 to `NotFound`.
+    async fn send(&self, date: NaiveDate) -> Result<reqwest::Response, BuilderDataError> {
+        if self.offline {
+            return Err(BuilderDataError::NotFound {
+                date: date.format("%Y-%m-%d").to_string(),
+            });
+        }
         let url = self.build_url(date);
         tracing::debug!("Fetching builder fills from: {}", url);
 
@@ -104,31 +487,46 @@ impl BuilderDataClient {
         }
 
         // Check for other errors
-        let response = response.error_for_status()?;
-
-        // Get compressed bytes
-        let compressed = response.bytes().await?;
-        tracing::debug!("Downloaded {} bytes (compressed)", compressed.len());
+        Ok(response.error_for_status()?)
+    }
 
-        // Decompress LZ4
-        let decompressed = decompress_lz4(&compressed)?;
-        tracing::debug!("Decompressed to {} bytes", decompressed.len());
+    /// Cached fills for `date`, filtered and parsed like a download would
+    /// be.
+    async fn load_cached(&self, date: NaiveDate) -> Option<(Vec<BuilderFill>, ParseReport)> {
+        let cache = self.cache.as_ref()?;
+        if let Some(mut fills) = cache.load_parsed(&self.builder_address, date).await {
+            // The parsed cache holds every row of the archive
+            let rows = fills.len();
+            fills.retain(|fill| self.filter.matches(fill));
+            let report = ParseReport {
+                rows,
+                parsed: fills.len(),
+                ..Default::default()
+            };
+            return Some((fills, report));
+        }
 
-        // Parse CSV
-        let fills = parse_builder_fills(&decompressed)?;
-        tracing::info!(
-            "Parsed {} builder fills for {}",
-            fills.len(),
-            date.format("%Y-%m-%d")
-        );
+        let compressed = cache.load_raw(&self.builder_address, date).await?;
+        match decode_archive(&compressed, &self.filter, self.parse_mode) {
+            Ok(result) => Some(result),
+            Err(e) => {
+                tracing::warn!("Unreadable cached archive for {}: {}", date, e);
+                None
+            }
+        }
+    }
 
-        Ok(fills)
+    fn tag_builder(&self, fills: &mut [BuilderFill]) {
+        for fill in fills {
+            fill.builder.clone_from(&self.builder_address);
+        }
     }
 
     /// Fetch fills for a date range (inclusive).
     ///
-    /// Fetches data for each date in the range. Dates with no data
-    /// are skipped (not treated as errors).
+    /// Dates with no data are skipped (not treated as errors). Dates are
+    /// fetched concurrently and retried as configured; see
+    /// [`fetch_fills_range_report`](Self::fetch_fills_range_report).
     ///
     /// # Arguments
     ///
@@ -137,40 +535,137 @@ impl BuilderDataClient {
     ///
     /// # Returns
     ///
-    /// All fills from the date range, combined and sorted by time.
+    /// All fills from the date range, combined and sorted by time, or the
+    /// error of the earliest date that could not be fetched.
     pub async fn fetch_fills_range(
         &self,
         from: NaiveDate,
         to: NaiveDate,
     ) -> Result<Vec<BuilderFill>, BuilderDataError> {
-        let mut all_fills = Vec::new();
-        let mut current = from;
+        self.fetch_fills_range_report(from, to).await?.into_result()
+    }
+
+    /// Fetch fills for a date range (inclusive), reporting per date which
+    /// were fetched, missing or failed.
+    ///
+    /// Up to the configured concurrency of dates are in flight at once.
+    /// Transient failures (network errors, 5xx and 429 responses, truncated
+    /// downloads) are retried with exponential backoff.
+    ///
+    /// # Errors
+    ///
+    /// Only `InvalidDate` if the range is invalid; per-date errors are
+    /// reported in [`RangeFetch::failed`].
+    pub async fn fetch_fills_range_report(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<RangeFetch, BuilderDataError> {
+        if from > to {
+            return Err(BuilderDataError::InvalidDate(format!(
+                "range start {} is after end {}",
+                from, to
+            )));
+        }
 
-        while current <= to {
-            match self.fetch_fills(current).await {
-                Ok(fills) => {
-                    all_fills.extend(fills);
+        let results: Vec<(NaiveDate, DateFetch)> =
+            self.fetch_fills_by_date(from, to).collect().await;
+
+        let mut report = RangeFetch::default();
+        for (date, result) in results {
+            match result {
+                Ok((fills, parse_report)) => {
+                    self.warn_skipped(date, &parse_report);
+                    report.fills.extend(fills);
+                    report.fetched.push(date);
+                    report.reports.push(parse_report);
                 }
                 Err(BuilderDataError::NotFound { .. }) => {
-                    // Skip dates with no data
-                    tracing::debug!("No data for {}", current.format("%Y-%m-%d"));
+                    tracing::debug!("No data for {}", date.format("%Y-%m-%d"));
+                    report.missing.push(date);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to fetch builder fills for {} on {}: {}",
+                        self.builder_address,
+                        date.format("%Y-%m-%d"),
+                        e
+                    );
+                    report.failed.push((date, e));
                 }
-                Err(e) => return Err(e),
             }
-            current = current
-                .succ_opt()
-                .ok_or_else(|| BuilderDataError::InvalidDate("date overflow".to_string()))?;
         }
 
         // Sort by time
-        all_fills.sort_by_key(|f| f.time);
+        report.fills.sort_by_key(|f| f.time);
 
-        Ok(all_fills)
+        Ok(report)
     }
+
+    /// Fetch fills date by date (inclusive), in date order.
+    ///
+    /// Dates are fetched concurrently and retried like
+    /// [`fetch_fills_range_report`](Self::fetch_fills_range_report), but
+    /// each date is handed over as soon as it and all earlier dates are
+    /// done, so only the configured concurrency of dates is held in memory
+    /// at once. Yields nothing if `from` is after `to`.
+    pub fn fetch_fills_by_date(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> impl Stream<Item = (NaiveDate, DateFetch)> + '_ {
+        let dates: Vec<NaiveDate> = from.iter_days().take_while(|d| *d <= to).collect();
+        stream::iter(dates)
+            .map(move |date| async move { (date, self.fetch_fills_with_retry(date).await) })
+            .buffered(self.concurrency)
+    }
+
+    /// Fetch one date, retrying transient failures.
+    async fn fetch_fills_with_retry(&self, date: NaiveDate) -> DateFetch {
+        let mut attempt = 0;
+        loop {
+            match self.fetch_fills_with_report(date).await {
+                Err(e) if e.is_retryable() && attempt < self.max_retries => {
+                    let delay = self.retry_delay * 2u32.saturating_pow(attempt);
+                    attempt += 1;
+                    tracing::debug!(
+                        "Retrying builder fills for {} ({}/{}) in {:?}: {}",
+                        date.format("%Y-%m-%d"),
+                        attempt,
+                        self.max_retries,
+                        delay,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Decompress and parse a `.csv.lz4` archive.
+pub(crate) fn parse_archive(compressed: &[u8]) -> Result<Vec<BuilderFill>, BuilderDataError> {
+    let decompressed = decompress_lz4(compressed)?;
+    tracing::debug!("Decompressed to {} bytes", decompressed.len());
+    parse_builder_fills(&decompressed)
+}
+
+/// Decode a `.csv.lz4` archive incrementally, keeping only rows passing
+/// `filter`.
+fn decode_archive(
+    compressed: &[u8],
+    filter: &FillFilter,
+    mode: ParseMode,
+) -> Result<(Vec<BuilderFill>, ParseReport), BuilderDataError> {
+    let decoder = lz4_flex::frame::FrameDecoder::new(compressed);
+    let mut reader = FillReader::new(decoder, filter.clone(), mode);
+    let fills = reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+    Ok((fills, reader.into_report()))
 }
 
 /// Decompress LZ4 data.
-fn decompress_lz4(compressed: &[u8]) -> Result<Vec<u8>, BuilderDataError> {
+pub(crate) fn decompress_lz4(compressed: &[u8]) -> Result<Vec<u8>, BuilderDataError> {
     let mut decoder = lz4_flex::frame::FrameDecoder::new(compressed);
     let mut decompressed = Vec::new();
 
@@ -197,10 +692,79 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_into_result_reports_earliest_failure() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+        let report = RangeFetch {
+            fetched: vec![day(1)],
+            missing: vec![day(2)],
+            failed: vec![
+                (
+                    day(3),
+                    BuilderDataError::Decompression("truncated".to_string()),
+                ),
+                (day(4), BuilderDataError::InvalidDate("x".to_string())),
+            ],
+            ..Default::default()
+        };
+
+        assert!(!report.is_complete());
+        assert!(matches!(
+            report.into_result(),
+            Err(BuilderDataError::Decompression(_))
+        ));
+        assert!(RangeFetch::default().into_result().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_range_served_from_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ArchiveCache::new(dir.path());
+        let builder = "0x2868fc0d9786a740b491577a43502259efa78a39";
+        let from = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+        let to = NaiveDate::from_ymd_opt(2026, 1, 12).unwrap();
+        for date in from.iter_days().take(3) {
+            cache
+                .store(builder, date, date.to_string().as_bytes(), &[])
+                .await;
+        }
+
+        let client = BuilderDataClient::new(builder)
+            .unwrap()
+            .with_cache(cache)
+            .with_concurrency(2);
+        let report = client.fetch_fills_range_report(from, to).await.unwrap();
+
+        assert_eq!(report.fetched.len(), 3);
+        assert!(report.missing.is_empty());
+        assert!(report.is_complete());
+        assert!(client.fetch_fills_range_report(to, from).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_offline_client_never_downloads() {
+        let dir = tempfile::tempdir().unwrap();
+        // Nothing listens here, so a download attempt would fail with `Http`
+        let endpoint = StatsEndpoint::mainnet().with_base_url("http://127.0.0.1:9");
+        let cache = ArchiveCache::new(dir.path()).with_endpoint(&endpoint);
+        let builder = "0x2868fc0d9786a740b491577a43502259efa78a39";
+        let cached = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+        cache.store(builder, cached, b"archive", &[]).await;
+
+        let client = BuilderDataClient::new(builder)
+            .unwrap()
+            .with_endpoint(endpoint)
+            .with_cache(cache)
+            .with_offline(true);
+
+        assert!(client.fetch_fills(cached).await.unwrap().is_empty());
+        let err = client.fetch_fills(cached.succ_opt().unwrap()).await;
+        assert!(matches!(err, Err(BuilderDataError::NotFound { .. })));
+    }
+
     #[test]
     fn test_build_url() {
-        let client =
-            BuilderDataClient::new("0x2868fc0d9786a740b491577a43502259efa78a39").unwrap();
+        let client = BuilderDataClient::new("0x2868fc0d9786a740b491577a43502259efa78a39").unwrap();
         let date = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
         let url = client.build_url(date);
 
@@ -209,4 +773,22 @@ mod tests {
             "https://stats-data.hyperliquid.xyz/Mainnet/builder_fills/0x2868fc0d9786a740b491577a43502259efa78a39/20260110.csv.lz4"
         );
     }
+
+    #[test]
+    fn test_build_url_for_endpoint() {
+        let date = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+        let client = BuilderDataClient::new("0xABC").unwrap();
+
+        let testnet = client.clone().with_endpoint(StatsEndpoint::testnet());
+        assert_eq!(
+            testnet.build_url(date),
+            "https://stats-data.hyperliquid.xyz/Testnet/builder_fills/0xabc/20260110.csv.lz4"
+        );
+
+        let local = client.with_endpoint(StatsEndpoint::new("http://127.0.0.1:9000/", "Mainnet"));
+        assert_eq!(
+            local.build_url(date),
+            "http://127.0.0.1:9000/Mainnet/builder_fills/0xabc/20260110.csv.lz4"
+        );
+    }
 }
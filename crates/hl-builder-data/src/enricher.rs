@@ -1,39 +1,45 @@
 //! Fill enrichment service for matching builder fills with regular fills.
 //!
 //! Since the builder fills CSV doesn't include a trade ID (tid), we match
-//! fills using a composite key of (user, coin, time, size, price, side).
+//! fills on user, coin and side, a time tolerance window, and price and size
+//! compared as decimal values (so `1.0` and `1` are equal).
+//!
+//! The two sources don't always split executions the same way, so besides
+//! one-to-one matches the matcher accepts:
+//! - one user fill spanning several builder fills ([`MatchKind::Split`]), and
+//! - several user fills sharing one builder fill ([`MatchKind::Partial`]).
+//!
+//! A missed match makes a builder fill look like a non-builder fill and can
+//! wrongly taint a user, so [`FillEnricher::match_fills`] reports how many
+//! fills matched, were ambiguous, or did not match at all.
 
 use crate::types::BuilderFill;
 use hl_types::{Asset, UserFill};
 use rust_decimal::Decimal;
 use std::collections::HashMap;
 
-/// Key for matching fills between builder data and regular fills.
-///
-/// Since builder fills don't have a trade ID, we use a composite key.
+/// Default time tolerance when matching fills (milliseconds).
+pub const DEFAULT_TIME_TOLERANCE_MS: u64 = 1000;
+
+/// Relative tolerance when comparing the volume-weighted price of split
+/// builder fills with the user fill's price (0.0001%).
+const VWAP_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 6);
+
+/// Key grouping fills that can possibly match each other.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct FillKey {
+struct GroupKey {
     user: String,
     coin: String,
-    /// Timestamp in milliseconds, rounded to nearest second for fuzzy matching.
-    time_sec: i64,
-    /// Size as string for exact decimal comparison.
-    size: String,
-    /// Price as string for exact decimal comparison.
-    price: String,
     /// True for buy/bid, false for sell/ask.
     is_buy: bool,
 }
 
-impl FillKey {
+impl GroupKey {
     /// Create a key from a builder fill.
     fn from_builder_fill(fill: &BuilderFill) -> Self {
         Self {
             user: fill.user.to_lowercase(),
             coin: fill.asset.symbol().to_uppercase(),
-            time_sec: fill.time.timestamp(),
-            size: fill.size.to_string(),
-            price: fill.price.to_string(),
             is_buy: fill.side.is_buy(),
         }
     }
@@ -43,25 +49,123 @@ impl FillKey {
         Self {
             user: user.to_lowercase(),
             coin: fill.asset.symbol().to_uppercase(),
-            time_sec: (fill.timestamp_ms / 1000) as i64,
-            size: fill.size.to_string(),
-            price: fill.price.to_string(),
             is_buy: matches!(fill.side, hl_types::Side::Buy),
         }
     }
 }
 
+/// How a user fill was matched to builder fills.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchKind {
+    /// One builder fill with the same price and size.
+    Exact,
+    /// Several distinguishable builder fills (different builder, fee or
+    /// counterparty) fit equally well; the closest in time was used.
+    Ambiguous,
+    /// The user fill corresponds to several builder fills (one-to-many).
+    Split,
+    /// The user fill is part of a larger builder fill (many-to-one).
+    Partial,
+}
+
+/// Builder fills matched to one user fill.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillMatch<'a> {
+    /// How the fill was matched.
+    pub kind: MatchKind,
+
+    /// Matched builder fills, closest in time first. More than one only for
+    /// [`MatchKind::Split`].
+    pub builder_fills: Vec<&'a BuilderFill>,
+
+    /// Fraction of the builder fills attributable to the user fill (below
+    /// one only for [`MatchKind::Partial`]).
+    pub share: Decimal,
+}
+
+impl<'a> FillMatch<'a> {
+    /// The closest matched builder fill.
+    pub fn builder_fill(&self) -> &'a BuilderFill {
+        self.builder_fills[0]
+    }
+
+    /// Builder fee attributable to the user fill.
+    pub fn builder_fee(&self) -> Decimal {
+        let total: Decimal = self.builder_fills.iter().map(|f| f.builder_fee).sum();
+        total * self.share
+    }
+}
+
+/// Match statistics for a batch of user fills.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MatchReport {
+    /// Number of user fills examined.
+    pub total: usize,
+
+    /// Fills matched unambiguously (exact, split or partial).
+    pub matched: usize,
+
+    /// Fills matched to one of several distinguishable candidates.
+    pub ambiguous: usize,
+
+    /// Fills with no builder fill match.
+    pub unmatched: usize,
+
+    /// Matched fills that spanned several builder fills.
+    pub split: usize,
+
+    /// Matched fills that shared a builder fill with other user fills.
+    pub partial: usize,
+
+    /// Trade IDs of unmatched fills.
+    pub unmatched_trade_ids: Vec<u64>,
+}
+
+impl MatchReport {
+    /// Number of fills attributed to a builder, ambiguous or not.
+    pub fn attributed(&self) -> usize {
+        self.matched + self.ambiguous
+    }
+
+    fn record(&mut self, fill: &UserFill, kind: Option<MatchKind>) {
+        self.total += 1;
+        match kind {
+            Some(MatchKind::Exact) => self.matched += 1,
+            Some(MatchKind::Ambiguous) => self.ambiguous += 1,
+            Some(MatchKind::Split) => {
+                self.matched += 1;
+                self.split += 1;
+            }
+            Some(MatchKind::Partial) => {
+                self.matched += 1;
+                self.partial += 1;
+            }
+            None => {
+                self.unmatched += 1;
+                self.unmatched_trade_ids.push(fill.trade_id);
+            }
+        }
+    }
+}
+
 /// Service for enriching regular fills with builder attribution data.
 ///
 /// # Matching Strategy
 ///
-/// Since builder fills don't include a trade ID, matching is done using:
+/// Since builder fills don't include a trade ID, candidates must share:
 /// 1. User address
 /// 2. Asset (coin)
-/// 3. Timestamp (rounded to second)
-/// 4. Size (exact match)
-/// 5. Price (exact match)
-/// 6. Side (buy/sell)
+/// 3. Side (buy/sell)
+/// 4. Time, within a tolerance window (default 1 second either side)
+///
+/// Among those, a builder fill with equal price and size is preferred,
+/// closest in time first. Failing that, a larger builder fill at the same
+/// price (the user fill is part of it), or several builder fills whose sizes
+/// add up to the user fill at the same average price.
+///
+/// Per-fill lookups like [`is_builder_fill`](Self::is_builder_fill) consider
+/// each fill on its own. [`match_fills`](Self::match_fills) matches a whole
+/// batch so each builder fill is used at most once.
 ///
 /// # Example
 ///
@@ -69,39 +173,152 @@ impl FillKey {
 /// use hl_builder_data::{BuilderFill, FillEnricher};
 ///
 /// let builder_fills: Vec<BuilderFill> = vec![/* ... */];
-/// let enricher = FillEnricher::new(builder_fills);
+/// let enricher = FillEnricher::new(builder_fills).with_time_tolerance_ms(2000);
 ///
 /// // Check if a fill was from the tracked builder
 /// // let is_builder = enricher.is_builder_fill(&user_fill, "0x...");
 /// ```
 pub struct FillEnricher {
-    /// Builder fills indexed by composite key.
-    fills_by_key: HashMap<FillKey, BuilderFill>,
+    /// All builder fills, in load order.
+    fills: Vec<BuilderFill>,
+
+    /// Indices into `fills` per group, sorted by time.
+    by_group: HashMap<GroupKey, Vec<usize>>,
 
-    /// Total number of builder fills loaded.
-    total_fills: usize,
+    /// Maximum time difference for a match (milliseconds).
+    time_tolerance_ms: u64,
 }
 
 impl FillEnricher {
     /// Create a new enricher from a list of builder fills.
     pub fn new(fills: Vec<BuilderFill>) -> Self {
-        let total_fills = fills.len();
-        let mut fills_by_key = HashMap::with_capacity(fills.len());
-
-        for fill in fills {
-            let key = FillKey::from_builder_fill(&fill);
-            fills_by_key.insert(key, fill);
+        let mut by_group: HashMap<GroupKey, Vec<usize>> = HashMap::new();
+        for (index, fill) in fills.iter().enumerate() {
+            by_group
+                .entry(GroupKey::from_builder_fill(fill))
+                .or_default()
+                .push(index);
+        }
+        for indices in by_group.values_mut() {
+            indices.sort_by_key(|&i| fills[i].timestamp_ms());
         }
 
         Self {
-            fills_by_key,
-            total_fills,
+            fills,
+            by_group,
+            time_tolerance_ms: DEFAULT_TIME_TOLERANCE_MS,
         }
     }
 
+    /// Set the maximum time difference between matching fills.
+    pub fn with_time_tolerance_ms(mut self, tolerance_ms: u64) -> Self {
+        self.time_tolerance_ms = tolerance_ms;
+        self
+    }
+
+    /// Get the time tolerance used for matching (milliseconds).
+    pub fn time_tolerance_ms(&self) -> u64 {
+        self.time_tolerance_ms
+    }
+
     /// Get the total number of builder fills loaded.
     pub fn total_fills(&self) -> usize {
-        self.total_fills
+        self.fills.len()
+    }
+
+    /// Find the builder fills matching a user fill.
+    ///
+    /// # Arguments
+    ///
+    /// * `fill` - The user fill to look up
+    /// * `user` - The user address (for matching)
+    pub fn find_match(&self, fill: &UserFill, user: &str) -> Option<FillMatch<'_>> {
+        let candidates = self.candidates(fill, user);
+
+        let exact: Vec<usize> = candidates
+            .iter()
+            .copied()
+            .filter(|&i| self.fills[i].price == fill.price && self.fills[i].size == fill.size)
+            .collect();
+        if !exact.is_empty() {
+            return Some(self.single_match(&exact, Decimal::ONE));
+        }
+
+        if let Some(&i) = candidates
+            .iter()
+            .find(|&&i| self.fills[i].price == fill.price && self.fills[i].size > fill.size)
+        {
+            return Some(self.partial_match(i, fill.size));
+        }
+
+        self.split_indices(fill, &candidates, |_| true)
+            .map(|indices| self.split_match(&indices))
+    }
+
+    /// Match a batch of one user's fills, using each builder fill at most once
+    /// (or, for partial matches, at most up to its size).
+    ///
+    /// Returns the match for each fill (in the same order) and statistics.
+    pub fn match_fills(
+        &self,
+        user: &str,
+        fills: &[UserFill],
+    ) -> (Vec<Option<FillMatch<'_>>>, MatchReport) {
+        let mut remaining: Vec<Decimal> = self.fills.iter().map(|f| f.size).collect();
+        let mut matches: Vec<Option<FillMatch<'_>>> = vec![None; fills.len()];
+        let candidates: Vec<Vec<usize>> = fills.iter().map(|f| self.candidates(f, user)).collect();
+
+        // Pass 1: one-to-one matches on untouched builder fills
+        for (slot, (fill, cands)) in fills.iter().zip(&candidates).enumerate() {
+            let exact: Vec<usize> = cands
+                .iter()
+                .copied()
+                .filter(|&i| {
+                    let bf = &self.fills[i];
+                    bf.price == fill.price && bf.size == fill.size && remaining[i] == bf.size
+                })
+                .collect();
+            if !exact.is_empty() {
+                remaining[exact[0]] = Decimal::ZERO;
+                matches[slot] = Some(self.single_match(&exact, Decimal::ONE));
+            }
+        }
+
+        // Pass 2: user fills that are part of a larger builder fill
+        for (slot, (fill, cands)) in fills.iter().zip(&candidates).enumerate() {
+            if matches[slot].is_some() {
+                continue;
+            }
+            if let Some(&i) = cands
+                .iter()
+                .find(|&&i| self.fills[i].price == fill.price && remaining[i] >= fill.size)
+            {
+                remaining[i] -= fill.size;
+                matches[slot] = Some(self.partial_match(i, fill.size));
+            }
+        }
+
+        // Pass 3: user fills spanning several untouched builder fills
+        for (slot, (fill, cands)) in fills.iter().zip(&candidates).enumerate() {
+            if matches[slot].is_some() {
+                continue;
+            }
+            if let Some(indices) =
+                self.split_indices(fill, cands, |i| remaining[i] == self.fills[i].size)
+            {
+                for &i in &indices {
+                    remaining[i] = Decimal::ZERO;
+                }
+                matches[slot] = Some(self.split_match(&indices));
+            }
+        }
+
+        let mut report = MatchReport::default();
+        for (fill, found) in fills.iter().zip(&matches) {
+            report.record(fill, found.as_ref().map(|m| m.kind));
+        }
+
+        (matches, report)
     }
 
     /// Check if a fill was attributed to the tracked builder.
@@ -111,19 +328,19 @@ impl FillEnricher {
     /// * `fill` - The user fill to check
     /// * `user` - The user address (for matching)
     pub fn is_builder_fill(&self, fill: &UserFill, user: &str) -> bool {
-        let key = FillKey::from_user_fill(fill, user);
-        self.fills_by_key.contains_key(&key)
+        self.find_match(fill, user).is_some()
     }
 
     /// Get the builder fill data if this fill was from the builder.
     ///
+    /// For split matches this is the builder fill closest in time.
+    ///
     /// # Arguments
     ///
     /// * `fill` - The user fill to look up
     /// * `user` - The user address (for matching)
     pub fn get_builder_fill(&self, fill: &UserFill, user: &str) -> Option<&BuilderFill> {
-        let key = FillKey::from_user_fill(fill, user);
-        self.fills_by_key.get(&key)
+        self.find_match(fill, user).map(|m| m.builder_fill())
     }
 
     /// Get the builder fee for a fill if it exists.
@@ -133,15 +350,56 @@ impl FillEnricher {
     /// * `fill` - The user fill to look up
     /// * `user` - The user address (for matching)
     pub fn get_builder_fee(&self, fill: &UserFill, user: &str) -> Option<Decimal> {
+        self.find_match(fill, user).map(|m| m.builder_fee())
+    }
+
+    /// Get the counterparty address of a fill if it was from the builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `fill` - The user fill to look up
+    /// * `user` - The user address (for matching)
+    pub fn get_counterparty(&self, fill: &UserFill, user: &str) -> Option<&str> {
+        self.get_builder_fill(fill, user)
+            .map(|bf| bf.counterparty.as_str())
+    }
+
+    /// Get the address of the builder that routed a fill, if any.
+    ///
+    /// When the enricher holds fills from several builders this attributes
+    /// the fill to exactly one of them.
+    ///
+    /// # Arguments
+    ///
+    /// * `fill` - The user fill to look up
+    /// * `user` - The user address (for matching)
+    pub fn get_builder(&self, fill: &UserFill, user: &str) -> Option<&str> {
         self.get_builder_fill(fill, user)
-            .map(|bf| bf.builder_fee)
+            .map(|bf| bf.builder.as_str())
+    }
+
+    /// Distinct builder addresses of the loaded fills, sorted.
+    pub fn builders(&self) -> Vec<&str> {
+        let mut builders: Vec<&str> = self.fills.iter().map(|f| f.builder.as_str()).collect();
+        builders.sort_unstable();
+        builders.dedup();
+        builders
+    }
+
+    /// Calculate builder fees collected by each builder.
+    pub fn builder_fees_by_builder(&self) -> HashMap<&str, Decimal> {
+        let mut fees = HashMap::new();
+        for fill in &self.fills {
+            *fees.entry(fill.builder.as_str()).or_insert(Decimal::ZERO) += fill.builder_fee;
+        }
+        fees
     }
 
     /// Get all builder fills for a specific user.
     pub fn fills_for_user(&self, user: &str) -> Vec<&BuilderFill> {
         let user_lower = user.to_lowercase();
-        self.fills_by_key
-            .values()
+        self.fills
+            .iter()
             .filter(|f| f.user.to_lowercase() == user_lower)
             .collect()
     }
@@ -149,26 +407,116 @@ impl FillEnricher {
     /// Get all builder fills for a specific asset.
     pub fn fills_for_asset(&self, asset: &Asset) -> Vec<&BuilderFill> {
         let symbol = asset.symbol().to_uppercase();
-        self.fills_by_key
-            .values()
+        self.fills
+            .iter()
             .filter(|f| f.asset.symbol().to_uppercase() == symbol)
             .collect()
     }
 
     /// Calculate total builder fees collected.
     pub fn total_builder_fees(&self) -> Decimal {
-        self.fills_by_key
-            .values()
-            .map(|f| f.builder_fee)
-            .sum()
+        self.fills.iter().map(|f| f.builder_fee).sum()
     }
 
     /// Calculate total volume (sum of notional values).
     pub fn total_volume(&self) -> Decimal {
-        self.fills_by_key
-            .values()
-            .map(|f| f.notional_value())
-            .sum()
+        self.fills.iter().map(|f| f.notional_value()).sum()
+    }
+
+    /// Builder fills in the fill's group within the time tolerance, closest
+    /// in time first.
+    fn candidates(&self, fill: &UserFill, user: &str) -> Vec<usize> {
+        let Some(indices) = self.by_group.get(&GroupKey::from_user_fill(fill, user)) else {
+            return Vec::new();
+        };
+
+        let time = fill.timestamp_ms as i64;
+        let tolerance = self.time_tolerance_ms as i64;
+        let start = indices.partition_point(|&i| self.fills[i].timestamp_ms() < time - tolerance);
+        let mut found: Vec<usize> = indices[start..]
+            .iter()
+            .copied()
+            .take_while(|&i| self.fills[i].timestamp_ms() <= time + tolerance)
+            .collect();
+        found.sort_by_key(|&i| ((self.fills[i].timestamp_ms() - time).abs(), i));
+        found
+    }
+
+    /// Match to the first of `indices`, flagging ambiguity if the others
+    /// would attribute the fill differently.
+    fn single_match(&self, indices: &[usize], share: Decimal) -> FillMatch<'_> {
+        let first = &self.fills[indices[0]];
+        let distinguishable = indices[1..].iter().any(|&i| {
+            let other = &self.fills[i];
+            other.builder != first.builder
+                || other.builder_fee != first.builder_fee
+                || other.counterparty != first.counterparty
+        });
+
+        FillMatch {
+            kind: if distinguishable {
+                MatchKind::Ambiguous
+            } else {
+                MatchKind::Exact
+            },
+            builder_fills: vec![first],
+            share,
+        }
+    }
+
+    /// Match a user fill of `size` to part of builder fill `index`.
+    fn partial_match(&self, index: usize, size: Decimal) -> FillMatch<'_> {
+        let builder_fill = &self.fills[index];
+        FillMatch {
+            kind: MatchKind::Partial,
+            builder_fills: vec![builder_fill],
+            share: size / builder_fill.size,
+        }
+    }
+
+    /// Find two or more candidates whose sizes add up to the user fill at the
+    /// same volume-weighted price, taking the closest in time first.
+    fn split_indices(
+        &self,
+        fill: &UserFill,
+        candidates: &[usize],
+        usable: impl Fn(usize) -> bool,
+    ) -> Option<Vec<usize>> {
+        let mut picked = Vec::new();
+        let mut size = Decimal::ZERO;
+        let mut notional = Decimal::ZERO;
+
+        for &i in candidates {
+            let bf = &self.fills[i];
+            if !usable(i) || size + bf.size > fill.size {
+                continue;
+            }
+            picked.push(i);
+            size += bf.size;
+            notional += bf.notional_value();
+            if size == fill.size {
+                break;
+            }
+        }
+
+        if picked.len() < 2 || size != fill.size {
+            return None;
+        }
+        let vwap = notional / size;
+        if (vwap - fill.price).abs() > fill.price * VWAP_TOLERANCE {
+            return None;
+        }
+
+        Some(picked)
+    }
+
+    /// Match a user fill to all of the builder fills at `indices`.
+    fn split_match(&self, indices: &[usize]) -> FillMatch<'_> {
+        FillMatch {
+            kind: MatchKind::Split,
+            builder_fills: indices.iter().map(|&i| &self.fills[i]).collect(),
+            share: Decimal::ONE,
+        }
     }
 }
 
@@ -213,6 +561,8 @@ mod tests {
             closed_pnl: Decimal::ZERO,
             twap_id: 0,
             builder_fee,
+            builder: "0xb1".to_string(),
+            line: 0,
         }
     }
 
@@ -239,6 +589,7 @@ mod tests {
             order_id: 67890,
             crossed: false,
             direction: "Open Long".to_string(),
+            liquidated_user: None,
         }
     }
 
@@ -297,6 +648,59 @@ mod tests {
         assert_eq!(fee, Some(dec!(0.003125)));
     }
 
+    #[test]
+    fn test_multiple_builders() {
+        let mut other =
+            make_builder_fill("0xabc", "ETH", 2000, dec!(3000), dec!(1), false, dec!(0.3));
+        other.builder = "0xb2".to_string();
+        let enricher = FillEnricher::new(vec![
+            make_builder_fill(
+                "0xabc",
+                "BTC",
+                1000,
+                dec!(50000),
+                dec!(0.1),
+                true,
+                dec!(0.5),
+            ),
+            other,
+        ]);
+
+        assert_eq!(enricher.builders(), vec!["0xb1", "0xb2"]);
+
+        let btc = make_user_fill("BTC", 1000000, dec!(50000), dec!(0.1), true);
+        let eth = make_user_fill("ETH", 2000000, dec!(3000), dec!(1), false);
+        assert_eq!(enricher.get_builder(&btc, "0xabc"), Some("0xb1"));
+        assert_eq!(enricher.get_builder(&eth, "0xabc"), Some("0xb2"));
+
+        let fees = enricher.builder_fees_by_builder();
+        assert_eq!(fees.get("0xb2"), Some(&dec!(0.3)));
+    }
+
+    #[test]
+    fn test_get_counterparty() {
+        let mut builder_fill = make_builder_fill(
+            "0xabc",
+            "BTC",
+            1000,
+            dec!(50000),
+            dec!(0.1),
+            true,
+            dec!(0.5),
+        );
+        builder_fill.counterparty = "0xdef".to_string();
+        let enricher = FillEnricher::new(vec![builder_fill]);
+
+        let user_fill = make_user_fill("BTC", 1000000, dec!(50000), dec!(0.1), true);
+        assert_eq!(
+            enricher.get_counterparty(&user_fill, "0xabc"),
+            Some("0xdef")
+        );
+
+        let other_fill = make_user_fill("BTC", 1000000, dec!(49000), dec!(0.1), true);
+        assert_eq!(enricher.get_counterparty(&other_fill, "0xabc"), None);
+    }
+
     #[test]
     fn test_total_fees_and_volume() {
         let builder_fills = vec![
@@ -310,4 +714,139 @@ mod tests {
         // 50000 * 0.1 + 3000 * 1.0 = 5000 + 3000 = 8000
         assert_eq!(enricher.total_volume(), dec!(8000));
     }
+
+    fn make_builder_fill_ms(
+        time_ms: i64,
+        price: Decimal,
+        size: Decimal,
+        fee: Decimal,
+    ) -> BuilderFill {
+        let mut fill = make_builder_fill("0xabc", "BTC", 0, price, size, true, fee);
+        fill.time = Utc.timestamp_millis_opt(time_ms).unwrap();
+        fill
+    }
+
+    #[test]
+    fn test_match_across_second_boundary() {
+        let enricher = FillEnricher::new(vec![make_builder_fill_ms(
+            1_999_900,
+            dec!(50000),
+            dec!(0.1),
+            dec!(0.5),
+        )]);
+
+        // Same execution, reported 200ms later and in the next second
+        let user_fill = make_user_fill("BTC", 2_000_100, dec!(50000), dec!(0.1), true);
+        assert!(enricher.is_builder_fill(&user_fill, "0xabc"));
+
+        // Outside the tolerance window
+        let late = make_user_fill("BTC", 2_001_000, dec!(50000), dec!(0.1), true);
+        assert!(!enricher.is_builder_fill(&late, "0xabc"));
+        let enricher = enricher.with_time_tolerance_ms(2000);
+        assert!(enricher.is_builder_fill(&late, "0xabc"));
+    }
+
+    #[test]
+    fn test_match_ignores_decimal_scale() {
+        let enricher = FillEnricher::new(vec![make_builder_fill(
+            "0xabc",
+            "ETH",
+            1000,
+            dec!(3000.0),
+            dec!(1.00),
+            true,
+            dec!(0.3),
+        )]);
+
+        let user_fill = make_user_fill("ETH", 1000000, dec!(3000), dec!(1), true);
+        let found = enricher.find_match(&user_fill, "0xabc").unwrap();
+        assert_eq!(found.kind, MatchKind::Exact);
+    }
+
+    #[test]
+    fn test_identical_fills_are_kept() {
+        let fills = vec![
+            make_builder_fill_ms(1_000_000, dec!(50000), dec!(0.1), dec!(0.5)),
+            make_builder_fill_ms(1_000_000, dec!(50000), dec!(0.1), dec!(0.5)),
+        ];
+        let enricher = FillEnricher::new(fills);
+        assert_eq!(enricher.total_fills(), 2);
+        assert_eq!(enricher.total_builder_fees(), dec!(1.0));
+
+        let user_fills = vec![
+            make_user_fill("BTC", 1_000_000, dec!(50000), dec!(0.1), true),
+            make_user_fill("BTC", 1_000_000, dec!(50000), dec!(0.1), true),
+        ];
+        let (matches, report) = enricher.match_fills("0xabc", &user_fills);
+        assert!(matches
+            .iter()
+            .all(|m| m.as_ref().map(|m| m.kind) == Some(MatchKind::Exact)));
+        assert_eq!(report.matched, 2);
+        assert_eq!(report.ambiguous, 0);
+    }
+
+    #[test]
+    fn test_ambiguous_when_candidates_differ() {
+        let mut other = make_builder_fill_ms(1_000_100, dec!(50000), dec!(0.1), dec!(0.5));
+        other.builder = "0xb2".to_string();
+        let enricher = FillEnricher::new(vec![
+            make_builder_fill_ms(1_000_000, dec!(50000), dec!(0.1), dec!(0.5)),
+            other,
+        ]);
+
+        let user_fill = make_user_fill("BTC", 1_000_000, dec!(50000), dec!(0.1), true);
+        let found = enricher.find_match(&user_fill, "0xabc").unwrap();
+        assert_eq!(found.kind, MatchKind::Ambiguous);
+        // The closest in time wins
+        assert_eq!(found.builder_fill().builder, "0xb1");
+    }
+
+    #[test]
+    fn test_split_match_one_to_many() {
+        let enricher = FillEnricher::new(vec![
+            make_builder_fill_ms(1_000_000, dec!(100), dec!(1), dec!(0.1)),
+            make_builder_fill_ms(1_000_200, dec!(102), dec!(1), dec!(0.1)),
+        ]);
+
+        // Aggregated user fill at the average price
+        let user_fill = make_user_fill("BTC", 1_000_000, dec!(101), dec!(2), true);
+        let found = enricher.find_match(&user_fill, "0xabc").unwrap();
+        assert_eq!(found.kind, MatchKind::Split);
+        assert_eq!(found.builder_fills.len(), 2);
+        assert_eq!(found.builder_fee(), dec!(0.2));
+    }
+
+    #[test]
+    fn test_partial_match_many_to_one() {
+        let enricher = FillEnricher::new(vec![make_builder_fill_ms(
+            1_000_000,
+            dec!(100),
+            dec!(3),
+            dec!(0.3),
+        )]);
+
+        let user_fills = vec![
+            make_user_fill("BTC", 1_000_000, dec!(100), dec!(1), true),
+            make_user_fill("BTC", 1_000_000, dec!(100), dec!(2), true),
+            make_user_fill("BTC", 1_000_000, dec!(100), dec!(1), true),
+        ];
+        let (matches, report) = enricher.match_fills("0xabc", &user_fills);
+
+        assert_eq!(matches[0].as_ref().unwrap().kind, MatchKind::Partial);
+        assert_eq!(matches[1].as_ref().unwrap().builder_fee(), dec!(0.2));
+        // The builder fill is used up by the first two
+        assert!(matches[2].is_none());
+        assert_eq!(
+            report,
+            MatchReport {
+                total: 3,
+                matched: 2,
+                ambiguous: 0,
+                unmatched: 1,
+                split: 0,
+                partial: 2,
+                unmatched_trade_ids: vec![12345],
+            }
+        );
+    }
 }
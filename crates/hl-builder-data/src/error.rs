@@ -1,5 +1,6 @@
 //! Error types for builder data operations.
 
+use crate::parser::RowError;
 use thiserror::Error;
 
 /// Errors that can occur when fetching or parsing builder data.
@@ -21,6 +22,14 @@ pub enum BuilderDataError {
     #[error("CSV parse error: {0}")]
     CsvParse(#[from] csv::Error),
 
+    /// A row could not be parsed (strict mode).
+    #[error("invalid row at {0}")]
+    InvalidRow(RowError),
+
+    /// The CSV header lacks required columns.
+    #[error("missing required columns: {}", .0.join(", "))]
+    MissingColumns(Vec<String>),
+
     /// Invalid builder address format.
     #[error("invalid builder address: {0}")]
     InvalidAddress(String),
@@ -29,7 +38,45 @@ pub enum BuilderDataError {
     #[error("invalid date format: {0}")]
     InvalidDate(String),
 
+    /// A local fill file does not follow the `<builder>/<YYYYMMDD>.csv[.lz4]`
+    /// convention or its contents do not match its name.
+    #[error("invalid archive {path}: {reason}")]
+    InvalidArchive { path: String, reason: String },
+
     /// IO error.
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
+
+impl BuilderDataError {
+    /// Whether the operation may succeed if retried: network failures,
+    /// server errors, rate limiting and corrupt (likely truncated) downloads.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Http(e) => match e.status() {
+                Some(status) => {
+                    status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                }
+                // Connection failures, timeouts and interrupted bodies
+                None => true,
+            },
+            Self::Decompression(_) => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(BuilderDataError::Decompression("eof".to_string()).is_retryable());
+        assert!(!BuilderDataError::NotFound {
+            date: "2026-01-10".to_string()
+        }
+        .is_retryable());
+        assert!(!BuilderDataError::InvalidAddress("x".to_string()).is_retryable());
+    }
+}
@@ -0,0 +1,461 @@
+//! Offline import of builder fill files from local disk.
+//!
+//! Files follow the same layout as the stats endpoint, relative to the
+//! import root:
+//!
+//! ```text
+//! <builder_address>/<YYYYMMDD>.csv.lz4
+//! <builder_address>/<YYYYMMDD>.csv
+//! ```
+//!
+//! The root may be a directory (searched recursively, so a mirrored
+//! `Mainnet/builder_fills/` tree works as is) or a `.tar` / `.tar.gz`
+//! archive with the same layout. Any `.csv` or `.csv.lz4` file that does not
+//! follow the convention is rejected rather than silently skipped, as are
+//! rows whose timestamp falls outside the date in the file name. Other
+//! files are ignored.
+
+use crate::cache::ArchiveCache;
+use crate::client::decompress_lz4;
+use crate::error::BuilderDataError;
+use crate::parser::{parse_builder_fills_with_report, ParseMode, ParseReport};
+use crate::types::BuilderFill;
+use chrono::NaiveDate;
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// Gzip magic number, used to detect compressed tarballs.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// One day of builder fills loaded from a local file.
+#[derive(Debug, Clone)]
+pub struct ImportedArchive {
+    /// Builder address (lowercase), from the parent directory name.
+    pub builder: String,
+
+    /// Date of the archive, from the file name.
+    pub date: NaiveDate,
+
+    /// Path of the file, within the tarball for tarball imports.
+    pub source: PathBuf,
+
+    /// Parsed fills, tagged with `builder`.
+    pub fills: Vec<BuilderFill>,
+
+    /// How the file parsed.
+    pub report: ParseReport,
+
+    /// LZ4-compressed CSV, as served by the stats endpoint.
+    compressed: Vec<u8>,
+}
+
+/// Imports builder fill files from a directory or tarball.
+///
+/// Importing is synchronous file IO; call it from `spawn_blocking` inside an
+/// async runtime.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use hl_builder_data::{ArchiveImporter, FillEnricher};
+///
+/// let fills = ArchiveImporter::new()
+///     .with_builder("0x2868fc0d9786a740b491577a43502259efa78a39")
+///     .load_fills("fixtures/builder_fills.tar.gz")
+///     .unwrap();
+/// let enricher = FillEnricher::new(fills);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveImporter {
+    builders: HashSet<String>,
+    parse_mode: ParseMode,
+}
+
+impl ArchiveImporter {
+    /// Create an importer that accepts files for any builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only import files for this builder. May be called several times;
+    /// files for other builders are skipped.
+    pub fn with_builder(mut self, address: &str) -> Self {
+        self.builders.insert(address.to_lowercase());
+        self
+    }
+
+    /// Set how rows that fail to parse are handled (strict by default).
+    pub fn with_parse_mode(mut self, mode: ParseMode) -> Self {
+        self.parse_mode = mode;
+        self
+    }
+
+    /// Import every builder fill file under `path`.
+    ///
+    /// Archives are returned ordered by builder and date.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidArchive` if a fill file breaks the naming convention, has
+    ///   rows outside its date, or the same builder and date appear twice
+    /// - `Io`, `Decompression` or `CsvParse` if a file cannot be read
+    pub fn import(&self, path: impl AsRef<Path>) -> Result<Vec<ImportedArchive>, BuilderDataError> {
+        let path = path.as_ref();
+        let mut archives = BTreeMap::new();
+
+        if path.is_dir() {
+            self.import_dir(path, path, &mut archives)?;
+        } else {
+            self.import_tarball(path, &mut archives)?;
+        }
+
+        Ok(archives.into_values().collect())
+    }
+
+    /// Import every builder fill file under `path` and return all fills
+    /// sorted by time, ready for a [`FillEnricher`](crate::FillEnricher).
+    pub fn load_fills(&self, path: impl AsRef<Path>) -> Result<Vec<BuilderFill>, BuilderDataError> {
+        let mut fills: Vec<BuilderFill> = self
+            .import(path)?
+            .into_iter()
+            .flat_map(|archive| archive.fills)
+            .collect();
+        fills.sort_by_key(|f| f.time);
+        Ok(fills)
+    }
+
+    fn import_dir(
+        &self,
+        root: &Path,
+        dir: &Path,
+        archives: &mut BTreeMap<(String, NaiveDate), ImportedArchive>,
+    ) -> Result<(), BuilderDataError> {
+        let mut entries = std::fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by_key(|e| e.path());
+
+        for entry in entries {
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                self.import_dir(root, &path, archives)?;
+                continue;
+            }
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            if let Some(name) = self.classify(relative)? {
+                let mut contents = Vec::new();
+                File::open(&path)?.read_to_end(&mut contents)?;
+                self.add(name, relative, contents, archives)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn import_tarball(
+        &self,
+        path: &Path,
+        archives: &mut BTreeMap<(String, NaiveDate), ImportedArchive>,
+    ) -> Result<(), BuilderDataError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let is_gzip = std::io::BufRead::fill_buf(&mut reader)?.starts_with(&GZIP_MAGIC);
+        let reader: Box<dyn Read> = if is_gzip {
+            Box::new(flate2::read::GzDecoder::new(reader))
+        } else {
+            Box::new(reader)
+        };
+
+        let mut tarball = tar::Archive::new(reader);
+        for entry in tarball.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let relative = entry.path()?.into_owned();
+            if let Some(name) = self.classify(&relative)? {
+                let mut contents = Vec::new();
+                entry.read_to_end(&mut contents)?;
+                self.add(name, &relative, contents, archives)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Work out the builder and date of a fill file from its path.
+    ///
+    /// Returns `None` for files that are not fill files or belong to a
+    /// builder that is not being imported.
+    fn classify(&self, path: &Path) -> Result<Option<ArchiveName>, BuilderDataError> {
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            return Ok(None);
+        };
+        let (stem, compressed) = if let Some(stem) = file_name.strip_suffix(".csv.lz4") {
+            (stem, true)
+        } else if let Some(stem) = file_name.strip_suffix(".csv") {
+            (stem, false)
+        } else {
+            return Ok(None);
+        };
+
+        let invalid = |reason: String| BuilderDataError::InvalidArchive {
+            path: path.display().to_string(),
+            reason,
+        };
+
+        if stem.len() != 8 || !stem.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid(format!(
+                "file name '{}' is not a YYYYMMDD date",
+                file_name
+            )));
+        }
+        let date = NaiveDate::parse_from_str(stem, "%Y%m%d")
+            .map_err(|_| invalid(format!("'{}' is not a valid date", stem)))?;
+
+        let builder = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .and_then(|n| n.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        if !is_address(&builder) {
+            return Err(invalid(format!(
+                "parent directory '{}' is not a builder address",
+                builder
+            )));
+        }
+
+        if !self.builders.is_empty() && !self.builders.contains(&builder) {
+            return Ok(None);
+        }
+
+        Ok(Some(ArchiveName {
+            builder,
+            date,
+            compressed,
+        }))
+    }
+
+    fn add(
+        &self,
+        name: ArchiveName,
+        source: &Path,
+        contents: Vec<u8>,
+        archives: &mut BTreeMap<(String, NaiveDate), ImportedArchive>,
+    ) -> Result<(), BuilderDataError> {
+        let invalid = |reason: String| BuilderDataError::InvalidArchive {
+            path: source.display().to_string(),
+            reason,
+        };
+
+        let key = (name.builder.clone(), name.date);
+        if let Some(existing) = archives.get(&key) {
+            return Err(invalid(format!(
+                "duplicate archive for builder {} on {} (also in {})",
+                name.builder,
+                name.date,
+                existing.source.display()
+            )));
+        }
+
+        let (csv, compressed) = if name.compressed {
+            (decompress_lz4(&contents)?, contents)
+        } else {
+            let compressed = compress_lz4(&contents)?;
+            (contents, compressed)
+        };
+
+        let (mut fills, report) = parse_builder_fills_with_report(&csv, self.parse_mode)?;
+        if let Some(fill) = fills.iter().find(|f| f.time.date_naive() != name.date) {
+            return Err(invalid(format!(
+                "fill at {} is outside the archive date {}",
+                fill.time, name.date
+            )));
+        }
+        for fill in &mut fills {
+            fill.builder.clone_from(&name.builder);
+        }
+
+        tracing::debug!(
+            "Imported {} builder fills for {} on {} from {}",
+            fills.len(),
+            name.builder,
+            name.date,
+            source.display()
+        );
+
+        archives.insert(
+            key,
+            ImportedArchive {
+                builder: name.builder,
+                date: name.date,
+                source: source.to_path_buf(),
+                fills,
+                report,
+                compressed,
+            },
+        );
+        Ok(())
+    }
+}
+
+impl ArchiveCache {
+    /// Add imported archives to the cache, so clients using it serve those
+    /// dates without going to the network.
+    pub async fn import(&self, archives: &[ImportedArchive]) {
+        for archive in archives {
+            if archive.report.is_clean() {
+                self.store(
+                    &archive.builder,
+                    archive.date,
+                    &archive.compressed,
+                    &archive.fills,
+                )
+                .await;
+            } else {
+                // Let clients re-parse with their own parse mode
+                self.store_raw(&archive.builder, archive.date, &archive.compressed)
+                    .await;
+            }
+        }
+    }
+}
+
+/// Builder and date parsed from a fill file path.
+struct ArchiveName {
+    builder: String,
+    date: NaiveDate,
+    compressed: bool,
+}
+
+/// Whether `s` looks like a lowercase `0x` address.
+fn is_address(s: &str) -> bool {
+    s.len() == 42 && s.starts_with("0x") && s[2..].bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn compress_lz4(data: &[u8]) -> Result<Vec<u8>, BuilderDataError> {
+    let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+    std::io::Write::write_all(&mut encoder, data)?;
+    Ok(encoder.finish().map_err(std::io::Error::other)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUILDER: &str = "0x2868fc0d9786a740b491577a43502259efa78a39";
+
+    const CSV: &str = "time,user,coin,side,px,sz,crossed,special_trade_type,tif,is_trigger,counterparty,closed_pnl,twap_id,builder_fee
+2026-01-10T00:00:07Z,0x7b73dfae34492a35715ca037b19e006befdbe4cc,SOL,Bid,135.88,0.23,false,Na,Alo,false,0xc029043cd00b80363130fa058818459a521842a1,0,0,0.003125
+";
+
+    fn write(root: &Path, relative: &str, contents: &[u8]) {
+        let path = root.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_import_directory_plain_and_lz4() {
+        let dir = tempfile::tempdir().unwrap();
+        let day2 = CSV.replace("2026-01-10", "2026-01-11");
+        write(
+            dir.path(),
+            &format!("{}/20260110.csv", BUILDER),
+            CSV.as_bytes(),
+        );
+        write(
+            dir.path(),
+            &format!("Mainnet/builder_fills/{}/20260111.csv.lz4", BUILDER),
+            &compress_lz4(day2.as_bytes()).unwrap(),
+        );
+        write(dir.path(), "README.md", b"not a fill file");
+
+        let archives = ArchiveImporter::new().import(dir.path()).unwrap();
+
+        assert_eq!(archives.len(), 2);
+        assert_eq!(
+            archives[0].date,
+            NaiveDate::from_ymd_opt(2026, 1, 10).unwrap()
+        );
+        assert_eq!(archives[1].fills.len(), 1);
+        assert_eq!(archives[1].fills[0].builder, BUILDER);
+    }
+
+    #[test]
+    fn test_rejects_bad_file_name() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            &format!("{}/2026-01-10.csv", BUILDER),
+            CSV.as_bytes(),
+        );
+
+        let err = ArchiveImporter::new().import(dir.path()).unwrap_err();
+        assert!(matches!(err, BuilderDataError::InvalidArchive { .. }));
+    }
+
+    #[test]
+    fn test_rejects_non_address_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "fills/20260110.csv", CSV.as_bytes());
+
+        let err = ArchiveImporter::new().import(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("not a builder address"));
+    }
+
+    #[test]
+    fn test_rejects_rows_outside_file_date() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            &format!("{}/20260111.csv", BUILDER),
+            CSV.as_bytes(),
+        );
+
+        let err = ArchiveImporter::new().import(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("outside the archive date"));
+    }
+
+    #[test]
+    fn test_rejects_duplicate_dates() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            &format!("{}/20260110.csv", BUILDER),
+            CSV.as_bytes(),
+        );
+        write(
+            dir.path(),
+            &format!("{}/20260110.csv.lz4", BUILDER),
+            &compress_lz4(CSV.as_bytes()).unwrap(),
+        );
+
+        let err = ArchiveImporter::new().import(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("duplicate archive"));
+    }
+
+    #[test]
+    fn test_builder_filter_skips_other_builders() {
+        let dir = tempfile::tempdir().unwrap();
+        let other = "0x0000000000000000000000000000000000000001";
+        write(
+            dir.path(),
+            &format!("{}/20260110.csv", BUILDER),
+            CSV.as_bytes(),
+        );
+        write(
+            dir.path(),
+            &format!("{}/20260110.csv", other),
+            CSV.as_bytes(),
+        );
+
+        let archives = ArchiveImporter::new()
+            .with_builder(&BUILDER.to_uppercase().replace("0X", "0x"))
+            .import(dir.path())
+            .unwrap();
+
+        assert_eq!(archives.len(), 1);
+        assert_eq!(archives[0].builder, BUILDER);
+    }
+}
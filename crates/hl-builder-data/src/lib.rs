@@ -12,6 +12,11 @@
 //!
 //! **Important**: The builder address must be entirely lowercase.
 //!
+//! Testnet archives live under `Testnet/` instead; pass
+//! [`StatsEndpoint::testnet`] to [`BuilderDataClient::with_endpoint`], or
+//! point [`StatsEndpoint::with_base_url`] at another server with the same
+//! layout.
+//!
 //! # Example
 //!
 //! ```rust,no_run
@@ -57,22 +62,83 @@
 //! }
 //! ```
 //!
+//! To track several builders, fetch with one client per builder address and
+//! pass all the fills to a single [`FillEnricher`]. Each fill remembers the
+//! builder it was downloaded for, so [`FillEnricher::get_builder`] tells you
+//! which one routed it.
+//!
+//! # Analytics
+//!
+//! [`BuilderAnalyzer`] turns a builder's fills into fee revenue, volume and
+//! new-versus-returning user counts per day or hour, with the top assets and
+//! users by volume. For long ranges, feed fills day by day to a
+//! [`BuilderAnalysis`] from [`BuilderAnalyzer::start`] instead of collecting
+//! them all first.
+//!
+//! # Caching
+//!
+//! [`BuilderDataClient::with_cache`] keeps downloaded archives and their
+//! parsed fills in a local [`ArchiveCache`]. Completed days are never
+//! downloaded twice; today's partial file is refreshed after a short TTL.
+//!
+//! # Parsing
+//!
+//! Archives are parsed by column name, so new or reordered upstream columns
+//! do not break parsing. By default a malformed row fails the whole day;
+//! with [`ParseMode::Lenient`] bad rows are skipped and listed, with line,
+//! column and reason, in a [`ParseReport`].
+//!
+//! # Streaming
+//!
+//! Archives of popular builders have millions of rows.
+//! [`BuilderDataClient::stream_fills`] decodes an archive as it downloads and
+//! yields fills as an async [`BuilderFillStream`], and a [`FillFilter`] set
+//! with [`BuilderDataClient::with_filter`] drops rows for other users or
+//! assets before they are parsed, for streamed and regular fetches alike.
+//! [`BuilderDataClient::fetch_fills_by_date`] yields a range one day at a
+//! time, so only a day's fills are held at once.
+//!
+//! # Offline Import
+//!
+//! [`ArchiveImporter`] reads `.csv` and `.csv.lz4` files laid out as
+//! `<builder>/<YYYYMMDD>.csv.lz4` from a directory or tarball. The fills can
+//! go straight into a [`FillEnricher`], or into an [`ArchiveCache`] via
+//! [`ArchiveCache::import`] so a client serves them without network access.
+//!
 //! # Limitations
 //!
 //! - Files are uploaded with ~24 hour delay
 //! - Returns 403 if no fills exist for that builder on that date
-//! - No trade ID in CSV, so matching uses composite key (user, coin, time, size, price, side)
+//! - No trade ID in CSV, so matching uses user, coin, side, a time window, price and size
 
+mod analytics;
+mod cache;
 mod client;
 mod enricher;
 mod error;
+mod import;
 mod parser;
+mod stream;
 mod types;
 
-pub use client::BuilderDataClient;
-pub use enricher::FillEnricher;
+pub use analytics::{
+    ActivitySummary, AnalyticsBucket, BucketSize, BuilderAnalysis, BuilderAnalytics,
+    BuilderAnalyzer, RankedActivity, DEFAULT_TOP_N,
+};
+pub use cache::{ArchiveCache, DEFAULT_TODAY_TTL};
+pub use client::{
+    BuilderDataClient, DateFetch, RangeFetch, StatsEndpoint, DEFAULT_CONCURRENCY,
+    DEFAULT_MAX_RETRIES, STATS_BASE_URL,
+};
+pub use enricher::{FillEnricher, FillMatch, MatchKind, MatchReport, DEFAULT_TIME_TOLERANCE_MS};
 pub use error::BuilderDataError;
-pub use types::{BuilderFill, BuilderFillSide};
+pub use import::{ArchiveImporter, ImportedArchive};
+pub use parser::{
+    parse_builder_fills, parse_builder_fills_with_report, ParseMode, ParseReport, RowError,
+    MAX_REPORTED_ROW_ERRORS,
+};
+pub use stream::{BuilderFillStream, FillFilter};
+pub use types::{BuilderFill, BuilderFillSide, BASE_MAKER_FEE_RATE, BASE_TAKER_FEE_RATE};
 
 // Re-export chrono::NaiveDate for convenience
 pub use chrono::NaiveDate;
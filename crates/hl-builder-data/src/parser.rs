@@ -1,7 +1,115 @@
 //! CSV parser for builder fill data.
+//!
+//! Columns are looked up by name from the header, so reordered columns and
+//! new columns added upstream are fine. Only `time`, `user`, `coin`, `side`,
+//! `px`, `sz` and `builder_fee` are required; the other known columns fall
+//! back to defaults when absent.
+//!
+//! In [`ParseMode::Strict`] the first bad row fails the whole file. In
+//! [`ParseMode::Lenient`] bad rows are skipped and described in a
+//! [`ParseReport`], so one malformed upstream row does not cost a whole day
+//! of data.
 
 use crate::error::BuilderDataError;
-use crate::types::{BuilderFill, BuilderFillRecord};
+use crate::stream::FillFilter;
+use crate::types::{BuilderFill, BuilderFillSide};
+use chrono::{DateTime, Utc};
+use csv::StringRecord;
+use hl_types::Asset;
+use rust_decimal::Decimal;
+use std::fmt;
+use std::io::Read;
+use std::str::FromStr;
+
+/// Maximum number of row errors kept in a [`ParseReport`]. Further errors
+/// are only counted.
+pub const MAX_REPORTED_ROW_ERRORS: usize = 100;
+
+/// Columns every archive must have.
+const REQUIRED_COLUMNS: [&str; 7] = ["time", "user", "coin", "side", "px", "sz", "builder_fee"];
+
+/// Columns understood by the parser; anything else is reported as unknown.
+const KNOWN_COLUMNS: [&str; 14] = [
+    "time",
+    "user",
+    "coin",
+    "side",
+    "px",
+    "sz",
+    "crossed",
+    "special_trade_type",
+    "tif",
+    "is_trigger",
+    "counterparty",
+    "closed_pnl",
+    "twap_id",
+    "builder_fee",
+];
+
+/// How to handle rows that cannot be parsed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Fail on the first bad row.
+    #[default]
+    Strict,
+    /// Skip bad rows and report them.
+    Lenient,
+}
+
+/// A row that could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowError {
+    /// Line number in the CSV (the header is line 1).
+    pub line: u64,
+
+    /// Offending column, if the problem is with a single value.
+    pub column: Option<String>,
+
+    /// What was wrong.
+    pub reason: String,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.column {
+            Some(column) => write!(f, "line {}, column {}: {}", self.line, column, self.reason),
+            None => write!(f, "line {}: {}", self.line, self.reason),
+        }
+    }
+}
+
+/// Summary of parsing one archive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseReport {
+    /// Data rows read, including filtered-out and skipped rows.
+    pub rows: usize,
+
+    /// Rows parsed into fills.
+    pub parsed: usize,
+
+    /// Bad rows skipped in lenient mode.
+    pub skipped: usize,
+
+    /// The first [`MAX_REPORTED_ROW_ERRORS`] skipped rows.
+    pub errors: Vec<RowError>,
+
+    /// Header columns the parser does not know, ignored.
+    pub unknown_columns: Vec<String>,
+}
+
+impl ParseReport {
+    /// Whether no rows were skipped.
+    pub fn is_clean(&self) -> bool {
+        self.skipped == 0
+    }
+
+    fn record_error(&mut self, error: RowError) {
+        self.skipped += 1;
+        if self.errors.len() < MAX_REPORTED_ROW_ERRORS {
+            self.errors.push(error);
+        }
+    }
+}
 
 /// Parse builder fills from CSV data.
 ///
@@ -13,20 +121,347 @@ use crate::types::{BuilderFill, BuilderFillRecord};
 ///
 /// A vector of parsed `BuilderFill` structs.
 pub fn parse_builder_fills(data: &[u8]) -> Result<Vec<BuilderFill>, BuilderDataError> {
-    let mut reader = csv::Reader::from_reader(data);
-    let mut fills = Vec::new();
+    FillReader::new(data, FillFilter::default(), ParseMode::Strict).collect()
+}
+
+/// Parse builder fills from CSV data, returning a report of skipped rows
+/// and unknown columns alongside the fills.
+///
+/// In lenient mode only unreadable input (or missing required columns)
+/// is an error.
+pub fn parse_builder_fills_with_report(
+    data: &[u8],
+    mode: ParseMode,
+) -> Result<(Vec<BuilderFill>, ParseReport), BuilderDataError> {
+    let mut reader = FillReader::new(data, FillFilter::default(), mode);
+    let fills = reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+    Ok((fills, reader.into_report()))
+}
 
-    for result in reader.deserialize() {
-        let record: BuilderFillRecord = result?;
-        let fill = BuilderFill::try_from(record)
-            .map_err(|e| BuilderDataError::CsvParse(csv::Error::from(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                e,
-            ))))?;
-        fills.push(fill);
+/// Positions of the known columns in an archive's header.
+#[derive(Debug, Clone, Copy)]
+struct Schema {
+    time: usize,
+    user: usize,
+    coin: usize,
+    side: usize,
+    px: usize,
+    sz: usize,
+    builder_fee: usize,
+    crossed: Option<usize>,
+    special_trade_type: Option<usize>,
+    tif: Option<usize>,
+    is_trigger: Option<usize>,
+    counterparty: Option<usize>,
+    closed_pnl: Option<usize>,
+    twap_id: Option<usize>,
+}
+
+impl Schema {
+    fn from_headers(headers: &StringRecord) -> Result<Self, BuilderDataError> {
+        let find = |name: &str| headers.iter().position(|h| h.trim() == name);
+
+        let missing: Vec<String> = REQUIRED_COLUMNS
+            .iter()
+            .filter(|name| find(name).is_none())
+            .map(|name| name.to_string())
+            .collect();
+        if !missing.is_empty() {
+            return Err(BuilderDataError::MissingColumns(missing));
+        }
+        let required = |name: &str| find(name).unwrap_or_default();
+
+        Ok(Self {
+            time: required("time"),
+            user: required("user"),
+            coin: required("coin"),
+            side: required("side"),
+            px: required("px"),
+            sz: required("sz"),
+            builder_fee: required("builder_fee"),
+            crossed: find("crossed"),
+            special_trade_type: find("special_trade_type"),
+            tif: find("tif"),
+            is_trigger: find("is_trigger"),
+            counterparty: find("counterparty"),
+            closed_pnl: find("closed_pnl"),
+            twap_id: find("twap_id"),
+        })
+    }
+
+    fn parse_row(&self, row: &Row<'_>) -> Result<BuilderFill, RowError> {
+        let side = row.required("side", self.side)?;
+        let side = BuilderFillSide::from_str(side)
+            .ok_or_else(|| row.error("side", format!("invalid side '{}'", side)))?;
+
+        Ok(BuilderFill {
+            time: row.parse("time", self.time, parse_datetime)?,
+            user: row.required("user", self.user)?.to_string(),
+            asset: Asset::from_symbol(row.required("coin", self.coin)?),
+            side,
+            price: row.parse("px", self.px, parse_decimal)?,
+            size: row.parse("sz", self.sz, parse_decimal)?,
+            crossed: row.parse_or("crossed", self.crossed, parse_bool, false)?,
+            special_trade_type: row.optional(self.special_trade_type, "Na"),
+            time_in_force: row.optional(self.tif, ""),
+            is_trigger: row.parse_or("is_trigger", self.is_trigger, parse_bool, false)?,
+            counterparty: row.optional(self.counterparty, ""),
+            closed_pnl: row.parse_or(
+                "closed_pnl",
+                self.closed_pnl,
+                parse_decimal,
+                Decimal::ZERO,
+            )?,
+            twap_id: row.parse_or("twap_id", self.twap_id, parse_u64, 0)?,
+            builder_fee: row.parse("builder_fee", self.builder_fee, parse_decimal)?,
+            builder: String::new(),
+            line: row.line,
+        })
+    }
+}
+
+/// A CSV record with its line number, for error reporting.
+struct Row<'a> {
+    record: &'a StringRecord,
+    line: u64,
+}
+
+impl Row<'_> {
+    fn error(&self, column: &str, reason: String) -> RowError {
+        RowError {
+            line: self.line,
+            column: Some(column.to_string()),
+            reason,
+        }
+    }
+
+    fn required(&self, column: &str, index: usize) -> Result<&str, RowError> {
+        self.record
+            .get(index)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| self.error(column, "missing value".to_string()))
+    }
+
+    fn optional(&self, index: Option<usize>, default: &str) -> String {
+        index
+            .and_then(|i| self.record.get(i))
+            .map(str::trim)
+            .unwrap_or(default)
+            .to_string()
+    }
+
+    fn parse<T>(
+        &self,
+        column: &str,
+        index: usize,
+        parse: fn(&str) -> Result<T, String>,
+    ) -> Result<T, RowError> {
+        parse(self.required(column, index)?).map_err(|reason| self.error(column, reason))
+    }
+
+    fn parse_or<T>(
+        &self,
+        column: &str,
+        index: Option<usize>,
+        parse: fn(&str) -> Result<T, String>,
+        default: T,
+    ) -> Result<T, RowError> {
+        match index.and_then(|i| self.record.get(i)).map(str::trim) {
+            Some(value) if !value.is_empty() => {
+                parse(value).map_err(|reason| self.error(column, reason))
+            }
+            _ => Ok(default),
+        }
     }
+}
+
+/// Map a failure to read the archive itself. Interrupted downloads and
+/// corrupt compression are both worth retrying, unlike bad CSV.
+fn read_error(e: csv::Error) -> BuilderDataError {
+    let message = e.to_string();
+    if let csv::ErrorKind::Io(io) = e.into_kind() {
+        if let Some(Ok(http)) = io
+            .into_inner()
+            .map(|inner| inner.downcast::<reqwest::Error>())
+        {
+            return BuilderDataError::Http(*http);
+        }
+    }
+    BuilderDataError::Decompression(message)
+}
+
+/// Parse an ISO 8601 datetime.
+fn parse_datetime(s: &str) -> Result<DateTime<Utc>, String> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| format!("invalid time '{}': {}", s, e))
+}
 
-    Ok(fills)
+/// Parse a decimal from its string form.
+fn parse_decimal(s: &str) -> Result<Decimal, String> {
+    Decimal::from_str(s).map_err(|e| format!("invalid decimal '{}': {}", s, e))
+}
+
+/// Parse a bool ("true"/"false").
+fn parse_bool(s: &str) -> Result<bool, String> {
+    match s {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(format!("invalid bool '{}'", s)),
+    }
+}
+
+fn parse_u64(s: &str) -> Result<u64, String> {
+    s.parse()
+        .map_err(|e| format!("invalid integer '{}': {}", s, e))
+}
+
+/// Incremental CSV reader yielding one [`BuilderFill`] per matching row.
+///
+/// Rows are filtered on their raw `user` and `coin` columns before the rest
+/// of the row is parsed, so rows outside the filter cost almost nothing.
+pub(crate) struct FillReader<R: Read> {
+    records: csv::StringRecordsIntoIter<R>,
+    /// Header-level failure, reported as the first item.
+    error: Option<BuilderDataError>,
+    schema: Option<Schema>,
+    filter: FillFilter,
+    mode: ParseMode,
+    report: ParseReport,
+    done: bool,
+}
+
+impl<R: Read> FillReader<R> {
+    pub(crate) fn new(reader: R, filter: FillFilter, mode: ParseMode) -> Self {
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+        let mut report = ParseReport::default();
+
+        let (schema, error) = match reader.headers() {
+            Ok(headers) if headers.is_empty() => (None, None),
+            Ok(headers) => {
+                report.unknown_columns = headers
+                    .iter()
+                    .filter(|h| !KNOWN_COLUMNS.contains(&h.trim()))
+                    .map(str::to_string)
+                    .collect();
+                match Schema::from_headers(headers) {
+                    Ok(schema) => (Some(schema), None),
+                    Err(e) => (None, Some(e)),
+                }
+            }
+            Err(e) => (None, Some(e.into())),
+        };
+
+        Self {
+            records: reader.into_records(),
+            error,
+            schema,
+            filter,
+            mode,
+            report,
+            done: false,
+        }
+    }
+
+    /// Report for the rows read so far.
+    pub(crate) fn into_report(self) -> ParseReport {
+        self.report
+    }
+
+    fn keep(&self, schema: &Schema, record: &StringRecord) -> bool {
+        // Rows missing a column are kept so parsing reports the problem
+        record
+            .get(schema.user)
+            .is_none_or(|user| self.filter.matches_user(user.trim()))
+            && record
+                .get(schema.coin)
+                .is_none_or(|coin| self.filter.matches_asset(coin.trim()))
+    }
+
+    /// Handle a bad row: an error in strict mode, skipped in lenient mode.
+    fn bad_row(&mut self, error: RowError) -> Option<BuilderDataError> {
+        match self.mode {
+            ParseMode::Strict => Some(BuilderDataError::InvalidRow(error)),
+            ParseMode::Lenient => {
+                tracing::debug!("Skipping builder fill row: {}", error);
+                self.report.record_error(error);
+                None
+            }
+        }
+    }
+}
+
+impl<R: Read> FillReader<R> {
+    fn next_fill(&mut self) -> Option<Result<BuilderFill, BuilderDataError>> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+
+        loop {
+            let record = match self.records.next()? {
+                Ok(record) => record,
+                Err(e) => {
+                    self.report.rows += 1;
+                    let row_error = match e.kind() {
+                        // Invalid UTF-8 only affects this record
+                        csv::ErrorKind::Utf8 { pos, err } => RowError {
+                            line: pos.as_ref().map_or(0, |p| p.line()),
+                            column: None,
+                            reason: err.to_string(),
+                        },
+                        csv::ErrorKind::Io(_) => return Some(Err(read_error(e))),
+                        _ => return Some(Err(e.into())),
+                    };
+                    match self.bad_row(row_error) {
+                        Some(e) => return Some(Err(e)),
+                        None => continue,
+                    }
+                }
+            };
+            self.report.rows += 1;
+
+            let Some(schema) = self.schema else {
+                return Some(Err(BuilderDataError::MissingColumns(
+                    REQUIRED_COLUMNS.iter().map(|c| c.to_string()).collect(),
+                )));
+            };
+            if !self.keep(&schema, &record) {
+                continue;
+            }
+
+            let row = Row {
+                record: &record,
+                line: record.position().map_or(0, |p| p.line()),
+            };
+            match schema.parse_row(&row) {
+                Ok(fill) => {
+                    self.report.parsed += 1;
+                    return Some(Ok(fill));
+                }
+                Err(row_error) => {
+                    if let Some(e) = self.bad_row(row_error) {
+                        return Some(Err(e));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for FillReader<R> {
+    type Item = Result<BuilderFill, BuilderDataError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let item = self.next_fill();
+        // Stop after the first error; later rows cannot be trusted in strict
+        // mode, and lenient mode only returns unrecoverable errors
+        self.done = !matches!(item, Some(Ok(_)));
+        item
+    }
 }
 
 #[cfg(test)]
@@ -60,6 +495,7 @@ mod tests {
         assert_eq!(first.closed_pnl, dec!(-8.047272));
         assert_eq!(first.twap_id, 0);
         assert_eq!(first.builder_fee, dec!(0.029137));
+        assert_eq!(first.line, 2);
 
         // Check second fill (SOL)
         let second = &fills[1];
@@ -84,4 +520,78 @@ mod tests {
         // 135.88 * 0.23 = 31.2524
         assert_eq!(second.notional_value(), dec!(31.2524));
     }
+
+    #[test]
+    fn test_strict_mode_reports_row_and_column() {
+        let csv = SAMPLE_CSV.replace("135.88", "abc");
+        let err = parse_builder_fills(csv.as_bytes()).unwrap_err();
+
+        match err {
+            BuilderDataError::InvalidRow(row) => {
+                assert_eq!(row.line, 3);
+                assert_eq!(row.column.as_deref(), Some("px"));
+                assert!(row.reason.contains("abc"));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn test_lenient_mode_skips_bad_rows() {
+        let csv = SAMPLE_CSV
+            .replace("135.88", "abc")
+            .replace(",Bid,135.84", ",Hold,135.84");
+
+        let (fills, report) =
+            parse_builder_fills_with_report(csv.as_bytes(), ParseMode::Lenient).unwrap();
+
+        assert_eq!(fills.len(), 1);
+        assert_eq!(report.rows, 3);
+        assert_eq!(report.parsed, 1);
+        assert_eq!(report.skipped, 2);
+        assert!(!report.is_clean());
+        assert_eq!(
+            report.errors[1].to_string(),
+            "line 4, column side: invalid side 'Hold'"
+        );
+    }
+
+    #[test]
+    fn test_lenient_mode_skips_short_rows() {
+        let csv = format!("{}2026-01-10T00:01:00Z,0xabc,BTC\n", SAMPLE_CSV);
+
+        let (fills, report) =
+            parse_builder_fills_with_report(csv.as_bytes(), ParseMode::Lenient).unwrap();
+
+        assert_eq!(fills.len(), 3);
+        assert_eq!(report.errors[0].line, 5);
+        assert_eq!(report.errors[0].column.as_deref(), Some("side"));
+    }
+
+    #[test]
+    fn test_reordered_and_new_columns() {
+        let csv = "builder_fee,coin,user,time,new_column,sz,px,side
+0.5,BTC,0xabc,2026-01-10T00:00:04Z,whatever,0.01,95000,Ask
+";
+        let (fills, report) =
+            parse_builder_fills_with_report(csv.as_bytes(), ParseMode::Strict).unwrap();
+
+        assert_eq!(report.unknown_columns, vec!["new_column".to_string()]);
+        let fill = &fills[0];
+        assert_eq!(fill.asset.symbol(), "BTC");
+        assert_eq!(fill.price, dec!(95000));
+        assert!(!fill.side.is_buy());
+        // Optional columns fall back to defaults
+        assert_eq!(fill.closed_pnl, Decimal::ZERO);
+        assert_eq!(fill.counterparty, "");
+        assert!(!fill.crossed);
+    }
+
+    #[test]
+    fn test_missing_required_column() {
+        let csv = "time,user,coin,side,px,sz\n2026-01-10T00:00:04Z,0xabc,BTC,Bid,1,1\n";
+
+        let err = parse_builder_fills_with_report(csv.as_bytes(), ParseMode::Lenient).unwrap_err();
+        assert_eq!(err.to_string(), "missing required columns: builder_fee");
+    }
 }
@@ -0,0 +1,399 @@
+//! Streaming decompression and parsing of builder fill archives.
+//!
+//! Popular builders produce archives with millions of rows. Instead of
+//! decompressing the whole file and collecting every row, a
+//! [`BuilderFillStream`] decodes the LZ4 frame and CSV rows incrementally on
+//! a blocking thread and hands fills over a bounded channel, so memory use
+//! stays flat no matter how large the file is.
+//!
+//! A [`FillFilter`] is pushed down into the parser: rows for other users or
+//! assets are dropped before their prices and sizes are even parsed.
+
+use crate::cache::ArchiveWriter;
+use crate::error::BuilderDataError;
+use crate::parser::{FillReader, ParseMode, ParseReport};
+use crate::types::BuilderFill;
+use futures::Stream;
+use std::collections::HashSet;
+use std::io::{self, Read};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+/// Fills buffered between the parser thread and the consumer.
+const FILL_BUFFER: usize = 1024;
+
+/// Downloaded chunks buffered ahead of the decoder.
+const CHUNK_BUFFER: usize = 16;
+
+/// Restricts which rows of an archive are kept.
+///
+/// An empty filter keeps everything.
+///
+/// # Example
+///
+/// ```rust
+/// use hl_builder_data::FillFilter;
+///
+/// let filter = FillFilter::new()
+///     .with_users(["0x7B73DFAE34492A35715CA037B19E006BEFDBE4CC"])
+///     .with_assets(["BTC", "ETH"]);
+/// assert!(filter.matches_user("0x7b73dfae34492a35715ca037b19e006befdbe4cc"));
+/// assert!(!filter.matches_asset("SOL"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FillFilter {
+    users: Option<HashSet<String>>,
+    assets: Option<HashSet<String>>,
+}
+
+impl FillFilter {
+    /// Create a filter that keeps every row.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only keep fills of these users (case-insensitive).
+    pub fn with_users<I, S>(mut self, users: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.users
+            .get_or_insert_with(HashSet::new)
+            .extend(users.into_iter().map(|u| u.as_ref().to_lowercase()));
+        self
+    }
+
+    /// Only keep fills of these assets, by exact symbol.
+    pub fn with_assets<I, S>(mut self, assets: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.assets
+            .get_or_insert_with(HashSet::new)
+            .extend(assets.into_iter().map(|a| a.as_ref().to_string()));
+        self
+    }
+
+    /// Whether the filter keeps every row.
+    pub fn is_empty(&self) -> bool {
+        self.users.is_none() && self.assets.is_none()
+    }
+
+    /// Whether rows of `user` are kept.
+    pub fn matches_user(&self, user: &str) -> bool {
+        self.users
+            .as_ref()
+            .is_none_or(|users| users.contains(&user.to_lowercase()))
+    }
+
+    /// Whether rows of the asset `symbol` are kept.
+    pub fn matches_asset(&self, symbol: &str) -> bool {
+        self.assets
+            .as_ref()
+            .is_none_or(|assets| assets.contains(symbol))
+    }
+
+    /// Whether `fill` passes the filter.
+    pub fn matches(&self, fill: &BuilderFill) -> bool {
+        self.matches_user(&fill.user) && self.matches_asset(fill.asset.symbol())
+    }
+}
+
+/// Async stream of fills decoded incrementally from an archive.
+///
+/// Yields an error and ends if the archive turns out to be corrupt or the
+/// download fails partway. Dropping the stream stops the parser.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use futures::TryStreamExt;
+/// use hl_builder_data::{BuilderDataClient, FillFilter, NaiveDate};
+///
+/// # async fn example() -> Result<(), hl_builder_data::BuilderDataError> {
+/// let client = BuilderDataClient::new("0x2868fc0d9786a740b491577a43502259efa78a39")?
+///     .with_filter(FillFilter::new().with_assets(["BTC"]));
+/// let mut fills = client
+///     .stream_fills(NaiveDate::from_ymd_opt(2026, 1, 10).unwrap())
+///     .await?;
+/// while let Some(fill) = fills.try_next().await? {
+///     println!("{} {} @ {}", fill.user, fill.size, fill.price);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BuilderFillStream {
+    rx: mpsc::Receiver<Result<BuilderFill, BuilderDataError>>,
+    report: oneshot::Receiver<ParseReport>,
+}
+
+impl BuilderFillStream {
+    /// Decode an LZ4-compressed CSV archive from `reader`.
+    ///
+    /// Must be called within a Tokio runtime; parsing runs on its blocking
+    /// pool. Fills are tagged with `builder`. In lenient mode bad rows are
+    /// skipped and counted in the [`report`](Self::report).
+    pub fn from_lz4_reader<R>(reader: R, filter: FillFilter, mode: ParseMode, builder: &str) -> Self
+    where
+        R: Read + Send + 'static,
+    {
+        let (tx, rx) = mpsc::channel(FILL_BUFFER);
+        let (report_tx, report) = oneshot::channel();
+        let builder = builder.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            let decoder = lz4_flex::frame::FrameDecoder::new(reader);
+            let mut reader = FillReader::new(decoder, filter, mode);
+            for result in reader.by_ref() {
+                let result = result.map(|mut fill| {
+                    fill.builder.clone_from(&builder);
+                    fill
+                });
+                let failed = result.is_err();
+                // Stop when the consumer has gone away or after an error
+                if tx.blocking_send(result).is_err() || failed {
+                    return;
+                }
+            }
+            let _ = report_tx.send(reader.into_report());
+        });
+
+        Self { rx, report }
+    }
+
+    /// Decode an archive while it is being downloaded, also writing the raw
+    /// bytes to `copy` if given.
+    ///
+    /// The returned task ends with the download and yields the writer if
+    /// the whole archive was written to it.
+    pub(crate) fn from_response(
+        mut response: reqwest::Response,
+        filter: FillFilter,
+        mode: ParseMode,
+        builder: &str,
+        mut copy: Option<ArchiveWriter>,
+    ) -> (Self, JoinHandle<Option<ArchiveWriter>>) {
+        let (chunk_tx, chunk_rx) = mpsc::channel(CHUNK_BUFFER);
+
+        let download = tokio::spawn(async move {
+            loop {
+                let chunk = match response.chunk().await {
+                    Ok(Some(chunk)) => chunk,
+                    Ok(None) => return copy,
+                    Err(e) => {
+                        let _ = chunk_tx.send(Err(io::Error::other(e))).await;
+                        return None;
+                    }
+                };
+                if let Some(writer) = &mut copy {
+                    if let Err(e) = writer.write(&chunk).await {
+                        tracing::warn!("Failed to cache downloaded archive: {}", e);
+                        copy = None;
+                    }
+                }
+                if chunk_tx.send(Ok(chunk.to_vec())).await.is_err() {
+                    return None;
+                }
+            }
+        });
+
+        let stream = Self::from_lz4_reader(ChunkReader::new(chunk_rx), filter, mode, builder);
+        (stream, download)
+    }
+
+    /// How the archive parsed, once the stream has been read to the end.
+    ///
+    /// `None` if decoding stopped early, after an error or because the
+    /// stream was not read to the end.
+    pub async fn report(self) -> Option<ParseReport> {
+        self.report.await.ok()
+    }
+}
+
+impl Stream for BuilderFillStream {
+    type Item = Result<BuilderFill, BuilderDataError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+/// Blocking reader over chunks received from an async task.
+struct ChunkReader {
+    rx: mpsc::Receiver<io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl ChunkReader {
+    fn new(rx: mpsc::Receiver<io::Result<Vec<u8>>>) -> Self {
+        Self {
+            rx,
+            chunk: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.rx.blocking_recv() {
+                Some(chunk) => {
+                    self.chunk = chunk?;
+                    self.pos = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt;
+
+    const CSV: &str = "time,user,coin,side,px,sz,crossed,special_trade_type,tif,is_trigger,counterparty,closed_pnl,twap_id,builder_fee
+2026-01-10T00:00:04Z,0x5be08c15441c7fd10ea8dcc9af14ed9a3af11ebd,BLAST,Bid,0.000869,335303,false,Na,Alo,false,0x31ca8395cf837de08b24da3f660e77761dfb974b,-8.047272,0,0.029137
+2026-01-10T00:00:07Z,0x7b73dfae34492a35715ca037b19e006befdbe4cc,SOL,Bid,135.88,0.23,false,Na,Alo,false,0xc029043cd00b80363130fa058818459a521842a1,0,0,0.003125
+2026-01-10T00:00:56Z,0x7b73dfae34492a35715ca037b19e006befdbe4cc,BTC,Ask,95000,0.01,true,Na,Gtc,false,0xf967239debef10dbc78e9bbbb2d8a16b72a614eb,0,0,0.5
+";
+
+    fn compress(data: &[u8]) -> Vec<u8> {
+        let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+        std::io::Write::write_all(&mut encoder, data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    async fn collect(
+        data: Vec<u8>,
+        filter: FillFilter,
+    ) -> Result<Vec<BuilderFill>, BuilderDataError> {
+        BuilderFillStream::from_lz4_reader(io::Cursor::new(data), filter, ParseMode::Strict, "0xb1")
+            .try_collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_stream_all_rows() {
+        let fills = collect(compress(CSV.as_bytes()), FillFilter::new())
+            .await
+            .unwrap();
+
+        assert_eq!(fills.len(), 3);
+        assert!(fills.iter().all(|f| f.builder == "0xb1"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_pushdown_filters() {
+        let filter = FillFilter::new()
+            .with_users(["0x7B73DFAE34492A35715CA037B19E006BEFDBE4CC"])
+            .with_assets(["BTC"]);
+        let fills = collect(compress(CSV.as_bytes()), filter).await.unwrap();
+
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].asset.symbol(), "BTC");
+    }
+
+    #[tokio::test]
+    async fn test_filtered_out_rows_are_not_parsed() {
+        // The malformed price only fails if the row is parsed
+        let csv = CSV.replace("0.000869", "not-a-price");
+        let filter = FillFilter::new().with_assets(["SOL"]);
+
+        let fills = collect(compress(csv.as_bytes()), filter).await.unwrap();
+        assert_eq!(fills.len(), 1);
+        assert!(collect(compress(csv.as_bytes()), FillFilter::new())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_truncated_archive_ends_with_error() {
+        let mut data = compress(CSV.as_bytes());
+        data.truncate(data.len() / 2);
+
+        assert!(collect(data, FillFilter::new()).await.is_err());
+    }
+
+    /// Serve `data` from a local server and start downloading it.
+    async fn download(data: Vec<u8>) -> (hl_mock_server::MockServer, reqwest::Response) {
+        let server = hl_mock_server::MockHyperliquid::new()
+            .with_file("/archive.csv.lz4", data)
+            .start()
+            .await
+            .unwrap();
+        let url = server.url().join("archive.csv.lz4").unwrap();
+        let response = reqwest::get(url).await.unwrap();
+        (server, response)
+    }
+
+    #[tokio::test]
+    async fn test_decode_while_downloading() {
+        let (_server, response) = download(compress(CSV.as_bytes())).await;
+        let filter = FillFilter::new().with_assets(["SOL", "BTC"]);
+
+        let (mut stream, download) =
+            BuilderFillStream::from_response(response, filter, ParseMode::Strict, "0xb1", None);
+        let mut fills = Vec::new();
+        while let Some(fill) = stream.try_next().await.unwrap() {
+            fills.push(fill);
+        }
+
+        assert_eq!(fills.len(), 2);
+        assert!(fills.iter().all(|f| f.builder == "0xb1"));
+        let report = stream.report().await.unwrap();
+        assert_eq!((report.rows, report.parsed), (3, 2));
+        assert!(download.await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_download_is_copied_to_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = crate::ArchiveCache::new(dir.path());
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+        let data = compress(CSV.as_bytes());
+        let (_server, response) = download(data.clone()).await;
+
+        let writer = cache.writer("0xb1", date).await.unwrap();
+        let (stream, download) = BuilderFillStream::from_response(
+            response,
+            FillFilter::new(),
+            ParseMode::Strict,
+            "0xb1",
+            Some(writer),
+        );
+        let fills: Vec<BuilderFill> = stream.try_collect().await.unwrap();
+        download.await.unwrap().unwrap().commit(None).await;
+
+        assert_eq!(fills.len(), 3);
+        assert_eq!(cache.load_raw("0xb1", date).await, Some(data));
+        // Only the committed object is left behind
+        let objects = std::fs::read_dir(dir.path().join("objects")).unwrap();
+        assert_eq!(objects.count(), 1);
+    }
+
+    #[test]
+    fn test_chunk_reader_reassembles_chunks() {
+        let (tx, rx) = mpsc::channel(4);
+        tx.try_send(Ok(b"ab".to_vec())).unwrap();
+        tx.try_send(Ok(Vec::new())).unwrap();
+        tx.try_send(Ok(b"cde".to_vec())).unwrap();
+        drop(tx);
+
+        let mut out = String::new();
+        ChunkReader::new(rx).read_to_string(&mut out).unwrap();
+        assert_eq!(out, "abcde");
+    }
+}
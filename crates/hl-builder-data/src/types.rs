@@ -1,9 +1,9 @@
 //! Types for builder fill data.
 
 use chrono::{DateTime, Utc};
-use hl_types::Asset;
+use hl_types::{Asset, Side, UserFill};
 use rust_decimal::Decimal;
-use serde::{Deserialize, Deserializer};
+use sha2::{Digest, Sha256};
 
 /// A fill attributed to a specific builder.
 ///
@@ -52,8 +52,27 @@ pub struct BuilderFill {
 
     /// Builder fee collected.
     pub builder_fee: Decimal,
+
+    /// Address of the builder that routed the fill (lowercase).
+    ///
+    /// Not part of the CSV; filled in from the archive the fill was
+    /// downloaded from. Empty if unknown.
+    pub builder: String,
+
+    /// Line of the row in its archive (the header is line 1), or 0 if
+    /// unknown.
+    ///
+    /// Archives only ever grow, so a row keeps its line as the day's file is
+    /// re-downloaded. Tells apart rows that are otherwise identical.
+    pub line: u64,
 }
 
+/// Exchange fee rate of a taker fill at the base fee tier (0.045%).
+pub const BASE_TAKER_FEE_RATE: Decimal = Decimal::from_parts(45, 0, 0, false, 5);
+
+/// Exchange fee rate of a maker fill at the base fee tier (0.015%).
+pub const BASE_MAKER_FEE_RATE: Decimal = Decimal::from_parts(15, 0, 0, false, 5);
+
 /// Order side for builder fills.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum BuilderFillSide {
@@ -79,107 +98,103 @@ impl BuilderFillSide {
     }
 }
 
-/// Raw CSV record for deserialization.
-/// Maps directly to the CSV columns.
-#[derive(Debug, Deserialize)]
-pub(crate) struct BuilderFillRecord {
-    #[serde(deserialize_with = "deserialize_datetime")]
-    pub time: DateTime<Utc>,
-    pub user: String,
-    pub coin: String,
-    pub side: String,
-    #[serde(deserialize_with = "deserialize_decimal")]
-    pub px: Decimal,
-    #[serde(deserialize_with = "deserialize_decimal")]
-    pub sz: Decimal,
-    #[serde(deserialize_with = "deserialize_bool")]
-    pub crossed: bool,
-    pub special_trade_type: String,
-    pub tif: String,
-    #[serde(deserialize_with = "deserialize_bool")]
-    pub is_trigger: bool,
-    pub counterparty: String,
-    #[serde(deserialize_with = "deserialize_decimal")]
-    pub closed_pnl: Decimal,
-    pub twap_id: u64,
-    #[serde(deserialize_with = "deserialize_decimal")]
-    pub builder_fee: Decimal,
-}
+impl BuilderFill {
+    /// Get the notional value of this fill (price * size).
+    pub fn notional_value(&self) -> Decimal {
+        self.price * self.size
+    }
 
-impl TryFrom<BuilderFillRecord> for BuilderFill {
-    type Error = String;
-
-    fn try_from(record: BuilderFillRecord) -> Result<Self, Self::Error> {
-        let side = BuilderFillSide::from_str(&record.side)
-            .ok_or_else(|| format!("invalid side: {}", record.side))?;
-
-        Ok(BuilderFill {
-            time: record.time,
-            user: record.user,
-            asset: Asset::from_symbol(&record.coin),
-            side,
-            price: record.px,
-            size: record.sz,
-            crossed: record.crossed,
-            special_trade_type: record.special_trade_type,
-            time_in_force: record.tif,
-            is_trigger: record.is_trigger,
-            counterparty: record.counterparty,
-            closed_pnl: record.closed_pnl,
-            twap_id: record.twap_id,
-            builder_fee: record.builder_fee,
-        })
+    /// Get timestamp as milliseconds since epoch.
+    pub fn timestamp_ms(&self) -> i64 {
+        self.time.timestamp_millis()
     }
-}
 
-/// Deserialize ISO 8601 datetime.
-fn deserialize_datetime<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let s = String::deserialize(deserializer)?;
-    DateTime::parse_from_rfc3339(&s)
-        .map(|dt| dt.with_timezone(&Utc))
-        .map_err(serde::de::Error::custom)
-}
+    /// Estimated exchange fee, at the base fee tier.
+    ///
+    /// Archives only record the builder fee. Users on a higher volume tier
+    /// or receiving maker rebates paid less than this.
+    pub fn exchange_fee(&self) -> Decimal {
+        let rate = if self.crossed {
+            BASE_TAKER_FEE_RATE
+        } else {
+            BASE_MAKER_FEE_RATE
+        };
+        self.notional_value() * rate
+    }
 
-/// Deserialize decimal from string.
-fn deserialize_decimal<'de, D>(deserializer: D) -> Result<Decimal, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let s = String::deserialize(deserializer)?;
-    s.parse::<Decimal>().map_err(serde::de::Error::custom)
-}
+    /// Total fee paid: the estimated [`exchange_fee`](Self::exchange_fee)
+    /// plus the builder fee.
+    pub fn total_fee(&self) -> Decimal {
+        self.exchange_fee() + self.builder_fee
+    }
 
-/// Deserialize bool from string ("true"/"false").
-fn deserialize_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    let s = String::deserialize(deserializer)?;
-    match s.as_str() {
-        "true" => Ok(true),
-        "false" => Ok(false),
-        _ => Err(serde::de::Error::custom(format!("invalid bool: {}", s))),
+    /// Stable identifier derived from the row's contents and its line in
+    /// the archive.
+    ///
+    /// Archives carry no trade ID, so this stands in for one when the fill
+    /// is used as a [`UserFill`]. The line keeps identical rows, such as two
+    /// equal fills of one order in the same millisecond, apart.
+    pub fn synthetic_trade_id(&self) -> u64 {
+        let mut hasher = Sha256::new();
+        for field in [
+            self.line.to_string(),
+            self.timestamp_ms().to_string(),
+            self.user.to_lowercase(),
+            self.asset.symbol().to_string(),
+            self.side.is_buy().to_string(),
+            self.price.normalize().to_string(),
+            self.size.normalize().to_string(),
+            self.counterparty.to_lowercase(),
+            self.twap_id.to_string(),
+        ] {
+            hasher.update(field.as_bytes());
+            hasher.update([0]);
+        }
+        let digest = hasher.finalize();
+        u64::from_be_bytes(digest[..8].try_into().expect("digest is 32 bytes"))
     }
 }
 
-impl BuilderFill {
-    /// Get the notional value of this fill (price * size).
-    pub fn notional_value(&self) -> Decimal {
-        self.price * self.size
+impl From<BuilderFillSide> for Side {
+    fn from(side: BuilderFillSide) -> Self {
+        match side {
+            BuilderFillSide::Bid => Side::Buy,
+            BuilderFillSide::Ask => Side::Sell,
+        }
     }
+}
 
-    /// Get timestamp as milliseconds since epoch.
-    pub fn timestamp_ms(&self) -> i64 {
-        self.time.timestamp_millis()
+/// Use an archive row as a regular fill.
+///
+/// Archives lack some of the API's fields:
+///
+/// - `fee` is [`BuilderFill::total_fee`], with the exchange fee estimated
+///   at the base fee tier
+/// - `trade_id` is [`BuilderFill::synthetic_trade_id`]
+/// - `order_id` is 0 and `direction` is empty
+impl From<&BuilderFill> for UserFill {
+    fn from(fill: &BuilderFill) -> Self {
+        UserFill {
+            asset: fill.asset.clone(),
+            timestamp_ms: fill.timestamp_ms().max(0) as u64,
+            price: fill.price,
+            size: fill.size,
+            side: fill.side.into(),
+            fee: fill.total_fee(),
+            closed_pnl: fill.closed_pnl,
+            trade_id: fill.synthetic_trade_id(),
+            order_id: 0,
+            crossed: fill.crossed,
+            direction: String::new(),
+            liquidated_user: None,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rust_decimal_macros::dec;
 
     #[test]
     fn test_builder_fill_side() {
@@ -190,4 +205,73 @@ mod tests {
         assert!(BuilderFillSide::Bid.is_buy());
         assert!(!BuilderFillSide::Ask.is_buy());
     }
+
+    fn sample_fill() -> BuilderFill {
+        BuilderFill {
+            time: DateTime::from_timestamp_millis(1768003207000).unwrap(),
+            user: "0x7b73dfae34492a35715ca037b19e006befdbe4cc".to_string(),
+            asset: Asset::from_symbol("SOL"),
+            side: BuilderFillSide::Ask,
+            price: dec!(135.88),
+            size: dec!(0.23),
+            crossed: true,
+            special_trade_type: "Na".to_string(),
+            time_in_force: "Alo".to_string(),
+            is_trigger: false,
+            counterparty: "0xc029043cd00b80363130fa058818459a521842a1".to_string(),
+            closed_pnl: dec!(1.5),
+            twap_id: 0,
+            builder_fee: dec!(0.003125),
+            builder: "0xb1".to_string(),
+            line: 3,
+        }
+    }
+
+    #[test]
+    fn test_into_user_fill() {
+        let fill = sample_fill();
+        let user_fill = UserFill::from(&fill);
+
+        assert_eq!(user_fill.asset, fill.asset);
+        assert_eq!(user_fill.timestamp_ms, 1768003207000);
+        assert_eq!(user_fill.side, Side::Sell);
+        assert_eq!(user_fill.price, dec!(135.88));
+        assert_eq!(user_fill.size, dec!(0.23));
+        // Taker exchange fee at the base tier plus the builder fee
+        assert_eq!(user_fill.fee, dec!(0.01406358) + dec!(0.003125));
+        assert_eq!(user_fill.closed_pnl, dec!(1.5));
+        assert_eq!(user_fill.trade_id, fill.synthetic_trade_id());
+        assert!(user_fill.crossed);
+    }
+
+    #[test]
+    fn test_synthetic_trade_id() {
+        let fill = sample_fill();
+        let mut same = fill.clone();
+        same.price = dec!(135.880);
+        same.builder = "0xb2".to_string();
+        let mut other = fill.clone();
+        other.size = dec!(0.24);
+
+        assert_eq!(fill.synthetic_trade_id(), same.synthetic_trade_id());
+        assert_ne!(fill.synthetic_trade_id(), other.synthetic_trade_id());
+    }
+
+    #[test]
+    fn test_identical_rows_get_distinct_ids() {
+        let fill = sample_fill();
+        let mut next_row = fill.clone();
+        next_row.line = fill.line + 1;
+
+        assert_ne!(fill.synthetic_trade_id(), next_row.synthetic_trade_id());
+    }
+
+    #[test]
+    fn test_maker_fee() {
+        let mut fill = sample_fill();
+        fill.crossed = false;
+
+        assert_eq!(fill.exchange_fee(), dec!(0.00468786));
+        assert_eq!(fill.total_fee(), dec!(0.00468786) + dec!(0.003125));
+    }
 }
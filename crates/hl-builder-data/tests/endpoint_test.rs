@@ -0,0 +1,101 @@
+//! Custom endpoint tests for hl-builder-data.
+//!
+//! These tests serve the fixture archives from a local stand-in for the stats
+//! server and need no network access.
+
+use chrono::NaiveDate;
+use hl_builder_data::{
+    ArchiveCache, BuilderDataClient, BuilderDataError, FillFilter, StatsEndpoint,
+};
+use hl_mock_server::{MockHyperliquid, MockServer};
+
+/// The builder the fixture archives belong to.
+const BUILDER_ADDRESS: &str = "0x2868fc0d9786a740b491577a43502259efa78a39";
+
+fn fixture_archive() -> Vec<u8> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/builder_fills")
+        .join(BUILDER_ADDRESS)
+        .join("20260110.csv");
+    let csv = std::fs::read(path).unwrap();
+
+    let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+    std::io::Write::write_all(&mut encoder, &csv).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// Serve the fixture archive at `path`, answering 403 for anything else
+/// like the real stats server.
+async fn serve(path: &str) -> MockServer {
+    MockHyperliquid::new()
+        .with_file(path, fixture_archive())
+        .start()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_fetch_from_local_testnet_endpoint() {
+    let path = format!(
+        "/Testnet/builder_fills/{}/20260110.csv.lz4",
+        BUILDER_ADDRESS
+    );
+    let server = serve(&path).await;
+
+    let client = BuilderDataClient::new(BUILDER_ADDRESS)
+        .unwrap()
+        .with_endpoint(StatsEndpoint::testnet().with_base_url(server.url().as_str()));
+    let date = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+    let fills = client.fetch_fills(date).await.unwrap();
+
+    assert_eq!(fills.len(), 3);
+    assert!(fills.iter().all(|f| f.builder == BUILDER_ADDRESS));
+}
+
+#[tokio::test]
+async fn test_network_segment_selects_archives() {
+    let path = format!(
+        "/Testnet/builder_fills/{}/20260110.csv.lz4",
+        BUILDER_ADDRESS
+    );
+    let server = serve(&path).await;
+
+    // Only the testnet archive exists, so mainnet has no data for the day
+    let client = BuilderDataClient::new(BUILDER_ADDRESS)
+        .unwrap()
+        .with_endpoint(StatsEndpoint::mainnet().with_base_url(server.url().as_str()));
+    let date = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+    let err = client.fetch_fills(date).await.unwrap_err();
+
+    assert!(matches!(err, BuilderDataError::NotFound { .. }));
+}
+
+#[tokio::test]
+async fn test_filtered_fetch_uses_cached_fills() {
+    let path = format!(
+        "/Mainnet/builder_fills/{}/20260110.csv.lz4",
+        BUILDER_ADDRESS
+    );
+    let server = serve(&path).await;
+    let endpoint = StatsEndpoint::mainnet().with_base_url(server.url().as_str());
+    let dir = tempfile::tempdir().unwrap();
+    let date = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+
+    let client = BuilderDataClient::new(BUILDER_ADDRESS)
+        .unwrap()
+        .with_endpoint(endpoint.clone())
+        .with_cache(ArchiveCache::new(dir.path()));
+    assert_eq!(client.fetch_fills(date).await.unwrap().len(), 3);
+    drop(server);
+
+    // Served from the cached fills, with the server gone
+    let filtered = BuilderDataClient::new(BUILDER_ADDRESS)
+        .unwrap()
+        .with_endpoint(endpoint)
+        .with_cache(ArchiveCache::new(dir.path()))
+        .with_filter(FillFilter::new().with_assets(["SOL"]));
+    let fills = filtered.fetch_fills(date).await.unwrap();
+
+    assert_eq!(fills.len(), 2);
+    assert!(fills.iter().all(|f| f.asset.symbol() == "SOL"));
+}
@@ -0,0 +1,68 @@
+//! Offline import tests for hl-builder-data.
+//!
+//! These tests run against the fixture archives in `tests/fixtures` and need
+//! no network access.
+
+use chrono::NaiveDate;
+use hl_builder_data::{ArchiveCache, ArchiveImporter, BuilderDataClient, FillEnricher};
+use std::path::PathBuf;
+
+/// The builder the fixture archives belong to.
+const BUILDER_ADDRESS: &str = "0x2868fc0d9786a740b491577a43502259efa78a39";
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/builder_fills")
+}
+
+/// Pack the fixture directory into a gzipped tarball.
+fn fixtures_tarball(dir: &std::path::Path) -> PathBuf {
+    let path = dir.join("builder_fills.tar.gz");
+    let file = std::fs::File::create(&path).unwrap();
+    let encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    builder
+        .append_dir_all("builder_fills", fixtures_dir())
+        .unwrap();
+    builder.into_inner().unwrap().finish().unwrap();
+    path
+}
+
+#[test]
+fn test_import_fixture_directory() {
+    let fills = ArchiveImporter::new().load_fills(fixtures_dir()).unwrap();
+
+    assert_eq!(fills.len(), 3);
+    assert!(fills.iter().all(|f| f.builder == BUILDER_ADDRESS));
+
+    let enricher = FillEnricher::new(fills);
+    assert_eq!(enricher.builders(), vec![BUILDER_ADDRESS]);
+    assert_eq!(enricher.total_builder_fees().to_string(), "0.033348");
+}
+
+#[test]
+fn test_tarball_matches_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let tarball = fixtures_tarball(dir.path());
+
+    let from_dir = ArchiveImporter::new().load_fills(fixtures_dir()).unwrap();
+    let from_tar = ArchiveImporter::new().load_fills(&tarball).unwrap();
+
+    assert_eq!(from_dir, from_tar);
+}
+
+#[tokio::test]
+async fn test_imported_cache_serves_client_offline() {
+    let dir = tempfile::tempdir().unwrap();
+    let archives = ArchiveImporter::new().import(fixtures_dir()).unwrap();
+
+    let cache = ArchiveCache::new(dir.path());
+    cache.import(&archives).await;
+
+    let client = BuilderDataClient::new(BUILDER_ADDRESS)
+        .unwrap()
+        .with_cache(cache);
+    let date = NaiveDate::from_ymd_opt(2026, 1, 10).unwrap();
+    let fills = client.fetch_fills(date).await.unwrap();
+
+    assert_eq!(fills, archives[0].fills);
+}
@@ -29,6 +29,10 @@ pub fn convert_fill(fill: &HyperstkFill) -> UserFill {
         order_id: fill.oid,
         crossed: fill.crossed,
         direction: fill.dir.clone(),
+        liquidated_user: fill
+            .liquidation
+            .as_ref()
+            .map(|liquidation| liquidation.liquidated_user.clone()),
     }
 }
 
@@ -48,6 +52,7 @@ fn convert_side(side: &HyperstkSide) -> Side {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use hl_types::FillDirection;
     use rust_decimal_macros::dec;
 
     fn make_hypersdk_fill() -> HyperstkFill {
@@ -88,6 +93,23 @@ mod tests {
         assert_eq!(user_fill.trade_id, 67890);
         assert_eq!(user_fill.order_id, 12345);
         assert!(user_fill.crossed);
+        assert_eq!(user_fill.fill_direction(), FillDirection::Open);
+    }
+
+    #[test]
+    fn test_convert_liquidation() {
+        let mut sdk_fill = make_hypersdk_fill();
+        sdk_fill.dir = "Close Long".to_string();
+        sdk_fill.liquidation = serde_json::from_value(serde_json::json!({
+            "liquidatedUser": "0xabc",
+            "markPx": "49000.0",
+            "method": "market",
+        }))
+        .unwrap();
+        let user_fill = convert_fill(&sdk_fill);
+
+        assert_eq!(user_fill.liquidated_user.as_deref(), Some("0xabc"));
+        assert_eq!(user_fill.fill_direction(), FillDirection::Liquidation);
     }
 
     #[test]
@@ -22,7 +22,28 @@ pub enum IndexerError {
     #[error("invalid time range: {0}")]
     InvalidTimeRange(String),
 
+    /// Filter that cannot be applied with this configuration.
+    #[error("invalid filter: {0}")]
+    InvalidFilter(String),
+
+    /// Page request that cannot be served, such as a zero limit.
+    #[error("invalid page: {0}")]
+    InvalidPage(String),
+
     /// No data available.
     #[error("no data available: {0}")]
     NoData(String),
 }
+
+impl IndexerError {
+    /// Whether the upstream API or data server failed, rather than the
+    /// request being invalid. Such errors may go away on their own.
+    pub fn is_upstream_failure(&self) -> bool {
+        match self {
+            IndexerError::Ingestion(e) => e.is_transient(),
+            #[cfg(feature = "builder-enrichment")]
+            IndexerError::BuilderData(hl_builder_data::BuilderDataError::Http(_)) => true,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Filters over a user's fills.
+//!
+//! A [`TradeFilter`] narrows the fills
+//! [`Indexer::get_user_fills_page`](crate::Indexer::get_user_fills_page)
+//! returns, before they are paginated, so pages and cursors only ever
+//! contain matching fills.
+
+use hl_types::{Asset, FillDirection, Side, UserFill};
+use rust_decimal::Decimal;
+
+/// Which fills to keep. The default keeps every fill.
+///
+/// All set conditions must hold for a fill to match.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TradeFilter {
+    /// Keep fills in any of these assets; empty keeps every asset.
+    pub assets: Vec<Asset>,
+    /// Keep fills on this side.
+    pub side: Option<Side>,
+    /// Keep fills with this direction.
+    pub direction: Option<FillDirection>,
+    /// Keep taker fills (`true`) or maker fills (`false`).
+    pub crossed: Option<bool>,
+    /// Keep fills with at least this notional value (price × size).
+    pub min_notional: Option<Decimal>,
+    /// Keep fills with at most this notional value (price × size).
+    pub max_notional: Option<Decimal>,
+    /// Keep fills of this order.
+    pub order_id: Option<u64>,
+    /// Keep fills routed through one of the configured builders.
+    ///
+    /// This needs builder attribution, so it is applied by the indexer
+    /// rather than by [`TradeFilter::matches`].
+    pub builder_only: bool,
+    /// Keep fills that realized PnL.
+    pub nonzero_pnl: bool,
+}
+
+impl TradeFilter {
+    /// A filter that keeps every fill.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep fills in any of `assets`.
+    pub fn with_assets(mut self, assets: impl IntoIterator<Item = Asset>) -> Self {
+        self.assets = assets.into_iter().collect();
+        self
+    }
+
+    /// Keep fills on `side`.
+    pub fn with_side(mut self, side: Side) -> Self {
+        self.side = Some(side);
+        self
+    }
+
+    /// Keep fills with `direction`.
+    pub fn with_direction(mut self, direction: FillDirection) -> Self {
+        self.direction = Some(direction);
+        self
+    }
+
+    /// Keep taker fills if `crossed`, maker fills otherwise.
+    pub fn with_crossed(mut self, crossed: bool) -> Self {
+        self.crossed = Some(crossed);
+        self
+    }
+
+    /// Keep fills whose notional value lies within the given bounds.
+    pub fn with_notional(mut self, min: Option<Decimal>, max: Option<Decimal>) -> Self {
+        self.min_notional = min;
+        self.max_notional = max;
+        self
+    }
+
+    /// Keep fills of order `order_id`.
+    pub fn with_order_id(mut self, order_id: u64) -> Self {
+        self.order_id = Some(order_id);
+        self
+    }
+
+    /// Keep only builder-attributed fills.
+    pub fn with_builder_only(mut self, builder_only: bool) -> Self {
+        self.builder_only = builder_only;
+        self
+    }
+
+    /// Keep only fills that realized PnL.
+    pub fn with_nonzero_pnl(mut self, nonzero_pnl: bool) -> Self {
+        self.nonzero_pnl = nonzero_pnl;
+        self
+    }
+
+    /// Whether `fill` meets every condition except `builder_only`.
+    pub fn matches(&self, fill: &UserFill) -> bool {
+        let notional = fill.notional_value();
+        (self.assets.is_empty() || self.assets.contains(&fill.asset))
+            && self.side.is_none_or(|side| fill.side == side)
+            && self
+                .direction
+                .is_none_or(|direction| fill.fill_direction() == direction)
+            && self.crossed.is_none_or(|crossed| fill.crossed == crossed)
+            && self.min_notional.is_none_or(|min| notional >= min)
+            && self.max_notional.is_none_or(|max| notional <= max)
+            && self.order_id.is_none_or(|id| fill.order_id == id)
+            && (!self.nonzero_pnl || !fill.closed_pnl.is_zero())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn fill(asset: &str, side: Side, direction: &str, closed_pnl: Decimal) -> UserFill {
+        UserFill {
+            asset: Asset::from_symbol(asset),
+            timestamp_ms: 1,
+            price: dec!(2000),
+            size: dec!(0.5),
+            side,
+            fee: dec!(0.5),
+            closed_pnl,
+            trade_id: 1,
+            order_id: 7,
+            crossed: true,
+            direction: direction.to_string(),
+            liquidated_user: None,
+        }
+    }
+
+    #[test]
+    fn test_default_matches_everything() {
+        let filter = TradeFilter::new();
+        assert!(filter.matches(&fill("ETH", Side::Buy, "Open Long", dec!(0))));
+        assert!(filter.matches(&fill("BTC", Side::Sell, "", dec!(0))));
+    }
+
+    #[test]
+    fn test_liquidations_on_eth() {
+        let filter = TradeFilter::new()
+            .with_assets([Asset::Eth])
+            .with_direction(FillDirection::Liquidation);
+
+        let liquidated = |asset| UserFill {
+            liquidated_user: Some("0xabc".to_string()),
+            ..fill(asset, Side::Sell, "Close Long", dec!(-80))
+        };
+        assert!(filter.matches(&liquidated("ETH")));
+        assert!(!filter.matches(&fill("ETH", Side::Sell, "Close Long", dec!(-80))));
+        assert!(!filter.matches(&liquidated("BTC")));
+    }
+
+    #[test]
+    fn test_each_condition() {
+        let buy = fill("ETH", Side::Buy, "Open Long", dec!(0));
+        let sell = fill("ETH", Side::Sell, "Close Long", dec!(25));
+
+        let side = TradeFilter::new().with_side(Side::Sell);
+        assert!(!side.matches(&buy) && side.matches(&sell));
+
+        let maker = TradeFilter::new().with_crossed(false);
+        assert!(!maker.matches(&buy));
+
+        // Notional is 2000 × 0.5 = 1000, bounds are inclusive
+        let notional = TradeFilter::new().with_notional(Some(dec!(1000)), Some(dec!(1000)));
+        assert!(notional.matches(&buy));
+        let notional = TradeFilter::new().with_notional(Some(dec!(1000.01)), None);
+        assert!(!notional.matches(&buy));
+
+        let order = TradeFilter::new().with_order_id(8);
+        assert!(!order.matches(&buy));
+
+        let pnl = TradeFilter::new().with_nonzero_pnl(true);
+        assert!(!pnl.matches(&buy) && pnl.matches(&sell));
+    }
+}
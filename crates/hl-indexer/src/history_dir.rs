@@ -0,0 +1,207 @@
+//! On-disk leaderboard history.
+//!
+//! Retained snapshots are written to one directory per [`LeaderboardConfig`]:
+//!
+//! ```text
+//! <dir>/<config hash>/config.json
+//! <dir>/<config hash>/<YYYY-MM-DD>.jsonl
+//! ```
+//!
+//! Each day file holds the snapshots computed on that (UTC) day, one JSON
+//! object per line, so retention can drop whole files without rewriting any.
+//!
+//! Like the builder archive cache, the history directory is best-effort:
+//! read or write failures are logged and the in-memory history is unaffected.
+
+use crate::leaderboard::LeaderboardConfig;
+use crate::snapshot::LeaderboardSnapshot;
+use chrono::{DateTime, NaiveDate};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+const CONFIG_FILE: &str = "config.json";
+const DAY_FORMAT: &str = "%Y-%m-%d";
+
+/// Directory holding persisted leaderboard history.
+#[derive(Debug, Clone)]
+pub(crate) struct HistoryDir {
+    dir: PathBuf,
+}
+
+impl HistoryDir {
+    pub(crate) fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Append a retained snapshot to its config's history.
+    pub(crate) async fn append(&self, config: &LeaderboardConfig, snapshot: &LeaderboardSnapshot) {
+        if let Err(e) = self.try_append(config, snapshot).await {
+            tracing::warn!(
+                "Failed to persist leaderboard snapshot v{} to {}: {}",
+                snapshot.version,
+                self.dir.display(),
+                e
+            );
+        }
+    }
+
+    async fn try_append(
+        &self,
+        config: &LeaderboardConfig,
+        snapshot: &LeaderboardSnapshot,
+    ) -> io::Result<()> {
+        let config_dir = self.config_dir(config)?;
+        tokio::fs::create_dir_all(&config_dir).await?;
+
+        let config_path = config_dir.join(CONFIG_FILE);
+        if tokio::fs::metadata(&config_path).await.is_err() {
+            let contents = serde_json::to_vec(config).map_err(io::Error::other)?;
+            let tmp = config_path.with_extension(format!("tmp.{}", std::process::id()));
+            tokio::fs::write(&tmp, contents).await?;
+            tokio::fs::rename(&tmp, &config_path).await?;
+        }
+
+        let mut line = serde_json::to_vec(snapshot).map_err(io::Error::other)?;
+        line.push(b'\n');
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(config_dir.join(day_file(snapshot.computed_at_ms)))
+            .await?;
+        file.write_all(&line).await?;
+        file.flush().await
+    }
+
+    /// Delete day files of a config's history that end before `cutoff_ms`.
+    pub(crate) async fn prune(&self, config: &LeaderboardConfig, cutoff_ms: u64) {
+        let result = async {
+            let config_dir = self.config_dir(config)?;
+            let mut files = tokio::fs::read_dir(&config_dir).await?;
+            while let Some(file) = files.next_entry().await? {
+                let path = file.path();
+                if day_of(&path).is_some_and(|day| day_end_ms(day) <= cutoff_ms) {
+                    tokio::fs::remove_file(&path).await?;
+                }
+            }
+            Ok::<_, io::Error>(())
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!(
+                "Failed to prune leaderboard history in {}: {}",
+                self.dir.display(),
+                e
+            );
+        }
+    }
+
+    /// Load every config's persisted snapshots computed at or after
+    /// `cutoff_ms`, oldest first.
+    ///
+    /// Unreadable configs and corrupt lines are skipped.
+    pub(crate) async fn load(
+        &self,
+        cutoff_ms: u64,
+    ) -> HashMap<LeaderboardConfig, Vec<LeaderboardSnapshot>> {
+        let mut history: HashMap<LeaderboardConfig, Vec<LeaderboardSnapshot>> = HashMap::new();
+        let mut config_dirs = match tokio::fs::read_dir(&self.dir).await {
+            Ok(dirs) => dirs,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return history,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to read leaderboard history from {}: {}",
+                    self.dir.display(),
+                    e
+                );
+                return history;
+            }
+        };
+
+        while let Ok(Some(config_dir)) = config_dirs.next_entry().await {
+            let path = config_dir.path();
+            match load_config_dir(&path, cutoff_ms).await {
+                Ok(Some((config, snapshots))) => {
+                    history.entry(config).or_default().extend(snapshots);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!("Skipping leaderboard history in {}: {}", path.display(), e);
+                }
+            }
+        }
+
+        for snapshots in history.values_mut() {
+            snapshots.sort_by_key(|s| (s.computed_at_ms, s.version));
+            snapshots.dedup_by_key(|s| s.version);
+        }
+        history
+    }
+
+    /// Directory for one config, named after a hash of its JSON form.
+    fn config_dir(&self, config: &LeaderboardConfig) -> io::Result<PathBuf> {
+        let json = serde_json::to_vec(config).map_err(io::Error::other)?;
+        let hash = hex::encode(Sha256::digest(json));
+        Ok(self.dir.join(&hash[..16]))
+    }
+}
+
+/// Read a config directory, or `None` if it is not one.
+async fn load_config_dir(
+    path: &Path,
+    cutoff_ms: u64,
+) -> io::Result<Option<(LeaderboardConfig, Vec<LeaderboardSnapshot>)>> {
+    let config = match tokio::fs::read(path.join(CONFIG_FILE)).await {
+        Ok(contents) => serde_json::from_slice(&contents).map_err(io::Error::other)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    let mut snapshots = Vec::new();
+    let mut files = tokio::fs::read_dir(path).await?;
+    while let Some(file) = files.next_entry().await? {
+        let path = file.path();
+        if day_of(&path).is_none_or(|day| day_end_ms(day) <= cutoff_ms) {
+            continue;
+        }
+        let contents = tokio::fs::read_to_string(&path).await?;
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str::<LeaderboardSnapshot>(line) {
+                Ok(snapshot) if snapshot.computed_at_ms >= cutoff_ms => snapshots.push(snapshot),
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!("Skipping corrupt snapshot in {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+    Ok(Some((config, snapshots)))
+}
+
+/// Name of the day file a snapshot computed at `at_ms` belongs in.
+fn day_file(at_ms: u64) -> String {
+    let day = DateTime::from_timestamp_millis(at_ms as i64)
+        .unwrap_or_default()
+        .date_naive();
+    format!("{}.jsonl", day.format(DAY_FORMAT))
+}
+
+/// The day a history file covers, if it is a day file.
+fn day_of(path: &Path) -> Option<NaiveDate> {
+    if path.extension()? != "jsonl" {
+        return None;
+    }
+    NaiveDate::parse_from_str(path.file_stem()?.to_str()?, DAY_FORMAT).ok()
+}
+
+/// End of a UTC day (milliseconds since epoch).
+fn day_end_ms(day: NaiveDate) -> u64 {
+    day.succ_opt()
+        .and_then(|next| next.and_hms_opt(0, 0, 0))
+        .map_or(u64::MAX, |end| {
+            end.and_utc().timestamp_millis().max(0) as u64
+        })
+}
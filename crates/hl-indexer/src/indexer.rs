@@ -5,17 +5,34 @@
 
 use crate::converter::convert_fills;
 use crate::error::IndexerError;
-use hl_ingestion::{CollectorHandle, DataSource, FillCollector, HyperliquidSource, Network};
+use crate::filter::TradeFilter;
+use crate::pagination::{paginate, FillPage, PageRequest};
+use crate::returns::{load_capital_base, CapitalBase, CapitalCache, DEFAULT_CAPITAL_TTL};
+use crate::roundtrip::{round_trips, RoundTrip};
+use hl_ingestion::{
+    AccountValuePoint, BreakerConfig, BreakerState, CacheStats, CacheTtls, CachingSource,
+    CircuitBreakerSource, ClearinghouseState, CollectedFills, CollectorHandle, DataSource,
+    Endpoints, Fill, FillCollector, HyperliquidSource, IngestionError, LedgerUpdate, Network,
+    RecordingSource, ReplaySource, UserBalance,
+};
 use hl_types::{Asset, PnLSummary, UserFill, UserPnL};
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
 #[cfg(feature = "builder-enrichment")]
-use hl_builder_data::{BuilderDataClient, FillEnricher};
+use hl_builder_data::{
+    ArchiveCache, BuilderDataClient, FillEnricher, FillFilter, MatchReport, ParseMode,
+    StatsEndpoint,
+};
 
 #[cfg(feature = "builder-enrichment")]
 use rust_decimal::Decimal;
 
+#[cfg(feature = "builder-enrichment")]
+use std::collections::HashMap;
+
 /// Source for fetching fills.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum FillSource {
@@ -25,28 +42,89 @@ pub enum FillSource {
     /// Use WebSocket for real-time fill collection. No fill limit,
     /// but only captures fills from when the collector starts.
     WebSocket,
+    /// Read fills from the configured builders' daily archives. No fill
+    /// limit and no per-user API calls, but only fills routed through those
+    /// builders are seen, with about a day's delay.
+    #[cfg(feature = "builder-enrichment")]
+    BuilderArchive,
+}
+
+/// Whether API calls are recorded to, or replayed from, fixture files.
+///
+/// HTTP API calls and builder fill archives are covered; WebSocket fills
+/// are not. Archives go to a `builder_archives` cache inside the directory
+/// (see [`IndexerConfig::builder_client`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Recording {
+    /// Call the API directly (default).
+    #[default]
+    Off,
+    /// Call the API and save every request and response under the directory.
+    Record(PathBuf),
+    /// Answer from responses saved by `Record`, without network access.
+    Replay(PathBuf),
 }
 
+/// Length of the first time chunk scanned for a page of fills; each
+/// further chunk is four times longer.
+const PAGE_CHUNK_MS: i64 = 3_600_000;
+
+/// Directory inside a recording that holds the builder archive cache.
+#[cfg(feature = "builder-enrichment")]
+const RECORDED_ARCHIVES_DIR: &str = "builder_archives";
+
+/// Days read from builder archives when no start time is given.
+#[cfg(feature = "builder-enrichment")]
+pub const DEFAULT_ARCHIVE_LOOKBACK_DAYS: i64 = 7;
+
 /// Configuration for the indexer.
 #[derive(Debug, Clone)]
 pub struct IndexerConfig {
     /// Network to connect to (mainnet or testnet).
     pub network: Network,
 
+    /// Where the network's API, WebSocket and stats data are reached.
+    pub endpoints: Endpoints,
+
     /// Source for fetching fills (API or WebSocket).
     pub fill_source: FillSource,
 
-    /// Optional builder address for enrichment.
+    /// Record or replay API calls.
+    pub recording: Recording,
+
+    /// How long API responses are reused (nothing is cached by default).
+    /// Identical concurrent API calls are merged either way.
+    pub cache: CacheTtls,
+
+    /// When to stop calling a failing API (never, by default). Cached
+    /// responses are still served while the breaker is open.
+    pub breaker: BreakerConfig,
+
+    /// How long a user's capital base for return % is reused.
+    pub capital_ttl: Duration,
+
+    /// Builder addresses for enrichment (empty for none).
     /// Only used when builder-enrichment feature is enabled.
-    pub builder_address: Option<String>,
+    pub builder_addresses: Vec<String>,
+
+    /// On-disk cache for builder fill archives.
+    #[cfg(feature = "builder-enrichment")]
+    pub builder_cache: Option<ArchiveCache>,
 }
 
 impl Default for IndexerConfig {
     fn default() -> Self {
         Self {
             network: Network::Mainnet,
+            endpoints: Endpoints::mainnet(),
             fill_source: FillSource::default(),
-            builder_address: None,
+            recording: Recording::Off,
+            cache: CacheTtls::disabled(),
+            breaker: BreakerConfig::disabled(),
+            capital_ttl: DEFAULT_CAPITAL_TTL,
+            builder_addresses: Vec::new(),
+            #[cfg(feature = "builder-enrichment")]
+            builder_cache: None,
         }
     }
 }
@@ -56,8 +134,15 @@ impl IndexerConfig {
     pub fn mainnet() -> Self {
         Self {
             network: Network::Mainnet,
+            endpoints: Endpoints::mainnet(),
             fill_source: FillSource::default(),
-            builder_address: None,
+            recording: Recording::Off,
+            cache: CacheTtls::disabled(),
+            breaker: BreakerConfig::disabled(),
+            capital_ttl: DEFAULT_CAPITAL_TTL,
+            builder_addresses: Vec::new(),
+            #[cfg(feature = "builder-enrichment")]
+            builder_cache: None,
         }
     }
 
@@ -65,8 +150,15 @@ impl IndexerConfig {
     pub fn testnet() -> Self {
         Self {
             network: Network::Testnet,
+            endpoints: Endpoints::testnet(),
             fill_source: FillSource::default(),
-            builder_address: None,
+            recording: Recording::Off,
+            cache: CacheTtls::disabled(),
+            breaker: BreakerConfig::disabled(),
+            capital_ttl: DEFAULT_CAPITAL_TTL,
+            builder_addresses: Vec::new(),
+            #[cfg(feature = "builder-enrichment")]
+            builder_cache: None,
         }
     }
 
@@ -86,12 +178,244 @@ impl IndexerConfig {
         self
     }
 
-    /// Set the builder address for enrichment.
+    /// Reach the network's services at `endpoints` instead of Hyperliquid's
+    /// public URLs.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hl_indexer::{Endpoints, IndexerConfig};
+    ///
+    /// // Point the whole stack at a local mock server
+    /// let config = IndexerConfig::mainnet()
+    ///     .with_endpoints(Endpoints::custom("http://localhost:8080".parse().unwrap()));
+    /// ```
+    pub fn with_endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    /// Record API calls to fixture files, or replay them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hl_indexer::{IndexerConfig, Recording};
+    ///
+    /// // Rerun a captured incident offline
+    /// let config = IndexerConfig::mainnet()
+    ///     .with_recording(Recording::Replay("incident-fixtures".into()));
+    /// ```
+    pub fn with_recording(mut self, recording: Recording) -> Self {
+        self.recording = recording;
+        self
+    }
+
+    /// Reuse API responses for the given per-method TTLs.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hl_indexer::{CacheTtls, IndexerConfig};
+    ///
+    /// let config = IndexerConfig::mainnet().with_cache(CacheTtls::default());
+    /// ```
+    pub fn with_cache(mut self, ttls: CacheTtls) -> Self {
+        self.cache = ttls;
+        self
+    }
+
+    /// Fail fast with [`IngestionError::Unavailable`] while the API keeps
+    /// failing, instead of waiting on every request.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use hl_indexer::{BreakerConfig, IndexerConfig};
+    ///
+    /// let config = IndexerConfig::mainnet().with_breaker(BreakerConfig::default());
+    /// ```
+    pub fn with_breaker(mut self, breaker: BreakerConfig) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    /// Set how long capital bases for return % are reused (see
+    /// [`Indexer::get_capital_base`]). Zero refetches them every time.
+    pub fn with_capital_ttl(mut self, ttl: Duration) -> Self {
+        self.capital_ttl = ttl;
+        self
+    }
+
+    /// Add a builder address for enrichment.
+    ///
+    /// May be called several times to attribute fills across multiple
+    /// builders.
     #[cfg(feature = "builder-enrichment")]
     pub fn with_builder(mut self, address: &str) -> Self {
-        self.builder_address = Some(address.to_string());
+        let address = address.to_lowercase();
+        if !self.builder_addresses.contains(&address) {
+            self.builder_addresses.push(address);
+        }
+        self
+    }
+
+    /// Add several builder addresses for enrichment.
+    #[cfg(feature = "builder-enrichment")]
+    pub fn with_builders<I, S>(self, addresses: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        addresses.into_iter().fold(self, |config, address| {
+            config.with_builder(address.as_ref())
+        })
+    }
+
+    /// Cache builder fill archives on disk instead of downloading them on
+    /// every enrichment.
+    #[cfg(feature = "builder-enrichment")]
+    pub fn with_builder_cache(mut self, cache: ArchiveCache) -> Self {
+        self.builder_cache = Some(cache);
+        self
+    }
+
+    /// Download builder fill archives from another server with the same
+    /// layout as `stats-data.hyperliquid.xyz`.
+    ///
+    /// An invalid URL is ignored with a warning.
+    #[cfg(feature = "builder-enrichment")]
+    #[deprecated(note = "set `Endpoints::stats_url` via `with_endpoints` instead")]
+    pub fn with_builder_stats_url(mut self, url: impl Into<String>) -> Self {
+        let url = url.into();
+        match url.parse() {
+            Ok(parsed) => self.endpoints.stats_url = parsed,
+            Err(e) => tracing::warn!("Ignoring invalid builder stats URL '{}': {}", url, e),
+        }
         self
     }
+
+    /// Endpoint builder fill archives are downloaded from.
+    ///
+    /// Archives are read from `endpoints.stats_url`, under the configured
+    /// network's directory, so testnet reads testnet archives.
+    #[cfg(feature = "builder-enrichment")]
+    pub fn builder_endpoint(&self) -> StatsEndpoint {
+        let endpoint = match self.network {
+            Network::Mainnet => StatsEndpoint::mainnet(),
+            Network::Testnet => StatsEndpoint::testnet(),
+        };
+        endpoint.with_base_url(self.endpoints.stats_url.as_str())
+    }
+
+    /// Client for builder `address`'s fill archives, set up like the
+    /// indexer's own.
+    ///
+    /// Archives are downloaded from [`builder_endpoint`](Self::builder_endpoint)
+    /// through the builder cache, and malformed rows are skipped. While
+    /// recording, archives are cached inside the recording instead, so it
+    /// holds everything a replay needs; on replay they are read from there
+    /// and never downloaded. Like the cache, recorded archives are kept per
+    /// stats endpoint, so replay with the endpoint the recording was made
+    /// with.
+    #[cfg(feature = "builder-enrichment")]
+    pub fn builder_client(
+        &self,
+        address: &str,
+    ) -> Result<BuilderDataClient, hl_builder_data::BuilderDataError> {
+        let client = BuilderDataClient::new(address)?
+            .with_endpoint(self.builder_endpoint())
+            .with_parse_mode(ParseMode::Lenient);
+        let cache = match &self.recording {
+            Recording::Off => self.builder_cache.clone(),
+            Recording::Record(dir) => Some(ArchiveCache::new(dir.join(RECORDED_ARCHIVES_DIR))),
+            // Whatever was recorded is served, however old
+            Recording::Replay(dir) => Some(
+                ArchiveCache::new(dir.join(RECORDED_ARCHIVES_DIR)).with_today_ttl(Duration::MAX),
+            ),
+        };
+        let client = match cache {
+            Some(cache) => client.with_cache(cache),
+            None => client,
+        };
+        Ok(client.with_offline(matches!(self.recording, Recording::Replay(_))))
+    }
+}
+
+/// The HTTP API source, optionally recording or replaying.
+enum Source {
+    Live(HyperliquidSource),
+    Recording(RecordingSource<HyperliquidSource>),
+    Replay(ReplaySource),
+}
+
+impl Source {
+    fn new(config: &IndexerConfig) -> Self {
+        let live = || HyperliquidSource::with_endpoints(config.network, &config.endpoints);
+        match &config.recording {
+            Recording::Off => Source::Live(live()),
+            Recording::Record(dir) => Source::Recording(RecordingSource::new(live(), dir)),
+            Recording::Replay(dir) => Source::Replay(ReplaySource::new(dir)),
+        }
+    }
+}
+
+impl DataSource for Source {
+    async fn get_user_fills(
+        &self,
+        user: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<Vec<Fill>, IngestionError> {
+        match self {
+            Source::Live(s) => s.get_user_fills(user, from_ms, to_ms).await,
+            Source::Recording(s) => s.get_user_fills(user, from_ms, to_ms).await,
+            Source::Replay(s) => s.get_user_fills(user, from_ms, to_ms).await,
+        }
+    }
+
+    async fn get_clearinghouse_state(
+        &self,
+        user: &str,
+    ) -> Result<ClearinghouseState, IngestionError> {
+        match self {
+            Source::Live(s) => s.get_clearinghouse_state(user).await,
+            Source::Recording(s) => s.get_clearinghouse_state(user).await,
+            Source::Replay(s) => s.get_clearinghouse_state(user).await,
+        }
+    }
+
+    async fn get_user_balances(&self, user: &str) -> Result<Vec<UserBalance>, IngestionError> {
+        match self {
+            Source::Live(s) => s.get_user_balances(user).await,
+            Source::Recording(s) => s.get_user_balances(user).await,
+            Source::Replay(s) => s.get_user_balances(user).await,
+        }
+    }
+
+    async fn get_account_value_history(
+        &self,
+        user: &str,
+    ) -> Result<Vec<AccountValuePoint>, IngestionError> {
+        match self {
+            Source::Live(s) => s.get_account_value_history(user).await,
+            Source::Recording(s) => s.get_account_value_history(user).await,
+            Source::Replay(s) => s.get_account_value_history(user).await,
+        }
+    }
+
+    async fn get_ledger_updates(
+        &self,
+        user: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<Vec<LedgerUpdate>, IngestionError> {
+        match self {
+            Source::Live(s) => s.get_ledger_updates(user, from_ms, to_ms).await,
+            Source::Recording(s) => s.get_ledger_updates(user, from_ms, to_ms).await,
+            Source::Replay(s) => s.get_ledger_updates(user, from_ms, to_ms).await,
+        }
+    }
 }
 
 /// The main indexer for fetching and processing Hyperliquid trade data.
@@ -153,7 +477,7 @@ impl IndexerConfig {
 /// ```
 pub struct Indexer {
     /// The data source for fetching from Hyperliquid (API mode).
-    source: Arc<HyperliquidSource>,
+    source: Arc<CachingSource<CircuitBreakerSource<Source>>>,
 
     /// WebSocket fill collector (WebSocket mode).
     fill_collector: FillCollector,
@@ -161,9 +485,12 @@ pub struct Indexer {
     /// Handle to the running collector task (if active).
     collector_handle: Arc<RwLock<Option<CollectorHandle>>>,
 
-    /// Builder data client (only with builder-enrichment feature).
+    /// Recently loaded capital bases.
+    capital: CapitalCache,
+
+    /// Builder data clients, one per builder (only with builder-enrichment feature).
     #[cfg(feature = "builder-enrichment")]
-    builder_client: Option<BuilderDataClient>,
+    builder_clients: Vec<BuilderDataClient>,
 
     /// Configuration.
     config: IndexerConfig,
@@ -172,21 +499,25 @@ pub struct Indexer {
 impl Indexer {
     /// Create a new indexer with the given configuration.
     pub fn new(config: IndexerConfig) -> Self {
-        let source = Arc::new(HyperliquidSource::new(config.network));
-        let fill_collector = FillCollector::new(config.network);
+        let upstream = CircuitBreakerSource::new(Source::new(&config)).with_config(config.breaker);
+        let source = Arc::new(CachingSource::new(upstream).with_ttls(config.cache));
+        let fill_collector =
+            FillCollector::new(config.network).with_ws_url(config.endpoints.ws_url.clone());
 
         #[cfg(feature = "builder-enrichment")]
-        let builder_client = config
-            .builder_address
-            .as_ref()
-            .and_then(|addr| BuilderDataClient::new(addr).ok());
+        let builder_clients = config
+            .builder_addresses
+            .iter()
+            .filter_map(|addr| config.builder_client(addr).ok())
+            .collect();
 
         Self {
             source,
             fill_collector,
             collector_handle: Arc::new(RwLock::new(None)),
+            capital: CapitalCache::new(config.capital_ttl),
             #[cfg(feature = "builder-enrichment")]
-            builder_client,
+            builder_clients,
             config,
         }
     }
@@ -202,8 +533,23 @@ impl Indexer {
     }
 
     /// Get the underlying data source.
-    pub fn source(&self) -> &HyperliquidSource {
-        &self.source
+    ///
+    /// This is the whole stack the indexer calls: response cache, circuit
+    /// breaker and recording or replay in front of the HTTP API. It used to
+    /// be the bare `HyperliquidSource`; build one with
+    /// `HyperliquidSource::with_endpoints` to call the API directly.
+    pub fn source(&self) -> &impl DataSource {
+        self.source.as_ref()
+    }
+
+    /// API response cache counters.
+    pub fn cache_stats(&self) -> CacheStats {
+        self.source.stats()
+    }
+
+    /// State of the circuit breaker in front of the API.
+    pub fn upstream_state(&self) -> BreakerState {
+        self.source.inner().state()
     }
 
     /// Get the configuration.
@@ -274,10 +620,26 @@ impl Indexer {
         self.fill_collector.clear().await;
     }
 
+    /// Subscribe to new-fill notifications from the WebSocket collector.
+    ///
+    /// The receiver changes whenever the collector stores new fills. In API
+    /// mode it never changes, so callers should combine it with a timer.
+    pub fn subscribe_fill_updates(&self) -> tokio::sync::watch::Receiver<usize> {
+        self.fill_collector.subscribe_updates()
+    }
+
+    /// Subscribe to the fills the WebSocket collector receives.
+    ///
+    /// Each message holds fills the collector had not stored before. In API
+    /// mode nothing is ever sent.
+    pub fn subscribe_new_fills(&self) -> tokio::sync::broadcast::Receiver<CollectedFills> {
+        self.fill_collector.subscribe_fills()
+    }
+
     /// Check if builder enrichment is enabled and configured.
     #[cfg(feature = "builder-enrichment")]
     pub fn has_builder_enrichment(&self) -> bool {
-        self.builder_client.is_some()
+        !self.builder_clients.is_empty()
     }
 
     /// Check if builder enrichment is enabled and configured.
@@ -349,7 +711,261 @@ impl Indexer {
 
                 Ok(fills)
             }
+            #[cfg(feature = "builder-enrichment")]
+            FillSource::BuilderArchive => {
+                let users = [user.to_string()];
+                let mut fills = self
+                    .get_builder_archive_fills(&users, from_ms, to_ms)
+                    .await?;
+                Ok(fills.remove(user).unwrap_or_default())
+            }
+        }
+    }
+
+    /// Fetch one page of a user's fills that match `filter`.
+    ///
+    /// Only the part of the window on the requested side of the page cursor
+    /// is fetched, in time chunks growing away from the cursor until
+    /// `limit + 1` fills match; see [`crate::pagination`]. Without a start
+    /// time only the latest fills are available, and they are fetched in
+    /// one call. Fills are filtered before they are paged, so every page is
+    /// full unless it is the last.
+    ///
+    /// With `filter.builder_only`, the whole `from_ms..to_ms` window is
+    /// fetched and attributed for every page instead, since matching a
+    /// fill to a builder depends on the fills around it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IndexerError::InvalidPage`] for a zero `limit`. With
+    /// `filter.builder_only`, returns [`IndexerError::InvalidFilter`]
+    /// unless builder attribution is available: the `builder-enrichment`
+    /// feature and at least one configured builder.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use hl_indexer::filter::TradeFilter;
+    /// use hl_indexer::pagination::{PageRequest, SortOrder};
+    /// use hl_indexer::{Asset, Indexer};
+    ///
+    /// # async fn example() -> Result<(), hl_indexer::IndexerError> {
+    /// let indexer = Indexer::mainnet();
+    /// let filter = TradeFilter::new().with_assets([Asset::Eth]);
+    /// let mut request = PageRequest::first(SortOrder::Desc, 500);
+    /// loop {
+    ///     let page = indexer
+    ///         .get_user_fills_page("0x...", Some(1704067200000), None, &filter, &request)
+    ///         .await?;
+    ///     println!("{} fills", page.fills.len());
+    ///     match page.next_cursor() {
+    ///         Some(cursor) => request.cursor = Some(cursor),
+    ///         None => break,
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_user_fills_page(
+        &self,
+        user: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+        filter: &TradeFilter,
+        request: &PageRequest,
+    ) -> Result<FillPage, IndexerError> {
+        if request.limit == 0 {
+            return Err(IndexerError::InvalidPage(
+                "limit must be at least 1".to_string(),
+            ));
+        }
+        if filter.builder_only {
+            let fills = self.get_user_fills(user, from_ms, to_ms).await?;
+            let mut fills = self
+                .builder_attributed_fills(user, fills, from_ms, to_ms)
+                .await?;
+            fills.retain(|f| filter.matches(f));
+            return Ok(paginate(fills, request));
+        }
+
+        let (from_ms, to_ms) = request.narrow_window(from_ms, to_ms);
+        if let (Some(from), Some(to)) = (from_ms, to_ms) {
+            if from > to {
+                return Ok(FillPage::empty());
+            }
+        }
+
+        let fills = match from_ms {
+            Some(from) => {
+                let to = to_ms.unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+                self.scan_page_window(user, from, to, filter, request)
+                    .await?
+            }
+            None => {
+                let fills = self.get_user_fills(user, None, to_ms).await?;
+                fills.into_iter().filter(|f| filter.matches(f)).collect()
+            }
+        };
+        Ok(paginate(fills, request))
+    }
+
+    /// Fetch the matching fills in `[from, to]` that `request` needs.
+    ///
+    /// Chunks are fetched from the end of the window the page starts at,
+    /// each four times longer than the last, until more than `limit` fills
+    /// match on the page's side of the cursor or the window is exhausted.
+    async fn scan_page_window(
+        &self,
+        user: &str,
+        from: i64,
+        to: i64,
+        filter: &TradeFilter,
+        request: &PageRequest,
+    ) -> Result<Vec<UserFill>, IndexerError> {
+        let mut fills = Vec::new();
+        let mut matched = 0;
+        let mut chunk_ms = PAGE_CHUNK_MS;
+        let (mut lo, mut hi) = (from, to);
+        while matched <= request.limit {
+            let (start, end) = if request.scans_forward() {
+                (lo, lo.saturating_add(chunk_ms - 1).min(hi))
+            } else {
+                (hi.saturating_sub(chunk_ms - 1).max(lo), hi)
+            };
+            for fill in self.get_user_fills(user, Some(start), Some(end)).await? {
+                if filter.matches(&fill) {
+                    matched += usize::from(request.includes(&fill));
+                    fills.push(fill);
+                }
+            }
+
+            if (start, end) == (lo, hi) {
+                break;
+            }
+            if request.scans_forward() {
+                lo = end + 1;
+            } else {
+                hi = start - 1;
+            }
+            chunk_ms = chunk_ms.saturating_mul(4);
+        }
+        Ok(fills)
+    }
+
+    /// Keep the fills routed through one of the configured builders.
+    #[cfg(feature = "builder-enrichment")]
+    async fn builder_attributed_fills(
+        &self,
+        user: &str,
+        fills: Vec<UserFill>,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<Vec<UserFill>, IndexerError> {
+        // Archive rows are builder fills by definition
+        if self.config.fill_source == FillSource::BuilderArchive {
+            return Ok(fills);
+        }
+        if self.builder_clients.is_empty() {
+            return Err(IndexerError::InvalidFilter(
+                "builder_only requires at least one configured builder".to_string(),
+            ));
+        }
+        let enriched = self.enrich_fills(user, fills, from_ms, to_ms).await?;
+        Ok(enriched
+            .fills
+            .iter()
+            .filter(|f| enriched.is_builder_fill(f, user))
+            .cloned()
+            .collect())
+    }
+
+    /// Keep the fills routed through one of the configured builders.
+    #[cfg(not(feature = "builder-enrichment"))]
+    async fn builder_attributed_fills(
+        &self,
+        _user: &str,
+        _fills: Vec<UserFill>,
+        _from_ms: Option<i64>,
+        _to_ms: Option<i64>,
+    ) -> Result<Vec<UserFill>, IndexerError> {
+        Err(IndexerError::InvalidFilter(
+            "builder_only requires the builder-enrichment feature".to_string(),
+        ))
+    }
+
+    /// Read fills of several users from the configured builders' archives.
+    ///
+    /// Every archive in the range is downloaded (or read from the cache)
+    /// once, however many users are asked for, and only their rows are
+    /// parsed. Without `from_ms` the last [`DEFAULT_ARCHIVE_LOOKBACK_DAYS`]
+    /// days are read. Days without an archive are skipped; any other
+    /// failure fails the whole call.
+    ///
+    /// Returns each user's fills sorted by time, keyed as given in `users`.
+    /// See [`UserFill`]'s `From<&BuilderFill>` impl for the fields archives
+    /// do not carry.
+    #[cfg(feature = "builder-enrichment")]
+    pub async fn get_builder_archive_fills(
+        &self,
+        users: &[String],
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<HashMap<String, Vec<UserFill>>, IndexerError> {
+        use chrono::{DateTime, Duration, Utc};
+
+        if self.builder_clients.is_empty() {
+            return Err(IndexerError::NoData(
+                "no builder addresses configured for builder archive fills".to_string(),
+            ));
+        }
+
+        let now = Utc::now();
+        let date_of = |ms: i64| DateTime::from_timestamp_millis(ms).map(|dt| dt.date_naive());
+        let from_date = from_ms
+            .and_then(date_of)
+            .unwrap_or_else(|| (now - Duration::days(DEFAULT_ARCHIVE_LOOKBACK_DAYS)).date_naive());
+        let to_date = to_ms.and_then(date_of).unwrap_or_else(|| now.date_naive());
+
+        // Requested spelling of each user, by lowercase address
+        let keys: HashMap<String, &String> = users.iter().map(|u| (u.to_lowercase(), u)).collect();
+        let mut by_user: HashMap<String, Vec<UserFill>> =
+            users.iter().map(|u| (u.clone(), Vec::new())).collect();
+
+        let filter = FillFilter::new().with_users(users);
+        for builder_client in &self.builder_clients {
+            let builder_fills = builder_client
+                .clone()
+                .with_filter(filter.clone())
+                .fetch_fills_range(from_date, to_date)
+                .await?;
+
+            for fill in &builder_fills {
+                let ms = fill.timestamp_ms();
+                if from_ms.is_some_and(|from| ms < from) || to_ms.is_some_and(|to| ms > to) {
+                    continue;
+                }
+                if let Some(user) = keys.get(&fill.user.to_lowercase()) {
+                    by_user
+                        .entry((*user).clone())
+                        .or_default()
+                        .push(UserFill::from(fill));
+                }
+            }
+        }
+
+        for fills in by_user.values_mut() {
+            fills.sort_by_key(|f| f.timestamp_ms);
         }
+
+        tracing::debug!(
+            "Read {} fills for {} users from builder archives ({} to {})",
+            by_user.values().map(Vec::len).sum::<usize>(),
+            users.len(),
+            from_date,
+            to_date
+        );
+
+        Ok(by_user)
     }
 
     /// Fetch fills from the HTTP API regardless of the configured fill source.
@@ -373,6 +989,55 @@ impl Indexer {
         Ok(fills)
     }
 
+    /// Fetch the user's account value history (oldest first).
+    pub async fn get_account_value_history(
+        &self,
+        user: &str,
+    ) -> Result<Vec<AccountValuePoint>, IndexerError> {
+        Ok(self.source.get_account_value_history(user).await?)
+    }
+
+    /// Fetch the user's deposits, withdrawals and transfers (oldest first).
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - User address (hex string)
+    /// * `from_ms` - Optional start time (milliseconds since epoch)
+    /// * `to_ms` - Optional end time (milliseconds since epoch)
+    pub async fn get_ledger_updates(
+        &self,
+        user: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<Vec<LedgerUpdate>, IndexerError> {
+        Ok(self.source.get_ledger_updates(user, from_ms, to_ms).await?)
+    }
+
+    /// Determine the user's capital base for a return % window.
+    ///
+    /// Results are reused for [`IndexerConfig::capital_ttl`], so deposits
+    /// and withdrawals may take that long to show up.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - User address (hex string)
+    /// * `from_ms` - Window start (milliseconds since epoch)
+    /// * `to_ms` - Window end, or `None` for now
+    pub async fn get_capital_base(
+        &self,
+        user: &str,
+        from_ms: u64,
+        to_ms: Option<u64>,
+    ) -> Result<CapitalBase, IndexerError> {
+        if let Some(capital) = self.capital.get(user, from_ms, to_ms) {
+            return Ok(capital);
+        }
+        let end_ms = to_ms.unwrap_or_else(|| chrono::Utc::now().timestamp_millis().max(0) as u64);
+        let capital = load_capital_base(self, user, from_ms, end_ms).await?;
+        self.capital.insert(user, from_ms, to_ms, &capital);
+        Ok(capital)
+    }
+
     /// Fetch fills and calculate PnL for a user.
     ///
     /// # Arguments
@@ -410,6 +1075,24 @@ impl Indexer {
         Ok(summary)
     }
 
+    /// Split a user's fills into round-trips, ordered by opening time.
+    ///
+    /// With `assets`, only positions in those assets are followed. See
+    /// [`crate::roundtrip`] for how fills are grouped.
+    pub async fn get_user_round_trips(
+        &self,
+        user: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+        assets: Option<&[Asset]>,
+    ) -> Result<Vec<RoundTrip>, IndexerError> {
+        let mut fills = self.get_user_fills(user, from_ms, to_ms).await?;
+        if let Some(assets) = assets {
+            fills.retain(|fill| assets.contains(&fill.asset));
+        }
+        Ok(round_trips(&fills))
+    }
+
     /// Fetch fills and build a PnL tracker for detailed analysis.
     ///
     /// Returns the `UserPnL` struct which can be used for more detailed
@@ -439,19 +1122,33 @@ impl Indexer {
         from_ms: Option<i64>,
         to_ms: Option<i64>,
     ) -> Result<EnrichedFillsResult, IndexerError> {
-        use chrono::{TimeZone, Utc};
-
         let fills = self.get_user_fills(user, from_ms, to_ms).await?;
+        self.enrich_fills(user, fills, from_ms, to_ms).await
+    }
 
-        // If no builder client, return fills without enrichment
-        let Some(builder_client) = &self.builder_client else {
+    /// Attribute `fills`, fetched for `from_ms..to_ms`, to the configured
+    /// builders.
+    #[cfg(feature = "builder-enrichment")]
+    async fn enrich_fills(
+        &self,
+        user: &str,
+        fills: Vec<UserFill>,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<EnrichedFillsResult, IndexerError> {
+        use chrono::{TimeZone, Utc};
+
+        // If no builder clients, return fills without enrichment
+        if self.builder_clients.is_empty() {
             return Ok(EnrichedFillsResult {
                 fills,
                 builder_fills_matched: 0,
                 total_builder_fees: Decimal::ZERO,
+                builder_fees_by_builder: HashMap::new(),
+                match_report: MatchReport::default(),
                 enricher: None,
             });
-        };
+        }
 
         // Determine date range for builder data
         let (start_date, end_date) = match (from_ms, to_ms) {
@@ -483,6 +1180,8 @@ impl Indexer {
                         fills,
                         builder_fills_matched: 0,
                         total_builder_fees: Decimal::ZERO,
+                        builder_fees_by_builder: HashMap::new(),
+                        match_report: MatchReport::default(),
                         enricher: None,
                     });
                 }
@@ -502,35 +1201,49 @@ impl Indexer {
             }
         };
 
-        // Fetch builder fills
-        let builder_fills = builder_client
-            .fetch_fills_range(start_date, end_date)
-            .await?;
+        // Fetch this user's builder fills from every configured builder
+        let filter = FillFilter::new().with_users([user]);
+        let mut builder_fills = Vec::new();
+        for builder_client in &self.builder_clients {
+            builder_fills.extend(
+                builder_client
+                    .clone()
+                    .with_filter(filter.clone())
+                    .fetch_fills_range(start_date, end_date)
+                    .await?,
+            );
+        }
 
         let enricher = FillEnricher::new(builder_fills);
 
-        // Count matches
-        let mut matched = 0;
+        // Match the whole batch so each builder fill is used at most once
+        let (matches, match_report) = enricher.match_fills(user, &fills);
         let mut total_fees = Decimal::ZERO;
-
-        for fill in &fills {
-            if let Some(builder_fill) = enricher.get_builder_fill(fill, user) {
-                matched += 1;
-                total_fees += builder_fill.builder_fee;
-            }
+        let mut fees_by_builder: HashMap<String, Decimal> = HashMap::new();
+
+        for found in matches.iter().flatten() {
+            let fee = found.builder_fee();
+            total_fees += fee;
+            *fees_by_builder
+                .entry(found.builder_fill().builder.clone())
+                .or_insert(Decimal::ZERO) += fee;
         }
 
         tracing::info!(
-            "Builder enrichment: {} of {} fills matched, total builder fees: {}",
-            matched,
+            "Builder enrichment: {} of {} fills matched ({} ambiguous, {} unmatched), total builder fees: {}",
+            match_report.attributed(),
             fills.len(),
+            match_report.ambiguous,
+            match_report.unmatched,
             total_fees
         );
 
         Ok(EnrichedFillsResult {
             fills,
-            builder_fills_matched: matched,
+            builder_fills_matched: match_report.attributed(),
             total_builder_fees: total_fees,
+            builder_fees_by_builder: fees_by_builder,
+            match_report,
             enricher: Some(enricher),
         })
     }
@@ -548,6 +1261,12 @@ pub struct EnrichedFillsResult {
     /// Total builder fees from matched fills.
     pub total_builder_fees: Decimal,
 
+    /// Builder fees from matched fills, per builder address.
+    pub builder_fees_by_builder: HashMap<String, Decimal>,
+
+    /// How many fills matched, were ambiguous or did not match.
+    pub match_report: MatchReport,
+
     /// The enricher for detailed lookups (if builder data was fetched).
     pub enricher: Option<FillEnricher>,
 }
@@ -566,6 +1285,11 @@ impl EnrichedFillsResult {
     pub fn get_builder_fee(&self, fill: &UserFill, user: &str) -> Option<Decimal> {
         self.enricher.as_ref()?.get_builder_fee(fill, user)
     }
+
+    /// Get the address of the builder that routed a specific fill.
+    pub fn get_builder(&self, fill: &UserFill, user: &str) -> Option<&str> {
+        self.enricher.as_ref()?.get_builder(fill, user)
+    }
 }
 
 #[cfg(test)]
@@ -576,7 +1300,7 @@ mod tests {
     fn test_config_default() {
         let config = IndexerConfig::default();
         assert!(matches!(config.network, Network::Mainnet));
-        assert!(config.builder_address.is_none());
+        assert!(config.builder_addresses.is_empty());
     }
 
     #[test]
@@ -595,7 +1319,82 @@ mod tests {
     #[test]
     fn test_config_with_builder() {
         let config = IndexerConfig::mainnet().with_builder("0x123");
-        assert_eq!(config.builder_address, Some("0x123".to_string()));
+        assert_eq!(config.builder_addresses, vec!["0x123".to_string()]);
+    }
+
+    #[cfg(feature = "builder-enrichment")]
+    #[test]
+    fn test_config_with_multiple_builders() {
+        let config = IndexerConfig::mainnet()
+            .with_builder("0xAAA")
+            .with_builders(["0xbbb", "0xaaa"]);
+        assert_eq!(
+            config.builder_addresses,
+            vec!["0xaaa".to_string(), "0xbbb".to_string()]
+        );
+    }
+
+    #[cfg(feature = "builder-enrichment")]
+    #[test]
+    fn test_builder_endpoint_follows_network() {
+        assert_eq!(
+            IndexerConfig::mainnet().builder_endpoint(),
+            StatsEndpoint::mainnet()
+        );
+        assert_eq!(
+            IndexerConfig::testnet().builder_endpoint(),
+            StatsEndpoint::testnet()
+        );
+
+        let endpoint = IndexerConfig::testnet()
+            .with_endpoints(
+                Endpoints::testnet().with_stats_url("http://localhost:9000".parse().unwrap()),
+            )
+            .builder_endpoint();
+        assert_eq!(endpoint.base_url(), "http://localhost:9000");
+        assert_eq!(endpoint.network(), "Testnet");
+    }
+
+    #[cfg(feature = "builder-enrichment")]
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_builder_stats_url() {
+        let config = IndexerConfig::testnet().with_builder_stats_url("http://localhost:9000");
+        assert_eq!(
+            config.builder_endpoint().base_url(),
+            "http://localhost:9000"
+        );
+        assert_eq!(config.builder_endpoint().network(), "Testnet");
+
+        // An invalid URL leaves the endpoint alone
+        let config = IndexerConfig::testnet().with_builder_stats_url("not a url");
+        assert_eq!(config.builder_endpoint(), StatsEndpoint::testnet());
+    }
+
+    #[test]
+    fn test_recording_default_off() {
+        assert_eq!(IndexerConfig::default().recording, Recording::Off);
+
+        let config = IndexerConfig::mainnet().with_recording(Recording::Replay("fixtures".into()));
+        assert_eq!(config.recording, Recording::Replay("fixtures".into()));
+    }
+
+    #[test]
+    fn test_cache_default_disabled() {
+        assert_eq!(IndexerConfig::default().cache, CacheTtls::disabled());
+
+        let indexer = Indexer::new(IndexerConfig::mainnet().with_cache(CacheTtls::default()));
+        assert_eq!(indexer.config().cache, CacheTtls::default());
+        assert_eq!(indexer.cache_stats(), CacheStats::default());
+    }
+
+    #[test]
+    fn test_breaker_default_disabled() {
+        assert_eq!(IndexerConfig::default().breaker, BreakerConfig::disabled());
+
+        let indexer = Indexer::new(IndexerConfig::mainnet().with_breaker(BreakerConfig::default()));
+        assert_eq!(indexer.config().breaker, BreakerConfig::default());
+        assert_eq!(indexer.upstream_state(), BreakerState::Closed);
     }
 
     #[test]
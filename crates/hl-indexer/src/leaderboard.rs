@@ -4,21 +4,25 @@
 //! various trading metrics like volume, PnL, and return percentage.
 
 use crate::error::IndexerError;
-use crate::taint::{analyze_user_taint, TaintAnalysisResult};
+use crate::returns::CapitalBase;
+use crate::taint::{PositionLifecycleTracker, TaintAnalysisResult};
+use crate::wash::{WashTradeDetector, WashTradeReport};
 use crate::Indexer;
 use futures::future::join_all;
 use hl_types::{Asset, UserFill};
 use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 
 /// Metric to rank the leaderboard by.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum LeaderboardMetric {
     /// Total trading volume.
     Volume,
     /// Realized PnL.
     Pnl,
-    /// Return percentage (requires from_ms and max_start_capital).
+    /// Return percentage on each user's own starting equity (requires from_ms).
     ReturnPct,
 }
 
@@ -43,6 +47,59 @@ impl LeaderboardMetric {
     }
 }
 
+/// Which builder routed a fill, and what it charged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuilderAttribution {
+    /// Builder address (lowercase).
+    pub builder: String,
+
+    /// Builder fee charged on the fill.
+    pub builder_fee: Decimal,
+}
+
+/// How one of a user's fills was attributed to builders.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FillAttribution {
+    /// Whether the fill went through a tracked builder.
+    pub is_builder: bool,
+
+    /// The builder that routed the fill, if known.
+    pub builder: Option<BuilderAttribution>,
+
+    /// The fill's counterparty, if known.
+    pub counterparty: Option<String>,
+}
+
+/// A user's trading through a single builder.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuilderStats {
+    /// Number of fills routed by the builder.
+    pub fill_count: usize,
+
+    /// Notional volume routed by the builder.
+    pub volume: Decimal,
+
+    /// Exchange fees paid on those fills.
+    pub fees: Decimal,
+
+    /// Builder fees paid on those fills.
+    pub builder_fees: Decimal,
+
+    /// Realized PnL (closed_pnl - fees) on those fills.
+    pub realized_pnl: Decimal,
+}
+
+impl BuilderStats {
+    /// Add a fill routed by this builder.
+    pub fn record(&mut self, fill: &UserFill, builder_fee: Decimal) {
+        self.fill_count += 1;
+        self.volume += fill.price * fill.size;
+        self.fees += fill.fee;
+        self.builder_fees += builder_fee;
+        self.realized_pnl += fill.closed_pnl - fill.fee;
+    }
+}
+
 /// User statistics for leaderboard ranking.
 #[derive(Debug, Clone)]
 pub struct UserStats {
@@ -66,9 +123,52 @@ pub struct UserStats {
 
     /// Taint analysis result.
     pub taint_result: TaintAnalysisResult,
+
+    /// Equity at the start of the window (if known).
+    pub starting_equity: Option<Decimal>,
+
+    /// Net deposits minus withdrawals during the window (if known).
+    pub net_deposits: Option<Decimal>,
+
+    /// Volume flagged as wash trading (see [`crate::wash`]).
+    pub flagged_volume: Decimal,
+
+    /// Number of fills flagged as wash trading.
+    pub flagged_fill_count: usize,
+
+    /// Volume, fees and PnL per builder address.
+    pub builder_breakdown: BTreeMap<String, BuilderStats>,
 }
 
 impl UserStats {
+    /// Record wash trading findings, optionally removing flagged volume.
+    ///
+    /// The report must cover the same fills that counted toward `volume`.
+    pub fn apply_wash_report(&mut self, report: &WashTradeReport, exclude_flagged: bool) {
+        self.flagged_volume = report.flagged_volume;
+        self.flagged_fill_count = report.flagged.len();
+        if exclude_flagged {
+            self.volume -= report.flagged_volume;
+        }
+    }
+
+    /// Replace `return_pct` with a Modified Dietz return on the user's own
+    /// capital over `[from_ms, to_ms]`.
+    ///
+    /// `max_capital` caps the capital base (see
+    /// [`crate::returns::modified_dietz_return`]).
+    pub fn apply_capital_base(
+        &mut self,
+        capital: &CapitalBase,
+        from_ms: u64,
+        to_ms: u64,
+        max_capital: Option<Decimal>,
+    ) {
+        self.return_pct = capital.return_pct(self.realized_pnl, from_ms, to_ms, max_capital);
+        self.starting_equity = Some(capital.starting_equity);
+        self.net_deposits = Some(capital.net_flows());
+    }
+
     /// Get the metric value for ranking.
     pub fn get_metric_value(&self, metric: LeaderboardMetric) -> Decimal {
         match metric {
@@ -80,7 +180,7 @@ impl UserStats {
 }
 
 /// Ranked leaderboard entry.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LeaderboardEntry {
     /// Rank (1-indexed).
     pub rank: usize,
@@ -108,18 +208,37 @@ pub struct LeaderboardEntry {
 
     /// Whether the user is tainted.
     pub tainted: bool,
+
+    /// Equity at the start of the window (if known).
+    pub starting_equity: Option<Decimal>,
+
+    /// Net deposits minus withdrawals during the window (if known).
+    pub net_deposits: Option<Decimal>,
+
+    /// Volume flagged as wash trading.
+    pub flagged_volume: Decimal,
+
+    /// Number of fills flagged as wash trading.
+    pub flagged_fill_count: usize,
+
+    /// Volume, fees and PnL per builder address.
+    pub builder_breakdown: BTreeMap<String, BuilderStats>,
 }
 
 /// Configuration for leaderboard calculation.
-#[derive(Debug, Clone)]
+///
+/// Also used as the key for cached snapshots (see [`crate::snapshot`]).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct LeaderboardConfig {
-    /// Target builder address (lowercase).
-    pub target_builder: Option<String>,
+    /// Target builder addresses (lowercase). A fill routed by any of them
+    /// counts as a builder fill; empty for none.
+    pub target_builders: Vec<String>,
 
     /// Whether to filter out tainted users.
     pub builder_only: bool,
 
-    /// Maximum start capital for return percentage calculation.
+    /// Optional cap on the capital base used for return percentage; also
+    /// the capital assumed when a user's own capital cannot be determined.
     pub max_start_capital: Option<Decimal>,
 
     /// Optional asset filter.
@@ -133,12 +252,48 @@ pub struct LeaderboardConfig {
 
     /// Metric to rank by.
     pub metric: LeaderboardMetric,
+
+    /// Subtract volume flagged as wash trading from each user's volume.
+    pub exclude_flagged_volume: bool,
 }
 
 /// Trait for checking if a fill is a builder fill.
 pub trait BuilderFillChecker: Send + Sync {
     /// Check if the given fill for the given user is a builder fill.
     fn is_builder_fill(&self, fill: &UserFill, user: &str) -> bool;
+
+    /// Get the builder that routed the given fill, if known.
+    ///
+    /// Used for the per-builder breakdown. Defaults to unknown.
+    fn attribute(&self, _fill: &UserFill, _user: &str) -> Option<BuilderAttribution> {
+        None
+    }
+
+    /// Get the counterparty of the given fill, if known.
+    ///
+    /// Used for wash trading detection. Defaults to unknown.
+    fn counterparty(&self, _fill: &UserFill, _user: &str) -> Option<String> {
+        None
+    }
+
+    /// Attribute a batch of one user's fills, returning one entry per fill.
+    ///
+    /// Leaderboards use this rather than the per-fill methods. The default
+    /// asks them for each fill; checkers matching against a shared pool of
+    /// builder fills override it so each builder fill is used at most once.
+    fn attribute_fills(&self, fills: &[&UserFill], user: &str) -> Vec<FillAttribution> {
+        fills
+            .iter()
+            .map(|fill| {
+                let is_builder = self.is_builder_fill(fill, user);
+                FillAttribution {
+                    is_builder,
+                    builder: is_builder.then(|| self.attribute(fill, user)).flatten(),
+                    counterparty: self.counterparty(fill, user),
+                }
+            })
+            .collect()
+    }
 }
 
 /// A no-op checker that always returns false (no builder fills).
@@ -156,6 +311,9 @@ mod enricher_checker {
     use hl_builder_data::FillEnricher;
 
     /// Wrapper that implements `BuilderFillChecker` for `FillEnricher`.
+    ///
+    /// Batches of fills are matched with [`FillEnricher::match_fills`], so
+    /// each builder fill is attributed to at most one of a user's fills.
     pub struct FillEnricherChecker {
         enricher: FillEnricher,
     }
@@ -176,6 +334,40 @@ mod enricher_checker {
         fn is_builder_fill(&self, fill: &UserFill, user: &str) -> bool {
             self.enricher.is_builder_fill(fill, user)
         }
+
+        fn attribute(&self, fill: &UserFill, user: &str) -> Option<BuilderAttribution> {
+            self.enricher
+                .find_match(fill, user)
+                .map(|found| BuilderAttribution {
+                    builder: found.builder_fill().builder.clone(),
+                    builder_fee: found.builder_fee(),
+                })
+        }
+
+        fn counterparty(&self, fill: &UserFill, user: &str) -> Option<String> {
+            self.enricher
+                .get_counterparty(fill, user)
+                .map(str::to_string)
+        }
+
+        fn attribute_fills(&self, fills: &[&UserFill], user: &str) -> Vec<FillAttribution> {
+            let owned: Vec<UserFill> = fills.iter().map(|f| (*f).clone()).collect();
+            let (matches, _) = self.enricher.match_fills(user, &owned);
+            matches
+                .into_iter()
+                .map(|found| match found {
+                    Some(found) => FillAttribution {
+                        is_builder: true,
+                        builder: Some(BuilderAttribution {
+                            builder: found.builder_fill().builder.clone(),
+                            builder_fee: found.builder_fee(),
+                        }),
+                        counterparty: Some(found.builder_fill().counterparty.clone()),
+                    },
+                    None => FillAttribution::default(),
+                })
+                .collect()
+        }
     }
 }
 
@@ -193,42 +385,74 @@ pub fn calculate_user_stats<C: BuilderFillChecker>(
     coin_filter: Option<&str>,
     builder_only: bool,
 ) -> UserStats {
-    // Filter by coin if specified
-    let fills: Vec<&UserFill> = if let Some(coin) = coin_filter {
-        let target_asset = Asset::from_symbol(coin);
-        fills.iter().filter(|f| f.asset == target_asset).collect()
-    } else {
-        fills.iter().collect()
-    };
+    let attributed = attribute_user_fills(user, fills, builder_checker, coin_filter);
+    stats_from_attributed(user, &attributed, max_start_capital, builder_only)
+}
+
+/// A fill together with its builder attribution.
+type AttributedFill<'a> = (&'a UserFill, FillAttribution);
 
+/// The user's fills in `coin_filter` (all if `None`), attributed in one batch.
+fn attribute_user_fills<'a, C: BuilderFillChecker>(
+    user: &str,
+    fills: &'a [UserFill],
+    builder_checker: &C,
+    coin_filter: Option<&str>,
+) -> Vec<AttributedFill<'a>> {
+    let target_asset = coin_filter.map(Asset::from_symbol);
+    let fills: Vec<&UserFill> = fills
+        .iter()
+        .filter(|f| target_asset.as_ref().is_none_or(|asset| f.asset == *asset))
+        .collect();
+    let attributions = builder_checker.attribute_fills(&fills, user);
+    fills.into_iter().zip(attributions).collect()
+}
+
+/// Calculate stats from already attributed fills.
+fn stats_from_attributed(
+    user: &str,
+    fills: &[AttributedFill<'_>],
+    max_start_capital: Option<Decimal>,
+    builder_only: bool,
+) -> UserStats {
     // Calculate volume and PnL
     // When builder_only=true, only count builder fills toward metrics
     let mut volume = Decimal::ZERO;
     let mut realized_pnl = Decimal::ZERO;
     let mut builder_fill_count = 0;
     let mut counted_fills = 0;
+    let mut builder_breakdown: BTreeMap<String, BuilderStats> = BTreeMap::new();
 
-    for fill in &fills {
-        let is_builder = builder_checker.is_builder_fill(fill, user);
-        if is_builder {
+    for (fill, attribution) in fills {
+        if attribution.is_builder {
             builder_fill_count += 1;
+            if let Some(builder) = &attribution.builder {
+                builder_breakdown
+                    .entry(builder.builder.clone())
+                    .or_default()
+                    .record(fill, builder.builder_fee);
+            }
         }
 
         // Only count this fill if we're not in builder_only mode, or if it's a builder fill
-        if !builder_only || is_builder {
+        if !builder_only || attribution.is_builder {
             volume += fill.price * fill.size;
             realized_pnl += fill.closed_pnl - fill.fee;
             counted_fills += 1;
         }
     }
 
-    // Analyze taint using the builder checker (always on all fills)
-    let owned_fills: Vec<UserFill> = fills.iter().map(|f| (*f).clone()).collect();
-    let taint_result = analyze_user_taint(&owned_fills, |fill| {
-        builder_checker.is_builder_fill(fill, user)
-    });
+    // Analyze taint in time order (always on all fills)
+    let mut by_time: Vec<&AttributedFill<'_>> = fills.iter().collect();
+    by_time.sort_by_key(|(fill, _)| fill.timestamp_ms);
+    let mut tracker = PositionLifecycleTracker::new();
+    for (fill, attribution) in by_time {
+        tracker.process_fill(fill, attribution.is_builder);
+    }
+    let taint_result = tracker.result();
 
-    // Calculate return percentage
+    // Return percentage on the capital cap, until replaced by one on the
+    // user's own capital (see `UserStats::apply_capital_base`)
     let return_pct = max_start_capital.map(|capital| {
         if capital > Decimal::ZERO {
             (realized_pnl / capital) * Decimal::from(100)
@@ -245,17 +469,95 @@ pub fn calculate_user_stats<C: BuilderFillChecker>(
         trade_count: counted_fills,
         builder_fill_count,
         taint_result,
+        starting_equity: None,
+        net_deposits: None,
+        flagged_volume: Decimal::ZERO,
+        flagged_fill_count: 0,
+        builder_breakdown,
     }
 }
 
+/// Fills that count toward a user's metrics after builder-only filtering,
+/// with their counterparties.
+fn counted_fills(
+    fills: &[AttributedFill<'_>],
+    builder_only: bool,
+) -> (Vec<UserFill>, Vec<Option<String>>) {
+    fills
+        .iter()
+        .filter(|(_, attribution)| !builder_only || attribution.is_builder)
+        .map(|(fill, attribution)| ((*fill).clone(), attribution.counterparty.clone()))
+        .unzip()
+}
+
 /// Fetch fills and calculate stats for all users in parallel.
+///
+/// Trades between competition participants and offsetting volume are
+/// flagged as wash trading; use [`calculate_leaderboard_with_detector`] to
+/// also account for linked addresses or tune detection windows.
 pub async fn calculate_leaderboard<C: BuilderFillChecker>(
     indexer: &Indexer,
     users: &[String],
     config: &LeaderboardConfig,
     builder_checker: &C,
 ) -> Result<Vec<UserStats>, IndexerError> {
-    // Fetch fills for all users in parallel
+    let detector = WashTradeDetector::new().with_participants(users);
+    calculate_leaderboard_with_detector(indexer, users, config, builder_checker, &detector).await
+}
+
+/// Read every user's fills from builder archives in one pass, if that is
+/// the indexer's fill source.
+#[cfg(feature = "builder-enrichment")]
+async fn prefetch_archive_fills(
+    indexer: &Indexer,
+    users: &[String],
+    config: &LeaderboardConfig,
+) -> Result<Option<HashMap<String, Vec<UserFill>>>, IndexerError> {
+    if indexer.fill_source() != crate::FillSource::BuilderArchive {
+        return Ok(None);
+    }
+    indexer
+        .get_builder_archive_fills(users, config.from_ms, config.to_ms)
+        .await
+        .map(Some)
+}
+
+#[cfg(not(feature = "builder-enrichment"))]
+async fn prefetch_archive_fills(
+    _indexer: &Indexer,
+    _users: &[String],
+    _config: &LeaderboardConfig,
+) -> Result<Option<HashMap<String, Vec<UserFill>>>, IndexerError> {
+    Ok(None)
+}
+
+/// Fetch fills and calculate stats for all users in parallel, flagging wash
+/// trading with the given detector.
+///
+/// Return percentage is measured on each user's own capital (see
+/// [`crate::returns`]) when ranking by `returnPct` or when a
+/// `max_start_capital` cap is given, provided `from_ms` is set. Capital bases
+/// are reused across refreshes (see [`Indexer::get_capital_base`]). If a
+/// user's capital cannot be determined, the return falls back to
+/// `realized_pnl / max_start_capital`.
+///
+/// With [`crate::FillSource::BuilderArchive`] the archives are read once for
+/// all users, and a failure to read them fails the whole leaderboard.
+/// Likewise, if the API fails for any user (see
+/// [`IndexerError::is_upstream_failure`]) the whole calculation fails, so a
+/// caller can keep its previous result; users whose fills cannot be fetched
+/// for other reasons are included with zero stats.
+pub async fn calculate_leaderboard_with_detector<C: BuilderFillChecker>(
+    indexer: &Indexer,
+    users: &[String],
+    config: &LeaderboardConfig,
+    builder_checker: &C,
+    detector: &WashTradeDetector,
+) -> Result<Vec<UserStats>, IndexerError> {
+    let equity_window = equity_window(config);
+    let archive_fills = &prefetch_archive_fills(indexer, users, config).await?;
+
+    // Fetch fills (and capital, if needed) for all users in parallel
     let fetch_futures: Vec<_> = users
         .iter()
         .map(|user| {
@@ -263,8 +565,18 @@ pub async fn calculate_leaderboard<C: BuilderFillChecker>(
             let from_ms = config.from_ms;
             let to_ms = config.to_ms;
             async move {
-                let fills = indexer.get_user_fills(&user, from_ms, to_ms).await;
-                (user, fills)
+                let fills = match archive_fills {
+                    Some(by_user) => Ok(by_user.get(&user).cloned().unwrap_or_default()),
+                    None => indexer.get_user_fills(&user, from_ms, to_ms).await,
+                };
+                let capital = match equity_window {
+                    Some((from, _)) => {
+                        let to = to_ms.map(|t| t.max(0) as u64);
+                        Some(indexer.get_capital_base(&user, from, to).await)
+                    }
+                    None => None,
+                };
+                (user, fills, capital)
             }
         })
         .collect();
@@ -274,19 +586,38 @@ pub async fn calculate_leaderboard<C: BuilderFillChecker>(
     // Calculate stats for each user
     let mut stats = Vec::with_capacity(users.len());
 
-    for (user, fills_result) in results {
+    for (user, fills_result, capital) in results {
         match fills_result {
             Ok(fills) => {
-                let user_stats = calculate_user_stats(
+                let attributed =
+                    attribute_user_fills(&user, &fills, builder_checker, config.coin.as_deref());
+                let mut user_stats = stats_from_attributed(
                     &user,
-                    &fills,
-                    builder_checker,
+                    &attributed,
                     config.max_start_capital,
-                    config.coin.as_deref(),
                     config.builder_only,
                 );
+                match (capital, equity_window) {
+                    (Some(Ok(capital)), Some((from, to))) => {
+                        user_stats.apply_capital_base(&capital, from, to, config.max_start_capital);
+                    }
+                    (Some(Err(e)), _) => {
+                        tracing::warn!("Failed to determine capital for user {}: {}", user, e);
+                    }
+                    _ => {}
+                }
+
+                let (counted, counterparties) = counted_fills(&attributed, config.builder_only);
+                let wash_report =
+                    detector.analyze_with_counterparties(&user, &counted, &counterparties);
+                user_stats.apply_wash_report(&wash_report, config.exclude_flagged_volume);
+
                 stats.push(user_stats);
             }
+            Err(e) if e.is_upstream_failure() => {
+                // Zero stats would rank the user last until the API recovers
+                return Err(e);
+            }
             Err(e) => {
                 tracing::warn!("Failed to fetch fills for user {}: {}", user, e);
                 // Include user with zero stats rather than failing entirely
@@ -298,6 +629,11 @@ pub async fn calculate_leaderboard<C: BuilderFillChecker>(
                     trade_count: 0,
                     builder_fill_count: 0,
                     taint_result: TaintAnalysisResult::default(),
+                    starting_equity: None,
+                    net_deposits: None,
+                    flagged_volume: Decimal::ZERO,
+                    flagged_fill_count: 0,
+                    builder_breakdown: BTreeMap::new(),
                 });
             }
         }
@@ -306,6 +642,17 @@ pub async fn calculate_leaderboard<C: BuilderFillChecker>(
     Ok(stats)
 }
 
+/// The window over which per-user capital is measured, if needed.
+fn equity_window(config: &LeaderboardConfig) -> Option<(u64, u64)> {
+    let wants_returns =
+        config.metric == LeaderboardMetric::ReturnPct || config.max_start_capital.is_some();
+    let from_ms = config.from_ms.filter(|_| wants_returns)?;
+    let to_ms = config
+        .to_ms
+        .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+    Some((from_ms.max(0) as u64, to_ms.max(0) as u64))
+}
+
 /// Rank the leaderboard entries by metric.
 ///
 /// Note: When `builder_only=true`, filtering happens at calculation time (only builder fills
@@ -341,6 +688,11 @@ pub fn rank_leaderboard(
                 trade_count: stats.trade_count,
                 builder_fill_count: stats.builder_fill_count,
                 tainted: stats.taint_result.tainted,
+                starting_equity: stats.starting_equity,
+                net_deposits: stats.net_deposits,
+                flagged_volume: stats.flagged_volume,
+                flagged_fill_count: stats.flagged_fill_count,
+                builder_breakdown: stats.builder_breakdown,
             }
         })
         .collect()
@@ -386,6 +738,7 @@ mod tests {
             order_id: trade_id,
             crossed: true,
             direction: "Test".to_string(),
+            liquidated_user: None,
         }
     }
 
@@ -417,6 +770,84 @@ mod tests {
         assert!(!stats.taint_result.tainted);
     }
 
+    #[test]
+    fn test_builder_breakdown_across_builders() {
+        /// Routes odd trade IDs through builder A and even ones through B.
+        struct TwoBuilderChecker;
+
+        impl BuilderFillChecker for TwoBuilderChecker {
+            fn is_builder_fill(&self, fill: &UserFill, _user: &str) -> bool {
+                fill.trade_id < 10
+            }
+
+            fn attribute(&self, fill: &UserFill, _user: &str) -> Option<BuilderAttribution> {
+                (fill.trade_id < 10).then(|| BuilderAttribution {
+                    builder: if fill.trade_id % 2 == 1 { "0xa" } else { "0xb" }.to_string(),
+                    builder_fee: dec!(1),
+                })
+            }
+        }
+
+        let fills = vec![
+            make_fill(
+                Asset::Btc,
+                Side::Buy,
+                dec!(50000),
+                dec!(0.1),
+                dec!(5),
+                dec!(0),
+                1,
+                1000,
+            ),
+            make_fill(
+                Asset::Btc,
+                Side::Sell,
+                dec!(51000),
+                dec!(0.1),
+                dec!(5.1),
+                dec!(100),
+                2,
+                2000,
+            ),
+            make_fill(
+                Asset::Eth,
+                Side::Buy,
+                dec!(3000),
+                dec!(1),
+                dec!(3),
+                dec!(0),
+                3,
+                3000,
+            ),
+            make_fill(
+                Asset::Eth,
+                Side::Sell,
+                dec!(3100),
+                dec!(1),
+                dec!(3.1),
+                dec!(100),
+                11,
+                4000,
+            ),
+        ];
+
+        let stats = calculate_user_stats("0xuser", &fills, &TwoBuilderChecker, None, None, false);
+
+        assert_eq!(stats.builder_fill_count, 3);
+        assert_eq!(stats.builder_breakdown.len(), 2);
+
+        let a = &stats.builder_breakdown["0xa"];
+        assert_eq!(a.fill_count, 2);
+        assert_eq!(a.volume, dec!(8000));
+        assert_eq!(a.fees, dec!(8));
+        assert_eq!(a.builder_fees, dec!(2));
+        assert_eq!(a.realized_pnl, dec!(-8));
+
+        let b = &stats.builder_breakdown["0xb"];
+        assert_eq!(b.fill_count, 1);
+        assert_eq!(b.realized_pnl, dec!(94.9));
+    }
+
     #[test]
     fn test_calculate_user_stats_pnl() {
         let fills = vec![
@@ -537,6 +968,11 @@ mod tests {
                 trade_count: 5,
                 builder_fill_count: 5,
                 taint_result: TaintAnalysisResult::default(),
+                starting_equity: None,
+                net_deposits: None,
+                flagged_volume: Decimal::ZERO,
+                flagged_fill_count: 0,
+                builder_breakdown: BTreeMap::new(),
             },
             UserStats {
                 user: "user2".to_string(),
@@ -546,6 +982,11 @@ mod tests {
                 trade_count: 10,
                 builder_fill_count: 10,
                 taint_result: TaintAnalysisResult::default(),
+                starting_equity: None,
+                net_deposits: None,
+                flagged_volume: Decimal::ZERO,
+                flagged_fill_count: 0,
+                builder_breakdown: BTreeMap::new(),
             },
             UserStats {
                 user: "user3".to_string(),
@@ -555,6 +996,11 @@ mod tests {
                 trade_count: 8,
                 builder_fill_count: 8,
                 taint_result: TaintAnalysisResult::default(),
+                starting_equity: None,
+                net_deposits: None,
+                flagged_volume: Decimal::ZERO,
+                flagged_fill_count: 0,
+                builder_breakdown: BTreeMap::new(),
             },
         ];
 
@@ -580,6 +1026,11 @@ mod tests {
                 trade_count: 5,
                 builder_fill_count: 5,
                 taint_result: TaintAnalysisResult::default(),
+                starting_equity: None,
+                net_deposits: None,
+                flagged_volume: Decimal::ZERO,
+                flagged_fill_count: 0,
+                builder_breakdown: BTreeMap::new(),
             },
             UserStats {
                 user: "user2".to_string(),
@@ -589,6 +1040,11 @@ mod tests {
                 trade_count: 10,
                 builder_fill_count: 10,
                 taint_result: TaintAnalysisResult::default(),
+                starting_equity: None,
+                net_deposits: None,
+                flagged_volume: Decimal::ZERO,
+                flagged_fill_count: 0,
+                builder_breakdown: BTreeMap::new(),
             },
         ];
 
@@ -614,6 +1070,11 @@ mod tests {
                 trade_count: 10,
                 builder_fill_count: 5,
                 taint_result: tainted_result.clone(),
+                starting_equity: None,
+                net_deposits: None,
+                flagged_volume: Decimal::ZERO,
+                flagged_fill_count: 0,
+                builder_breakdown: BTreeMap::new(),
             },
             UserStats {
                 user: "user2".to_string(),
@@ -623,6 +1084,11 @@ mod tests {
                 trade_count: 5,
                 builder_fill_count: 5,
                 taint_result: TaintAnalysisResult::default(),
+                starting_equity: None,
+                net_deposits: None,
+                flagged_volume: Decimal::ZERO,
+                flagged_fill_count: 0,
+                builder_breakdown: BTreeMap::new(),
             },
         ];
 
@@ -648,6 +1114,11 @@ mod tests {
                 trade_count: 10,
                 builder_fill_count: 5,
                 taint_result: tainted_result, // Tainted
+                starting_equity: None,
+                net_deposits: None,
+                flagged_volume: Decimal::ZERO,
+                flagged_fill_count: 0,
+                builder_breakdown: BTreeMap::new(),
             },
             UserStats {
                 user: "user2".to_string(),
@@ -657,6 +1128,11 @@ mod tests {
                 trade_count: 5,
                 builder_fill_count: 5,
                 taint_result: TaintAnalysisResult::default(), // Clean
+                starting_equity: None,
+                net_deposits: None,
+                flagged_volume: Decimal::ZERO,
+                flagged_fill_count: 0,
+                builder_breakdown: BTreeMap::new(),
             },
         ];
 
@@ -667,4 +1143,140 @@ mod tests {
         assert!(ranked[0].tainted);
         assert!(!ranked[1].tainted);
     }
+
+    #[test]
+    fn test_apply_capital_base_uses_own_equity() {
+        use crate::returns::{CapitalBase, CashFlow, EquitySource};
+
+        let mut stats = UserStats {
+            user: "user1".to_string(),
+            volume: dec!(10000),
+            realized_pnl: dec!(150),
+            return_pct: Some(dec!(1.5)), // pnl / global capital of 10000
+            trade_count: 4,
+            builder_fill_count: 0,
+            taint_result: TaintAnalysisResult::default(),
+            starting_equity: None,
+            net_deposits: None,
+            flagged_volume: Decimal::ZERO,
+            flagged_fill_count: 0,
+            builder_breakdown: BTreeMap::new(),
+        };
+        let capital = CapitalBase {
+            starting_equity: dec!(1000),
+            source: EquitySource::AccountValue,
+            flows: vec![CashFlow {
+                time_ms: 500,
+                amount: dec!(1000),
+            }],
+        };
+
+        stats.apply_capital_base(&capital, 0, 1000, None);
+
+        // 150 / (1000 + 0.5 * 1000) = 10%
+        assert_eq!(stats.return_pct, Some(dec!(10)));
+        assert_eq!(stats.starting_equity, Some(dec!(1000)));
+        assert_eq!(stats.net_deposits, Some(dec!(1000)));
+
+        // A cap below the actual capital of 1500 is applied instead
+        stats.apply_capital_base(&capital, 0, 1000, Some(dec!(500)));
+        assert_eq!(stats.return_pct, Some(dec!(30)));
+        assert_eq!(stats.starting_equity, Some(dec!(1000)));
+
+        // A cap above it leaves the return alone
+        stats.apply_capital_base(&capital, 0, 1000, Some(dec!(100000)));
+        assert_eq!(stats.return_pct, Some(dec!(10)));
+    }
+
+    #[test]
+    fn test_apply_wash_report_excludes_volume() {
+        use crate::wash::WashTradeReport;
+
+        let mut stats = UserStats {
+            user: "user1".to_string(),
+            volume: dec!(10000),
+            realized_pnl: Decimal::ZERO,
+            return_pct: None,
+            trade_count: 4,
+            builder_fill_count: 0,
+            taint_result: TaintAnalysisResult::default(),
+            starting_equity: None,
+            net_deposits: None,
+            flagged_volume: Decimal::ZERO,
+            flagged_fill_count: 0,
+            builder_breakdown: BTreeMap::new(),
+        };
+        let report = WashTradeReport {
+            flagged: Vec::new(),
+            flagged_volume: dec!(4000),
+            total_volume: dec!(10000),
+        };
+
+        let mut reported_only = stats.clone();
+        reported_only.apply_wash_report(&report, false);
+        assert_eq!(reported_only.volume, dec!(10000));
+        assert_eq!(reported_only.flagged_volume, dec!(4000));
+
+        stats.apply_wash_report(&report, true);
+        assert_eq!(stats.volume, dec!(6000));
+    }
+
+    #[cfg(feature = "builder-enrichment")]
+    #[test]
+    fn test_enricher_checker_uses_each_builder_fill_once() {
+        use chrono::{TimeZone, Utc};
+        use hl_builder_data::{BuilderFill, BuilderFillSide, FillEnricher};
+
+        let builder_fill = BuilderFill {
+            time: Utc.timestamp_opt(1, 0).unwrap(),
+            user: "0xuser".to_string(),
+            asset: Asset::Btc,
+            side: BuilderFillSide::Bid,
+            price: dec!(50000),
+            size: dec!(1),
+            crossed: true,
+            special_trade_type: "Na".to_string(),
+            time_in_force: "Gtc".to_string(),
+            is_trigger: false,
+            counterparty: "0xother".to_string(),
+            closed_pnl: Decimal::ZERO,
+            twap_id: 0,
+            builder_fee: dec!(2),
+            builder: "0xb1".to_string(),
+            line: 0,
+        };
+        let checker = FillEnricherChecker::new(FillEnricher::new(vec![builder_fill]));
+
+        // The exact match uses up the builder fill; the smaller fill at the
+        // same price must not be counted as part of it as well
+        let price = dec!(50000);
+        let fills = vec![
+            make_fill(
+                Asset::Btc,
+                Side::Buy,
+                price,
+                dec!(1),
+                dec!(5),
+                dec!(0),
+                1,
+                1000,
+            ),
+            make_fill(
+                Asset::Btc,
+                Side::Buy,
+                price,
+                dec!(0.5),
+                dec!(2.5),
+                dec!(0),
+                2,
+                1000,
+            ),
+        ];
+        let stats = calculate_user_stats("0xuser", &fills, &checker, None, None, true);
+
+        assert_eq!(stats.builder_fill_count, 1);
+        assert_eq!(stats.trade_count, 1);
+        assert_eq!(stats.volume, dec!(50000));
+        assert_eq!(stats.builder_breakdown["0xb1"].builder_fees, dec!(2));
+    }
 }
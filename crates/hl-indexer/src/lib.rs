@@ -72,17 +72,29 @@
 
 mod converter;
 mod error;
+pub mod filter;
+mod history_dir;
 mod indexer;
 pub mod leaderboard;
+pub mod pagination;
+pub mod returns;
+pub mod roundtrip;
+pub mod snapshot;
 pub mod taint;
+pub mod wash;
 
 pub use converter::{convert_fill, convert_fills};
 pub use error::IndexerError;
-pub use indexer::{FillSource, Indexer, IndexerConfig};
+pub use indexer::{FillSource, Indexer, IndexerConfig, Recording};
 
 #[cfg(feature = "builder-enrichment")]
-pub use indexer::EnrichedFillsResult;
+pub use indexer::{EnrichedFillsResult, DEFAULT_ARCHIVE_LOOKBACK_DAYS};
+
+#[cfg(feature = "builder-enrichment")]
+pub use hl_builder_data::{ArchiveCache, ArchiveImporter, StatsEndpoint};
 
 // Re-export commonly used types from dependencies for convenience
-pub use hl_ingestion::Network;
-pub use hl_types::{Asset, PnLSummary, Position, Side, UserFill, UserPnL};
+pub use hl_ingestion::{
+    BreakerConfig, BreakerState, CacheStats, CacheTtls, CollectedFills, Endpoints, Network,
+};
+pub use hl_types::{Asset, FillDirection, PnLSummary, Position, Side, UserFill, UserPnL};
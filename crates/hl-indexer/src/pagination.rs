@@ -0,0 +1,353 @@
+//! Keyset pagination over a user's fills.
+//!
+//! Fills are ordered by `(timestamp_ms, trade_id)`, which is unique per
+//! user, so a page boundary can be named by the key of the fill next to it.
+//! Unlike offsets, such cursors stay valid while new fills arrive: a page
+//! never repeats or skips a fill because others were inserted before it.
+//!
+//! A cursor also bounds the time window, so
+//! [`Indexer::get_user_fills_page`](crate::Indexer::get_user_fills_page)
+//! only fetches fills on the requested side of it rather than the whole
+//! history. From there it scans outwards in growing time chunks
+//! ([`PageRequest::scans_forward`]) and stops once the page is full, so a
+//! page costs about `limit` fills however long the window is.
+
+use hl_types::UserFill;
+
+/// Order fills are returned in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SortOrder {
+    /// Newest first.
+    #[default]
+    Desc,
+    /// Oldest first.
+    Asc,
+}
+
+impl SortOrder {
+    /// Get the order as a string (for API responses).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Desc => "desc",
+            SortOrder::Asc => "asc",
+        }
+    }
+
+    /// Parse an order from a string (case-insensitive).
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "desc" => Some(SortOrder::Desc),
+            "asc" => Some(SortOrder::Asc),
+            _ => None,
+        }
+    }
+}
+
+/// Position of a fill in the `(timestamp_ms, trade_id)` ordering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FillKey {
+    /// Fill timestamp (milliseconds since epoch).
+    pub timestamp_ms: u64,
+    /// Trade ID, breaking ties between fills in the same millisecond.
+    pub trade_id: u64,
+}
+
+impl FillKey {
+    /// The key of a fill.
+    pub fn of(fill: &UserFill) -> Self {
+        Self {
+            timestamp_ms: fill.timestamp_ms,
+            trade_id: fill.trade_id,
+        }
+    }
+}
+
+/// Where a page starts, relative to the requested [`SortOrder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PageCursor {
+    /// The page holds the fills that follow this key.
+    After(FillKey),
+    /// The page holds the fills that precede this key.
+    Before(FillKey),
+}
+
+/// A page of fills to fetch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PageRequest {
+    /// Order of the returned fills.
+    pub order: SortOrder,
+    /// Page boundary; `None` for the first page.
+    pub cursor: Option<PageCursor>,
+    /// Maximum number of fills in the page.
+    pub limit: usize,
+}
+
+impl PageRequest {
+    /// The first page of `limit` fills in `order`.
+    pub fn first(order: SortOrder, limit: usize) -> Self {
+        Self {
+            order,
+            cursor: None,
+            limit,
+        }
+    }
+
+    /// Whether the page holds the earliest fills of its (narrowed) window,
+    /// so the window is scanned forwards in time; otherwise it holds the
+    /// latest and is scanned backwards.
+    pub fn scans_forward(&self) -> bool {
+        matches!(
+            (self.cursor, self.order),
+            (None | Some(PageCursor::After(_)), SortOrder::Asc)
+                | (Some(PageCursor::Before(_)), SortOrder::Desc)
+        )
+    }
+
+    /// Whether `fill` lies on this page's side of the cursor.
+    pub fn includes(&self, fill: &UserFill) -> bool {
+        let key = FillKey::of(fill);
+        match (self.cursor, self.order) {
+            (None, _) => true,
+            (Some(PageCursor::After(at)), SortOrder::Asc)
+            | (Some(PageCursor::Before(at)), SortOrder::Desc) => key > at,
+            (Some(PageCursor::After(at)), SortOrder::Desc)
+            | (Some(PageCursor::Before(at)), SortOrder::Asc) => key < at,
+        }
+    }
+
+    /// Narrow a time window to the side of the cursor this page is on.
+    ///
+    /// The cursor's own millisecond stays in the window, since other fills
+    /// may share its timestamp.
+    pub fn narrow_window(
+        &self,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> (Option<i64>, Option<i64>) {
+        let Some(PageCursor::After(key) | PageCursor::Before(key)) = self.cursor else {
+            return (from_ms, to_ms);
+        };
+        let bound = key.timestamp_ms.min(i64::MAX as u64) as i64;
+        if self.scans_forward() {
+            (Some(from_ms.map_or(bound, |from| from.max(bound))), to_ms)
+        } else {
+            (from_ms, Some(to_ms.map_or(bound, |to| to.min(bound))))
+        }
+    }
+}
+
+/// A page of fills plus what lies on either side of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillPage {
+    /// Fills in the requested order.
+    pub fills: Vec<UserFill>,
+    /// Whether fills precede this page in the requested order.
+    pub has_prev: bool,
+    /// Whether fills follow this page in the requested order.
+    pub has_next: bool,
+}
+
+impl FillPage {
+    /// An empty page with nothing on either side.
+    pub fn empty() -> Self {
+        Self {
+            fills: Vec::new(),
+            has_prev: false,
+            has_next: false,
+        }
+    }
+
+    /// Cursor for the page after this one, if there is one.
+    pub fn next_cursor(&self) -> Option<PageCursor> {
+        let last = self.fills.last().filter(|_| self.has_next)?;
+        Some(PageCursor::After(FillKey::of(last)))
+    }
+
+    /// Cursor for the page before this one, if there is one.
+    pub fn prev_cursor(&self) -> Option<PageCursor> {
+        let first = self.fills.first().filter(|_| self.has_prev)?;
+        Some(PageCursor::Before(FillKey::of(first)))
+    }
+}
+
+/// Sort `fills` and cut out the requested page.
+///
+/// When paging from a cursor, the fill the cursor was taken from lies on
+/// the other side, so the page always reports more fills that way.
+pub fn paginate(mut fills: Vec<UserFill>, request: &PageRequest) -> FillPage {
+    fills.sort_unstable_by_key(FillKey::of);
+    if request.order == SortOrder::Desc {
+        fills.reverse();
+    }
+    fills.retain(|fill| request.includes(fill));
+
+    match request.cursor {
+        None => {
+            let has_next = fills.len() > request.limit;
+            fills.truncate(request.limit);
+            FillPage {
+                fills,
+                has_prev: false,
+                has_next,
+            }
+        }
+        Some(PageCursor::After(_)) => {
+            let has_next = fills.len() > request.limit;
+            fills.truncate(request.limit);
+            FillPage {
+                fills,
+                has_prev: true,
+                has_next,
+            }
+        }
+        Some(PageCursor::Before(_)) => {
+            let has_prev = fills.len() > request.limit;
+            let start = fills.len().saturating_sub(request.limit);
+            FillPage {
+                fills: fills.split_off(start),
+                has_prev,
+                has_next: true,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hl_types::{Asset, Side};
+    use rust_decimal::Decimal;
+
+    fn fill(timestamp_ms: u64, trade_id: u64) -> UserFill {
+        UserFill {
+            asset: Asset::from_symbol("BTC"),
+            timestamp_ms,
+            price: Decimal::ONE,
+            size: Decimal::ONE,
+            side: Side::Buy,
+            fee: Decimal::ZERO,
+            closed_pnl: Decimal::ZERO,
+            trade_id,
+            order_id: trade_id,
+            crossed: true,
+            direction: "Open Long".to_string(),
+            liquidated_user: None,
+        }
+    }
+
+    /// Fills at t=1..=5, with t=3 holding two fills.
+    fn history() -> Vec<UserFill> {
+        vec![
+            fill(3, 31),
+            fill(1, 10),
+            fill(5, 50),
+            fill(3, 30),
+            fill(2, 20),
+            fill(4, 40),
+        ]
+    }
+
+    fn ids(page: &FillPage) -> Vec<u64> {
+        page.fills.iter().map(|f| f.trade_id).collect()
+    }
+
+    #[test]
+    fn test_first_page() {
+        let page = paginate(history(), &PageRequest::first(SortOrder::Desc, 4));
+        assert_eq!(ids(&page), vec![50, 40, 31, 30]);
+        assert!(page.has_next && !page.has_prev);
+        assert!(page.prev_cursor().is_none());
+
+        let page = paginate(history(), &PageRequest::first(SortOrder::Asc, 10));
+        assert_eq!(ids(&page), vec![10, 20, 30, 31, 40, 50]);
+        assert!(page.next_cursor().is_none());
+    }
+
+    #[test]
+    fn test_walk_forward_and_back() {
+        for order in [SortOrder::Desc, SortOrder::Asc] {
+            let mut request = PageRequest::first(order, 2);
+            let mut pages = Vec::new();
+            loop {
+                let page = paginate(history(), &request);
+                pages.push(ids(&page));
+                match page.next_cursor() {
+                    Some(cursor) => request.cursor = Some(cursor),
+                    None => break,
+                }
+            }
+            let walked: Vec<u64> = pages.concat();
+            let mut expected = vec![10, 20, 30, 31, 40, 50];
+            if order == SortOrder::Desc {
+                expected.reverse();
+            }
+            assert_eq!(walked, expected);
+
+            // Walking back from the last page revisits the same pages
+            let mut page = paginate(history(), &request);
+            for expected in pages.iter().rev().skip(1) {
+                request.cursor = page.prev_cursor();
+                page = paginate(history(), &request);
+                assert_eq!(&ids(&page), expected);
+            }
+            assert!(page.prev_cursor().is_none());
+        }
+    }
+
+    #[test]
+    fn test_ties_split_across_pages() {
+        let request = PageRequest {
+            order: SortOrder::Asc,
+            cursor: Some(PageCursor::After(FillKey {
+                timestamp_ms: 3,
+                trade_id: 30,
+            })),
+            limit: 1,
+        };
+        assert_eq!(ids(&paginate(history(), &request)), vec![31]);
+    }
+
+    #[test]
+    fn test_narrow_window() {
+        let key = FillKey {
+            timestamp_ms: 3,
+            trade_id: 30,
+        };
+        let older = PageRequest {
+            order: SortOrder::Desc,
+            cursor: Some(PageCursor::After(key)),
+            limit: 10,
+        };
+        assert_eq!(older.narrow_window(Some(1), None), (Some(1), Some(3)));
+        assert_eq!(older.narrow_window(None, Some(2)), (None, Some(2)));
+
+        let newer = PageRequest {
+            cursor: Some(PageCursor::Before(key)),
+            ..older
+        };
+        assert_eq!(newer.narrow_window(None, Some(9)), (Some(3), Some(9)));
+
+        let first = PageRequest::first(SortOrder::Asc, 10);
+        assert_eq!(first.narrow_window(Some(1), None), (Some(1), None));
+    }
+
+    #[test]
+    fn test_scan_direction() {
+        let key = FillKey {
+            timestamp_ms: 3,
+            trade_id: 30,
+        };
+        assert!(!PageRequest::first(SortOrder::Desc, 1).scans_forward());
+        assert!(PageRequest::first(SortOrder::Asc, 1).scans_forward());
+
+        let newer = PageRequest {
+            order: SortOrder::Desc,
+            cursor: Some(PageCursor::Before(key)),
+            limit: 1,
+        };
+        assert!(newer.scans_forward());
+        assert!(newer.includes(&fill(3, 31)));
+        assert!(!newer.includes(&fill(3, 30)));
+        assert!(!newer.includes(&fill(2, 20)));
+    }
+}
@@ -0,0 +1,419 @@
+//! Return percentage based on each user's actual capital.
+//!
+//! A fixed capital figure for every participant makes return % meaningless:
+//! a whale with $1M earning $10k looks identical to a small account that
+//! doubled. Instead we measure each user's equity at the start of the window
+//! and adjust for money moved in or out during it using the Modified Dietz
+//! method:
+//!
+//! ```text
+//!              gain
+//! R = ---------------------------
+//!     V_start + Σ w_i · F_i
+//!
+//! w_i = (T_end - t_i) / (T_end - T_start)
+//! ```
+//!
+//! where `F_i` are external cash flows (deposits positive, withdrawals
+//! negative). Weighting by the time each flow was in the account means a
+//! deposit made just before the window ends barely counts as capital, so
+//! topping up at the last minute cannot dilute (or inflate) a return.
+//!
+//! The gain is the user's realized PnL over the window, consistent with the
+//! `pnl` leaderboard metric.
+
+use crate::error::IndexerError;
+use crate::Indexer;
+use hl_ingestion::{AccountValuePoint, LedgerUpdate};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Default time a capital base is reused (see [`Indexer::get_capital_base`]).
+pub const DEFAULT_CAPITAL_TTL: Duration = Duration::from_secs(300);
+
+/// An external capital flow into (positive) or out of (negative) an account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CashFlow {
+    /// Time of the flow in milliseconds since epoch.
+    pub time_ms: u64,
+
+    /// Signed amount in USDC.
+    pub amount: Decimal,
+}
+
+/// Where the starting equity figure came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EquitySource {
+    /// Account value snapshot at or before the window start.
+    AccountValue,
+
+    /// Net deposits before the window start, reconstructed from the ledger.
+    /// Used when no account value history is available; ignores any trading
+    /// PnL made before the window.
+    Ledger,
+}
+
+/// A user's capital at the start of a window plus flows during it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapitalBase {
+    /// Equity at the start of the window.
+    pub starting_equity: Decimal,
+
+    /// How `starting_equity` was determined.
+    pub source: EquitySource,
+
+    /// Cash flows within the window, in time order.
+    pub flows: Vec<CashFlow>,
+}
+
+impl CapitalBase {
+    /// Sum of all flows within the window.
+    pub fn net_flows(&self) -> Decimal {
+        self.flows.iter().map(|f| f.amount).sum()
+    }
+
+    /// Modified Dietz return percentage for `gain` over the window.
+    ///
+    /// See [`modified_dietz_return`].
+    pub fn return_pct(
+        &self,
+        gain: Decimal,
+        from_ms: u64,
+        to_ms: u64,
+        max_capital: Option<Decimal>,
+    ) -> Option<Decimal> {
+        modified_dietz_return(
+            gain,
+            self.starting_equity,
+            &self.flows,
+            from_ms,
+            to_ms,
+            max_capital,
+        )
+    }
+}
+
+/// Account value at `at_ms`, taken from the latest snapshot at or before it.
+///
+/// Returns zero if every snapshot is later (the account did not exist yet),
+/// and `None` if there is no history at all.
+pub fn starting_equity_at(history: &[AccountValuePoint], at_ms: u64) -> Option<Decimal> {
+    if history.is_empty() {
+        return None;
+    }
+
+    Some(
+        history
+            .iter()
+            .filter(|p| p.time <= at_ms)
+            .max_by_key(|p| p.time)
+            .map(|p| p.account_value)
+            .unwrap_or(Decimal::ZERO),
+    )
+}
+
+/// Extract the non-zero capital flows for `user` from ledger updates.
+pub fn cash_flows(user: &str, updates: &[LedgerUpdate]) -> Vec<CashFlow> {
+    let mut flows: Vec<CashFlow> = updates
+        .iter()
+        .map(|u| CashFlow {
+            time_ms: u.time,
+            amount: u.delta.net_flow_for(user),
+        })
+        .filter(|f| !f.amount.is_zero())
+        .collect();
+    flows.sort_by_key(|f| f.time_ms);
+    flows
+}
+
+/// Compute a Modified Dietz return percentage.
+///
+/// # Arguments
+///
+/// * `gain` - Profit over the window
+/// * `starting_equity` - Equity at `from_ms`
+/// * `flows` - Cash flows; those outside `[from_ms, to_ms]` are ignored
+/// * `from_ms` / `to_ms` - The measurement window
+/// * `max_capital` - Optional cap on the capital base, so very large accounts
+///   are measured as if they had traded with at most this much
+///
+/// # Returns
+///
+/// The return in percent, or `None` if the window is empty or there was no
+/// capital at risk (a non-positive denominator).
+pub fn modified_dietz_return(
+    gain: Decimal,
+    starting_equity: Decimal,
+    flows: &[CashFlow],
+    from_ms: u64,
+    to_ms: u64,
+    max_capital: Option<Decimal>,
+) -> Option<Decimal> {
+    if to_ms <= from_ms {
+        return None;
+    }
+
+    let period = Decimal::from(to_ms - from_ms);
+    let weighted_flows: Decimal = flows
+        .iter()
+        .filter(|f| f.time_ms >= from_ms && f.time_ms <= to_ms)
+        .map(|f| f.amount * Decimal::from(to_ms - f.time_ms) / period)
+        .sum();
+
+    let mut capital = starting_equity + weighted_flows;
+    if let Some(cap) = max_capital {
+        capital = capital.min(cap);
+    }
+
+    if capital <= Decimal::ZERO {
+        return None;
+    }
+
+    Some(gain / capital * Decimal::from(100))
+}
+
+/// Determine a user's capital base for the window `[from_ms, to_ms]`.
+///
+/// Prefers the account value history for the starting equity; if that is
+/// unavailable, falls back to net deposits before `from_ms` from the ledger.
+pub async fn load_capital_base(
+    indexer: &Indexer,
+    user: &str,
+    from_ms: u64,
+    to_ms: u64,
+) -> Result<CapitalBase, IndexerError> {
+    let history = match indexer.get_account_value_history(user).await {
+        Ok(history) => history,
+        Err(e) => {
+            tracing::debug!(
+                "No account value history for {} ({}), using ledger",
+                user,
+                e
+            );
+            Vec::new()
+        }
+    };
+
+    if let Some(starting_equity) = starting_equity_at(&history, from_ms) {
+        let updates = indexer
+            .get_ledger_updates(user, Some(from_ms as i64), Some(to_ms as i64))
+            .await?;
+        return Ok(CapitalBase {
+            starting_equity,
+            source: EquitySource::AccountValue,
+            flows: cash_flows(user, &updates),
+        });
+    }
+
+    // Reconstruct from the full ledger: everything deposited before the window
+    let updates = indexer
+        .get_ledger_updates(user, None, Some(to_ms as i64))
+        .await?;
+    let all_flows = cash_flows(user, &updates);
+    let (before, during): (Vec<CashFlow>, Vec<CashFlow>) =
+        all_flows.into_iter().partition(|f| f.time_ms < from_ms);
+    let starting_equity = before
+        .iter()
+        .map(|f| f.amount)
+        .sum::<Decimal>()
+        .max(Decimal::ZERO);
+
+    Ok(CapitalBase {
+        starting_equity,
+        source: EquitySource::Ledger,
+        flows: during,
+    })
+}
+
+/// Capital bases by user and window.
+///
+/// A leaderboard refresh needs two API calls per user to determine capital,
+/// and starting equity does not change once the window has begun, so results
+/// are reused for a while. Flows made since an entry was loaded are missed
+/// until it expires.
+pub(crate) struct CapitalCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<CapitalKey, (Instant, CapitalBase)>>,
+}
+
+/// User, window start and window end (`None` for an open window).
+type CapitalKey = (String, u64, Option<u64>);
+
+impl CapitalCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn get(&self, user: &str, from_ms: u64, to_ms: Option<u64>) -> Option<CapitalBase> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries
+            .get(&(user.to_lowercase(), from_ms, to_ms))
+            .filter(|(loaded_at, _)| loaded_at.elapsed() < self.ttl)
+            .map(|(_, capital)| capital.clone())
+    }
+
+    pub(crate) fn insert(
+        &self,
+        user: &str,
+        from_ms: u64,
+        to_ms: Option<u64>,
+        capital: &CapitalBase,
+    ) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.retain(|_, (loaded_at, _)| loaded_at.elapsed() < self.ttl);
+        entries.insert(
+            (user.to_lowercase(), from_ms, to_ms),
+            (Instant::now(), capital.clone()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hl_ingestion::LedgerDelta;
+    use rust_decimal_macros::dec;
+
+    fn point(time: u64, value: Decimal) -> AccountValuePoint {
+        AccountValuePoint {
+            time,
+            account_value: value,
+        }
+    }
+
+    #[test]
+    fn test_starting_equity_at() {
+        let history = vec![point(100, dec!(1000)), point(200, dec!(1500))];
+
+        assert_eq!(starting_equity_at(&history, 150), Some(dec!(1000)));
+        assert_eq!(starting_equity_at(&history, 200), Some(dec!(1500)));
+        // Account did not exist yet
+        assert_eq!(starting_equity_at(&history, 50), Some(Decimal::ZERO));
+        assert_eq!(starting_equity_at(&[], 150), None);
+    }
+
+    #[test]
+    fn test_return_without_flows() {
+        // 100 gain on 1000 starting equity = 10%
+        let r = modified_dietz_return(dec!(100), dec!(1000), &[], 0, 1000, None);
+        assert_eq!(r, Some(dec!(10)));
+    }
+
+    #[test]
+    fn test_mid_window_deposit_is_half_weighted() {
+        // Deposit of 1000 halfway through counts as 500 of capital
+        let flows = vec![CashFlow {
+            time_ms: 500,
+            amount: dec!(1000),
+        }];
+        let r = modified_dietz_return(dec!(150), dec!(1000), &flows, 0, 1000, None);
+        assert_eq!(r, Some(dec!(10)));
+    }
+
+    #[test]
+    fn test_last_minute_deposit_barely_counts() {
+        let flows = vec![CashFlow {
+            time_ms: 1000,
+            amount: dec!(1000000),
+        }];
+        let r = modified_dietz_return(dec!(100), dec!(1000), &flows, 0, 1000, None);
+        assert_eq!(r, Some(dec!(10)));
+    }
+
+    #[test]
+    fn test_large_account_uses_actual_capital() {
+        let r = modified_dietz_return(dec!(100), dec!(100000), &[], 0, 1000, None);
+        assert_eq!(r, Some(dec!(0.1)));
+    }
+
+    #[test]
+    fn test_max_capital_caps_large_accounts() {
+        // 100 gain on 100000 of capital, capped at 10000 = 1%
+        let r = modified_dietz_return(dec!(100), dec!(100000), &[], 0, 1000, Some(dec!(10000)));
+        assert_eq!(r, Some(dec!(1)));
+
+        // Smaller accounts are measured on their own capital
+        let r = modified_dietz_return(dec!(100), dec!(1000), &[], 0, 1000, Some(dec!(10000)));
+        assert_eq!(r, Some(dec!(10)));
+    }
+
+    #[test]
+    fn test_capital_cache_expires() {
+        let capital = CapitalBase {
+            starting_equity: dec!(1000),
+            source: EquitySource::AccountValue,
+            flows: Vec::new(),
+        };
+
+        let cache = CapitalCache::new(Duration::from_secs(60));
+        cache.insert("0xABC", 0, None, &capital);
+        assert_eq!(cache.get("0xabc", 0, None), Some(capital.clone()));
+        assert_eq!(cache.get("0xabc", 0, Some(1000)), None);
+
+        let disabled = CapitalCache::new(Duration::ZERO);
+        disabled.insert("0xabc", 0, None, &capital);
+        assert_eq!(disabled.get("0xabc", 0, None), None);
+    }
+
+    #[test]
+    fn test_no_capital_returns_none() {
+        assert_eq!(
+            modified_dietz_return(dec!(100), Decimal::ZERO, &[], 0, 1000, None),
+            None
+        );
+        assert_eq!(
+            modified_dietz_return(dec!(100), dec!(1000), &[], 1000, 1000, None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_cash_flows_skip_internal_moves() {
+        let updates = vec![
+            LedgerUpdate {
+                time: 20,
+                hash: "0x2".to_string(),
+                delta: LedgerDelta::Withdraw {
+                    usdc: dec!(50),
+                    fee: dec!(1),
+                },
+            },
+            LedgerUpdate {
+                time: 10,
+                hash: "0x1".to_string(),
+                delta: LedgerDelta::Deposit { usdc: dec!(500) },
+            },
+            LedgerUpdate {
+                time: 15,
+                hash: "0x3".to_string(),
+                delta: LedgerDelta::AccountClassTransfer {
+                    usdc: dec!(100),
+                    to_perp: true,
+                },
+            },
+        ];
+
+        let flows = cash_flows("0xabc", &updates);
+        assert_eq!(
+            flows,
+            vec![
+                CashFlow {
+                    time_ms: 10,
+                    amount: dec!(500)
+                },
+                CashFlow {
+                    time_ms: 20,
+                    amount: dec!(-50)
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Round-trips: positions followed from open to flat.
+//!
+//! A round-trip starts with the fill that moves a user's net position in an
+//! asset away from zero and ends with the fill that brings it back. A fill
+//! that flips the position closes one round-trip and opens the next: its
+//! size and volume are split between them, while its fee and closed PnL go
+//! to the round-trip it closes.
+//!
+//! Positions are assumed to be flat before the first fill, so fills should
+//! start before the earliest position of interest was opened.
+//!
+//! [`round_trips`] splits a slice of fills; [`RoundTripTracker`] does the
+//! same for fills arriving in batches, handing out round-trips as they
+//! settle.
+
+use hl_types::{Asset, Side, UserFill};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+
+/// One position in one asset, from open to flat.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundTrip {
+    /// The asset traded.
+    pub asset: Asset,
+    /// Side of the opening fill: [`Side::Buy`] for a long position,
+    /// [`Side::Sell`] for a short one.
+    pub side: Side,
+    /// Timestamp of the opening fill (milliseconds since epoch).
+    pub opened_at_ms: u64,
+    /// Timestamp of the closing fill, or `None` if still open.
+    pub closed_at_ms: Option<u64>,
+    /// Number of fills, including a flipping fill on both sides.
+    pub fill_count: usize,
+    /// Largest absolute position size reached.
+    pub max_size: Decimal,
+    /// Notional volume traded (price × size).
+    pub volume: Decimal,
+    /// Fees paid.
+    pub fees: Decimal,
+    /// Realized PnL.
+    pub realized_pnl: Decimal,
+}
+
+impl RoundTrip {
+    fn open(fill: &UserFill, side: Side, size: Decimal) -> Self {
+        Self {
+            asset: fill.asset.clone(),
+            side,
+            opened_at_ms: fill.timestamp_ms,
+            closed_at_ms: None,
+            fill_count: 1,
+            max_size: size,
+            volume: fill.price * size,
+            fees: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+        }
+    }
+
+    /// Whether the position is still open.
+    pub fn is_open(&self) -> bool {
+        self.closed_at_ms.is_none()
+    }
+
+    /// Realized PnL minus fees.
+    pub fn net_pnl(&self) -> Decimal {
+        self.realized_pnl - self.fees
+    }
+}
+
+/// Split `fills` into round-trips, ordered by opening time.
+///
+/// Round-trips still open after the last fill are included with
+/// `closed_at_ms: None`.
+pub fn round_trips(fills: &[UserFill]) -> Vec<RoundTrip> {
+    let mut fills: Vec<&UserFill> = fills.iter().collect();
+    fills.sort_by_key(|f| (f.timestamp_ms, f.trade_id));
+
+    let mut tracker = RoundTripTracker::new();
+    for fill in fills {
+        tracker.add_fill(fill);
+    }
+    tracker.finish()
+}
+
+/// Builds round-trips from fills fed one batch at a time.
+///
+/// Fills must be added in `(timestamp_ms, trade_id)` order. Round-trips are
+/// handed out by [`take_settled`](Self::take_settled) once nothing added
+/// later could open before them, so [`round_trips`] order is kept across
+/// batches while only the round-trips opened since the oldest open position
+/// are held.
+#[derive(Debug, Default)]
+pub struct RoundTripTracker {
+    /// Net position per asset while not flat, and the index of its
+    /// round-trip counted from the first one ever opened.
+    open: HashMap<Asset, (Decimal, usize)>,
+    /// Round-trips not yet taken, in opening order.
+    trips: VecDeque<RoundTrip>,
+    /// Number of round-trips already taken.
+    taken: usize,
+    /// Timestamp of the last fill added.
+    last_fill_ms: u64,
+}
+
+impl RoundTripTracker {
+    /// A tracker with every position flat.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply the next fill.
+    pub fn add_fill(&mut self, fill: &UserFill) {
+        self.last_fill_ms = fill.timestamp_ms;
+        let signed_size = match fill.side {
+            Side::Buy => fill.size,
+            Side::Sell => -fill.size,
+        };
+
+        let Some((position, index)) = self.open.remove(&fill.asset) else {
+            if !signed_size.is_zero() {
+                let mut trip = RoundTrip::open(fill, fill.side, fill.size);
+                trip.fees = fill.fee;
+                trip.realized_pnl = fill.closed_pnl;
+                let index = self.push(trip);
+                self.open.insert(fill.asset.clone(), (signed_size, index));
+            }
+            return;
+        };
+
+        let new_position = position + signed_size;
+        let trip = &mut self.trips[index - self.taken];
+        trip.fill_count += 1;
+        trip.fees += fill.fee;
+        trip.realized_pnl += fill.closed_pnl;
+
+        if new_position.is_zero() || new_position.is_sign_positive() == position.is_sign_positive()
+        {
+            trip.volume += fill.notional_value();
+            trip.max_size = trip.max_size.max(new_position.abs());
+            if new_position.is_zero() {
+                trip.closed_at_ms = Some(fill.timestamp_ms);
+            } else {
+                self.open.insert(fill.asset.clone(), (new_position, index));
+            }
+        } else {
+            // Flipped: the fill closes this position and opens the reverse
+            trip.volume += fill.price * position.abs();
+            trip.closed_at_ms = Some(fill.timestamp_ms);
+            let index = self.push(RoundTrip::open(fill, fill.side, new_position.abs()));
+            self.open.insert(fill.asset.clone(), (new_position, index));
+        }
+    }
+
+    /// Take the closed round-trips that no later fill can reorder, by
+    /// opening time.
+    pub fn take_settled(&mut self) -> Vec<RoundTrip> {
+        // Later fills open round-trips at or after the last fill's timestamp
+        let oldest_open = self
+            .open
+            .values()
+            .map(|(_, index)| self.trips[index - self.taken].opened_at_ms)
+            .min()
+            .unwrap_or(u64::MAX);
+        let settled_before = oldest_open.min(self.last_fill_ms);
+
+        let count = self
+            .trips
+            .iter()
+            .take_while(|trip| trip.opened_at_ms < settled_before)
+            .count();
+        self.taken += count;
+        let mut settled: Vec<RoundTrip> = self.trips.drain(..count).collect();
+        sort_by_opening(&mut settled);
+        settled
+    }
+
+    /// Take the remaining round-trips, by opening time, including those
+    /// still open.
+    pub fn finish(self) -> Vec<RoundTrip> {
+        let mut trips: Vec<RoundTrip> = self.trips.into();
+        sort_by_opening(&mut trips);
+        trips
+    }
+
+    fn push(&mut self, trip: RoundTrip) -> usize {
+        self.trips.push_back(trip);
+        self.taken + self.trips.len() - 1
+    }
+}
+
+fn sort_by_opening(trips: &mut [RoundTrip]) {
+    trips.sort_by(|a, b| {
+        (a.opened_at_ms, a.asset.symbol()).cmp(&(b.opened_at_ms, b.asset.symbol()))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn fill(asset: &str, ts: u64, side: Side, size: Decimal, closed_pnl: Decimal) -> UserFill {
+        UserFill {
+            asset: Asset::from_symbol(asset),
+            timestamp_ms: ts,
+            price: dec!(100),
+            size,
+            side,
+            fee: dec!(0.1),
+            closed_pnl,
+            trade_id: ts,
+            order_id: ts,
+            crossed: true,
+            direction: String::new(),
+            liquidated_user: None,
+        }
+    }
+
+    #[test]
+    fn test_open_add_reduce_close() {
+        let trips = round_trips(&[
+            fill("ETH", 1, Side::Buy, dec!(1), dec!(0)),
+            fill("ETH", 2, Side::Buy, dec!(2), dec!(0)),
+            fill("ETH", 3, Side::Sell, dec!(1.5), dec!(15)),
+            fill("ETH", 4, Side::Sell, dec!(1.5), dec!(-5)),
+        ]);
+
+        assert_eq!(trips.len(), 1);
+        let trip = &trips[0];
+        assert_eq!(trip.side, Side::Buy);
+        assert_eq!((trip.opened_at_ms, trip.closed_at_ms), (1, Some(4)));
+        assert_eq!(trip.fill_count, 4);
+        assert_eq!(trip.max_size, dec!(3));
+        assert_eq!(trip.volume, dec!(600));
+        assert_eq!(trip.realized_pnl, dec!(10));
+        assert_eq!(trip.net_pnl(), dec!(9.6));
+    }
+
+    #[test]
+    fn test_flip_splits_the_fill() {
+        let trips = round_trips(&[
+            fill("BTC", 1, Side::Buy, dec!(1), dec!(0)),
+            fill("BTC", 2, Side::Sell, dec!(3), dec!(20)),
+            fill("BTC", 3, Side::Buy, dec!(0.5), dec!(4)),
+        ]);
+
+        assert_eq!(trips.len(), 2);
+        let (long, short) = (&trips[0], &trips[1]);
+        assert_eq!(long.closed_at_ms, Some(2));
+        assert_eq!(long.volume, dec!(200));
+        assert_eq!(long.fees, dec!(0.2));
+        assert_eq!(long.realized_pnl, dec!(20));
+
+        assert_eq!(short.side, Side::Sell);
+        assert!(short.is_open());
+        assert_eq!(short.opened_at_ms, 2);
+        assert_eq!(short.fill_count, 2);
+        assert_eq!(short.max_size, dec!(2));
+        assert_eq!(short.volume, dec!(250));
+        assert_eq!(short.fees, dec!(0.1));
+        assert_eq!(short.realized_pnl, dec!(4));
+    }
+
+    #[test]
+    fn test_assets_are_independent_and_ordered_by_open() {
+        let trips = round_trips(&[
+            fill("ETH", 5, Side::Buy, dec!(1), dec!(0)),
+            fill("BTC", 2, Side::Sell, dec!(1), dec!(0)),
+            fill("ETH", 4, Side::Sell, dec!(1), dec!(0)),
+            fill("BTC", 3, Side::Buy, dec!(1), dec!(1)),
+            fill("ETH", 6, Side::Buy, dec!(1), dec!(0)),
+        ]);
+
+        let summary: Vec<_> = trips
+            .iter()
+            .map(|t| (t.asset.symbol(), t.opened_at_ms, t.closed_at_ms))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("BTC", 2, Some(3)), ("ETH", 4, Some(5)), ("ETH", 6, None)]
+        );
+    }
+
+    #[test]
+    fn test_tracker_matches_round_trips_across_batches() {
+        let fills = [
+            fill("BTC", 1, Side::Buy, dec!(1), dec!(0)),
+            fill("ETH", 2, Side::Sell, dec!(1), dec!(0)),
+            fill("BTC", 3, Side::Sell, dec!(1), dec!(2)),
+            fill("SOL", 3, Side::Buy, dec!(2), dec!(0)),
+            fill("SOL", 4, Side::Sell, dec!(3), dec!(1)),
+            fill("ETH", 5, Side::Buy, dec!(1), dec!(-1)),
+            fill("BTC", 6, Side::Buy, dec!(1), dec!(0)),
+        ];
+
+        let mut tracker = RoundTripTracker::new();
+        let mut trips = Vec::new();
+        let mut settled_counts = Vec::new();
+        for batch in fills.chunks(2) {
+            for fill in batch {
+                tracker.add_fill(fill);
+            }
+            let settled = tracker.take_settled();
+            settled_counts.push(settled.len());
+            trips.extend(settled);
+        }
+        trips.extend(tracker.finish());
+
+        // The short SOL position opened at 4 holds back the BTC one at 6
+        assert_eq!(settled_counts, vec![0, 1, 2, 0]);
+        assert_eq!(trips, round_trips(&fills));
+    }
+}
@@ -0,0 +1,756 @@
+//! Versioned leaderboard snapshots.
+//!
+//! Computing a leaderboard means fetching every participant's fills (and
+//! optionally builder data), which is far too slow to do per HTTP request.
+//! This module stores the most recent ranked result for each
+//! [`LeaderboardConfig`] so callers can serve it instantly while a background
+//! task keeps it fresh.
+//!
+//! Alongside the latest snapshot, a thinned-out history is retained (one
+//! snapshot per history interval) so callers can show rank movement over time
+//! and answer "what did the leaderboard look like at time T?".
+//!
+//! History outlives the live snapshot: when an idle config is evicted its
+//! history is archived until it ages out, and with
+//! [`SnapshotStore::with_history_dir`] retained snapshots are also written to
+//! disk and restored by [`SnapshotStore::load_history`] after a restart.
+
+use crate::history_dir::HistoryDir;
+use crate::leaderboard::{LeaderboardConfig, LeaderboardEntry};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// A ranked leaderboard computed at a point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardSnapshot {
+    /// Monotonically increasing version, unique across the store.
+    pub version: u64,
+
+    /// When the snapshot was computed (milliseconds since epoch).
+    pub computed_at_ms: u64,
+
+    /// Ranked entries.
+    pub entries: Vec<LeaderboardEntry>,
+
+    /// Total number of users included in the calculation.
+    pub total_users: usize,
+}
+
+impl LeaderboardSnapshot {
+    /// Get a user's rank in this snapshot, if they are on the leaderboard.
+    pub fn rank_of(&self, user: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .find(|e| e.user.eq_ignore_ascii_case(user))
+            .map(|e| e.rank)
+    }
+}
+
+/// Default minimum spacing between retained history snapshots (15 minutes).
+pub const DEFAULT_HISTORY_INTERVAL_MS: u64 = 15 * 60 * 1000;
+
+/// Default age after which history snapshots are dropped (30 days).
+pub const DEFAULT_HISTORY_RETENTION_MS: u64 = 30 * 24 * 60 * 60 * 1000;
+
+/// Default maximum number of configs tracked at once.
+pub const DEFAULT_MAX_TRACKED: usize = 64;
+
+/// A stored snapshot plus bookkeeping for refresh and eviction.
+struct StoredSnapshot {
+    snapshot: Arc<LeaderboardSnapshot>,
+    /// Retained past snapshots, oldest first. May include `snapshot` itself.
+    history: VecDeque<Arc<LeaderboardSnapshot>>,
+    /// Last time a reader asked for this config (milliseconds since epoch).
+    last_accessed_ms: AtomicU64,
+    /// Whether the latest refresh attempt failed, so `snapshot` is stale.
+    refresh_failed: AtomicBool,
+}
+
+impl StoredSnapshot {
+    /// Record a new snapshot in history if enough time has passed since the
+    /// last retained one, then drop history older than the retention window.
+    ///
+    /// Returns whether the snapshot was retained.
+    fn record_history(
+        &mut self,
+        snapshot: Arc<LeaderboardSnapshot>,
+        interval_ms: u64,
+        retention_ms: u64,
+    ) -> bool {
+        let due = self.history.back().is_none_or(|last| {
+            snapshot.computed_at_ms >= last.computed_at_ms.saturating_add(interval_ms)
+        });
+        if due {
+            self.history.push_back(snapshot.clone());
+        }
+
+        prune_history(
+            &mut self.history,
+            snapshot.computed_at_ms.saturating_sub(retention_ms),
+        );
+        due
+    }
+
+    /// All known snapshots for this config, oldest first, ending with the latest.
+    fn timeline(&self) -> impl Iterator<Item = &Arc<LeaderboardSnapshot>> {
+        let latest_in_history = self
+            .history
+            .back()
+            .is_some_and(|last| last.version == self.snapshot.version);
+        let latest = (!latest_in_history).then_some(&self.snapshot);
+        self.history.iter().chain(latest)
+    }
+
+    fn touch(&self) {
+        self.last_accessed_ms.store(now_ms(), Ordering::Relaxed);
+    }
+}
+
+/// Thread-safe store of the latest snapshot per leaderboard configuration.
+///
+/// Every config that has been published is "tracked": [`SnapshotStore::tracked_configs`]
+/// returns it so a refresher can recompute it, until it goes unread for longer
+/// than the idle timeout passed to [`SnapshotStore::evict_idle`]. Pinned
+/// configs are never evicted. An evicted config's history is archived and
+/// still served by [`SnapshotStore::as_of`] and [`SnapshotStore::history`]
+/// until it is older than the retention window; publishing the config again
+/// picks it back up.
+///
+/// Every tracked config is recomputed on each refresh, so the number of them
+/// is capped; see [`SnapshotStore::has_room`].
+pub struct SnapshotStore {
+    snapshots: RwLock<HashMap<LeaderboardConfig, StoredSnapshot>>,
+    /// History of configs that are not tracked, oldest first.
+    archived: RwLock<HashMap<LeaderboardConfig, VecDeque<Arc<LeaderboardSnapshot>>>>,
+    pinned: RwLock<HashSet<LeaderboardConfig>>,
+    next_version: AtomicU64,
+    history_interval_ms: u64,
+    history_retention_ms: u64,
+    history_dir: Option<HistoryDir>,
+    max_tracked: usize,
+}
+
+impl Default for SnapshotStore {
+    fn default() -> Self {
+        Self {
+            snapshots: RwLock::new(HashMap::new()),
+            archived: RwLock::new(HashMap::new()),
+            pinned: RwLock::new(HashSet::new()),
+            next_version: AtomicU64::new(0),
+            history_interval_ms: DEFAULT_HISTORY_INTERVAL_MS,
+            history_retention_ms: DEFAULT_HISTORY_RETENTION_MS,
+            history_dir: None,
+            max_tracked: DEFAULT_MAX_TRACKED,
+        }
+    }
+}
+
+impl SnapshotStore {
+    /// Create an empty store with default history settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how history is thinned out and how long it is kept.
+    ///
+    /// # Arguments
+    /// * `interval_ms` - Minimum spacing between retained history snapshots
+    /// * `retention_ms` - History older than this (relative to the newest snapshot) is dropped
+    pub fn with_history(mut self, interval_ms: u64, retention_ms: u64) -> Self {
+        self.history_interval_ms = interval_ms;
+        self.history_retention_ms = retention_ms;
+        self
+    }
+
+    /// Persist retained history snapshots under `dir`, one subdirectory per
+    /// config. Call [`SnapshotStore::load_history`] at startup to restore them.
+    ///
+    /// Only retained snapshots are written, so after a restart history has
+    /// the spacing set by [`SnapshotStore::with_history`]. Without a
+    /// directory, history is lost when the process exits.
+    pub fn with_history_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.history_dir = Some(HistoryDir::new(dir));
+        self
+    }
+
+    /// Restore history persisted by a previous run from the directory set
+    /// with [`SnapshotStore::with_history_dir`].
+    ///
+    /// Restored history is archived until its config is published again.
+    /// Snapshots older than the retention window are skipped, and versions
+    /// assigned afterwards continue after the newest restored one.
+    ///
+    /// Returns the number of restored snapshots.
+    pub async fn load_history(&self) -> usize {
+        let Some(dir) = &self.history_dir else {
+            return 0;
+        };
+        let cutoff = now_ms().saturating_sub(self.history_retention_ms);
+        let loaded = dir.load(cutoff).await;
+
+        let mut restored = 0;
+        let mut archived = self.archived.write().await;
+        for (config, snapshots) in loaded {
+            if let Some(newest) = snapshots.iter().map(|s| s.version).max() {
+                self.next_version.fetch_max(newest, Ordering::Relaxed);
+            }
+            restored += snapshots.len();
+            let history = archived.entry(config).or_default();
+            history.extend(snapshots.into_iter().map(Arc::new));
+            history.make_contiguous().sort_by_key(|s| s.computed_at_ms);
+        }
+        archived.retain(|_, history| !history.is_empty());
+        restored
+    }
+
+    /// Set the maximum number of configs tracked at once, pinned ones included.
+    pub fn with_max_tracked(mut self, max_tracked: usize) -> Self {
+        self.max_tracked = max_tracked;
+        self
+    }
+
+    /// Exempt a config from idle eviction.
+    pub async fn pin(&self, config: LeaderboardConfig) {
+        self.pinned.write().await.insert(config);
+    }
+
+    /// Get the latest snapshot for a config, marking it as recently used.
+    pub async fn get(&self, config: &LeaderboardConfig) -> Option<Arc<LeaderboardSnapshot>> {
+        let snapshots = self.snapshots.read().await;
+        let stored = snapshots.get(config)?;
+        stored.touch();
+        Some(stored.snapshot.clone())
+    }
+
+    /// Get the latest snapshot for a config without marking it as used.
+    pub async fn peek(&self, config: &LeaderboardConfig) -> Option<Arc<LeaderboardSnapshot>> {
+        let snapshots = self.snapshots.read().await;
+        Some(snapshots.get(config)?.snapshot.clone())
+    }
+
+    /// Get the most recent snapshot computed at or before `at_ms`.
+    ///
+    /// Returns `None` if the config has no history, live or archived, or no
+    /// retained snapshot is that old.
+    pub async fn as_of(
+        &self,
+        config: &LeaderboardConfig,
+        at_ms: u64,
+    ) -> Option<Arc<LeaderboardSnapshot>> {
+        self.timeline(config)
+            .await?
+            .into_iter()
+            .take_while(|s| s.computed_at_ms <= at_ms)
+            .last()
+    }
+
+    /// Get retained snapshots for a config within an optional time range,
+    /// oldest first. The latest snapshot is always included if in range.
+    ///
+    /// Returns `None` if the config has no history, live or archived.
+    pub async fn history(
+        &self,
+        config: &LeaderboardConfig,
+        since_ms: Option<u64>,
+        until_ms: Option<u64>,
+    ) -> Option<Vec<Arc<LeaderboardSnapshot>>> {
+        let timeline = self.timeline(config).await?;
+        Some(
+            timeline
+                .into_iter()
+                .filter(|s| since_ms.is_none_or(|since| s.computed_at_ms >= since))
+                .filter(|s| until_ms.is_none_or(|until| s.computed_at_ms <= until))
+                .collect(),
+        )
+    }
+
+    /// All known snapshots for a config, oldest first: the live timeline if
+    /// it is tracked, otherwise its archived history.
+    async fn timeline(&self, config: &LeaderboardConfig) -> Option<Vec<Arc<LeaderboardSnapshot>>> {
+        if let Some(stored) = self.snapshots.read().await.get(config) {
+            stored.touch();
+            return Some(stored.timeline().cloned().collect());
+        }
+        let archived = self.archived.read().await;
+        Some(archived.get(config)?.iter().cloned().collect())
+    }
+
+    /// Publish freshly computed entries for a config.
+    ///
+    /// Assigns the next version number and replaces any previous snapshot.
+    pub async fn publish(
+        &self,
+        config: LeaderboardConfig,
+        entries: Vec<LeaderboardEntry>,
+        total_users: usize,
+    ) -> Arc<LeaderboardSnapshot> {
+        let now = now_ms();
+        let snapshot = Arc::new(LeaderboardSnapshot {
+            version: self.next_version.fetch_add(1, Ordering::Relaxed) + 1,
+            computed_at_ms: now,
+            entries,
+            total_users,
+        });
+
+        let retained = {
+            let mut snapshots = self.snapshots.write().await;
+            if !snapshots.contains_key(&config) {
+                let history = self
+                    .archived
+                    .write()
+                    .await
+                    .remove(&config)
+                    .unwrap_or_default();
+                snapshots.insert(
+                    config.clone(),
+                    StoredSnapshot {
+                        snapshot: snapshot.clone(),
+                        history,
+                        last_accessed_ms: AtomicU64::new(now),
+                        refresh_failed: AtomicBool::new(false),
+                    },
+                );
+            }
+            let stored = snapshots
+                .get_mut(&config)
+                .expect("config was inserted above");
+            stored.snapshot = snapshot.clone();
+            stored.refresh_failed.store(false, Ordering::Relaxed);
+            stored.record_history(
+                snapshot.clone(),
+                self.history_interval_ms,
+                self.history_retention_ms,
+            )
+        };
+
+        if let (true, Some(dir)) = (retained, &self.history_dir) {
+            dir.append(&config, &snapshot).await;
+            dir.prune(&config, now.saturating_sub(self.history_retention_ms))
+                .await;
+        }
+
+        snapshot
+    }
+
+    /// Record that recomputing a config's leaderboard failed. Its latest
+    /// snapshot keeps being served, flagged by [`SnapshotStore::is_stale`],
+    /// until the next successful publish.
+    pub async fn mark_refresh_failed(&self, config: &LeaderboardConfig) {
+        if let Some(stored) = self.snapshots.read().await.get(config) {
+            stored.refresh_failed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Whether the latest refresh of a config failed since its snapshot was
+    /// published.
+    pub async fn is_stale(&self, config: &LeaderboardConfig) -> bool {
+        self.snapshots
+            .read()
+            .await
+            .get(config)
+            .is_some_and(|stored| stored.refresh_failed.load(Ordering::Relaxed))
+    }
+
+    /// Get every config that currently has a snapshot.
+    pub async fn tracked_configs(&self) -> Vec<LeaderboardConfig> {
+        self.snapshots.read().await.keys().cloned().collect()
+    }
+
+    /// Stop tracking configs that have not been read for longer than
+    /// `max_idle_ms`. Pinned configs are kept regardless.
+    ///
+    /// An evicted config's history is archived rather than dropped, and
+    /// archived history older than the retention window is discarded.
+    ///
+    /// Returns the number of evicted configs.
+    pub async fn evict_idle(&self, max_idle_ms: u64) -> usize {
+        let now = now_ms();
+        let cutoff = now.saturating_sub(max_idle_ms);
+        let pinned = self.pinned.read().await;
+        let mut snapshots = self.snapshots.write().await;
+        let idle: Vec<LeaderboardConfig> = snapshots
+            .iter()
+            .filter(|(config, s)| {
+                !pinned.contains(*config) && s.last_accessed_ms.load(Ordering::Relaxed) < cutoff
+            })
+            .map(|(config, _)| config.clone())
+            .collect();
+
+        let mut archived = self.archived.write().await;
+        for config in &idle {
+            if let Some(stored) = snapshots.remove(config) {
+                let history = stored.timeline().cloned().collect();
+                archived.insert(config.clone(), history);
+            }
+        }
+        let retention_cutoff = now.saturating_sub(self.history_retention_ms);
+        archived.retain(|_, history| {
+            prune_history(history, retention_cutoff);
+            !history.is_empty()
+        });
+
+        idle.len()
+    }
+
+    /// Whether `pending` more configs, not tracked yet, can be tracked
+    /// without exceeding the maximum set by [`SnapshotStore::with_max_tracked`].
+    ///
+    /// Callers should check this before computing a leaderboard for a new
+    /// config, since [`SnapshotStore::publish`] always stores the result.
+    pub async fn has_room(&self, pending: usize) -> bool {
+        self.len().await + pending <= self.max_tracked
+    }
+
+    /// Number of tracked configs.
+    pub async fn len(&self) -> usize {
+        self.snapshots.read().await.len()
+    }
+
+    /// Check if the store is empty.
+    pub async fn is_empty(&self) -> bool {
+        self.snapshots.read().await.is_empty()
+    }
+}
+
+/// Drop snapshots computed before `cutoff_ms` from the front of a history.
+fn prune_history(history: &mut VecDeque<Arc<LeaderboardSnapshot>>, cutoff_ms: u64) {
+    while history
+        .front()
+        .is_some_and(|oldest| oldest.computed_at_ms < cutoff_ms)
+    {
+        history.pop_front();
+    }
+}
+
+/// Current time in milliseconds since epoch.
+fn now_ms() -> u64 {
+    chrono::Utc::now().timestamp_millis().max(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leaderboard::LeaderboardMetric;
+    use rust_decimal::Decimal;
+
+    fn make_config(metric: LeaderboardMetric) -> LeaderboardConfig {
+        LeaderboardConfig {
+            target_builders: Vec::new(),
+            builder_only: false,
+            max_start_capital: None,
+            coin: None,
+            from_ms: Some(1000),
+            to_ms: None,
+            metric,
+            exclude_flagged_volume: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_missing() {
+        let store = SnapshotStore::new();
+        assert!(store
+            .get(&make_config(LeaderboardMetric::Volume))
+            .await
+            .is_none());
+        assert!(store.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn test_publish_assigns_versions() {
+        let store = SnapshotStore::new();
+        let volume = make_config(LeaderboardMetric::Volume);
+        let pnl = make_config(LeaderboardMetric::Pnl);
+
+        let first = store.publish(volume.clone(), Vec::new(), 3).await;
+        let second = store.publish(pnl.clone(), Vec::new(), 3).await;
+        let third = store.publish(volume.clone(), Vec::new(), 4).await;
+
+        assert_eq!(first.version, 1);
+        assert_eq!(second.version, 2);
+        assert_eq!(third.version, 3);
+
+        // Latest publish replaces the previous snapshot for the same config
+        let latest = store.get(&volume).await.unwrap();
+        assert_eq!(latest.version, 3);
+        assert_eq!(latest.total_users, 4);
+        assert_eq!(store.len().await, 2);
+    }
+
+    fn make_entry(user: &str, rank: usize) -> LeaderboardEntry {
+        LeaderboardEntry {
+            rank,
+            user: user.to_string(),
+            metric_value: Decimal::ZERO,
+            volume: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+            return_pct: None,
+            trade_count: 0,
+            builder_fill_count: 0,
+            tainted: false,
+            starting_equity: None,
+            net_deposits: None,
+            flagged_volume: Decimal::ZERO,
+            flagged_fill_count: 0,
+            builder_breakdown: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_rank_of() {
+        let snapshot = LeaderboardSnapshot {
+            version: 1,
+            computed_at_ms: 0,
+            entries: vec![make_entry("0xaaa", 1), make_entry("0xbbb", 2)],
+            total_users: 2,
+        };
+        assert_eq!(snapshot.rank_of("0xbbb"), Some(2));
+        assert_eq!(snapshot.rank_of("0xBBB"), Some(2));
+        assert_eq!(snapshot.rank_of("0xccc"), None);
+    }
+
+    #[tokio::test]
+    async fn test_history_and_as_of() {
+        // Retain every publish so the timeline is easy to reason about
+        let store = SnapshotStore::new().with_history(0, DEFAULT_HISTORY_RETENTION_MS);
+        let config = make_config(LeaderboardMetric::Volume);
+
+        let first = store
+            .publish(config.clone(), vec![make_entry("0xaaa", 1)], 1)
+            .await;
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let second = store
+            .publish(config.clone(), vec![make_entry("0xaaa", 2)], 1)
+            .await;
+
+        let history = store.history(&config, None, None).await.unwrap();
+        let versions: Vec<u64> = history.iter().map(|s| s.version).collect();
+        assert_eq!(versions, vec![first.version, second.version]);
+
+        let since_second = store
+            .history(&config, Some(second.computed_at_ms), None)
+            .await
+            .unwrap();
+        assert_eq!(since_second.len(), 1);
+
+        // As-of lookups pick the newest snapshot not after the timestamp
+        let at_first = store.as_of(&config, first.computed_at_ms).await.unwrap();
+        assert_eq!(at_first.version, first.version);
+        let at_now = store.as_of(&config, u64::MAX).await.unwrap();
+        assert_eq!(at_now.version, second.version);
+        assert!(store
+            .as_of(&config, first.computed_at_ms - 1)
+            .await
+            .is_none());
+
+        let untracked = make_config(LeaderboardMetric::Pnl);
+        assert!(store.history(&untracked, None, None).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_history_interval_thins_snapshots() {
+        let store = SnapshotStore::new().with_history(60 * 60 * 1000, DEFAULT_HISTORY_RETENTION_MS);
+        let config = make_config(LeaderboardMetric::Volume);
+
+        let first = store.publish(config.clone(), Vec::new(), 0).await;
+        let second = store.publish(config.clone(), Vec::new(), 0).await;
+
+        // Only the first publish is retained, but the latest is still visible
+        let history = store.history(&config, None, None).await.unwrap();
+        let versions: Vec<u64> = history.iter().map(|s| s.version).collect();
+        assert_eq!(versions, vec![first.version, second.version]);
+
+        let third = store.publish(config.clone(), Vec::new(), 0).await;
+        let history = store.history(&config, None, None).await.unwrap();
+        let versions: Vec<u64> = history.iter().map(|s| s.version).collect();
+        assert_eq!(versions, vec![first.version, third.version]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_marks_stale() {
+        let store = SnapshotStore::new();
+        let config = make_config(LeaderboardMetric::Volume);
+
+        // Nothing to mark before the first publish
+        store.mark_refresh_failed(&config).await;
+        assert!(!store.is_stale(&config).await);
+
+        let first = store.publish(config.clone(), Vec::new(), 0).await;
+        store.mark_refresh_failed(&config).await;
+        assert!(store.is_stale(&config).await);
+        assert_eq!(store.get(&config).await.unwrap().version, first.version);
+
+        store.publish(config.clone(), Vec::new(), 0).await;
+        assert!(!store.is_stale(&config).await);
+    }
+
+    #[tokio::test]
+    async fn test_pinned_configs_survive_eviction() {
+        let store = SnapshotStore::new();
+        let pinned = make_config(LeaderboardMetric::Volume);
+        let unpinned = make_config(LeaderboardMetric::Pnl);
+        store.pin(pinned.clone()).await;
+        store.publish(pinned.clone(), Vec::new(), 0).await;
+        store.publish(unpinned, Vec::new(), 0).await;
+
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        assert_eq!(store.evict_idle(0).await, 1);
+        assert_eq!(store.tracked_configs().await, vec![pinned]);
+    }
+
+    #[tokio::test]
+    async fn test_evict_idle() {
+        let store = SnapshotStore::new();
+        let config = make_config(LeaderboardMetric::Volume);
+        store.publish(config.clone(), Vec::new(), 0).await;
+
+        // Nothing is older than an hour
+        assert_eq!(store.evict_idle(60 * 60 * 1000).await, 0);
+        assert_eq!(store.tracked_configs().await, vec![config]);
+
+        // Zero idle time with a clock that has moved on evicts everything
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        assert_eq!(store.evict_idle(0).await, 1);
+        assert!(store.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn test_evicted_history_is_archived() {
+        let store = SnapshotStore::new().with_history(0, DEFAULT_HISTORY_RETENTION_MS);
+        let config = make_config(LeaderboardMetric::Volume);
+        let first = store
+            .publish(config.clone(), vec![make_entry("0xaaa", 1)], 1)
+            .await;
+
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        assert_eq!(store.evict_idle(0).await, 1);
+        assert!(store.get(&config).await.is_none());
+
+        // History outlives the live snapshot
+        let history = store.history(&config, None, None).await.unwrap();
+        assert_eq!(history.len(), 1);
+        let at_first = store.as_of(&config, first.computed_at_ms).await.unwrap();
+        assert_eq!(at_first.version, first.version);
+        assert!(store.is_empty().await);
+
+        // Publishing again continues the same history
+        let second = store.publish(config.clone(), Vec::new(), 0).await;
+        let history = store.history(&config, None, None).await.unwrap();
+        let versions: Vec<u64> = history.iter().map(|s| s.version).collect();
+        assert_eq!(versions, vec![first.version, second.version]);
+    }
+
+    #[tokio::test]
+    async fn test_history_dir_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = make_config(LeaderboardMetric::Volume);
+        let other = make_config(LeaderboardMetric::Pnl);
+
+        let store = SnapshotStore::new()
+            .with_history(0, DEFAULT_HISTORY_RETENTION_MS)
+            .with_history_dir(dir.path());
+        let first = store
+            .publish(config.clone(), vec![make_entry("0xaaa", 1)], 1)
+            .await;
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let second = store
+            .publish(config.clone(), vec![make_entry("0xaaa", 2)], 1)
+            .await;
+        store.publish(other.clone(), Vec::new(), 0).await;
+        drop(store);
+
+        let restarted = SnapshotStore::new()
+            .with_history(0, DEFAULT_HISTORY_RETENTION_MS)
+            .with_history_dir(dir.path());
+        assert_eq!(restarted.load_history().await, 3);
+        assert!(restarted.is_empty().await);
+
+        let history = restarted.history(&config, None, None).await.unwrap();
+        let versions: Vec<u64> = history.iter().map(|s| s.version).collect();
+        assert_eq!(versions, vec![first.version, second.version]);
+        assert_eq!(history[1].entries, vec![make_entry("0xaaa", 2)]);
+        let at_first = restarted
+            .as_of(&config, first.computed_at_ms)
+            .await
+            .unwrap();
+        assert_eq!(at_first.version, first.version);
+
+        // New versions continue after the restored ones
+        let third = restarted.publish(config.clone(), Vec::new(), 0).await;
+        assert_eq!(third.version, 4);
+    }
+
+    #[tokio::test]
+    async fn test_load_history_skips_expired_and_corrupt() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = make_config(LeaderboardMetric::Volume);
+        let store = SnapshotStore::new()
+            .with_history(0, DEFAULT_HISTORY_RETENTION_MS)
+            .with_history_dir(dir.path());
+        store.publish(config.clone(), Vec::new(), 0).await;
+
+        // A snapshot from long ago and a truncated line
+        let config_dir = std::fs::read_dir(dir.path())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let expired = LeaderboardSnapshot {
+            version: 100,
+            computed_at_ms: 1_000,
+            entries: Vec::new(),
+            total_users: 0,
+        };
+        std::fs::write(
+            config_dir.join("1970-01-01.jsonl"),
+            format!("{}\n", serde_json::to_string(&expired).unwrap()),
+        )
+        .unwrap();
+        let today = std::fs::read_dir(&config_dir)
+            .unwrap()
+            .map(|f| f.unwrap().path())
+            .find(|p| {
+                p.extension().is_some_and(|e| e == "jsonl") && !p.ends_with("1970-01-01.jsonl")
+            })
+            .unwrap();
+        let mut contents = std::fs::read_to_string(&today).unwrap();
+        contents.push_str("{\"version\":\n");
+        std::fs::write(&today, contents).unwrap();
+
+        let restarted = SnapshotStore::new().with_history_dir(dir.path());
+        assert_eq!(restarted.load_history().await, 1);
+        let history = restarted.history(&config, None, None).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].version, 1);
+    }
+
+    #[tokio::test]
+    async fn test_has_room() {
+        let store = SnapshotStore::new().with_max_tracked(2);
+        assert!(store.has_room(2).await);
+        assert!(!store.has_room(3).await);
+
+        store
+            .publish(make_config(LeaderboardMetric::Volume), Vec::new(), 0)
+            .await;
+        assert!(store.has_room(1).await);
+        assert!(!store.has_room(2).await);
+    }
+
+    #[tokio::test]
+    async fn test_peek_does_not_keep_alive() {
+        let store = SnapshotStore::new();
+        let config = make_config(LeaderboardMetric::Volume);
+        let published = store.publish(config.clone(), Vec::new(), 0).await;
+
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let peeked = store.peek(&config).await.unwrap();
+        assert_eq!(peeked.version, published.version);
+        assert_eq!(store.evict_idle(0).await, 1);
+    }
+}
@@ -190,6 +190,7 @@ mod tests {
             order_id: trade_id,
             crossed: true,
             direction: "Test".to_string(),
+            liquidated_user: None,
         }
     }
 
@@ -0,0 +1,474 @@
+//! Wash trading and self-dealing detection.
+//!
+//! Volume competitions invite farming: two participants (or one person with
+//! several addresses) trade back and forth to inflate volume at little cost.
+//! This module flags fills that look like that so the volume can be reported
+//! and, optionally, excluded from rankings.
+//!
+//! A fill is flagged if:
+//! - its counterparty is the user themselves (self-trade),
+//! - its counterparty is a known linked address of the user,
+//! - its counterparty is another competition participant, or
+//! - it is offset by an opposite fill of the same size and near-identical
+//!   price within a short window. If both legs were against the same
+//!   counterparty the pattern is reported as circular.
+//!
+//! Counterparty information comes from builder data, so the first three
+//! checks only apply to fills that can be matched there. Offsetting volume is
+//! detected from the user's own fills alone.
+
+use hl_types::{Asset, Side, UserFill};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+
+/// Default window for offsetting fills (5 minutes).
+pub const DEFAULT_OFFSET_WINDOW_MS: u64 = 5 * 60 * 1000;
+
+/// Default maximum price difference between offsetting fills (10 bps).
+pub const DEFAULT_MAX_PRICE_DRIFT_BPS: u32 = 10;
+
+/// Why a fill was flagged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WashTradeReason {
+    /// The user traded with themselves.
+    SelfTrade,
+    /// The counterparty is a known linked address.
+    LinkedCounterparty,
+    /// The counterparty is another competition participant.
+    ParticipantCounterparty,
+    /// Offset by an opposite fill against the same counterparty.
+    CircularVolume,
+    /// Offset by an opposite fill of the same size shortly before.
+    OffsettingVolume,
+}
+
+impl WashTradeReason {
+    /// Get string representation.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::SelfTrade => "selfTrade",
+            Self::LinkedCounterparty => "linkedCounterparty",
+            Self::ParticipantCounterparty => "participantCounterparty",
+            Self::CircularVolume => "circularVolume",
+            Self::OffsettingVolume => "offsettingVolume",
+        }
+    }
+}
+
+/// A single flagged fill.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlaggedFill {
+    /// Trade ID of the fill.
+    pub trade_id: u64,
+
+    /// Fill timestamp (milliseconds).
+    pub timestamp_ms: u64,
+
+    /// Asset traded.
+    pub asset: Asset,
+
+    /// Notional value (price * size).
+    pub notional: Decimal,
+
+    /// Why it was flagged.
+    pub reason: WashTradeReason,
+
+    /// Counterparty address, if known.
+    pub counterparty: Option<String>,
+}
+
+/// Result of analyzing a user's fills for wash trading.
+#[derive(Debug, Clone, Default)]
+pub struct WashTradeReport {
+    /// Flagged fills in time order.
+    pub flagged: Vec<FlaggedFill>,
+
+    /// Notional volume of flagged fills.
+    pub flagged_volume: Decimal,
+
+    /// Notional volume of all analyzed fills.
+    pub total_volume: Decimal,
+}
+
+impl WashTradeReport {
+    /// Check if any fill was flagged.
+    pub fn is_flagged(&self) -> bool {
+        !self.flagged.is_empty()
+    }
+
+    /// Trade IDs of all flagged fills.
+    pub fn flagged_trade_ids(&self) -> HashSet<u64> {
+        self.flagged.iter().map(|f| f.trade_id).collect()
+    }
+
+    /// Flagged volume broken down by reason.
+    pub fn volume_by_reason(&self) -> HashMap<WashTradeReason, Decimal> {
+        let mut by_reason = HashMap::new();
+        for fill in &self.flagged {
+            *by_reason.entry(fill.reason).or_insert(Decimal::ZERO) += fill.notional;
+        }
+        by_reason
+    }
+}
+
+/// Detects wash trading patterns in a user's fills.
+///
+/// # Example
+///
+/// ```rust
+/// use hl_indexer::wash::WashTradeDetector;
+///
+/// let detector = WashTradeDetector::new()
+///     .with_participants(["0xaaa", "0xbbb"])
+///     .with_linked_group(["0xaaa", "0xccc"]);
+///
+/// let report = detector.analyze("0xaaa", &[], |_| None);
+/// assert!(!report.is_flagged());
+/// ```
+#[derive(Debug, Clone)]
+pub struct WashTradeDetector {
+    /// Competition participants (lowercase).
+    participants: HashSet<String>,
+
+    /// Linked addresses per address (lowercase, symmetric).
+    linked: HashMap<String, HashSet<String>>,
+
+    /// Maximum time between two offsetting fills.
+    offset_window_ms: u64,
+
+    /// Maximum price difference between offsetting fills, in basis points.
+    max_price_drift_bps: u32,
+}
+
+impl Default for WashTradeDetector {
+    fn default() -> Self {
+        Self {
+            participants: HashSet::new(),
+            linked: HashMap::new(),
+            offset_window_ms: DEFAULT_OFFSET_WINDOW_MS,
+            max_price_drift_bps: DEFAULT_MAX_PRICE_DRIFT_BPS,
+        }
+    }
+}
+
+impl WashTradeDetector {
+    /// Create a detector with default windows and no known addresses.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the competition participants.
+    pub fn with_participants<I, S>(mut self, participants: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.participants = participants
+            .into_iter()
+            .map(|p| p.as_ref().to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Register a group of addresses known to belong to the same person.
+    ///
+    /// Every address in the group is linked to every other.
+    pub fn with_linked_group<I, S>(mut self, group: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let group: Vec<String> = group
+            .into_iter()
+            .map(|a| a.as_ref().to_lowercase())
+            .collect();
+        for address in &group {
+            let links = self.linked.entry(address.clone()).or_default();
+            links.extend(group.iter().filter(|a| *a != address).cloned());
+        }
+        self
+    }
+
+    /// Set the maximum time between two offsetting fills.
+    pub fn with_offset_window_ms(mut self, window_ms: u64) -> Self {
+        self.offset_window_ms = window_ms;
+        self
+    }
+
+    /// Set the maximum price difference between offsetting fills.
+    pub fn with_max_price_drift_bps(mut self, bps: u32) -> Self {
+        self.max_price_drift_bps = bps;
+        self
+    }
+
+    /// Check if two addresses are linked.
+    pub fn are_linked(&self, a: &str, b: &str) -> bool {
+        self.linked
+            .get(&a.to_lowercase())
+            .is_some_and(|links| links.contains(&b.to_lowercase()))
+    }
+
+    /// Analyze a user's fills.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user whose fills these are
+    /// * `fills` - Fills to analyze (any order)
+    /// * `counterparty_of` - Returns the counterparty address of a fill, if known
+    pub fn analyze<F>(&self, user: &str, fills: &[UserFill], counterparty_of: F) -> WashTradeReport
+    where
+        F: Fn(&UserFill) -> Option<String>,
+    {
+        let counterparties: Vec<Option<String>> = fills.iter().map(counterparty_of).collect();
+        self.analyze_with_counterparties(user, fills, &counterparties)
+    }
+
+    /// Analyze a user's fills whose counterparties are already known.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user whose fills these are
+    /// * `fills` - Fills to analyze (any order)
+    /// * `counterparties` - Counterparty address of each fill, if known
+    pub fn analyze_with_counterparties(
+        &self,
+        user: &str,
+        fills: &[UserFill],
+        counterparties: &[Option<String>],
+    ) -> WashTradeReport {
+        let user = user.to_lowercase();
+        let mut order: Vec<usize> = (0..fills.len()).collect();
+        order.sort_by_key(|&i| fills[i].timestamp_ms);
+        let sorted: Vec<&UserFill> = order.iter().map(|&i| &fills[i]).collect();
+
+        let counterparties: Vec<Option<String>> = order
+            .iter()
+            .map(|&i| {
+                counterparties
+                    .get(i)
+                    .cloned()
+                    .flatten()
+                    .map(|c| c.to_lowercase())
+            })
+            .collect();
+
+        // Counterparty checks first; they are the strongest evidence
+        let mut reasons: Vec<Option<WashTradeReason>> = counterparties
+            .iter()
+            .map(|cp| {
+                cp.as_deref()
+                    .and_then(|cp| self.counterparty_reason(&user, cp))
+            })
+            .collect();
+
+        for (first, second) in self.offsetting_pairs(&sorted) {
+            let circular = matches!(
+                (&counterparties[first], &counterparties[second]),
+                (Some(a), Some(b)) if a == b
+            );
+            let reason = if circular {
+                WashTradeReason::CircularVolume
+            } else {
+                WashTradeReason::OffsettingVolume
+            };
+            for idx in [first, second] {
+                reasons[idx].get_or_insert(reason);
+            }
+        }
+
+        let mut report = WashTradeReport::default();
+        for (idx, fill) in sorted.iter().enumerate() {
+            let notional = fill.price * fill.size;
+            report.total_volume += notional;
+
+            if let Some(reason) = reasons[idx] {
+                report.flagged_volume += notional;
+                report.flagged.push(FlaggedFill {
+                    trade_id: fill.trade_id,
+                    timestamp_ms: fill.timestamp_ms,
+                    asset: fill.asset.clone(),
+                    notional,
+                    reason,
+                    counterparty: counterparties[idx].clone(),
+                });
+            }
+        }
+
+        report
+    }
+
+    /// Classify a counterparty relationship, if suspicious.
+    fn counterparty_reason(&self, user: &str, counterparty: &str) -> Option<WashTradeReason> {
+        if counterparty == user {
+            Some(WashTradeReason::SelfTrade)
+        } else if self.are_linked(user, counterparty) {
+            Some(WashTradeReason::LinkedCounterparty)
+        } else if self.participants.contains(counterparty) {
+            Some(WashTradeReason::ParticipantCounterparty)
+        } else {
+            None
+        }
+    }
+
+    /// Pair up opposite-side fills of equal size and near-equal price in the
+    /// same asset within the offset window.
+    ///
+    /// `fills` must be sorted by time. Each fill is used in at most one pair;
+    /// the earliest eligible fill is matched first. Returns index pairs.
+    fn offsetting_pairs(&self, fills: &[&UserFill]) -> Vec<(usize, usize)> {
+        let mut open: HashMap<&Asset, Vec<usize>> = HashMap::new();
+        let mut pairs = Vec::new();
+
+        for (idx, fill) in fills.iter().enumerate() {
+            let candidates = open.entry(&fill.asset).or_default();
+
+            // Forget fills that fell out of the window
+            candidates.retain(|&c| {
+                fill.timestamp_ms.saturating_sub(fills[c].timestamp_ms) <= self.offset_window_ms
+            });
+
+            let matched = candidates
+                .iter()
+                .position(|&c| self.is_offsetting(fills[c], fill));
+
+            match matched {
+                Some(pos) => pairs.push((candidates.remove(pos), idx)),
+                None => candidates.push(idx),
+            }
+        }
+
+        pairs
+    }
+
+    /// Check if `later` undoes `earlier`.
+    fn is_offsetting(&self, earlier: &UserFill, later: &UserFill) -> bool {
+        let opposite = matches!(
+            (earlier.side, later.side),
+            (Side::Buy, Side::Sell) | (Side::Sell, Side::Buy)
+        );
+        if !opposite || earlier.size != later.size || earlier.price.is_zero() {
+            return false;
+        }
+
+        let drift_bps =
+            ((later.price - earlier.price).abs() / earlier.price) * Decimal::from(10_000);
+        drift_bps <= Decimal::from(self.max_price_drift_bps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn make_fill(
+        trade_id: u64,
+        timestamp_ms: u64,
+        side: Side,
+        price: Decimal,
+        size: Decimal,
+    ) -> UserFill {
+        UserFill {
+            asset: Asset::from_symbol("BTC"),
+            timestamp_ms,
+            price,
+            size,
+            side,
+            fee: Decimal::ZERO,
+            closed_pnl: Decimal::ZERO,
+            trade_id,
+            order_id: trade_id,
+            crossed: true,
+            direction: String::new(),
+            liquidated_user: None,
+        }
+    }
+
+    #[test]
+    fn test_participant_and_linked_counterparties() {
+        let detector = WashTradeDetector::new()
+            .with_participants(["0xAAA", "0xbbb"])
+            .with_linked_group(["0xaaa", "0xccc"]);
+
+        let fills = vec![
+            make_fill(1, 1_000, Side::Buy, dec!(100), dec!(1)),
+            make_fill(2, 10_000_000, Side::Buy, dec!(100), dec!(2)),
+            make_fill(3, 20_000_000, Side::Buy, dec!(100), dec!(3)),
+            make_fill(4, 30_000_000, Side::Buy, dec!(100), dec!(4)),
+        ];
+        let counterparties: HashMap<u64, &str> =
+            [(1, "0xbbb"), (2, "0xCCC"), (3, "0xddd"), (4, "0xaaa")]
+                .into_iter()
+                .collect();
+
+        let report = detector.analyze("0xaaa", &fills, |f| {
+            counterparties.get(&f.trade_id).map(|c| c.to_string())
+        });
+
+        let reasons: Vec<(u64, WashTradeReason)> = report
+            .flagged
+            .iter()
+            .map(|f| (f.trade_id, f.reason))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (1, WashTradeReason::ParticipantCounterparty),
+                (2, WashTradeReason::LinkedCounterparty),
+                (4, WashTradeReason::SelfTrade),
+            ]
+        );
+        assert_eq!(report.flagged_volume, dec!(700));
+        assert_eq!(report.total_volume, dec!(1000));
+    }
+
+    #[test]
+    fn test_offsetting_volume_within_window() {
+        let detector = WashTradeDetector::new().with_offset_window_ms(60_000);
+
+        let fills = vec![
+            make_fill(1, 0, Side::Buy, dec!(100), dec!(1)),
+            // Offsets fill 1 within the window at nearly the same price
+            make_fill(2, 30_000, Side::Sell, dec!(100.05), dec!(1)),
+            // Same size but outside the window of anything open
+            make_fill(3, 200_000, Side::Buy, dec!(100), dec!(1)),
+            // Different size, not offsetting
+            make_fill(4, 210_000, Side::Sell, dec!(100), dec!(2)),
+        ];
+
+        let report = detector.analyze("0xaaa", &fills, |_| None);
+        assert_eq!(report.flagged_trade_ids(), [1, 2].into_iter().collect());
+        assert!(report
+            .flagged
+            .iter()
+            .all(|f| f.reason == WashTradeReason::OffsettingVolume));
+    }
+
+    #[test]
+    fn test_price_drift_breaks_offset() {
+        let detector = WashTradeDetector::new();
+        let fills = vec![
+            make_fill(1, 0, Side::Buy, dec!(100), dec!(1)),
+            make_fill(2, 1_000, Side::Sell, dec!(101), dec!(1)),
+        ];
+
+        let report = detector.analyze("0xaaa", &fills, |_| None);
+        assert!(!report.is_flagged());
+    }
+
+    #[test]
+    fn test_circular_volume_with_same_counterparty() {
+        let detector = WashTradeDetector::new();
+        let fills = vec![
+            make_fill(1, 0, Side::Buy, dec!(100), dec!(1)),
+            make_fill(2, 1_000, Side::Sell, dec!(100), dec!(1)),
+        ];
+
+        let report = detector.analyze("0xaaa", &fills, |_| Some("0xeee".to_string()));
+        let volume = report.volume_by_reason();
+        assert_eq!(
+            volume.get(&WashTradeReason::CircularVolume),
+            Some(&dec!(200))
+        );
+    }
+}
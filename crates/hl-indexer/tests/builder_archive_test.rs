@@ -0,0 +1,148 @@
+//! Builder archive fill source tests.
+//!
+//! These tests read the hl-builder-data fixture archives through an on-disk
+//! cache or a local mock server and need no network access.
+
+#![cfg(feature = "builder-enrichment")]
+
+use hl_indexer::leaderboard::{
+    calculate_leaderboard, LeaderboardConfig, LeaderboardMetric, NoBuilderChecker,
+};
+use hl_indexer::{
+    ArchiveCache, ArchiveImporter, Endpoints, FillSource, Indexer, IndexerConfig, Recording, Side,
+};
+use hl_mock_server::MockHyperliquid;
+use rust_decimal_macros::dec;
+use std::path::PathBuf;
+
+const BUILDER_ADDRESS: &str = "0x2868fc0d9786a740b491577a43502259efa78a39";
+
+const USER: &str = "0x7b73dfae34492a35715ca037b19e006befdbe4cc";
+
+// Jan 10, 2026 00:00:00 UTC and the end of that day in milliseconds
+const FROM_MS: i64 = 1768003200000;
+const TO_MS: i64 = 1768089599999;
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../hl-builder-data/tests/fixtures/builder_fills")
+}
+
+/// An archive-backed indexer whose cache holds the fixture archives.
+async fn archive_indexer(cache_dir: &std::path::Path) -> Indexer {
+    let archives = ArchiveImporter::new().import(fixtures_dir()).unwrap();
+
+    let config = IndexerConfig::mainnet()
+        .with_fill_source(FillSource::BuilderArchive)
+        .with_builder(BUILDER_ADDRESS)
+        // Nothing listens here, so any download attempt would fail the test
+        .with_endpoints(Endpoints::custom("http://127.0.0.1:9".parse().unwrap()));
+    let cache = ArchiveCache::new(cache_dir).with_endpoint(&config.builder_endpoint());
+    cache.import(&archives).await;
+    Indexer::new(config.with_builder_cache(cache))
+}
+
+#[tokio::test]
+async fn test_user_fills_from_archive() {
+    let dir = tempfile::tempdir().unwrap();
+    let indexer = archive_indexer(dir.path()).await;
+
+    let fills = indexer
+        .get_user_fills(USER, Some(FROM_MS), Some(TO_MS))
+        .await
+        .unwrap();
+
+    assert_eq!(fills.len(), 2);
+    assert!(fills
+        .windows(2)
+        .all(|w| w[0].timestamp_ms <= w[1].timestamp_ms));
+    assert!(fills.iter().all(|f| f.side == Side::Buy));
+    assert_eq!(fills[0].price, dec!(135.88));
+    // Maker exchange fee at the base tier plus the builder fee
+    assert_eq!(fills[0].fee, dec!(0.00468786) + dec!(0.003125));
+    assert_ne!(fills[0].trade_id, fills[1].trade_id);
+}
+
+#[tokio::test]
+async fn test_leaderboard_from_archive() {
+    let dir = tempfile::tempdir().unwrap();
+    let indexer = archive_indexer(dir.path()).await;
+    let users = vec![
+        USER.to_string(),
+        "0x5be08c15441c7fd10ea8dcc9af14ed9a3af11ebd".to_string(),
+        "0x0000000000000000000000000000000000000001".to_string(),
+    ];
+    let config = LeaderboardConfig {
+        target_builders: vec![BUILDER_ADDRESS.to_string()],
+        builder_only: false,
+        max_start_capital: None,
+        coin: None,
+        from_ms: Some(FROM_MS),
+        to_ms: Some(TO_MS),
+        metric: LeaderboardMetric::Volume,
+        exclude_flagged_volume: false,
+    };
+
+    let stats = calculate_leaderboard(&indexer, &users, &config, &NoBuilderChecker)
+        .await
+        .unwrap();
+
+    let trades: Vec<usize> = stats.iter().map(|s| s.trade_count).collect();
+    assert_eq!(trades, vec![2, 1, 0]);
+    assert_eq!(
+        stats[0].volume,
+        dec!(135.88) * dec!(0.23) + dec!(135.84) * dec!(0.08)
+    );
+}
+
+#[tokio::test]
+async fn test_archive_source_requires_builders() {
+    let indexer =
+        Indexer::new(IndexerConfig::mainnet().with_fill_source(FillSource::BuilderArchive));
+
+    assert!(indexer.get_user_fills(USER, None, None).await.is_err());
+}
+
+#[tokio::test]
+async fn test_recorded_archives_replay_offline() {
+    let dir = tempfile::tempdir().unwrap();
+    let csv = std::fs::read(fixtures_dir().join(BUILDER_ADDRESS).join("20260110.csv")).unwrap();
+    let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+    std::io::Write::write_all(&mut encoder, &csv).unwrap();
+    let server = MockHyperliquid::new()
+        .with_file(
+            &format!(
+                "/Mainnet/builder_fills/{}/20260110.csv.lz4",
+                BUILDER_ADDRESS
+            ),
+            encoder.finish().unwrap(),
+        )
+        .start()
+        .await
+        .unwrap();
+    let config = IndexerConfig::mainnet()
+        .with_fill_source(FillSource::BuilderArchive)
+        .with_builder(BUILDER_ADDRESS)
+        .with_endpoints(Endpoints::custom(server.url()));
+
+    let recorder = Indexer::new(
+        config
+            .clone()
+            .with_recording(Recording::Record(dir.path().to_path_buf())),
+    );
+    let recorded = recorder
+        .get_user_fills(USER, Some(FROM_MS), Some(TO_MS))
+        .await
+        .unwrap();
+    // Any download attempt during the replay would now fail the test
+    drop(server);
+
+    let replay = Indexer::new(config.with_recording(Recording::Replay(dir.path().to_path_buf())));
+    let replayed = replay
+        .get_user_fills(USER, Some(FROM_MS), Some(TO_MS))
+        .await
+        .unwrap();
+
+    assert_eq!(recorded.len(), 2);
+    assert_eq!(replayed, recorded);
+}
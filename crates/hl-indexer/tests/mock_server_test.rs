@@ -0,0 +1,321 @@
+//! End-to-end indexer tests against a local mock of the Hyperliquid API.
+//!
+//! Everything is served from `127.0.0.1`, so these need no network access.
+
+use hl_indexer::filter::TradeFilter;
+use hl_indexer::leaderboard::{
+    calculate_leaderboard, LeaderboardConfig, LeaderboardMetric, NoBuilderChecker,
+};
+use hl_indexer::pagination::{PageRequest, SortOrder};
+use hl_indexer::{
+    BreakerConfig, BreakerState, CacheTtls, Endpoints, Indexer, IndexerConfig, IndexerError,
+};
+use hl_mock_server::{sample_fill, MockHyperliquid, MockServer};
+use rust_decimal_macros::dec;
+use std::time::Duration;
+
+const USER: &str = "0x7b73dfae34492a35715ca037b19e006befdbe4cc";
+
+fn indexer(server: &MockServer, config: IndexerConfig) -> Indexer {
+    Indexer::new(config.with_endpoints(Endpoints::custom(server.url())))
+}
+
+#[tokio::test]
+async fn test_user_fills_from_api() {
+    let server = MockHyperliquid::new()
+        .with_fills(USER, (1..=3).map(|i| sample_fill(i * 1_000, i)).collect())
+        .start()
+        .await
+        .unwrap();
+    let indexer = indexer(&server, IndexerConfig::mainnet());
+
+    let fills = indexer.get_user_fills(USER, Some(0), None).await.unwrap();
+
+    assert_eq!(fills.len(), 3);
+    assert!(fills.iter().all(|f| f.price == dec!(50000)));
+    assert_eq!(server.info_request_count("userFillsByTime"), 1);
+}
+
+#[tokio::test]
+async fn test_cached_fills_reach_api_once() {
+    let server = MockHyperliquid::new()
+        .with_fills(USER, (1..=3).map(|i| sample_fill(i * 1_000, i)).collect())
+        .start()
+        .await
+        .unwrap();
+    let indexer = indexer(
+        &server,
+        IndexerConfig::mainnet().with_cache(CacheTtls::default()),
+    );
+
+    let (a, b) = tokio::join!(
+        indexer.get_user_fills(USER, Some(0), None),
+        indexer.get_user_fills(USER, Some(0), None),
+    );
+    let c = indexer.get_user_fills(USER, Some(0), None).await;
+
+    assert_eq!(a.unwrap().len(), 3);
+    assert_eq!(b.unwrap(), c.unwrap());
+    assert_eq!(server.info_request_count("userFillsByTime"), 1);
+    let stats = indexer.cache_stats();
+    assert_eq!((stats.misses, stats.coalesced + stats.hits), (1, 2));
+}
+
+#[tokio::test]
+async fn test_breaker_opens_during_outage() {
+    let server = MockHyperliquid::new()
+        .with_fills(USER, vec![sample_fill(1_000, 1)])
+        .start()
+        .await
+        .unwrap();
+    let config = IndexerConfig::mainnet()
+        .with_cache(CacheTtls::default())
+        .with_breaker(BreakerConfig {
+            failure_threshold: 2,
+            cooldown: Duration::from_secs(60),
+        });
+    let indexer = indexer(&server, config);
+    indexer.get_user_fills(USER, Some(0), None).await.unwrap();
+
+    server.set_unavailable(true);
+    for _ in 0..2 {
+        let err = indexer
+            .get_user_fills(USER, Some(1), None)
+            .await
+            .unwrap_err();
+        assert!(err.is_upstream_failure());
+    }
+    assert_eq!(indexer.upstream_state(), BreakerState::Open);
+
+    // Further calls fail fast, but cached responses are still served
+    let err = indexer
+        .get_user_fills(USER, Some(1), None)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        IndexerError::Ingestion(hl_ingestion::IngestionError::Unavailable(_))
+    ));
+    assert_eq!(
+        indexer
+            .get_user_fills(USER, Some(0), None)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(server.info_request_count("userFillsByTime"), 3);
+}
+
+#[tokio::test]
+async fn test_return_pct_reuses_capital_across_refreshes() {
+    let server = MockHyperliquid::new()
+        .with_fills(USER, (1..=3).map(|i| sample_fill(i * 1_000, i)).collect())
+        .with_portfolio(
+            USER,
+            serde_json::json!([[
+                "allTime",
+                { "accountValueHistory": [[0, "1000000.0"]], "pnlHistory": [], "vlm": "0.0" },
+            ]]),
+        )
+        .start()
+        .await
+        .unwrap();
+    let indexer = indexer(&server, IndexerConfig::mainnet());
+    let users = vec![USER.to_string()];
+    let config = LeaderboardConfig {
+        target_builders: Vec::new(),
+        builder_only: false,
+        max_start_capital: None,
+        coin: None,
+        from_ms: Some(0),
+        to_ms: Some(10_000),
+        metric: LeaderboardMetric::ReturnPct,
+        exclude_flagged_volume: false,
+    };
+
+    for _ in 0..2 {
+        let stats = calculate_leaderboard(&indexer, &users, &config, &NoBuilderChecker)
+            .await
+            .unwrap();
+        // 0.6 of fees measured on the whole account
+        assert_eq!(stats[0].starting_equity, Some(dec!(1000000)));
+        assert_eq!(stats[0].return_pct, Some(dec!(-0.00006)));
+    }
+
+    // The account's capital exceeds maxStartCapital, so the cap is used
+    let capped = LeaderboardConfig {
+        max_start_capital: Some(dec!(1000)),
+        ..config
+    };
+    let stats = calculate_leaderboard(&indexer, &users, &capped, &NoBuilderChecker)
+        .await
+        .unwrap();
+    assert_eq!(stats[0].starting_equity, Some(dec!(1000000)));
+    assert_eq!(stats[0].return_pct, Some(dec!(-0.06)));
+
+    assert_eq!(server.info_request_count("portfolio"), 1);
+    assert_eq!(server.info_request_count("userNonFundingLedgerUpdates"), 1);
+}
+
+#[tokio::test]
+async fn test_pages_fetch_only_what_they_need() {
+    const DAY_MS: u64 = 86_400_000;
+    // Jan 1, 2026 00:00:00 UTC; one fill at noon on each of 30 days
+    const FROM_MS: u64 = 1767225600000;
+    let server = MockHyperliquid::new()
+        .with_fills(
+            USER,
+            (0..30)
+                .map(|i| sample_fill(FROM_MS + i * DAY_MS + DAY_MS / 2, i + 1))
+                .collect(),
+        )
+        .start()
+        .await
+        .unwrap();
+    let indexer = indexer(&server, IndexerConfig::mainnet());
+    let from = Some(FROM_MS as i64);
+    let to = Some((FROM_MS + 30 * DAY_MS - 1) as i64);
+    let filter = TradeFilter::new();
+
+    let mut request = PageRequest::first(SortOrder::Desc, 2);
+    let page = indexer
+        .get_user_fills_page(USER, from, to, &filter, &request)
+        .await
+        .unwrap();
+    let ids: Vec<u64> = page.fills.iter().map(|f| f.trade_id).collect();
+    assert_eq!(ids, vec![30, 29]);
+    assert!(page.has_next);
+    // Only the last few days of the month were fetched
+    assert!(server.info_request_count("userFillsByTime") <= 4);
+
+    // Walking every page still sees each fill once
+    let mut walked = ids;
+    let mut page = page;
+    while let Some(cursor) = page.next_cursor() {
+        request.cursor = Some(cursor);
+        page = indexer
+            .get_user_fills_page(USER, from, to, &filter, &request)
+            .await
+            .unwrap();
+        walked.extend(page.fills.iter().map(|f| f.trade_id));
+    }
+    assert_eq!(walked, (1..=30).rev().collect::<Vec<u64>>());
+
+    let empty = PageRequest::first(SortOrder::Asc, 0);
+    let err = indexer
+        .get_user_fills_page(USER, from, to, &filter, &empty)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, IndexerError::InvalidPage(_)));
+}
+
+#[cfg(feature = "builder-enrichment")]
+mod builder_archive {
+    use super::*;
+    use hl_indexer::FillSource;
+
+    const BUILDER_ADDRESS: &str = "0x2868fc0d9786a740b491577a43502259efa78a39";
+
+    // Jan 10, 2026 00:00:00 UTC and the end of that day in milliseconds
+    const FROM_MS: i64 = 1768003200000;
+    const TO_MS: i64 = 1768089599999;
+
+    /// The hl-builder-data fixture archive for Jan 10, 2026, compressed like
+    /// the stats server's.
+    fn fixture_archive() -> Vec<u8> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../hl-builder-data/tests/fixtures/builder_fills")
+            .join(BUILDER_ADDRESS)
+            .join("20260110.csv");
+        let csv = std::fs::read(path).unwrap();
+
+        let mut encoder = lz4_flex::frame::FrameEncoder::new(Vec::new());
+        std::io::Write::write_all(&mut encoder, &csv).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_user_fills_from_downloaded_archive() {
+        let path = format!(
+            "/Mainnet/builder_fills/{}/20260110.csv.lz4",
+            BUILDER_ADDRESS
+        );
+        let server = MockHyperliquid::new()
+            .with_file(&path, fixture_archive())
+            .start()
+            .await
+            .unwrap();
+        let config = IndexerConfig::mainnet()
+            .with_fill_source(FillSource::BuilderArchive)
+            .with_builder(BUILDER_ADDRESS);
+        let indexer = indexer(&server, config);
+
+        let fills = indexer
+            .get_user_fills(USER, Some(FROM_MS), Some(TO_MS))
+            .await
+            .unwrap();
+
+        assert_eq!(fills.len(), 2);
+        assert_eq!(fills[0].price, dec!(135.88));
+        // Archives replace per-user API calls entirely
+        assert!(server.info_requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_builder_only_pages_attribute_whole_window() {
+        let path = format!(
+            "/Mainnet/builder_fills/{}/20260110.csv.lz4",
+            BUILDER_ADDRESS
+        );
+        // The two archive rows of USER, plus a fill not routed via the builder
+        let sol = |time_ms, px: &str, sz: &str, tid| {
+            let mut fill = sample_fill(time_ms, tid);
+            fill.coin = "SOL".to_string();
+            fill.px = px.parse().unwrap();
+            fill.sz = sz.parse().unwrap();
+            fill
+        };
+        let fills = vec![
+            sol(1768003207000, "135.88", "0.23", 1),
+            sample_fill(1768003230000, 2),
+            sol(1768003256000, "135.84", "0.08", 3),
+        ];
+        let server = MockHyperliquid::new()
+            .with_fills(USER, fills)
+            .with_file(&path, fixture_archive())
+            .start()
+            .await
+            .unwrap();
+        let indexer = indexer(
+            &server,
+            IndexerConfig::mainnet().with_builder(BUILDER_ADDRESS),
+        );
+        let filter = TradeFilter {
+            builder_only: true,
+            ..TradeFilter::new()
+        };
+
+        let mut request = PageRequest::first(SortOrder::Asc, 1);
+        let mut walked = Vec::new();
+        loop {
+            let page = indexer
+                .get_user_fills_page(USER, Some(FROM_MS), Some(TO_MS), &filter, &request)
+                .await
+                .unwrap();
+            walked.extend(page.fills.iter().map(|f| f.trade_id));
+            match page.next_cursor() {
+                Some(cursor) => request.cursor = Some(cursor),
+                None => break,
+            }
+        }
+
+        assert_eq!(walked, vec![1, 3]);
+        // Every page attributed the whole window, not just the part past its cursor
+        let requests = server.info_requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests
+            .iter()
+            .all(|r| r["startTime"].as_i64() == Some(FROM_MS)));
+    }
+}
@@ -0,0 +1,248 @@
+//! Property tests for PnL, taint and user stats over synthetic histories.
+//!
+//! Every case is a seeded [`FillGenerator`] history, so a failure reported
+//! by proptest can be reproduced from its seed and fill count alone.
+
+use hl_indexer::leaderboard::{calculate_user_stats, NoBuilderChecker, UserStats};
+use hl_indexer::taint::{analyze_user_taint, PositionLifecycleTracker};
+use hl_types::{Asset, FillGenerator, UserFill, UserPnL};
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+use std::collections::HashSet;
+
+fn history(seed: u64, count: usize) -> Vec<UserFill> {
+    FillGenerator::new(seed).generate(count)
+}
+
+fn pnl_of(fills: &[UserFill]) -> UserPnL {
+    let mut pnl = UserPnL::new("0xabc".to_string());
+    pnl.add_fills(fills.iter().cloned());
+    pnl
+}
+
+/// A history together with a random subset of its trade IDs marked as
+/// routed through the builder.
+fn history_with_builder_fills() -> impl Strategy<Value = (Vec<UserFill>, HashSet<u64>)> {
+    (any::<u64>(), 1usize..300).prop_flat_map(|(seed, count)| {
+        let fills = history(seed, count);
+        proptest::collection::vec(any::<bool>(), count).prop_map(move |marks| {
+            let builder_ids = fills
+                .iter()
+                .zip(marks)
+                .filter(|(_, builder)| *builder)
+                .map(|(f, _)| f.trade_id)
+                .collect();
+            (fills.clone(), builder_ids)
+        })
+    })
+}
+
+fn assert_same_stats(a: &UserStats, b: &UserStats) {
+    assert_eq!(a.volume, b.volume);
+    assert_eq!(a.realized_pnl, b.realized_pnl);
+    assert_eq!(a.trade_count, b.trade_count);
+    assert_eq!(a.builder_fill_count, b.builder_fill_count);
+
+    let (ta, tb) = (&a.taint_result, &b.taint_result);
+    assert_eq!(ta.tainted, tb.tainted);
+    assert_eq!(ta.tainted_fills, tb.tainted_fills);
+    assert_eq!(ta.builder_fills, tb.builder_fills);
+    assert_eq!(ta.first_taint_timestamp_ms, tb.first_taint_timestamp_ms);
+    let assets = |assets: &[Asset]| assets.iter().cloned().collect::<HashSet<_>>();
+    assert_eq!(assets(&ta.tainted_assets), assets(&tb.tainted_assets));
+}
+
+proptest! {
+    #[test]
+    fn test_totals_equal_sum_of_assets(seed in any::<u64>(), count in 0usize..500) {
+        let fills = history(seed, count);
+        let summary = pnl_of(&fills).calculate_pnl(None);
+
+        let by_asset = summary.by_asset.values();
+        prop_assert_eq!(summary.realized_pnl, by_asset.clone().map(|a| a.realized_pnl).sum());
+        prop_assert_eq!(summary.total_fees, by_asset.clone().map(|a| a.fees).sum());
+        prop_assert_eq!(summary.net_pnl, by_asset.clone().map(|a| a.net_pnl).sum());
+        prop_assert_eq!(summary.total_volume, by_asset.clone().map(|a| a.volume).sum());
+        prop_assert_eq!(summary.fill_count, by_asset.map(|a| a.fill_count).sum::<usize>());
+
+        prop_assert_eq!(summary.fill_count, fills.len());
+        prop_assert_eq!(summary.net_pnl, fills.iter().map(UserFill::net_pnl).sum::<Decimal>());
+    }
+
+    #[test]
+    fn test_asset_filters_partition_totals(
+        seed in any::<u64>(),
+        count in 0usize..500,
+        split in 0usize..6,
+    ) {
+        let fills = history(seed, count);
+        let pnl = pnl_of(&fills);
+        let assets: Vec<Asset> = pnl.assets().into_iter().cloned().collect();
+        let (left, right) = assets.split_at(split.min(assets.len()));
+
+        let whole = pnl.calculate_pnl(None);
+        let left = pnl.calculate_pnl(Some(left));
+        let right = pnl.calculate_pnl(Some(right));
+
+        prop_assert_eq!(left.net_pnl + right.net_pnl, whole.net_pnl);
+        prop_assert_eq!(left.total_volume + right.total_volume, whole.total_volume);
+        prop_assert_eq!(left.fill_count + right.fill_count, whole.fill_count);
+    }
+
+    #[test]
+    fn test_range_splits_sum_to_whole(
+        seed in any::<u64>(),
+        count in 1usize..500,
+        cut in 0.0f64..1.0,
+    ) {
+        let fills = history(seed, count);
+        let pnl = pnl_of(&fills);
+        let (first, last) = pnl.time_range().unwrap();
+        let split = first + ((last - first) as f64 * cut) as u64;
+
+        let whole = pnl.calculate_pnl_in_range(first, last, None);
+        let before = pnl.calculate_pnl_in_range(first, split, None);
+        let after = pnl.calculate_pnl_in_range(split + 1, last, None);
+
+        prop_assert_eq!(&whole, &pnl.calculate_pnl(None));
+        prop_assert_eq!(before.realized_pnl + after.realized_pnl, whole.realized_pnl);
+        prop_assert_eq!(before.total_fees + after.total_fees, whole.total_fees);
+        prop_assert_eq!(before.total_volume + after.total_volume, whole.total_volume);
+        prop_assert_eq!(before.fill_count + after.fill_count, whole.fill_count);
+        for (asset, total) in &whole.by_asset {
+            let part = |s: &hl_types::PnLSummary| {
+                s.by_asset.get(asset).map_or(Decimal::ZERO, |a| a.net_pnl)
+            };
+            prop_assert_eq!(part(&before) + part(&after), total.net_pnl);
+        }
+    }
+
+    #[test]
+    fn test_pnl_is_order_independent(
+        fills in (any::<u64>(), 0usize..300)
+            .prop_flat_map(|(seed, count)| Just(history(seed, count)).prop_shuffle()),
+    ) {
+        let mut sorted = fills.clone();
+        sorted.sort_by_key(|f| f.timestamp_ms);
+
+        let shuffled = pnl_of(&fills);
+        let ordered = pnl_of(&sorted);
+
+        prop_assert_eq!(shuffled.calculate_pnl(None), ordered.calculate_pnl(None));
+        prop_assert_eq!(shuffled.all_fills(), ordered.all_fills());
+        prop_assert_eq!(shuffled.time_range(), ordered.time_range());
+    }
+
+    #[test]
+    fn test_stats_are_order_independent(
+        (fills, builder_ids, shuffled) in history_with_builder_fills()
+            .prop_flat_map(|(fills, ids)| {
+                (Just(fills.clone()), Just(ids), Just(fills).prop_shuffle())
+            }),
+    ) {
+        let checker = TradeIdChecker(builder_ids);
+
+        for builder_only in [false, true] {
+            let a = calculate_user_stats("0xabc", &fills, &checker, None, None, builder_only);
+            let b = calculate_user_stats("0xabc", &shuffled, &checker, None, None, builder_only);
+            assert_same_stats(&a, &b);
+        }
+    }
+
+    #[test]
+    fn test_stats_match_pnl(seed in any::<u64>(), count in 0usize..500) {
+        let fills = history(seed, count);
+        let stats = calculate_user_stats("0xabc", &fills, &NoBuilderChecker, None, None, false);
+        let summary = pnl_of(&fills).calculate_pnl(None);
+
+        prop_assert_eq!(stats.volume, summary.total_volume);
+        prop_assert_eq!(stats.realized_pnl, summary.net_pnl);
+        prop_assert_eq!(stats.trade_count, summary.fill_count);
+
+        // Per-coin stats add up to the unfiltered ones
+        let mut volume = Decimal::ZERO;
+        let mut realized = Decimal::ZERO;
+        for asset in summary.by_asset.keys() {
+            let coin = calculate_user_stats(
+                "0xabc", &fills, &NoBuilderChecker, None, Some(asset.symbol()), false,
+            );
+            volume += coin.volume;
+            realized += coin.realized_pnl;
+        }
+        prop_assert_eq!(volume, stats.volume);
+        prop_assert_eq!(realized, stats.realized_pnl);
+    }
+
+    #[test]
+    fn test_taint_is_monotone_over_time((fills, builder_ids) in history_with_builder_fills()) {
+        let mut sorted = fills;
+        sorted.sort_by_key(|f| f.timestamp_ms);
+
+        let mut tracker = PositionLifecycleTracker::new();
+        let mut previous = tracker.result();
+        for fill in &sorted {
+            tracker.process_fill(fill, builder_ids.contains(&fill.trade_id));
+            let result = tracker.result();
+
+            prop_assert!(result.tainted || !previous.tainted, "taint cleared at {}", fill.trade_id);
+            prop_assert!(result.tainted_fills >= previous.tainted_fills);
+            prop_assert!(result.tainted_assets.len() >= previous.tainted_assets.len());
+            if previous.tainted {
+                // The first taint is never moved by later fills
+                prop_assert_eq!(result.first_taint_timestamp_ms, previous.first_taint_timestamp_ms);
+            }
+            previous = result;
+        }
+    }
+
+    #[test]
+    fn test_fewer_builder_fills_never_clear_taint(
+        (fills, builder_ids) in history_with_builder_fills(),
+        drop_every in 1usize..5,
+    ) {
+        let fewer: HashSet<u64> = builder_ids
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(i, _)| i % drop_every != 0)
+            .map(|(_, id)| id)
+            .collect();
+
+        let with_all = analyze_user_taint(&fills, |f| builder_ids.contains(&f.trade_id));
+        let with_fewer = analyze_user_taint(&fills, |f| fewer.contains(&f.trade_id));
+
+        prop_assert!(with_fewer.tainted || !with_all.tainted);
+        prop_assert!(with_fewer.tainted_fills >= with_all.tainted_fills);
+        let assets = |assets: &[Asset]| assets.iter().cloned().collect::<HashSet<_>>();
+        let (fewer_assets, all_assets) =
+            (assets(&with_fewer.tainted_assets), assets(&with_all.tainted_assets));
+        prop_assert!(fewer_assets.is_superset(&all_assets));
+    }
+
+    #[test]
+    fn test_tracker_positions_match_history(seed in any::<u64>(), count in 0usize..500) {
+        let mut generator = FillGenerator::new(seed);
+        let fills = generator.generate(count);
+
+        let result = analyze_user_taint(&fills, |_| true);
+        prop_assert!(!result.tainted);
+        prop_assert_eq!(result.builder_fills, fills.len());
+
+        let mut tracker = PositionLifecycleTracker::new();
+        for fill in &fills {
+            tracker.process_fill(fill, true);
+        }
+        for fill in &fills {
+            prop_assert_eq!(tracker.get_position(&fill.asset), generator.position(&fill.asset));
+        }
+    }
+}
+
+/// Builder checker that recognizes a fixed set of trade IDs.
+struct TradeIdChecker(HashSet<u64>);
+
+impl hl_indexer::leaderboard::BuilderFillChecker for TradeIdChecker {
+    fn is_builder_fill(&self, fill: &UserFill, _user: &str) -> bool {
+        self.0.contains(&fill.trade_id)
+    }
+}
@@ -2,7 +2,9 @@
 //!
 //! This module provides a thin HTTP client that can make direct API calls
 //! to Hyperliquid, bypassing hypersdk where needed. The primary use case
-//! is accessing `userFillsByTime` with pagination support.
+//! is accessing `userFillsByTime` with pagination support; it also covers
+//! `portfolio` (account value history) and `userNonFundingLedgerUpdates`
+//! (deposits, withdrawals and transfers).
 //!
 //! # Design
 //!
@@ -27,24 +29,23 @@
 //! - **No builder attribution**: Fill data lacks builder field
 
 use crate::error::IngestionError;
+use crate::types::{LedgerUpdate, PortfolioPeriod};
 use hypersdk::hypercore::types::Fill;
 use hypersdk::Address;
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::collections::HashSet;
 use url::Url;
 
-/// Hyperliquid mainnet API base URL.
-const MAINNET_URL: &str = "https://api.hyperliquid.xyz";
-
-/// Hyperliquid testnet API base URL.
-const TESTNET_URL: &str = "https://api.hyperliquid-testnet.xyz";
-
 /// Maximum fills per API request (API limit).
 const MAX_FILLS_PER_REQUEST: usize = 2000;
 
 /// Maximum total fills we'll fetch (API limit for userFillsByTime).
 const MAX_TOTAL_FILLS: usize = 10000;
 
+/// Page size for userNonFundingLedgerUpdates. A full page means there may be more.
+const MAX_LEDGER_UPDATES_PER_REQUEST: usize = 500;
+
 /// Direct API client for Hyperliquid endpoints.
 ///
 /// Use this client for endpoints that hypersdk doesn't expose or doesn't
@@ -55,19 +56,11 @@ pub(crate) struct ApiClient {
 }
 
 impl ApiClient {
-    /// Create a client for Hyperliquid mainnet.
-    pub fn mainnet() -> Self {
+    /// Create a client for the API at `base_url`.
+    pub fn new(base_url: Url) -> Self {
         Self {
             http_client: reqwest::Client::new(),
-            base_url: Url::parse(MAINNET_URL).expect("mainnet URL is valid"),
-        }
-    }
-
-    /// Create a client for Hyperliquid testnet.
-    pub fn testnet() -> Self {
-        Self {
-            http_client: reqwest::Client::new(),
-            base_url: Url::parse(TESTNET_URL).expect("testnet URL is valid"),
+            base_url,
         }
     }
 
@@ -120,17 +113,7 @@ impl ApiClient {
             };
 
             // Make API call
-            let info_url = self.base_url.join("/info").expect("valid URL join");
-            let response: Vec<Fill> = self
-                .http_client
-                .post(info_url)
-                .json(&request)
-                .send()
-                .await?
-                .error_for_status()
-                .map_err(|e| IngestionError::Network(e.to_string()))?
-                .json()
-                .await?;
+            let response: Vec<Fill> = self.post_info(&request).await?;
 
             // Empty response means no more data
             if response.is_empty() {
@@ -183,6 +166,100 @@ impl ApiClient {
 
         Ok(all_fills)
     }
+
+    /// Fetch the user's portfolio (account value and PnL history per period).
+    pub async fn portfolio(
+        &self,
+        user: Address,
+    ) -> Result<Vec<(String, PortfolioPeriod)>, IngestionError> {
+        let request = InfoRequest::Portfolio {
+            user: format!("{:?}", user),
+        };
+        self.post_info(&request).await
+    }
+
+    /// Fetch non-funding ledger updates (deposits, withdrawals, transfers).
+    ///
+    /// Pages forward in time until a partial page is returned or `end_time`
+    /// is reached.
+    ///
+    /// # Returns
+    ///
+    /// Updates sorted by time ascending, deduplicated by `(time, hash)`.
+    pub async fn user_non_funding_ledger_updates(
+        &self,
+        user: Address,
+        start_time: i64,
+        end_time: Option<i64>,
+    ) -> Result<Vec<LedgerUpdate>, IngestionError> {
+        let mut all_updates: Vec<LedgerUpdate> = Vec::new();
+        let mut seen: HashSet<(u64, String)> = HashSet::new();
+        let mut current_start = start_time.max(0) as u64;
+        let end_time_u64 = end_time.map(|t| t.max(0) as u64);
+
+        loop {
+            let request = InfoRequest::UserNonFundingLedgerUpdates {
+                user: format!("{:?}", user),
+                start_time: current_start,
+                end_time: end_time_u64,
+            };
+            let response: Vec<LedgerUpdate> = self.post_info(&request).await?;
+            let response_len = response.len();
+
+            let latest_time = response.iter().map(|u| u.time).max();
+            for update in response {
+                if seen.insert((update.time, update.hash.clone())) {
+                    all_updates.push(update);
+                }
+            }
+
+            match latest_time {
+                Some(latest)
+                    if response_len >= MAX_LEDGER_UPDATES_PER_REQUEST
+                        && end_time_u64.is_none_or(|end| latest < end) =>
+                {
+                    current_start = latest + 1;
+                }
+                _ => break,
+            }
+        }
+
+        all_updates.sort_by_key(|u| u.time);
+        Ok(all_updates)
+    }
+
+    /// POST a request to the `/info` endpoint and decode the JSON response.
+    async fn post_info<T: DeserializeOwned>(
+        &self,
+        request: &InfoRequest,
+    ) -> Result<T, IngestionError> {
+        let info_url = endpoint_url(&self.base_url, "info")?;
+        let response = self
+            .http_client
+            .post(info_url)
+            .json(request)
+            .send()
+            .await?
+            .error_for_status()
+            .map_err(|e| IngestionError::Network(e.to_string()))?
+            .json()
+            .await?;
+        Ok(response)
+    }
+}
+
+/// `base_url` with `name` appended to its path, keeping any path prefix.
+fn endpoint_url(base_url: &Url, name: &str) -> Result<Url, IngestionError> {
+    let mut url = base_url.clone();
+    let Ok(mut segments) = url.path_segments_mut() else {
+        return Err(IngestionError::Config(format!(
+            "API URL '{}' cannot have a path",
+            base_url
+        )));
+    };
+    segments.pop_if_empty().push(name);
+    drop(segments);
+    Ok(url)
 }
 
 /// Request types for the /info endpoint.
@@ -206,6 +283,24 @@ enum InfoRequest {
         #[serde(skip_serializing_if = "Option::is_none")]
         aggregate_by_time: Option<bool>,
     },
+
+    /// Fetch account value and PnL history.
+    Portfolio {
+        /// User address as hex string
+        user: String,
+    },
+
+    /// Fetch deposits, withdrawals and transfers within a time window.
+    #[serde(rename_all = "camelCase")]
+    UserNonFundingLedgerUpdates {
+        /// User address as hex string
+        user: String,
+        /// Start of time window (inclusive), milliseconds since epoch
+        start_time: u64,
+        /// Optional end of time window, milliseconds since epoch
+        #[serde(skip_serializing_if = "Option::is_none")]
+        end_time: Option<u64>,
+    },
 }
 
 #[cfg(test)]
@@ -230,6 +325,28 @@ mod tests {
         assert!(!json.contains("aggregateByTime"));
     }
 
+    #[test]
+    fn test_endpoint_url_keeps_path_prefix() {
+        let info = |base: &str| {
+            endpoint_url(&base.parse().unwrap(), "info")
+                .map(String::from)
+                .map_err(|e| e.to_string())
+        };
+        assert_eq!(
+            info("https://api.hyperliquid.xyz").unwrap(),
+            "https://api.hyperliquid.xyz/info"
+        );
+        assert_eq!(
+            info("https://node.example/hl").unwrap(),
+            "https://node.example/hl/info"
+        );
+        assert_eq!(
+            info("https://node.example/hl/").unwrap(),
+            "https://node.example/hl/info"
+        );
+        assert!(info("mailto:node@example.com").is_err());
+    }
+
     #[test]
     fn test_info_request_without_end_time() {
         let request = InfoRequest::UserFillsByTime {
@@ -243,4 +360,24 @@ mod tests {
         assert!(!json.contains("endTime"));
         assert!(json.contains("\"aggregateByTime\":true"));
     }
+
+    #[test]
+    fn test_ledger_request_serialization() {
+        let request = InfoRequest::UserNonFundingLedgerUpdates {
+            user: "0xabc".to_string(),
+            start_time: 1000,
+            end_time: Some(2000),
+        };
+
+        let json = serde_json::to_string(&request).unwrap();
+        assert!(json.contains("\"type\":\"userNonFundingLedgerUpdates\""));
+        assert!(json.contains("\"startTime\":1000"));
+        assert!(json.contains("\"endTime\":2000"));
+
+        let portfolio = InfoRequest::Portfolio {
+            user: "0xabc".to_string(),
+        };
+        let json = serde_json::to_string(&portfolio).unwrap();
+        assert_eq!(json, r#"{"type":"portfolio","user":"0xabc"}"#);
+    }
 }
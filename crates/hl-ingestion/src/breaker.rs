@@ -0,0 +1,423 @@
+//! Circuit breaker for an unhealthy upstream.
+//!
+//! When the Hyperliquid API is degraded, every request otherwise waits for
+//! its own timeout or error. [`CircuitBreakerSource`] counts consecutive
+//! transient failures (see [`IngestionError::is_transient`]) and, once
+//! [`BreakerConfig::failure_threshold`] is reached, *opens*: requests fail
+//! immediately with [`IngestionError::Unavailable`] for
+//! [`BreakerConfig::cooldown`]. After the cooldown the breaker is
+//! *half-open* and lets a single trial request through; success closes it,
+//! failure opens it for another cooldown.
+//!
+//! Errors caused by the request itself, such as an invalid address, say
+//! nothing about upstream health and are passed through without counting.
+
+use crate::types::{AccountValuePoint, LedgerUpdate};
+use crate::{error::IngestionError, DataSource};
+use hypersdk::hypercore::types::{ClearinghouseState, Fill, UserBalance};
+use std::future::Future;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// When the breaker opens and how long it stays open.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakerConfig {
+    /// Consecutive transient failures that open the breaker (default 5).
+    /// Zero disables the breaker.
+    pub failure_threshold: u32,
+    /// How long the breaker stays open before a trial request (default 30s).
+    pub cooldown: Duration,
+}
+
+impl BreakerConfig {
+    /// Never open; every request reaches the wrapped source.
+    pub const fn disabled() -> Self {
+        Self {
+            failure_threshold: 0,
+            cooldown: Duration::ZERO,
+        }
+    }
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Whether requests currently reach the wrapped source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Requests pass through.
+    Closed,
+    /// Requests fail fast until the cooldown ends.
+    Open,
+    /// The cooldown has ended; the next request is a trial.
+    HalfOpen,
+}
+
+impl BreakerState {
+    /// Lowercase name, as used in logs and health output.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BreakerState::Closed => "closed",
+            BreakerState::Open => "open",
+            BreakerState::HalfOpen => "half_open",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    consecutive_failures: u32,
+    /// When the breaker last opened, if it is open or half-open.
+    opened_at: Option<Instant>,
+    /// When the current half-open trial started. A trial that never
+    /// finishes (e.g. its future was dropped) is retried after a cooldown.
+    trial_started_at: Option<Instant>,
+}
+
+/// Data source decorator that stops calling an upstream that keeps failing.
+///
+/// See the [module docs](self) for the state machine.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use hl_ingestion::{BreakerConfig, CircuitBreakerSource, DataSource, HyperliquidSource};
+/// use std::time::Duration;
+///
+/// # async fn example() {
+/// let source = CircuitBreakerSource::new(HyperliquidSource::mainnet()).with_config(
+///     BreakerConfig {
+///         failure_threshold: 3,
+///         cooldown: Duration::from_secs(10),
+///     },
+/// );
+///
+/// if let Err(e) = source.get_user_fills("0x...", None, None).await {
+///     println!("{} (breaker {})", e, source.state().as_str());
+/// }
+/// # }
+/// ```
+pub struct CircuitBreakerSource<S> {
+    inner: S,
+    config: BreakerConfig,
+    health: Mutex<Health>,
+}
+
+impl<S: DataSource> CircuitBreakerSource<S> {
+    /// Guard `inner` with the default [`BreakerConfig`].
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            config: BreakerConfig::default(),
+            health: Mutex::new(Health::default()),
+        }
+    }
+
+    /// Set when the breaker opens and for how long.
+    pub fn with_config(mut self, config: BreakerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// The wrapped data source.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// The breaker configuration.
+    pub fn config(&self) -> BreakerConfig {
+        self.config
+    }
+
+    /// The current breaker state.
+    pub fn state(&self) -> BreakerState {
+        match self.lock().opened_at {
+            None => BreakerState::Closed,
+            Some(at) if at.elapsed() < self.config.cooldown => BreakerState::Open,
+            Some(_) => BreakerState::HalfOpen,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Health> {
+        self.health.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Run `request` unless the breaker is open, recording its outcome.
+    async fn call<T, Fut>(&self, request: Fut) -> Result<T, IngestionError>
+    where
+        Fut: Future<Output = Result<T, IngestionError>>,
+    {
+        self.admit()?;
+        let result = request.await;
+        self.record(&result);
+        result
+    }
+
+    /// Fail fast while open; while half-open, admit one trial per cooldown.
+    fn admit(&self) -> Result<(), IngestionError> {
+        let mut health = self.lock();
+        let Some(opened_at) = health.opened_at else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        let cooldown = self.config.cooldown;
+        let trial_due = opened_at + cooldown <= now
+            && health
+                .trial_started_at
+                .is_none_or(|started| started + cooldown <= now);
+        if trial_due {
+            health.trial_started_at = Some(now);
+            return Ok(());
+        }
+
+        let retry_in = (opened_at + cooldown).saturating_duration_since(now);
+        Err(IngestionError::Unavailable(format!(
+            "upstream failing, circuit open (retry in {}s)",
+            retry_in.as_secs().max(1)
+        )))
+    }
+
+    fn record<T>(&self, result: &Result<T, IngestionError>) {
+        let mut health = self.lock();
+        match result {
+            Err(e) if e.is_transient() => {
+                health.consecutive_failures = health.consecutive_failures.saturating_add(1);
+                let threshold = self.config.failure_threshold;
+                if threshold > 0 && health.consecutive_failures >= threshold {
+                    if health.opened_at.is_none() {
+                        tracing::warn!(
+                            "Opening circuit after {} consecutive failures: {}",
+                            health.consecutive_failures,
+                            e
+                        );
+                    }
+                    health.opened_at = Some(Instant::now());
+                    health.trial_started_at = None;
+                }
+            }
+            _ => {
+                if health.opened_at.is_some() {
+                    tracing::info!("Upstream recovered, closing circuit");
+                }
+                *health = Health::default();
+            }
+        }
+    }
+}
+
+impl<S: DataSource> DataSource for CircuitBreakerSource<S> {
+    async fn get_user_fills(
+        &self,
+        user: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<Vec<Fill>, IngestionError> {
+        self.call(self.inner.get_user_fills(user, from_ms, to_ms))
+            .await
+    }
+
+    async fn get_clearinghouse_state(
+        &self,
+        user: &str,
+    ) -> Result<ClearinghouseState, IngestionError> {
+        self.call(self.inner.get_clearinghouse_state(user)).await
+    }
+
+    async fn get_user_balances(&self, user: &str) -> Result<Vec<UserBalance>, IngestionError> {
+        self.call(self.inner.get_user_balances(user)).await
+    }
+
+    async fn get_account_value_history(
+        &self,
+        user: &str,
+    ) -> Result<Vec<AccountValuePoint>, IngestionError> {
+        self.call(self.inner.get_account_value_history(user)).await
+    }
+
+    async fn get_ledger_updates(
+        &self,
+        user: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<Vec<LedgerUpdate>, IngestionError> {
+        self.call(self.inner.get_ledger_updates(user, from_ms, to_ms))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockSource;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// Wraps a `MockSource`, counting calls and failing them while `down`.
+    #[derive(Default)]
+    struct FlakySource {
+        inner: MockSource,
+        calls: AtomicUsize,
+        down: AtomicBool,
+        error: Option<IngestionError>,
+    }
+
+    impl FlakySource {
+        fn down() -> Self {
+            let source = Self::default();
+            source.set_down(true);
+            source
+        }
+
+        fn set_down(&self, down: bool) {
+            self.down.store(down, Ordering::SeqCst);
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    impl DataSource for FlakySource {
+        async fn get_user_fills(
+            &self,
+            user: &str,
+            from_ms: Option<i64>,
+            to_ms: Option<i64>,
+        ) -> Result<Vec<Fill>, IngestionError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.down.load(Ordering::SeqCst) {
+                let error = self.error.clone();
+                return Err(error.unwrap_or(IngestionError::Network("502".to_string())));
+            }
+            self.inner.get_user_fills(user, from_ms, to_ms).await
+        }
+
+        async fn get_clearinghouse_state(
+            &self,
+            user: &str,
+        ) -> Result<ClearinghouseState, IngestionError> {
+            self.inner.get_clearinghouse_state(user).await
+        }
+
+        async fn get_user_balances(&self, user: &str) -> Result<Vec<UserBalance>, IngestionError> {
+            self.inner.get_user_balances(user).await
+        }
+
+        async fn get_account_value_history(
+            &self,
+            user: &str,
+        ) -> Result<Vec<AccountValuePoint>, IngestionError> {
+            self.inner.get_account_value_history(user).await
+        }
+
+        async fn get_ledger_updates(
+            &self,
+            user: &str,
+            from_ms: Option<i64>,
+            to_ms: Option<i64>,
+        ) -> Result<Vec<LedgerUpdate>, IngestionError> {
+            self.inner.get_ledger_updates(user, from_ms, to_ms).await
+        }
+    }
+
+    fn config(cooldown_ms: u64) -> BreakerConfig {
+        BreakerConfig {
+            failure_threshold: 3,
+            cooldown: Duration::from_millis(cooldown_ms),
+        }
+    }
+
+    async fn fetch<S: DataSource>(source: &S) -> Result<Vec<Fill>, IngestionError> {
+        source.get_user_fills("0xabc", None, None).await
+    }
+
+    #[tokio::test]
+    async fn test_opens_after_consecutive_failures() {
+        let source = CircuitBreakerSource::new(FlakySource::down()).with_config(config(60_000));
+
+        for _ in 0..3 {
+            assert!(matches!(
+                fetch(&source).await,
+                Err(IngestionError::Network(_))
+            ));
+        }
+        assert_eq!(source.state(), BreakerState::Open);
+
+        // Open: fail fast without calling upstream
+        let err = fetch(&source).await.unwrap_err();
+        assert!(matches!(err, IngestionError::Unavailable(_)));
+        assert_eq!(source.inner().calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_success_resets_failure_count() {
+        let source = CircuitBreakerSource::new(FlakySource::down()).with_config(config(60_000));
+
+        fetch(&source).await.unwrap_err();
+        fetch(&source).await.unwrap_err();
+        source.inner().set_down(false);
+        fetch(&source).await.unwrap();
+        source.inner().set_down(true);
+        fetch(&source).await.unwrap_err();
+        fetch(&source).await.unwrap_err();
+
+        assert_eq!(source.state(), BreakerState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_request_errors_do_not_count() {
+        let source = CircuitBreakerSource::new(FlakySource {
+            error: Some(IngestionError::InvalidAddress("0xabc".to_string())),
+            ..FlakySource::down()
+        })
+        .with_config(config(60_000));
+
+        for _ in 0..5 {
+            fetch(&source).await.unwrap_err();
+        }
+        assert_eq!(source.state(), BreakerState::Closed);
+        assert_eq!(source.inner().calls(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_half_open_trial() {
+        let source = CircuitBreakerSource::new(FlakySource::down()).with_config(config(50));
+        for _ in 0..3 {
+            fetch(&source).await.unwrap_err();
+        }
+
+        // A failed trial opens the breaker for another cooldown
+        tokio::time::sleep(Duration::from_millis(70)).await;
+        assert_eq!(source.state(), BreakerState::HalfOpen);
+        fetch(&source).await.unwrap_err();
+        assert_eq!(source.state(), BreakerState::Open);
+        assert_eq!(source.inner().calls(), 4);
+
+        // A successful trial closes it
+        source.inner().set_down(false);
+        tokio::time::sleep(Duration::from_millis(70)).await;
+        fetch(&source).await.unwrap();
+        assert_eq!(source.state(), BreakerState::Closed);
+        fetch(&source).await.unwrap();
+        assert_eq!(source.inner().calls(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_never_opens() {
+        let source =
+            CircuitBreakerSource::new(FlakySource::down()).with_config(BreakerConfig::disabled());
+
+        for _ in 0..10 {
+            assert!(matches!(
+                fetch(&source).await,
+                Err(IngestionError::Network(_))
+            ));
+        }
+        assert_eq!(source.state(), BreakerState::Closed);
+    }
+}
@@ -0,0 +1,618 @@
+//! Response caching with request coalescing.
+//!
+//! [`CachingSource`] wraps another [`DataSource`] so that concurrent and
+//! repeated requests for the same data reach the API once:
+//!
+//! - **Coalescing**: identical requests that overlap in time share a single
+//!   upstream call; everyone waiting gets its result, errors included.
+//! - **TTLs**: successful responses are reused for a per-method TTL
+//!   ([`CacheTtls`]). Errors are never cached.
+//! - **Closed windows**: fill and ledger queries whose window ended more
+//!   than a minute ago can no longer change, so they are kept for an hour
+//!   regardless of TTL.
+//! - **Bounded size**: each method keeps at most
+//!   [`DEFAULT_MAX_CACHE_ENTRIES`] responses (see
+//!   [`CachingSource::with_max_entries`]); the least recently used one is
+//!   evicted to make room.
+
+use crate::types::{AccountValuePoint, LedgerUpdate};
+use crate::{error::IngestionError, DataSource};
+use hypersdk::hypercore::types::{ClearinghouseState, Fill, UserBalance};
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+
+/// How long after its end a time window is treated as closed.
+///
+/// Fills are indexed shortly after they happen, so a window ending just now
+/// may still gain fills.
+const CLOSED_WINDOW_GRACE_MS: i64 = 60_000;
+
+/// How long responses for closed windows are kept.
+const CLOSED_WINDOW_TTL: Duration = Duration::from_secs(3600);
+
+/// Default cap on cached responses per method.
+pub const DEFAULT_MAX_CACHE_ENTRIES: usize = 256;
+
+/// How long successful responses are reused, per method.
+///
+/// A zero TTL turns caching off for that method, closed windows included;
+/// identical in-flight requests are still coalesced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheTtls {
+    /// [`DataSource::get_user_fills`] (default 10s).
+    pub user_fills: Duration,
+    /// [`DataSource::get_clearinghouse_state`] (default 5s).
+    pub clearinghouse_state: Duration,
+    /// [`DataSource::get_user_balances`] (default 5s).
+    pub user_balances: Duration,
+    /// [`DataSource::get_account_value_history`] (default 60s).
+    pub account_value_history: Duration,
+    /// [`DataSource::get_ledger_updates`] (default 30s).
+    pub ledger_updates: Duration,
+}
+
+impl CacheTtls {
+    /// Cache nothing; only coalesce in-flight requests.
+    pub const fn disabled() -> Self {
+        Self {
+            user_fills: Duration::ZERO,
+            clearinghouse_state: Duration::ZERO,
+            user_balances: Duration::ZERO,
+            account_value_history: Duration::ZERO,
+            ledger_updates: Duration::ZERO,
+        }
+    }
+}
+
+impl Default for CacheTtls {
+    fn default() -> Self {
+        Self {
+            user_fills: Duration::from_secs(10),
+            clearinghouse_state: Duration::from_secs(5),
+            user_balances: Duration::from_secs(5),
+            account_value_history: Duration::from_secs(60),
+            ledger_updates: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Cache counters, summed over all methods.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Requests answered from the cache.
+    pub hits: u64,
+    /// Requests that called the wrapped source.
+    pub misses: u64,
+    /// Requests that waited for an identical in-flight request instead.
+    pub coalesced: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+/// A cached response.
+struct Entry<T> {
+    value: T,
+    expires_at: Instant,
+    /// When the entry was last stored or served, for LRU eviction.
+    last_used: Instant,
+}
+
+impl<T> Entry<T> {
+    fn is_fresh(&self, now: Instant) -> bool {
+        self.expires_at > now
+    }
+}
+
+type Flight<T> = Arc<OnceCell<Result<T, IngestionError>>>;
+
+/// Cached responses and in-flight requests for one method.
+struct MethodCache<K, T> {
+    ttl: Duration,
+    max_entries: usize,
+    entries: Mutex<HashMap<K, Entry<T>>>,
+    in_flight: Mutex<HashMap<K, Flight<T>>>,
+}
+
+impl<K: Eq + Hash + Clone, T: Clone> MethodCache<K, T> {
+    fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            ttl,
+            max_entries,
+            entries: Mutex::new(HashMap::new()),
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The cached response for `key`, or the result of `fetch`, shared with
+    /// any identical request already in flight.
+    async fn get<F, Fut>(
+        &self,
+        key: K,
+        closed: bool,
+        counters: &Counters,
+        fetch: F,
+    ) -> Result<T, IngestionError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, IngestionError>>,
+    {
+        if let Some(entry) = lock(&self.entries).get_mut(&key) {
+            let now = Instant::now();
+            if entry.is_fresh(now) {
+                entry.last_used = now;
+                counters.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(entry.value.clone());
+            }
+        }
+
+        let flight = lock(&self.in_flight)
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let mut fetched = false;
+        let result = flight
+            .get_or_init(|| async {
+                fetched = true;
+                fetch().await
+            })
+            .await
+            .clone();
+
+        if !fetched {
+            counters.coalesced.fetch_add(1, Ordering::Relaxed);
+            return result;
+        }
+        counters.misses.fetch_add(1, Ordering::Relaxed);
+
+        // Store before ending the flight so later callers find the entry
+        if let Ok(value) = &result {
+            self.store(key.clone(), value.clone(), closed);
+        }
+        let mut in_flight = lock(&self.in_flight);
+        if in_flight
+            .get(&key)
+            .is_some_and(|current| Arc::ptr_eq(current, &flight))
+        {
+            in_flight.remove(&key);
+        }
+        result
+    }
+
+    fn store(&self, key: K, value: T, closed: bool) {
+        if self.ttl.is_zero() || self.max_entries == 0 {
+            return;
+        }
+        let now = Instant::now();
+        let ttl = if closed {
+            CLOSED_WINDOW_TTL.max(self.ttl)
+        } else {
+            self.ttl
+        };
+
+        let mut entries = lock(&self.entries);
+        entries.retain(|_, entry| entry.is_fresh(now));
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                value,
+                expires_at: now + ttl,
+                last_used: now,
+            },
+        );
+    }
+
+    fn clear(&self) {
+        lock(&self.entries).clear();
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+type WindowKey = (String, Option<i64>, Option<i64>);
+
+/// Whether a window has ended long enough ago that its data is final.
+fn is_closed(to_ms: Option<i64>) -> bool {
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    to_ms.is_some_and(|to| to < now_ms - CLOSED_WINDOW_GRACE_MS)
+}
+
+/// Data source decorator that caches responses and coalesces identical
+/// concurrent requests.
+///
+/// See the [module docs](self) for what is cached and for how long.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use hl_ingestion::{CacheTtls, CachingSource, DataSource, HyperliquidSource};
+/// use std::time::Duration;
+///
+/// # async fn example() -> Result<(), hl_ingestion::IngestionError> {
+/// let source = CachingSource::new(HyperliquidSource::mainnet()).with_ttls(CacheTtls {
+///     user_fills: Duration::from_secs(30),
+///     ..CacheTtls::default()
+/// });
+///
+/// // The second call is served from the cache
+/// let fills = source.get_user_fills("0x...", Some(1704067200000), None).await?;
+/// let again = source.get_user_fills("0x...", Some(1704067200000), None).await?;
+/// assert_eq!(source.stats().hits, 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct CachingSource<S> {
+    inner: S,
+    ttls: CacheTtls,
+    max_entries: usize,
+    counters: Counters,
+    user_fills: MethodCache<WindowKey, Vec<Fill>>,
+    clearinghouse_state: MethodCache<String, ClearinghouseState>,
+    user_balances: MethodCache<String, Vec<UserBalance>>,
+    account_value_history: MethodCache<String, Vec<AccountValuePoint>>,
+    ledger_updates: MethodCache<WindowKey, Vec<LedgerUpdate>>,
+}
+
+impl<S: DataSource> CachingSource<S> {
+    /// Cache responses from `inner` with the default [`CacheTtls`].
+    pub fn new(inner: S) -> Self {
+        Self::with_parts(inner, CacheTtls::default(), DEFAULT_MAX_CACHE_ENTRIES)
+    }
+
+    /// Set the per-method TTLs, dropping anything cached so far.
+    pub fn with_ttls(self, ttls: CacheTtls) -> Self {
+        Self::with_parts(self.inner, ttls, self.max_entries)
+    }
+
+    /// Cap the responses kept per method (default [`DEFAULT_MAX_CACHE_ENTRIES`]),
+    /// dropping anything cached so far.
+    pub fn with_max_entries(self, max_entries: usize) -> Self {
+        Self::with_parts(self.inner, self.ttls, max_entries)
+    }
+
+    fn with_parts(inner: S, ttls: CacheTtls, max_entries: usize) -> Self {
+        Self {
+            inner,
+            ttls,
+            max_entries,
+            counters: Counters::default(),
+            user_fills: MethodCache::new(ttls.user_fills, max_entries),
+            clearinghouse_state: MethodCache::new(ttls.clearinghouse_state, max_entries),
+            user_balances: MethodCache::new(ttls.user_balances, max_entries),
+            account_value_history: MethodCache::new(ttls.account_value_history, max_entries),
+            ledger_updates: MethodCache::new(ttls.ledger_updates, max_entries),
+        }
+    }
+
+    /// The wrapped data source.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// The per-method TTLs.
+    pub fn ttls(&self) -> CacheTtls {
+        self.ttls
+    }
+
+    /// Hit, miss and coalescing counts since creation.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            coalesced: self.counters.coalesced.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Drop every cached response, closed windows included.
+    pub fn clear(&self) {
+        self.user_fills.clear();
+        self.clearinghouse_state.clear();
+        self.user_balances.clear();
+        self.account_value_history.clear();
+        self.ledger_updates.clear();
+    }
+}
+
+impl<S: DataSource> DataSource for CachingSource<S> {
+    async fn get_user_fills(
+        &self,
+        user: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<Vec<Fill>, IngestionError> {
+        // Without a start the API returns the most recent fills, which
+        // change as new ones arrive even for a window that has ended
+        let closed = from_ms.is_some() && is_closed(to_ms);
+        let key = (user.to_lowercase(), from_ms, to_ms);
+        self.user_fills
+            .get(key, closed, &self.counters, || {
+                self.inner.get_user_fills(user, from_ms, to_ms)
+            })
+            .await
+    }
+
+    async fn get_clearinghouse_state(
+        &self,
+        user: &str,
+    ) -> Result<ClearinghouseState, IngestionError> {
+        self.clearinghouse_state
+            .get(user.to_lowercase(), false, &self.counters, || {
+                self.inner.get_clearinghouse_state(user)
+            })
+            .await
+    }
+
+    async fn get_user_balances(&self, user: &str) -> Result<Vec<UserBalance>, IngestionError> {
+        self.user_balances
+            .get(user.to_lowercase(), false, &self.counters, || {
+                self.inner.get_user_balances(user)
+            })
+            .await
+    }
+
+    async fn get_account_value_history(
+        &self,
+        user: &str,
+    ) -> Result<Vec<AccountValuePoint>, IngestionError> {
+        self.account_value_history
+            .get(user.to_lowercase(), false, &self.counters, || {
+                self.inner.get_account_value_history(user)
+            })
+            .await
+    }
+
+    async fn get_ledger_updates(
+        &self,
+        user: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<Vec<LedgerUpdate>, IngestionError> {
+        let key = (user.to_lowercase(), from_ms, to_ms);
+        self.ledger_updates
+            .get(key, is_closed(to_ms), &self.counters, || {
+                self.inner.get_ledger_updates(user, from_ms, to_ms)
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockSource;
+    use std::sync::atomic::AtomicUsize;
+
+    /// Wraps a `MockSource`, counting calls and optionally slowing or
+    /// failing them.
+    #[derive(Default)]
+    struct CountingSource {
+        inner: MockSource,
+        calls: AtomicUsize,
+        delay: Duration,
+        fail: bool,
+    }
+
+    impl CountingSource {
+        fn slow(delay: Duration) -> Self {
+            Self {
+                delay,
+                ..Self::default()
+            }
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+
+        async fn call(&self) -> Result<(), IngestionError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            if self.fail {
+                return Err(IngestionError::Network("upstream down".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    impl DataSource for CountingSource {
+        async fn get_user_fills(
+            &self,
+            user: &str,
+            from_ms: Option<i64>,
+            to_ms: Option<i64>,
+        ) -> Result<Vec<Fill>, IngestionError> {
+            self.call().await?;
+            self.inner.get_user_fills(user, from_ms, to_ms).await
+        }
+
+        async fn get_clearinghouse_state(
+            &self,
+            user: &str,
+        ) -> Result<ClearinghouseState, IngestionError> {
+            self.call().await?;
+            self.inner.get_clearinghouse_state(user).await
+        }
+
+        async fn get_user_balances(&self, user: &str) -> Result<Vec<UserBalance>, IngestionError> {
+            self.call().await?;
+            self.inner.get_user_balances(user).await
+        }
+
+        async fn get_account_value_history(
+            &self,
+            user: &str,
+        ) -> Result<Vec<AccountValuePoint>, IngestionError> {
+            self.call().await?;
+            self.inner.get_account_value_history(user).await
+        }
+
+        async fn get_ledger_updates(
+            &self,
+            user: &str,
+            from_ms: Option<i64>,
+            to_ms: Option<i64>,
+        ) -> Result<Vec<LedgerUpdate>, IngestionError> {
+            self.call().await?;
+            self.inner.get_ledger_updates(user, from_ms, to_ms).await
+        }
+    }
+
+    fn short_ttls() -> CacheTtls {
+        CacheTtls {
+            user_fills: Duration::from_millis(50),
+            ledger_updates: Duration::from_millis(50),
+            ..CacheTtls::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repeated_request_is_cached() {
+        let source = CachingSource::new(CountingSource::default());
+
+        source.get_user_fills("0xABC", Some(0), None).await.unwrap();
+        source.get_user_fills("0xabc", Some(0), None).await.unwrap();
+        // A different window is a different request
+        source.get_user_fills("0xabc", Some(1), None).await.unwrap();
+
+        assert_eq!(source.inner().calls(), 2);
+        let stats = source.stats();
+        assert_eq!((stats.hits, stats.misses, stats.coalesced), (1, 2, 0));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_are_coalesced() {
+        let source = CachingSource::new(CountingSource::slow(Duration::from_millis(50)))
+            .with_ttls(CacheTtls::disabled());
+
+        let requests = (0..10).map(|_| source.get_user_balances("0xabc"));
+        let results = futures::future::join_all(requests).await;
+
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(source.inner().calls(), 1);
+        let stats = source.stats();
+        assert_eq!((stats.misses, stats.coalesced), (1, 9));
+
+        // Nothing is kept once the shared request is done
+        source.get_user_balances("0xabc").await.unwrap();
+        assert_eq!(source.inner().calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_shared_but_not_cached() {
+        let source = CachingSource::new(CountingSource {
+            delay: Duration::from_millis(50),
+            fail: true,
+            ..CountingSource::default()
+        });
+
+        let (a, b) = tokio::join!(
+            source.get_clearinghouse_state("0xabc"),
+            source.get_clearinghouse_state("0xabc"),
+        );
+        assert!(matches!(a, Err(IngestionError::Network(_))));
+        assert!(matches!(b, Err(IngestionError::Network(_))));
+        assert_eq!(source.inner().calls(), 1);
+
+        assert!(source.get_clearinghouse_state("0xabc").await.is_err());
+        assert_eq!(source.inner().calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_entries_expire_after_ttl() {
+        let source = CachingSource::new(CountingSource::default()).with_ttls(short_ttls());
+        let now_ms = chrono::Utc::now().timestamp_millis();
+
+        source.get_user_fills("0xabc", Some(0), None).await.unwrap();
+        source
+            .get_ledger_updates("0xabc", None, Some(now_ms))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        source.get_user_fills("0xabc", Some(0), None).await.unwrap();
+        source
+            .get_ledger_updates("0xabc", None, Some(now_ms))
+            .await
+            .unwrap();
+
+        assert_eq!(source.inner().calls(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_closed_windows_outlive_ttl() {
+        let source = CachingSource::new(CountingSource::default()).with_ttls(short_ttls());
+        let day_ago = chrono::Utc::now().timestamp_millis() - 86_400_000;
+
+        source
+            .get_user_fills("0xabc", Some(0), Some(day_ago))
+            .await
+            .unwrap();
+        source
+            .get_ledger_updates("0xabc", None, Some(day_ago))
+            .await
+            .unwrap();
+        // Recent fills without a start can still change
+        source
+            .get_user_fills("0xabc", None, Some(day_ago))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        source
+            .get_user_fills("0xabc", Some(0), Some(day_ago))
+            .await
+            .unwrap();
+        source
+            .get_ledger_updates("0xabc", None, Some(day_ago))
+            .await
+            .unwrap();
+        source
+            .get_user_fills("0xabc", None, Some(day_ago))
+            .await
+            .unwrap();
+        assert_eq!(source.inner().calls(), 4);
+
+        source.clear();
+        source
+            .get_user_fills("0xabc", Some(0), Some(day_ago))
+            .await
+            .unwrap();
+        assert_eq!(source.inner().calls(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_entry_is_evicted() {
+        let source = CachingSource::new(CountingSource::default()).with_max_entries(2);
+
+        source.get_user_balances("0xa").await.unwrap();
+        source.get_user_balances("0xb").await.unwrap();
+        // Using 0xa makes 0xb the eviction candidate
+        source.get_user_balances("0xa").await.unwrap();
+        source.get_user_balances("0xc").await.unwrap();
+        assert_eq!(source.inner().calls(), 3);
+
+        source.get_user_balances("0xa").await.unwrap();
+        source.get_user_balances("0xc").await.unwrap();
+        assert_eq!(source.inner().calls(), 3);
+        source.get_user_balances("0xb").await.unwrap();
+        assert_eq!(source.inner().calls(), 4);
+    }
+}
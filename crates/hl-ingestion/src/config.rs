@@ -1,13 +1,32 @@
 //! Configuration for the ingestion layer.
 //!
-//! Minimal configuration - hypersdk handles RPC URLs internally,
-//! so we only need to specify mainnet vs testnet.
+//! [`Network`] selects the chain (mainnet vs testnet). [`Endpoints`] says
+//! where its services are reached, defaulting to Hyperliquid's public URLs
+//! for the network, so the stack can be pointed at a local mock server or a
+//! private node instead.
 //!
 //! # Environment Variables
 //!
 //! - `HL_NETWORK`: "mainnet" or "testnet" (default: mainnet)
+//! - `HL_ENDPOINTS_FILE`: TOML file with endpoint URLs (see [`Endpoints`])
+//! - `HL_API_URL`: base URL serving `/info`, `/exchange`, `/ws` and stats files
+//! - `HL_INFO_URL`, `HL_EXCHANGE_URL`, `HL_WS_URL`, `HL_STATS_URL`: override
+//!   a single endpoint
 
+use crate::error::IngestionError;
+use serde::Deserialize;
 use std::env;
+use std::path::Path;
+use url::Url;
+
+/// Hyperliquid mainnet API base URL.
+const MAINNET_API_URL: &str = "https://api.hyperliquid.xyz";
+
+/// Hyperliquid testnet API base URL.
+const TESTNET_API_URL: &str = "https://api.hyperliquid-testnet.xyz";
+
+/// Base URL of the stats data server (builder fill archives).
+const STATS_URL: &str = "https://stats-data.hyperliquid.xyz";
 
 /// Network selection for Hyperliquid.
 ///
@@ -67,6 +86,250 @@ impl Network {
     }
 }
 
+/// URLs of the Hyperliquid services for one deployment.
+///
+/// Defaults come from [`Endpoints::for_network`]. A TOML file or environment
+/// variables can replace any of them; `api_url` is a shorthand for a server
+/// that serves everything from one base URL, like a local mock or a node
+/// behind a path prefix:
+///
+/// ```toml
+/// api_url = "http://localhost:8080"
+/// # Individual URLs win over api_url
+/// stats_url = "http://localhost:9000"
+/// ```
+///
+/// HTTP URLs must use `http` or `https`, and the WebSocket URL `ws` or
+/// `wss`.
+///
+/// # Example
+///
+/// ```rust
+/// use hl_ingestion::{Endpoints, Network};
+///
+/// let endpoints = Endpoints::custom("http://localhost:8080".parse().unwrap());
+/// assert_eq!(endpoints.ws_url.as_str(), "ws://localhost:8080/ws");
+///
+/// let prefixed = Endpoints::custom("https://node.example/hl".parse().unwrap());
+/// assert_eq!(prefixed.ws_url.as_str(), "wss://node.example/hl/ws");
+/// assert_eq!(Endpoints::for_network(Network::Mainnet), Endpoints::mainnet());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoints {
+    /// Base URL of the HTTP API serving `/info`.
+    pub info_url: Url,
+
+    /// Base URL serving `/exchange` (order placement and other signed
+    /// actions).
+    pub exchange_url: Url,
+
+    /// WebSocket URL for subscriptions.
+    pub ws_url: Url,
+
+    /// Base URL of the stats data server; builder fill archives live under
+    /// `<stats_url>/<Mainnet|Testnet>/builder_fills/`.
+    pub stats_url: Url,
+}
+
+impl Endpoints {
+    /// Hyperliquid's public mainnet endpoints.
+    pub fn mainnet() -> Self {
+        Self::custom(Url::parse(MAINNET_API_URL).expect("mainnet URL is valid"))
+            .with_stats_url(Url::parse(STATS_URL).expect("stats URL is valid"))
+    }
+
+    /// Hyperliquid's public testnet endpoints.
+    pub fn testnet() -> Self {
+        Self::custom(Url::parse(TESTNET_API_URL).expect("testnet URL is valid"))
+            .with_stats_url(Url::parse(STATS_URL).expect("stats URL is valid"))
+    }
+
+    /// Public endpoints of `network`.
+    pub fn for_network(network: Network) -> Self {
+        match network {
+            Network::Mainnet => Self::mainnet(),
+            Network::Testnet => Self::testnet(),
+        }
+    }
+
+    /// Every service served from `api_url`, with the WebSocket at `ws`
+    /// under its path.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `api_url` is not an `http` or `https` URL; see
+    /// [`Endpoints::try_custom`].
+    pub fn custom(api_url: Url) -> Self {
+        Self::try_custom(api_url).expect("api_url is an http(s) URL")
+    }
+
+    /// Every service served from `api_url`, with the WebSocket at `ws`
+    /// under its path.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Config` if `api_url` is not an `http` or `https` URL.
+    pub fn try_custom(api_url: Url) -> Result<Self, IngestionError> {
+        check_scheme("api_url", &api_url, HTTP_SCHEMES)?;
+        Ok(Self {
+            info_url: api_url.clone(),
+            exchange_url: api_url.clone(),
+            ws_url: websocket_url(&api_url)?,
+            stats_url: api_url,
+        })
+    }
+
+    /// Replace the stats data URL.
+    pub fn with_stats_url(mut self, url: Url) -> Self {
+        self.stats_url = url;
+        self
+    }
+
+    /// Replace the exchange URL.
+    pub fn with_exchange_url(mut self, url: Url) -> Self {
+        self.exchange_url = url;
+        self
+    }
+
+    /// Replace the WebSocket URL.
+    pub fn with_ws_url(mut self, url: Url) -> Self {
+        self.ws_url = url;
+        self
+    }
+
+    /// Load endpoints for `network` from the environment.
+    ///
+    /// Starts from the network's public endpoints, applies
+    /// `HL_ENDPOINTS_FILE` if set, then the `HL_*_URL` variables.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Config` if the file cannot be read or parsed, or a URL is
+    /// invalid or has the wrong scheme.
+    pub fn from_env(network: Network) -> Result<Self, IngestionError> {
+        let mut endpoints = match env::var("HL_ENDPOINTS_FILE") {
+            Ok(path) => Self::from_file(network, path)?,
+            Err(_) => Self::for_network(network),
+        };
+        let overrides = EndpointOverrides {
+            api_url: env::var("HL_API_URL").ok(),
+            info_url: env::var("HL_INFO_URL").ok(),
+            exchange_url: env::var("HL_EXCHANGE_URL").ok(),
+            ws_url: env::var("HL_WS_URL").ok(),
+            stats_url: env::var("HL_STATS_URL").ok(),
+        };
+        endpoints.apply(&overrides)?;
+        Ok(endpoints)
+    }
+
+    /// Load endpoints for `network` from a TOML file.
+    ///
+    /// Keys left out of the file keep the network's public endpoints.
+    pub fn from_file(network: Network, path: impl AsRef<Path>) -> Result<Self, IngestionError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            IngestionError::Config(format!("cannot read {}: {}", path.display(), e))
+        })?;
+        Self::from_toml(network, &contents)
+            .map_err(|e| IngestionError::Config(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parse endpoints for `network` from TOML.
+    pub fn from_toml(network: Network, toml: &str) -> Result<Self, IngestionError> {
+        let overrides: EndpointOverrides =
+            toml::from_str(toml).map_err(|e| IngestionError::Config(e.to_string()))?;
+        let mut endpoints = Self::for_network(network);
+        endpoints.apply(&overrides)?;
+        Ok(endpoints)
+    }
+
+    /// Apply `api_url` first, then the individual URLs.
+    fn apply(&mut self, overrides: &EndpointOverrides) -> Result<(), IngestionError> {
+        if let Some(url) = &overrides.api_url {
+            *self = Self::try_custom(parse_url("api_url", url, HTTP_SCHEMES)?)?;
+        }
+        let http_fields = [
+            ("info_url", &overrides.info_url, &mut self.info_url),
+            (
+                "exchange_url",
+                &overrides.exchange_url,
+                &mut self.exchange_url,
+            ),
+            ("stats_url", &overrides.stats_url, &mut self.stats_url),
+        ];
+        for (name, value, field) in http_fields {
+            if let Some(value) = value {
+                *field = parse_url(name, value, HTTP_SCHEMES)?;
+            }
+        }
+        if let Some(value) = &overrides.ws_url {
+            self.ws_url = parse_url("ws_url", value, WS_SCHEMES)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Self::mainnet()
+    }
+}
+
+/// Endpoint URLs given in a file or the environment.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EndpointOverrides {
+    api_url: Option<String>,
+    info_url: Option<String>,
+    exchange_url: Option<String>,
+    ws_url: Option<String>,
+    stats_url: Option<String>,
+}
+
+/// Schemes accepted for the HTTP endpoints.
+const HTTP_SCHEMES: &[&str] = &["http", "https"];
+
+/// Schemes accepted for the WebSocket endpoint.
+const WS_SCHEMES: &[&str] = &["ws", "wss"];
+
+fn parse_url(name: &str, value: &str, schemes: &[&str]) -> Result<Url, IngestionError> {
+    let url = Url::parse(value)
+        .map_err(|e| IngestionError::Config(format!("invalid {} '{}': {}", name, value, e)))?;
+    check_scheme(name, &url, schemes)?;
+    Ok(url)
+}
+
+fn check_scheme(name: &str, url: &Url, schemes: &[&str]) -> Result<(), IngestionError> {
+    if schemes.contains(&url.scheme()) {
+        return Ok(());
+    }
+    Err(IngestionError::Config(format!(
+        "invalid {} '{}': scheme must be {}",
+        name,
+        url,
+        schemes.join(" or ")
+    )))
+}
+
+/// The `ws` URL under `api_url`'s path, `wss` for `https` and `ws` for
+/// `http`.
+fn websocket_url(api_url: &Url) -> Result<Url, IngestionError> {
+    let mut url = api_url.clone();
+    let scheme = if api_url.scheme() == "https" {
+        "wss"
+    } else {
+        "ws"
+    };
+    url.set_scheme(scheme).map_err(|()| {
+        IngestionError::Config(format!("no WebSocket URL for api_url '{}'", api_url))
+    })?;
+    let path = format!("{}/ws", api_url.path().trim_end_matches('/'));
+    url.set_path(&path);
+    url.set_query(None);
+    url.set_fragment(None);
+    Ok(url)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,4 +350,83 @@ mod tests {
         assert!(Network::Mainnet.is_mainnet());
         assert!(!Network::Testnet.is_mainnet());
     }
+
+    #[test]
+    fn test_public_endpoints() {
+        let mainnet = Endpoints::mainnet();
+        assert_eq!(mainnet.info_url.as_str(), "https://api.hyperliquid.xyz/");
+        assert_eq!(mainnet.ws_url.as_str(), "wss://api.hyperliquid.xyz/ws");
+        assert_eq!(
+            mainnet.stats_url.as_str(),
+            "https://stats-data.hyperliquid.xyz/"
+        );
+
+        let testnet = Endpoints::for_network(Network::Testnet);
+        assert_eq!(
+            testnet.ws_url.as_str(),
+            "wss://api.hyperliquid-testnet.xyz/ws"
+        );
+        assert_eq!(testnet.stats_url, mainnet.stats_url);
+    }
+
+    #[test]
+    fn test_endpoints_from_toml() {
+        let endpoints = Endpoints::from_toml(
+            Network::Testnet,
+            r#"
+            api_url = "http://localhost:8080"
+            stats_url = "http://localhost:9000"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(endpoints.info_url.as_str(), "http://localhost:8080/");
+        assert_eq!(endpoints.exchange_url.as_str(), "http://localhost:8080/");
+        assert_eq!(endpoints.ws_url.as_str(), "ws://localhost:8080/ws");
+        assert_eq!(endpoints.stats_url.as_str(), "http://localhost:9000/");
+
+        // Keys left out keep the network's public endpoints
+        let partial =
+            Endpoints::from_toml(Network::Testnet, r#"ws_url = "ws://node:4000/ws""#).unwrap();
+        assert_eq!(partial.info_url, Endpoints::testnet().info_url);
+        assert_eq!(partial.ws_url.as_str(), "ws://node:4000/ws");
+    }
+
+    #[test]
+    fn test_endpoints_keep_the_base_path() {
+        let endpoints = Endpoints::from_toml(
+            Network::Mainnet,
+            r#"
+            api_url = "https://node.example/hl"
+            exchange_url = "https://signer.example/hl/"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(endpoints.info_url.as_str(), "https://node.example/hl");
+        assert_eq!(endpoints.ws_url.as_str(), "wss://node.example/hl/ws");
+        assert_eq!(
+            endpoints.exchange_url.as_str(),
+            "https://signer.example/hl/"
+        );
+    }
+
+    #[test]
+    fn test_invalid_endpoints() {
+        let err = Endpoints::from_toml(Network::Mainnet, r#"info_url = "not a url""#).unwrap_err();
+        assert!(err.to_string().contains("invalid info_url"));
+
+        assert!(Endpoints::from_toml(Network::Mainnet, r#"infourl = "http://x""#).is_err());
+
+        // A URL without an http(s) scheme is an error, not a panic
+        for toml in [
+            r#"api_url = "localhost:8080""#,
+            r#"exchange_url = "ftp://node/""#,
+            r#"ws_url = "http://node/ws""#,
+        ] {
+            let err = Endpoints::from_toml(Network::Mainnet, toml).unwrap_err();
+            assert!(err.to_string().contains("scheme must be"), "{}", err);
+        }
+        assert!(Endpoints::try_custom("localhost:8080".parse().unwrap()).is_err());
+        assert!(Endpoints::from_file(Network::Mainnet, "/nonexistent/endpoints.toml").is_err());
+    }
 }
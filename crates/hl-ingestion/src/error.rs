@@ -17,7 +17,7 @@ use thiserror::Error;
 ///
 /// The trade-off is we lose the original error chain, but for our use case
 /// (network errors from an external API) the string message is sufficient.
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum IngestionError {
     /// Network/HTTP errors from hypersdk.
     /// Contains the error message as an owned string.
@@ -44,6 +44,24 @@ pub enum IngestionError {
     /// WebSocket connection error.
     #[error("websocket error: {0}")]
     WebSocket(String),
+
+    /// The upstream is known to be failing and was not called
+    /// (see [`crate::CircuitBreakerSource`]).
+    #[error("upstream unavailable: {0}")]
+    Unavailable(String),
+}
+
+impl IngestionError {
+    /// Whether the error reflects upstream health rather than the request,
+    /// so that retrying later may succeed.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            IngestionError::Network(_)
+                | IngestionError::WebSocket(_)
+                | IngestionError::Unavailable(_)
+        )
+    }
 }
 
 // Convert from anyhow::Error (what hypersdk returns) to our error type.
@@ -25,7 +25,9 @@
 //! `userFillsByTime` which supports pagination up to 10,000 fills.
 //! Without time parameters, we fall back to hypersdk's `userFills` (max 500).
 
-use crate::{api_client::ApiClient, config::Network, error::IngestionError, DataSource};
+use crate::config::{Endpoints, Network};
+use crate::types::{merge_account_value_history, AccountValuePoint, LedgerUpdate};
+use crate::{api_client::ApiClient, error::IngestionError, DataSource};
 use hypersdk::hypercore::types::{ClearinghouseState, Fill, UserBalance};
 
 /// Production data source for Hyperliquid using hypersdk.
@@ -60,10 +62,18 @@ impl HyperliquidSource {
     /// the first API call. The client uses HTTP/2 with connection pooling
     /// for efficient request handling.
     pub fn new(network: Network) -> Self {
-        let (client, api_client) = match network {
-            Network::Mainnet => (hypersdk::hypercore::mainnet(), ApiClient::mainnet()),
-            Network::Testnet => (hypersdk::hypercore::testnet(), ApiClient::testnet()),
-        };
+        Self::with_endpoints(network, &Endpoints::for_network(network))
+    }
+
+    /// Create a source for `network` that talks to the API at
+    /// `endpoints.info_url`, e.g. a private node or a local mock.
+    pub fn with_endpoints(network: Network, endpoints: &Endpoints) -> Self {
+        let client = match network {
+            Network::Mainnet => hypersdk::hypercore::mainnet(),
+            Network::Testnet => hypersdk::hypercore::testnet(),
+        }
+        .with_url(endpoints.info_url.clone());
+        let api_client = ApiClient::new(endpoints.info_url.clone());
         Self { client, api_client }
     }
 
@@ -173,4 +183,30 @@ impl DataSource for HyperliquidSource {
         let balances = self.client.user_balances(address).await?;
         Ok(balances)
     }
+
+    /// Fetch the user's account value history via the `portfolio` endpoint.
+    ///
+    /// All spot+perp periods are merged so recent history keeps its finer
+    /// granularity.
+    async fn get_account_value_history(
+        &self,
+        user: &str,
+    ) -> Result<Vec<AccountValuePoint>, IngestionError> {
+        let address = Self::parse_address(user)?;
+        let periods = self.api_client.portfolio(address).await?;
+        Ok(merge_account_value_history(&periods))
+    }
+
+    /// Fetch deposits, withdrawals and transfers via `userNonFundingLedgerUpdates`.
+    async fn get_ledger_updates(
+        &self,
+        user: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<Vec<LedgerUpdate>, IngestionError> {
+        let address = Self::parse_address(user)?;
+        self.api_client
+            .user_non_funding_ledger_updates(address, from_ms.unwrap_or(0), to_ms)
+            .await
+    }
 }
@@ -80,6 +80,32 @@
 //! handle.stop().await;
 //! ```
 //!
+//! ## Custom Endpoints
+//!
+//! [`HyperliquidSource::with_endpoints`] and [`FillCollector::with_ws_url`]
+//! talk to a private node or a local mock instead of the public API.
+//! [`Endpoints::from_env`] builds the URLs from `HL_*` variables or a TOML
+//! file.
+//!
+//! ## Record and Replay
+//!
+//! [`RecordingSource`] wraps any source and saves each request and response
+//! to fixture files; [`ReplaySource`] serves them back offline, so a
+//! production incident can be captured once and debugged locally.
+//!
+//! ## Caching
+//!
+//! [`CachingSource`] wraps any source to reuse recent responses and to
+//! merge identical concurrent requests into one API call, which matters
+//! when leaderboard refreshes and PnL requests ask for the same users.
+//!
+//! ## Circuit Breaker
+//!
+//! [`CircuitBreakerSource`] stops calling an upstream after repeated
+//! network failures and fails fast with [`IngestionError::Unavailable`]
+//! until a trial request succeeds. Put it inside a [`CachingSource`] so
+//! cached responses are still served while it is open.
+//!
 //! ## Known Limitations
 //!
 //! ### Fill Limit (Historical API)
@@ -98,18 +124,26 @@
 //! will require an alternative data source in the future.
 
 mod api_client;
+mod breaker;
+mod cache;
 pub mod config;
 pub mod error;
 mod hyperliquid;
 mod mock;
+mod replay;
+pub mod types;
 mod ws_collector;
 
 // Re-export our types
-pub use config::Network;
+pub use breaker::{BreakerConfig, BreakerState, CircuitBreakerSource};
+pub use cache::{CacheStats, CacheTtls, CachingSource, DEFAULT_MAX_CACHE_ENTRIES};
+pub use config::{Endpoints, Network};
 pub use error::IngestionError;
 pub use hyperliquid::HyperliquidSource;
 pub use mock::MockSource;
-pub use ws_collector::{CollectorHandle, FillCollector};
+pub use replay::{RecordingSource, ReplaySource};
+pub use types::{AccountValuePoint, LedgerDelta, LedgerUpdate, PortfolioPeriod};
+pub use ws_collector::{CollectedFills, CollectorHandle, FillCollector};
 
 // Re-export hypersdk types that appear in our public API.
 // This allows downstream crates to use these types without adding
@@ -137,6 +171,9 @@ pub use hypersdk::hypercore::types::{
 ///
 /// - [`HyperliquidSource`]: Production implementation using hypersdk
 /// - [`MockSource`]: Test implementation with configurable responses
+/// - [`RecordingSource`] and [`ReplaySource`]: Capture and replay of real traffic
+/// - [`CachingSource`]: Response caching and request coalescing for any source
+/// - [`CircuitBreakerSource`]: Fails fast while the wrapped source keeps failing
 ///
 /// ## Why `Send + Sync`?
 ///
@@ -189,4 +226,53 @@ pub trait DataSource: Send + Sync {
         &self,
         user: &str,
     ) -> impl std::future::Future<Output = Result<Vec<UserBalance>, IngestionError>> + Send;
+
+    /// Fetch the user's account value history.
+    ///
+    /// # Returns
+    ///
+    /// Account value snapshots sorted by time ascending. Granularity is finer
+    /// for recent history (the API samples older periods more sparsely).
+    ///
+    /// The default implementation returns [`IngestionError::NoData`], so
+    /// sources written before this method existed keep compiling.
+    fn get_account_value_history(
+        &self,
+        user: &str,
+    ) -> impl std::future::Future<Output = Result<Vec<AccountValuePoint>, IngestionError>> + Send
+    {
+        let _ = user;
+        async {
+            Err(IngestionError::NoData(
+                "account value history not supported by this source".to_string(),
+            ))
+        }
+    }
+
+    /// Fetch non-funding ledger updates (deposits, withdrawals, transfers).
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The user's address as a hex string
+    /// * `from_ms` - Optional start of time window (inclusive); defaults to the beginning
+    /// * `to_ms` - Optional end of time window (inclusive)
+    ///
+    /// # Returns
+    ///
+    /// Ledger updates sorted by time ascending.
+    ///
+    /// The default implementation returns [`IngestionError::NoData`].
+    fn get_ledger_updates(
+        &self,
+        user: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> impl std::future::Future<Output = Result<Vec<LedgerUpdate>, IngestionError>> + Send {
+        let _ = (user, from_ms, to_ms);
+        async {
+            Err(IngestionError::NoData(
+                "ledger updates not supported by this source".to_string(),
+            ))
+        }
+    }
 }
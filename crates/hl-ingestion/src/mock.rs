@@ -14,6 +14,7 @@
 //! let fills = mock.get_user_fills("0x...", None, None).await?;
 //! ```
 
+use crate::types::{AccountValuePoint, LedgerUpdate};
 use crate::{error::IngestionError, DataSource};
 use hypersdk::hypercore::types::{ClearinghouseState, Fill, UserBalance};
 
@@ -37,6 +38,12 @@ pub struct MockSource {
 
     /// User balances to return from `get_user_balances`.
     pub user_balances: Vec<UserBalance>,
+
+    /// Account value history to return from `get_account_value_history`.
+    pub account_value_history: Vec<AccountValuePoint>,
+
+    /// Ledger updates to return from `get_ledger_updates`.
+    pub ledger_updates: Vec<LedgerUpdate>,
 }
 
 impl MockSource {
@@ -68,6 +75,18 @@ impl MockSource {
         self.user_balances = balances;
         self
     }
+
+    /// Set the account value history to return (builder pattern).
+    pub fn with_account_value_history(mut self, history: Vec<AccountValuePoint>) -> Self {
+        self.account_value_history = history;
+        self
+    }
+
+    /// Set the ledger updates to return (builder pattern).
+    pub fn with_ledger_updates(mut self, updates: Vec<LedgerUpdate>) -> Self {
+        self.ledger_updates = updates;
+        self
+    }
 }
 
 impl DataSource for MockSource {
@@ -106,6 +125,32 @@ impl DataSource for MockSource {
     async fn get_user_balances(&self, _user: &str) -> Result<Vec<UserBalance>, IngestionError> {
         Ok(self.user_balances.clone())
     }
+
+    async fn get_account_value_history(
+        &self,
+        _user: &str,
+    ) -> Result<Vec<AccountValuePoint>, IngestionError> {
+        Ok(self.account_value_history.clone())
+    }
+
+    async fn get_ledger_updates(
+        &self,
+        _user: &str,
+        from_ms: Option<i64>,
+        to_ms: Option<i64>,
+    ) -> Result<Vec<LedgerUpdate>, IngestionError> {
+        let updates = self
+            .ledger_updates
+            .iter()
+            .filter(|u| {
+                let t = u.time as i64;
+                from_ms.is_none_or(|from| t >= from) && to_ms.is_none_or(|to| t <= to)
+            })
+            .cloned()
+            .collect();
+
+        Ok(updates)
+    }
 }
 
 #[cfg(test)]
@@ -125,4 +170,42 @@ mod tests {
         let result = mock.get_clearinghouse_state("0x123").await;
         assert!(result.is_err());
     }
+
+    /// A source implementing only the required methods.
+    struct FillsOnly(MockSource);
+
+    impl DataSource for FillsOnly {
+        async fn get_user_fills(
+            &self,
+            user: &str,
+            from_ms: Option<i64>,
+            to_ms: Option<i64>,
+        ) -> Result<Vec<Fill>, IngestionError> {
+            self.0.get_user_fills(user, from_ms, to_ms).await
+        }
+
+        async fn get_clearinghouse_state(
+            &self,
+            user: &str,
+        ) -> Result<ClearinghouseState, IngestionError> {
+            self.0.get_clearinghouse_state(user).await
+        }
+
+        async fn get_user_balances(&self, user: &str) -> Result<Vec<UserBalance>, IngestionError> {
+            self.0.get_user_balances(user).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_methods_report_no_data() {
+        let source = FillsOnly(MockSource::new());
+        assert!(matches!(
+            source.get_account_value_history("0x123").await,
+            Err(IngestionError::NoData(_))
+        ));
+        assert!(matches!(
+            source.get_ledger_updates("0x123", None, None).await,
+            Err(IngestionError::NoData(_))
+        ));
+    }
 }
@@ -0,0 +1,245 @@
+//! Response types for info endpoints that hypersdk does not model.
+//!
+//! These mirror the JSON returned by the Hyperliquid `/info` endpoint as
+//! closely as possible. Amounts arrive as decimal strings and are parsed into
+//! [`Decimal`] so callers never have to deal with float rounding.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Account value at a point in time, from the `portfolio` endpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountValuePoint {
+    /// Snapshot time in milliseconds since epoch.
+    pub time: u64,
+
+    /// Total account value (USDC) at that time.
+    pub account_value: Decimal,
+}
+
+/// Account value and PnL history for one period of the `portfolio` response.
+///
+/// The API returns a list of `[period, PortfolioPeriod]` pairs where period is
+/// one of `day`, `week`, `month`, `allTime` (and `perp*` variants that only
+/// cover the perp account). Shorter periods have finer granularity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioPeriod {
+    /// Account values as `(timestamp_ms, value)` pairs.
+    pub account_value_history: Vec<(u64, Decimal)>,
+
+    /// Cumulative PnL as `(timestamp_ms, value)` pairs.
+    pub pnl_history: Vec<(u64, Decimal)>,
+
+    /// Traded volume over the period.
+    pub vlm: Decimal,
+}
+
+/// Merge the account value histories of a `portfolio` response into one
+/// ascending series.
+///
+/// Combines the spot+perp periods (`day`, `week`, `month`, `allTime`) so that
+/// recent history keeps its fine granularity while older history is still
+/// covered. `perp*` periods are skipped since they exclude spot balances.
+pub fn merge_account_value_history(
+    periods: &[(String, PortfolioPeriod)],
+) -> Vec<AccountValuePoint> {
+    let mut by_time = std::collections::BTreeMap::new();
+    for (name, period) in periods {
+        if name.starts_with("perp") {
+            continue;
+        }
+        for (time, value) in &period.account_value_history {
+            by_time.insert(*time, *value);
+        }
+    }
+
+    by_time
+        .into_iter()
+        .map(|(time, account_value)| AccountValuePoint {
+            time,
+            account_value,
+        })
+        .collect()
+}
+
+/// A non-funding ledger update (deposit, withdrawal, transfer, ...).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerUpdate {
+    /// Time of the update in milliseconds since epoch.
+    pub time: u64,
+
+    /// Transaction hash.
+    pub hash: String,
+
+    /// What happened.
+    pub delta: LedgerDelta,
+}
+
+/// The balance change carried by a [`LedgerUpdate`].
+///
+/// Only the variants that move capital in or out of an account are modelled
+/// in detail. Everything else (vault actions, liquidations, rewards, ...)
+/// deserializes as [`LedgerDelta::Other`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LedgerDelta {
+    /// USDC deposited from the bridge.
+    Deposit {
+        /// Amount deposited.
+        usdc: Decimal,
+    },
+
+    /// USDC withdrawn to the bridge.
+    Withdraw {
+        /// Amount withdrawn.
+        usdc: Decimal,
+        /// Withdrawal fee.
+        #[serde(default)]
+        fee: Decimal,
+    },
+
+    /// USDC sent between two accounts.
+    InternalTransfer {
+        /// Amount transferred.
+        usdc: Decimal,
+        /// Sender address.
+        user: String,
+        /// Recipient address.
+        destination: String,
+        /// Transfer fee.
+        #[serde(default)]
+        fee: Decimal,
+    },
+
+    /// USDC moved between a master account and one of its sub-accounts.
+    SubAccountTransfer {
+        /// Amount transferred.
+        usdc: Decimal,
+        /// Sender address.
+        user: String,
+        /// Recipient address.
+        destination: String,
+    },
+
+    /// USDC moved between the spot and perp balances of the same account.
+    #[serde(rename_all = "camelCase")]
+    AccountClassTransfer {
+        /// Amount transferred.
+        usdc: Decimal,
+        /// True if moved from spot to perp.
+        to_perp: bool,
+    },
+
+    /// Any other ledger update.
+    #[serde(other)]
+    Other,
+}
+
+impl LedgerDelta {
+    /// Net capital flow into `user`'s account (negative for outflows).
+    ///
+    /// Spot/perp class transfers and unmodelled updates are not external
+    /// flows and return zero.
+    pub fn net_flow_for(&self, user: &str) -> Decimal {
+        match self {
+            Self::Deposit { usdc } => *usdc,
+            Self::Withdraw { usdc, .. } => -*usdc,
+            Self::InternalTransfer {
+                usdc,
+                user: from,
+                destination,
+                ..
+            }
+            | Self::SubAccountTransfer {
+                usdc,
+                user: from,
+                destination,
+            } => {
+                if destination.eq_ignore_ascii_case(user) {
+                    *usdc
+                } else if from.eq_ignore_ascii_case(user) {
+                    -*usdc
+                } else {
+                    Decimal::ZERO
+                }
+            }
+            Self::AccountClassTransfer { .. } | Self::Other => Decimal::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ledger_update_deserialization() {
+        let json = r#"[
+            {"time": 1, "hash": "0x01", "delta": {"type": "deposit", "usdc": "1000.5"}},
+            {"time": 2, "hash": "0x02", "delta": {"type": "withdraw", "usdc": "100", "nonce": 7, "fee": "1"}},
+            {"time": 3, "hash": "0x03", "delta": {"type": "accountClassTransfer", "usdc": "50", "toPerp": true}},
+            {"time": 4, "hash": "0x04", "delta": {"type": "vaultDeposit", "vault": "0xv", "usdc": "10"}}
+        ]"#;
+
+        let updates: Vec<LedgerUpdate> = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            updates[0].delta,
+            LedgerDelta::Deposit {
+                usdc: Decimal::new(10005, 1)
+            }
+        );
+        assert!(matches!(updates[1].delta, LedgerDelta::Withdraw { .. }));
+        assert!(matches!(
+            updates[2].delta,
+            LedgerDelta::AccountClassTransfer { to_perp: true, .. }
+        ));
+        assert_eq!(updates[3].delta, LedgerDelta::Other);
+    }
+
+    #[test]
+    fn test_net_flow_for_transfers() {
+        let transfer = LedgerDelta::InternalTransfer {
+            usdc: Decimal::from(25),
+            user: "0xaaa".to_string(),
+            destination: "0xBBB".to_string(),
+            fee: Decimal::ONE,
+        };
+        assert_eq!(transfer.net_flow_for("0xaaa"), Decimal::from(-25));
+        assert_eq!(transfer.net_flow_for("0xbbb"), Decimal::from(25));
+        assert_eq!(transfer.net_flow_for("0xccc"), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_merge_account_value_history() {
+        let period = |points: Vec<(u64, i64)>| PortfolioPeriod {
+            account_value_history: points
+                .into_iter()
+                .map(|(t, v)| (t, Decimal::from(v)))
+                .collect(),
+            pnl_history: Vec::new(),
+            vlm: Decimal::ZERO,
+        };
+        let periods = vec![
+            ("day".to_string(), period(vec![(300, 30), (200, 20)])),
+            ("allTime".to_string(), period(vec![(100, 10), (300, 30)])),
+            ("perpDay".to_string(), period(vec![(250, 99)])),
+        ];
+
+        let merged = merge_account_value_history(&periods);
+        let times: Vec<u64> = merged.iter().map(|p| p.time).collect();
+        assert_eq!(times, vec![100, 200, 300]);
+    }
+
+    #[test]
+    fn test_portfolio_deserialization() {
+        let json = r#"[["day", {"accountValueHistory": [[1000, "10.5"]], "pnlHistory": [[1000, "0.0"]], "vlm": "0.0"}]]"#;
+        let periods: Vec<(String, PortfolioPeriod)> = serde_json::from_str(json).unwrap();
+        assert_eq!(periods[0].0, "day");
+        assert_eq!(
+            periods[0].1.account_value_history,
+            vec![(1000, Decimal::new(105, 1))]
+        );
+    }
+}
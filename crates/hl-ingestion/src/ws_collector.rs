@@ -27,18 +27,26 @@
 //! ```
 
 use crate::error::IngestionError;
-use crate::Network;
+use crate::{Endpoints, Network};
 use hypersdk::hypercore::types::{Fill, Incoming, Subscription};
 use hypersdk::hypercore::ws::Connection;
 use hypersdk::Address;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, watch, RwLock};
 use url::Url;
 
-/// WebSocket URLs for Hyperliquid.
-const MAINNET_WS_URL: &str = "wss://api.hyperliquid.xyz/ws";
-const TESTNET_WS_URL: &str = "wss://api.hyperliquid-testnet.xyz/ws";
+/// Batches of new fills a lagging subscriber may fall behind by.
+const NEW_FILLS_CAPACITY: usize = 256;
+
+/// Fills stored for the first time by a running collector.
+#[derive(Debug, Clone)]
+pub struct CollectedFills {
+    /// The user the collector was started for.
+    pub user: String,
+    /// The new fills, in the order they were received.
+    pub fills: Vec<Fill>,
+}
 
 /// A collector that captures fills in real-time via WebSocket.
 ///
@@ -49,10 +57,16 @@ const TESTNET_WS_URL: &str = "wss://api.hyperliquid-testnet.xyz/ws";
 pub struct FillCollector {
     /// Network to connect to.
     network: Network,
+    /// WebSocket URL to connect to.
+    ws_url: Url,
     /// Thread-safe storage for fills, keyed by trade ID.
     fills: Arc<RwLock<HashMap<u64, Fill>>>,
     /// Whether the collector is currently running.
     is_running: Arc<RwLock<bool>>,
+    /// Publishes the number of stored fills whenever new fills arrive.
+    fill_updates: Arc<watch::Sender<usize>>,
+    /// Publishes each batch of fills not seen before.
+    new_fills: broadcast::Sender<CollectedFills>,
 }
 
 impl FillCollector {
@@ -60,8 +74,11 @@ impl FillCollector {
     pub fn new(network: Network) -> Self {
         Self {
             network,
+            ws_url: Endpoints::for_network(network).ws_url,
             fills: Arc::new(RwLock::new(HashMap::new())),
             is_running: Arc::new(RwLock::new(false)),
+            fill_updates: Arc::new(watch::channel(0).0),
+            new_fills: broadcast::channel(NEW_FILLS_CAPACITY).0,
         }
     }
 
@@ -75,12 +92,15 @@ impl FillCollector {
         Self::new(Network::Testnet)
     }
 
-    /// Get the WebSocket URL for the configured network.
-    fn ws_url(&self) -> Url {
-        match self.network {
-            Network::Mainnet => Url::parse(MAINNET_WS_URL).expect("valid mainnet ws url"),
-            Network::Testnet => Url::parse(TESTNET_WS_URL).expect("valid testnet ws url"),
-        }
+    /// Connect to `url` instead of the network's public WebSocket.
+    pub fn with_ws_url(mut self, url: Url) -> Self {
+        self.ws_url = url;
+        self
+    }
+
+    /// Get the WebSocket URL the collector connects to.
+    pub fn ws_url(&self) -> &Url {
+        &self.ws_url
     }
 
     /// Start collecting fills for the specified user.
@@ -112,8 +132,7 @@ impl FillCollector {
         }
 
         // Create connection
-        let url = self.ws_url();
-        let connection = Connection::new(url);
+        let connection = Connection::new(self.ws_url.clone());
 
         // Subscribe to user fills
         connection.subscribe(Subscription::UserFills { user: user_address });
@@ -123,6 +142,8 @@ impl FillCollector {
         // Spawn background task
         let fills_store = self.fills.clone();
         let is_running = self.is_running.clone();
+        let fill_updates = self.fill_updates.clone();
+        let new_fills = self.new_fills.clone();
         let user_str = user.to_string();
 
         let task_handle = tokio::spawn(async move {
@@ -130,6 +151,9 @@ impl FillCollector {
 
             let mut conn = connection;
             let mut total_received = 0usize;
+            // The first `userFills` message after (re)subscribing is a
+            // snapshot of past fills
+            let mut snapshot = true;
 
             loop {
                 // Check if we should stop
@@ -145,12 +169,19 @@ impl FillCollector {
                 match conn.next().await {
                     Some(Incoming::UserFills { user: _, fills }) => {
                         let fill_count = fills.len();
+                        let is_snapshot = std::mem::take(&mut snapshot);
                         if fill_count > 0 {
                             let mut store = fills_store.write().await;
-                            for fill in fills {
-                                store.insert(fill.tid, fill);
-                            }
+                            let unseen = store_fills(&mut store, fills, is_snapshot);
                             total_received += fill_count;
+                            fill_updates.send_replace(store.len());
+                            if !unseen.is_empty() {
+                                // Nobody may be listening; that is fine
+                                let _ = new_fills.send(CollectedFills {
+                                    user: user_str.clone(),
+                                    fills: unseen,
+                                });
+                            }
                             tracing::debug!(
                                 "Received {} fills for {}, total stored: {}",
                                 fill_count,
@@ -171,6 +202,7 @@ impl FillCollector {
                     None => {
                         // Connection closed, hypersdk should auto-reconnect
                         tracing::warn!("WebSocket connection closed, waiting for reconnect...");
+                        snapshot = true;
                         tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
                     }
                 }
@@ -204,6 +236,26 @@ impl FillCollector {
     /// Clear all collected fills.
     pub async fn clear(&self) {
         self.fills.write().await.clear();
+        self.fill_updates.send_replace(0);
+    }
+
+    /// Subscribe to fill updates.
+    ///
+    /// The receiver is notified every time new fills are stored and holds the
+    /// current number of stored fills. Use [`watch::Receiver::changed`] to wait
+    /// for the next batch.
+    pub fn subscribe_updates(&self) -> watch::Receiver<usize> {
+        self.fill_updates.subscribe()
+    }
+
+    /// Subscribe to new fills.
+    ///
+    /// Every batch of fills the collector stores for the first time is sent
+    /// to each subscriber. A subscriber that falls more than a few hundred
+    /// batches behind skips the oldest and sees
+    /// [`broadcast::error::RecvError::Lagged`].
+    pub fn subscribe_fills(&self) -> broadcast::Receiver<CollectedFills> {
+        self.new_fills.subscribe()
     }
 
     /// Check if the collector is currently running.
@@ -262,6 +314,28 @@ impl CollectorHandle {
     }
 }
 
+/// Store a batch of received fills, returning those to publish as new.
+///
+/// Fills already stored, e.g. replayed after a reconnect, are not new. A
+/// snapshot holds past fills, so only those newer than every stored fill
+/// are new: none on the first connection, and the ones missed while
+/// disconnected on a reconnect.
+fn store_fills(store: &mut HashMap<u64, Fill>, fills: Vec<Fill>, snapshot: bool) -> Vec<Fill> {
+    let latest = if snapshot {
+        store.values().map(|f| f.time).max()
+    } else {
+        None
+    };
+    let mut unseen = Vec::new();
+    for fill in fills {
+        let new = !snapshot || latest.is_some_and(|time| fill.time > time);
+        if store.insert(fill.tid, fill.clone()).is_none() && new {
+            unseen.push(fill);
+        }
+    }
+    unseen
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -275,10 +349,16 @@ mod tests {
     #[test]
     fn test_ws_url() {
         let mainnet = FillCollector::mainnet();
-        assert_eq!(mainnet.ws_url().as_str(), MAINNET_WS_URL);
+        assert_eq!(mainnet.ws_url().as_str(), "wss://api.hyperliquid.xyz/ws");
 
         let testnet = FillCollector::testnet();
-        assert_eq!(testnet.ws_url().as_str(), TESTNET_WS_URL);
+        assert_eq!(
+            testnet.ws_url().as_str(),
+            "wss://api.hyperliquid-testnet.xyz/ws"
+        );
+
+        let local = FillCollector::mainnet().with_ws_url("ws://127.0.0.1:8080/ws".parse().unwrap());
+        assert_eq!(local.ws_url().as_str(), "ws://127.0.0.1:8080/ws");
     }
 
     #[tokio::test]
@@ -287,6 +367,45 @@ mod tests {
         assert_eq!(collector.fill_count().await, 0);
     }
 
+    #[tokio::test]
+    async fn test_subscribe_updates_starts_at_zero() {
+        let collector = FillCollector::mainnet();
+        let updates = collector.subscribe_updates();
+        assert_eq!(*updates.borrow(), 0);
+    }
+
+    fn fill(time: u64, tid: u64) -> Fill {
+        serde_json::from_value(serde_json::json!({
+            "coin": "BTC", "px": "50000.0", "sz": "0.01", "side": "B", "time": time,
+            "startPosition": "0.0", "dir": "Open Long", "closedPnl": "0.0",
+            "hash": "0x0", "oid": tid, "crossed": true, "fee": "0.2", "tid": tid,
+            "feeToken": "USDC",
+        }))
+        .unwrap()
+    }
+
+    fn tids(fills: &[Fill]) -> Vec<u64> {
+        fills.iter().map(|f| f.tid).collect()
+    }
+
+    #[test]
+    fn test_snapshots_are_not_published() {
+        let mut store = HashMap::new();
+
+        let published = store_fills(&mut store, vec![fill(1_000, 1), fill(2_000, 2)], true);
+        assert!(published.is_empty());
+        assert_eq!(store.len(), 2);
+
+        let published = store_fills(&mut store, vec![fill(2_000, 2), fill(3_000, 3)], false);
+        assert_eq!(tids(&published), vec![3]);
+
+        // A reconnect snapshot publishes only what was missed meanwhile
+        let snapshot = vec![fill(500, 0), fill(3_000, 3), fill(4_000, 4)];
+        let published = store_fills(&mut store, snapshot, true);
+        assert_eq!(tids(&published), vec![4]);
+        assert_eq!(store.len(), 5);
+    }
+
     #[tokio::test]
     async fn test_clear() {
         let collector = FillCollector::mainnet();
@@ -163,3 +163,44 @@ async fn test_invalid_address_handling() {
         Err(e) => println!("   Got expected error: {}", e),
     }
 }
+
+#[tokio::test]
+#[ignore] // Requires network access to Hyperliquid API
+async fn test_fetch_account_value_history() {
+    println!("Fetching account value history...");
+    let source = HyperliquidSource::mainnet();
+
+    match source.get_account_value_history(TEST_ADDRESS).await {
+        Ok(history) => {
+            println!("   Success! Got {} points.", history.len());
+            if let (Some(first), Some(last)) = (history.first(), history.last()) {
+                println!("   Oldest: {} @ {}", first.account_value, first.time);
+                println!("   Newest: {} @ {}", last.account_value, last.time);
+            }
+            assert!(history.windows(2).all(|w| w[0].time < w[1].time));
+        }
+        Err(e) => {
+            panic!("Failed to fetch account value history: {}", e);
+        }
+    }
+}
+
+#[tokio::test]
+#[ignore] // Requires network access to Hyperliquid API
+async fn test_fetch_ledger_updates() {
+    println!("Fetching ledger updates...");
+    let source = HyperliquidSource::mainnet();
+
+    match source.get_ledger_updates(TEST_ADDRESS, None, None).await {
+        Ok(updates) => {
+            println!("   Success! Got {} updates.", updates.len());
+            for update in updates.iter().take(5) {
+                println!("     - {} {:?}", update.time, update.delta);
+            }
+            assert!(updates.windows(2).all(|w| w[0].time <= w[1].time));
+        }
+        Err(e) => {
+            panic!("Failed to fetch ledger updates: {}", e);
+        }
+    }
+}
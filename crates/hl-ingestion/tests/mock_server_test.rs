@@ -0,0 +1,301 @@
+//! End-to-end tests against a local mock of the Hyperliquid API.
+//!
+//! Unlike `fetch_data` and `ws_collector`, these need no network access and
+//! run by default.
+
+use hl_ingestion::{
+    DataSource, Endpoints, FillCollector, HyperliquidSource, LedgerDelta, LedgerUpdate, Network,
+};
+use hl_mock_server::{sample_fill, Fill, MockHyperliquid, MockServer, WsEvent};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::time::Duration;
+
+const USER: &str = "0x00000000000000000000000000000000000000a1";
+
+/// `count` fills one second apart, starting at 1s.
+fn fills(count: u64) -> Vec<Fill> {
+    (1..=count).map(|i| sample_fill(i * 1_000, i)).collect()
+}
+
+fn source(server: &MockServer) -> HyperliquidSource {
+    HyperliquidSource::with_endpoints(Network::Mainnet, &Endpoints::custom(server.url()))
+}
+
+#[tokio::test]
+async fn test_fills_by_time_paginates() {
+    let server = MockHyperliquid::new()
+        .with_fills(USER, fills(4_500))
+        .start()
+        .await
+        .unwrap();
+
+    let fetched = source(&server)
+        .get_user_fills(USER, Some(0), None)
+        .await
+        .unwrap();
+
+    assert_eq!(fetched.len(), 4_500);
+    assert!(fetched.windows(2).all(|w| w[0].time > w[1].time));
+    assert_eq!(server.info_request_count("userFillsByTime"), 3);
+}
+
+#[tokio::test]
+async fn test_fills_by_time_stops_at_limit() {
+    let server = MockHyperliquid::new()
+        .with_fills(USER, fills(12_000))
+        .start()
+        .await
+        .unwrap();
+
+    let fetched = source(&server)
+        .get_user_fills(USER, Some(0), None)
+        .await
+        .unwrap();
+
+    // Only the 10,000 most recent fills are reachable
+    assert_eq!(fetched.len(), 10_000);
+    assert_eq!(fetched.last().unwrap().tid, 2_001);
+}
+
+#[tokio::test]
+async fn test_fills_by_time_respects_window() {
+    let server = MockHyperliquid::new()
+        .with_fills(USER, fills(100))
+        .start()
+        .await
+        .unwrap();
+
+    let fetched = source(&server)
+        .get_user_fills(USER, Some(10_000), Some(19_999))
+        .await
+        .unwrap();
+
+    let tids: Vec<u64> = fetched.iter().map(|f| f.tid).collect();
+    assert_eq!(tids, (10..=19).rev().collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_recent_fills_without_window() {
+    let server = MockHyperliquid::new()
+        .with_fills(USER, fills(2_500))
+        .start()
+        .await
+        .unwrap();
+
+    let fetched = source(&server)
+        .get_user_fills(USER, None, Some(2_400_000))
+        .await
+        .unwrap();
+
+    // userFills returns one page of the most recent fills, then the window
+    // end is applied client side
+    assert_eq!(fetched.len(), 1_900);
+    assert_eq!(server.info_request_count("userFills"), 1);
+}
+
+#[tokio::test]
+async fn test_clearinghouse_state_and_balances() {
+    let server = MockHyperliquid::new()
+        .with_clearinghouse_state(
+            USER,
+            json!({
+                "marginSummary": {
+                    "accountValue": "1250.5",
+                    "totalNtlPos": "500.0",
+                    "totalRawUsd": "750.5",
+                    "totalMarginUsed": "50.0",
+                },
+                "crossMarginSummary": {
+                    "accountValue": "1250.5",
+                    "totalNtlPos": "500.0",
+                    "totalRawUsd": "750.5",
+                    "totalMarginUsed": "50.0",
+                },
+                "crossMaintenanceMarginUsed": "12.5",
+                "withdrawable": "1200.5",
+                "assetPositions": [{
+                    "type": "oneWay",
+                    "position": {
+                        "coin": "ETH",
+                        "szi": "0.25",
+                        "leverage": { "type": "cross", "value": 10 },
+                        "entryPx": "2000.0",
+                        "positionValue": "500.0",
+                        "unrealizedPnl": "0.0",
+                        "returnOnEquity": "0.0",
+                        "liquidationPx": null,
+                        "marginUsed": "50.0",
+                        "maxLeverage": 25,
+                        "cumFunding": {
+                            "allTime": "0.0",
+                            "sinceOpen": "0.0",
+                            "sinceChange": "0.0",
+                        },
+                    },
+                }],
+                "time": 1704067200000u64,
+            }),
+        )
+        .with_spot_balances(
+            USER,
+            json!([{
+                "coin": "USDC",
+                "token": 0,
+                "hold": "0.0",
+                "total": "99.5",
+                "entryNtl": "0.0",
+            }]),
+        )
+        .start()
+        .await
+        .unwrap();
+    let source = source(&server);
+
+    let state = source.get_clearinghouse_state(USER).await.unwrap();
+    assert_eq!(state.withdrawable, Decimal::new(12005, 1));
+    assert_eq!(state.asset_positions[0].position.coin, "ETH");
+
+    let balances = source.get_user_balances(USER).await.unwrap();
+    assert_eq!(balances[0].total, Decimal::new(995, 1));
+
+    // Accounts the server knows nothing about look freshly created
+    let other = "0x00000000000000000000000000000000000000b0";
+    let state = source.get_clearinghouse_state(other).await.unwrap();
+    assert!(state.asset_positions.is_empty());
+    assert!(source.get_user_balances(other).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_account_value_history_and_ledger() {
+    let updates: Vec<LedgerUpdate> = (1..=700u64)
+        .map(|i| LedgerUpdate {
+            time: i * 1_000,
+            hash: format!("0x{:x}", i),
+            delta: LedgerDelta::Deposit {
+                usdc: Decimal::from(i),
+            },
+        })
+        .collect();
+    let server = MockHyperliquid::new()
+        .with_portfolio(
+            USER,
+            json!([
+                [
+                    "day",
+                    { "accountValueHistory": [[2000, "110.0"]], "pnlHistory": [], "vlm": "0.0" },
+                ],
+                [
+                    "allTime",
+                    { "accountValueHistory": [[1000, "100.0"]], "pnlHistory": [], "vlm": "0.0" },
+                ],
+            ]),
+        )
+        .with_ledger_updates(USER, &updates)
+        .start()
+        .await
+        .unwrap();
+    let source = source(&server);
+
+    let history = source.get_account_value_history(USER).await.unwrap();
+    let times: Vec<u64> = history.iter().map(|p| p.time).collect();
+    assert_eq!(times, vec![1000, 2000]);
+
+    let fetched = source.get_ledger_updates(USER, None, None).await.unwrap();
+    assert_eq!(fetched, updates);
+    assert_eq!(server.info_request_count("userNonFundingLedgerUpdates"), 2);
+}
+
+#[tokio::test]
+async fn test_collector_survives_disconnect() {
+    let server = MockHyperliquid::new()
+        .with_ws_script(
+            USER,
+            vec![
+                WsEvent::Fills(vec![sample_fill(1_000, 1), sample_fill(2_000, 2)]),
+                WsEvent::Disconnect,
+                // Redelivered after reconnecting, as a snapshot would be
+                WsEvent::Fills(vec![sample_fill(2_000, 2), sample_fill(3_000, 3)]),
+            ],
+        )
+        .start()
+        .await
+        .unwrap();
+
+    let collector = FillCollector::new(Network::Mainnet).with_ws_url(server.ws_url());
+    let mut updates = collector.subscribe_updates();
+    let handle = collector.start(USER).await.unwrap();
+
+    tokio::time::timeout(
+        Duration::from_secs(10),
+        updates.wait_for(|count| *count == 3),
+    )
+    .await
+    .expect("fills arrive after reconnecting")
+    .unwrap();
+    handle.stop().await;
+
+    let tids: Vec<u64> = collector.get_fills().await.iter().map(|f| f.tid).collect();
+    assert_eq!(tids, vec![1, 2, 3]);
+    assert!(server.ws_connections() >= 2);
+}
+
+#[tokio::test]
+async fn test_collector_publishes_only_new_fills() {
+    let server = MockHyperliquid::new()
+        .with_ws_script(
+            USER,
+            vec![
+                WsEvent::Fills(vec![sample_fill(1_000, 1), sample_fill(2_000, 2)]),
+                WsEvent::Disconnect,
+                WsEvent::Fills(vec![sample_fill(2_000, 2), sample_fill(3_000, 3)]),
+            ],
+        )
+        .start()
+        .await
+        .unwrap();
+
+    let collector = FillCollector::new(Network::Mainnet).with_ws_url(server.ws_url());
+    let mut new_fills = collector.subscribe_fills();
+    let handle = collector.start(USER).await.unwrap();
+
+    let mut batches = Vec::new();
+    for _ in 0..2 {
+        let batch = tokio::time::timeout(Duration::from_secs(10), new_fills.recv())
+            .await
+            .expect("fills arrive")
+            .unwrap();
+        assert_eq!(batch.user, USER);
+        batches.push(batch.fills.iter().map(|f| f.tid).collect::<Vec<_>>());
+    }
+    handle.stop().await;
+
+    // The redelivered fill 2 is not published again
+    assert_eq!(batches, vec![vec![1, 2], vec![3]]);
+}
+
+#[tokio::test]
+async fn test_collector_does_not_publish_snapshot() {
+    let server = MockHyperliquid::new()
+        .with_fills(USER, vec![sample_fill(1_000, 1), sample_fill(2_000, 2)])
+        .with_ws_script(USER, vec![WsEvent::Fills(vec![sample_fill(3_000, 3)])])
+        .start()
+        .await
+        .unwrap();
+
+    let collector = FillCollector::new(Network::Mainnet).with_ws_url(server.ws_url());
+    let mut new_fills = collector.subscribe_fills();
+    let handle = collector.start(USER).await.unwrap();
+
+    let batch = tokio::time::timeout(Duration::from_secs(10), new_fills.recv())
+        .await
+        .expect("fills arrive")
+        .unwrap();
+    handle.stop().await;
+
+    // Past fills from the subscription snapshot are stored, not published
+    let published: Vec<u64> = batch.fills.iter().map(|f| f.tid).collect();
+    assert_eq!(published, vec![3]);
+    let tids: Vec<u64> = collector.get_fills().await.iter().map(|f| f.tid).collect();
+    assert_eq!(tids, vec![1, 2, 3]);
+}
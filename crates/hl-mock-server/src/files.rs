@@ -0,0 +1,14 @@
+//! Static files, standing in for the stats-data server.
+
+use crate::{lock, SharedState};
+use axum::extract::State;
+use axum::http::{StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+
+pub(crate) async fn handle(State(state): State<SharedState>, uri: Uri) -> Response {
+    match lock(&state).files.get(uri.path()) {
+        Some(contents) => contents.clone().into_response(),
+        // The stats-data bucket answers 403, not 404, for missing objects
+        None => StatusCode::FORBIDDEN.into_response(),
+    }
+}
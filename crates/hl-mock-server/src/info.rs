@@ -0,0 +1,227 @@
+//! The `POST /info` endpoint.
+//!
+//! Requests are dispatched on their `type` field. Anything this workspace
+//! doesn't send is rejected with `422`, as the real API does for request
+//! bodies it can't parse. While the server is marked unavailable, everything
+//! gets `503`.
+
+use crate::{lock, Fill, MockState, SharedState};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde_json::{json, Value};
+
+/// Fills returned per `userFills` / `userFillsByTime` response.
+pub const FILLS_PAGE_SIZE: usize = 2000;
+
+/// How many of a user's most recent fills the API serves at all.
+pub const MAX_RETAINED_FILLS: usize = 10_000;
+
+/// Updates returned per `userNonFundingLedgerUpdates` response.
+pub const LEDGER_PAGE_SIZE: usize = 500;
+
+pub(crate) async fn handle(
+    State(state): State<SharedState>,
+    Json(request): Json<Value>,
+) -> Response {
+    let mut state = lock(&state);
+    state.info_requests.push(request.clone());
+    if state.unavailable {
+        return (StatusCode::SERVICE_UNAVAILABLE, "Service Unavailable").into_response();
+    }
+
+    match respond(&state, &request) {
+        Some(body) => Json(body).into_response(),
+        None => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Failed to deserialize the JSON body into the target type",
+        )
+            .into_response(),
+    }
+}
+
+/// The response body for `request`, or `None` if it isn't understood.
+fn respond(state: &MockState, request: &Value) -> Option<Value> {
+    let user = request["user"].as_str()?.to_lowercase();
+    let start_time = request["startTime"].as_u64();
+    let end_time = request["endTime"].as_u64();
+
+    let body = match request["type"].as_str()? {
+        "userFills" => json!(fills_page(retained_fills(state, &user), 0, None)),
+        "userFillsByTime" => json!(fills_page(
+            retained_fills(state, &user),
+            start_time?,
+            end_time
+        )),
+        "clearinghouseState" => state
+            .clearinghouse_states
+            .get(&user)
+            .cloned()
+            .unwrap_or_else(empty_clearinghouse_state),
+        "spotClearinghouseState" => json!({
+            "balances": state.spot_balances.get(&user).cloned().unwrap_or_else(|| json!([])),
+        }),
+        "portfolio" => state
+            .portfolios
+            .get(&user)
+            .cloned()
+            .unwrap_or_else(|| json!([])),
+        "userNonFundingLedgerUpdates" => {
+            let updates = state.ledger_updates.get(&user).map(Vec::as_slice);
+            json!(ledger_page(
+                updates.unwrap_or_default(),
+                start_time?,
+                end_time
+            ))
+        }
+        _ => return None,
+    };
+    Some(body)
+}
+
+/// The user's fills the API still serves, most recent first.
+pub(crate) fn retained_fills<'a>(state: &'a MockState, user: &str) -> &'a [Fill] {
+    let fills = state.fills.get(user).map(Vec::as_slice).unwrap_or_default();
+    &fills[..fills.len().min(MAX_RETAINED_FILLS)]
+}
+
+/// The most recent page of `fills` (most recent first) in `[start, end]`.
+pub(crate) fn fills_page(fills: &[Fill], start: u64, end: Option<u64>) -> Vec<Fill> {
+    fills
+        .iter()
+        .filter(|f| f.time >= start && end.is_none_or(|end| f.time <= end))
+        .take(FILLS_PAGE_SIZE)
+        .cloned()
+        .collect()
+}
+
+/// The oldest page of `updates` (oldest first) in `[start, end]`.
+fn ledger_page(updates: &[Value], start: u64, end: Option<u64>) -> Vec<Value> {
+    updates
+        .iter()
+        .filter(|u| {
+            let time = update_time(u);
+            time >= start && end.is_none_or(|end| time <= end)
+        })
+        .take(LEDGER_PAGE_SIZE)
+        .cloned()
+        .collect()
+}
+
+pub(crate) fn update_time(update: &Value) -> u64 {
+    update["time"].as_u64().unwrap_or_default()
+}
+
+/// What the API returns for an account that never traded.
+fn empty_clearinghouse_state() -> Value {
+    let summary = json!({
+        "accountValue": "0.0",
+        "totalNtlPos": "0.0",
+        "totalRawUsd": "0.0",
+        "totalMarginUsed": "0.0",
+    });
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    json!({
+        "marginSummary": summary,
+        "crossMarginSummary": summary,
+        "crossMaintenanceMarginUsed": "0.0",
+        "withdrawable": "0.0",
+        "assetPositions": [],
+        "time": now_ms,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sample_fill;
+
+    fn state_with_fills(count: u64) -> MockState {
+        let mut fills: Vec<Fill> = (1..=count).map(|i| sample_fill(i * 1_000, i)).collect();
+        fills.reverse();
+        let mut state = MockState::default();
+        state.fills.insert("0xabc".to_string(), fills);
+        state
+    }
+
+    fn tids(body: Value) -> Vec<u64> {
+        body.as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["tid"].as_u64().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_fills_by_time_pages_backwards() {
+        let state = state_with_fills(2_500);
+        let request = json!({ "type": "userFillsByTime", "user": "0xABC", "startTime": 0 });
+
+        let first = tids(respond(&state, &request).unwrap());
+        assert_eq!(first.len(), FILLS_PAGE_SIZE);
+        assert_eq!((first[0], *first.last().unwrap()), (2_500, 501));
+
+        let request = json!({
+            "type": "userFillsByTime",
+            "user": "0xabc",
+            "startTime": 0,
+            "endTime": 501 * 1_000 - 1,
+        });
+        let second = tids(respond(&state, &request).unwrap());
+        assert_eq!(second.len(), 500);
+        assert_eq!((second[0], *second.last().unwrap()), (500, 1));
+    }
+
+    #[test]
+    fn test_only_recent_fills_are_retained() {
+        let state = state_with_fills(MAX_RETAINED_FILLS as u64 + 10);
+        let request = json!({
+            "type": "userFillsByTime",
+            "user": "0xabc",
+            "startTime": 0,
+            "endTime": 20_000,
+        });
+
+        // The 10 oldest fills fall outside the retained window
+        let page = tids(respond(&state, &request).unwrap());
+        assert_eq!(page, (11..=20).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_ledger_updates_page_forwards() {
+        let mut state = MockState::default();
+        let updates = (1..=600)
+            .map(|i| json!({ "time": i, "hash": "0x0" }))
+            .collect();
+        state.ledger_updates.insert("0xabc".to_string(), updates);
+
+        let request =
+            json!({ "type": "userNonFundingLedgerUpdates", "user": "0xabc", "startTime": 50 });
+        let page = respond(&state, &request).unwrap();
+        let page = page.as_array().unwrap();
+        assert_eq!(page.len(), LEDGER_PAGE_SIZE);
+        assert_eq!(page[0]["time"], 50);
+    }
+
+    #[test]
+    fn test_unknown_user_gets_empty_account() {
+        let state = MockState::default();
+
+        let request = json!({ "type": "clearinghouseState", "user": "0xabc" });
+        let body = respond(&state, &request).unwrap();
+        assert_eq!(body["assetPositions"], json!([]));
+
+        let request = json!({ "type": "spotClearinghouseState", "user": "0xabc" });
+        assert_eq!(
+            respond(&state, &request).unwrap(),
+            json!({ "balances": [] })
+        );
+
+        let request = json!({ "type": "userFillsByTime", "user": "0xabc" });
+        assert_eq!(respond(&state, &request), None, "startTime is required");
+    }
+}
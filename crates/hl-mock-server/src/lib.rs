@@ -0,0 +1,410 @@
+//! # hl-mock-server
+//!
+//! A local stand-in for the Hyperliquid API, so ingestion, the WebSocket
+//! collector and builder-data downloads can be tested end to end offline.
+//!
+//! One server answers everything on `127.0.0.1`:
+//!
+//! - `POST /info`: the info requests this workspace makes (`userFills`,
+//!   `userFillsByTime`, `clearinghouseState`, `spotClearinghouseState`,
+//!   `portfolio`, `userNonFundingLedgerUpdates`), paginated like the real
+//!   API (see [`MockHyperliquid::with_fills`]).
+//! - `GET /ws`: `userFills` subscriptions that play a scripted sequence of
+//!   [`WsEvent`]s, including disconnects.
+//! - Any other `GET`: static files, standing in for the stats-data server
+//!   builder fill archives are downloaded from.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use hl_mock_server::{sample_fill, MockHyperliquid, WsEvent};
+//!
+//! # async fn example() -> std::io::Result<()> {
+//! let user = "0x0000000000000000000000000000000000000001";
+//! let server = MockHyperliquid::new()
+//!     .with_fills(user, vec![sample_fill(1704067200000, 1)])
+//!     .with_ws_script(
+//!         user,
+//!         vec![
+//!             WsEvent::Fills(vec![sample_fill(1704067201000, 2)]),
+//!             WsEvent::Disconnect,
+//!         ],
+//!     )
+//!     .with_file("/Mainnet/builder_fills/0xabc/20260110.csv.lz4", Vec::new())
+//!     .start()
+//!     .await?;
+//!
+//! // Point clients at the server, e.g. `Endpoints::custom(server.url())`
+//! println!("{} {}", server.url(), server.ws_url());
+//! # Ok(())
+//! # }
+//! ```
+
+mod files;
+mod info;
+mod ws;
+
+pub use info::{FILLS_PAGE_SIZE, LEDGER_PAGE_SIZE, MAX_RETAINED_FILLS};
+pub use ws::WsEvent;
+
+// Re-exported so tests can build fixtures without depending on hypersdk.
+pub use hypersdk::hypercore::types::{Fill, Side};
+
+use axum::routing::{get, post};
+use axum::Router;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use url::Url;
+
+/// Everything the server answers with, keyed by lowercase user address.
+#[derive(Debug, Default)]
+struct MockState {
+    /// Fills per user, most recent first.
+    fills: HashMap<String, Vec<Fill>>,
+    clearinghouse_states: HashMap<String, Value>,
+    spot_balances: HashMap<String, Value>,
+    portfolios: HashMap<String, Value>,
+    /// Ledger updates per user, oldest first.
+    ledger_updates: HashMap<String, Vec<Value>>,
+    /// Static files by request path.
+    files: HashMap<String, Vec<u8>>,
+    /// Remaining WebSocket events per user.
+    ws_scripts: HashMap<String, VecDeque<WsEvent>>,
+    /// Bodies of every `/info` request, in arrival order.
+    info_requests: Vec<Value>,
+    /// WebSocket connections accepted so far.
+    ws_connections: usize,
+    /// Whether `/info` answers `503`, like an exchange incident.
+    unavailable: bool,
+}
+
+type SharedState = Arc<Mutex<MockState>>;
+
+fn lock(state: &SharedState) -> MutexGuard<'_, MockState> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Builder for a mock Hyperliquid server.
+///
+/// Users are matched case-insensitively. Users without configured data get
+/// what the real API returns for a fresh account: no fills, an empty
+/// clearinghouse state and no balances.
+#[derive(Debug, Default)]
+pub struct MockHyperliquid {
+    state: MockState,
+}
+
+impl MockHyperliquid {
+    /// Create a server with no data.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add fills for `user`, in any order.
+    ///
+    /// `userFills` returns the most recent [`FILLS_PAGE_SIZE`] fills.
+    /// `userFillsByTime` returns up to [`FILLS_PAGE_SIZE`] fills in
+    /// `[startTime, endTime]`, most recent first, so clients page backwards
+    /// by moving `endTime` before the oldest fill received. Like the real
+    /// API, only a user's [`MAX_RETAINED_FILLS`] most recent fills are
+    /// served at all.
+    pub fn with_fills(mut self, user: &str, fills: Vec<Fill>) -> Self {
+        let entry = self.state.fills.entry(user.to_lowercase()).or_default();
+        entry.extend(fills);
+        entry.sort_by_key(|f| std::cmp::Reverse(f.time));
+        self
+    }
+
+    /// Set the `clearinghouseState` response for `user`, as API JSON.
+    pub fn with_clearinghouse_state(mut self, user: &str, state: Value) -> Self {
+        self.state
+            .clearinghouse_states
+            .insert(user.to_lowercase(), state);
+        self
+    }
+
+    /// Set the spot balances (the `balances` array of
+    /// `spotClearinghouseState`) for `user`, as API JSON.
+    pub fn with_spot_balances(mut self, user: &str, balances: Value) -> Self {
+        self.state
+            .spot_balances
+            .insert(user.to_lowercase(), balances);
+        self
+    }
+
+    /// Set the `portfolio` response for `user`, as API JSON.
+    pub fn with_portfolio(mut self, user: &str, portfolio: Value) -> Self {
+        self.state.portfolios.insert(user.to_lowercase(), portfolio);
+        self
+    }
+
+    /// Add non-funding ledger updates for `user`.
+    ///
+    /// Each update must serialize to an object with a millisecond `time`.
+    /// Responses hold up to [`LEDGER_PAGE_SIZE`] updates from `startTime`
+    /// onwards, oldest first.
+    pub fn with_ledger_updates<T: Serialize>(mut self, user: &str, updates: &[T]) -> Self {
+        let entry = self
+            .state
+            .ledger_updates
+            .entry(user.to_lowercase())
+            .or_default();
+        entry.extend(
+            updates
+                .iter()
+                .map(|u| serde_json::to_value(u).expect("ledger updates serialize to JSON")),
+        );
+        entry.sort_by_key(info::update_time);
+        self
+    }
+
+    /// Serve `contents` for `GET path`, e.g.
+    /// `/Mainnet/builder_fills/<builder>/<YYYYMMDD>.csv.lz4`.
+    ///
+    /// Unknown paths answer `403 Forbidden`, like the stats-data bucket.
+    pub fn with_file(mut self, path: &str, contents: impl Into<Vec<u8>>) -> Self {
+        self.state.files.insert(path.to_string(), contents.into());
+        self
+    }
+
+    /// Script what a `userFills` subscription for `user` receives.
+    ///
+    /// Events play in order once the subscription arrives. A
+    /// [`WsEvent::Disconnect`] drops the connection, and the remaining
+    /// events play when the client reconnects and subscribes again.
+    pub fn with_ws_script(mut self, user: &str, events: Vec<WsEvent>) -> Self {
+        self.state
+            .ws_scripts
+            .entry(user.to_lowercase())
+            .or_default()
+            .extend(events);
+        self
+    }
+
+    /// Start serving on a free local port.
+    pub async fn start(self) -> std::io::Result<MockServer> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(self.state));
+
+        let router = Router::new()
+            .route("/info", post(info::handle))
+            .route("/ws", get(ws::handle))
+            .fallback(files::handle)
+            .with_state(state.clone());
+        let task = tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router).await {
+                tracing::warn!("Mock Hyperliquid server stopped: {}", e);
+            }
+        });
+
+        Ok(MockServer { addr, state, task })
+    }
+}
+
+/// A running mock server. Stops accepting connections when dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    state: SharedState,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Address the server listens on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Base URL serving `/info`, `/ws` and static files.
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{}", self.addr)).expect("valid URL")
+    }
+
+    /// URL of the WebSocket endpoint.
+    pub fn ws_url(&self) -> Url {
+        Url::parse(&format!("ws://{}/ws", self.addr)).expect("valid URL")
+    }
+
+    /// Bodies of every `/info` request received so far.
+    pub fn info_requests(&self) -> Vec<Value> {
+        lock(&self.state).info_requests.clone()
+    }
+
+    /// Number of `/info` requests of the given `type` received so far.
+    pub fn info_request_count(&self, request_type: &str) -> usize {
+        lock(&self.state)
+            .info_requests
+            .iter()
+            .filter(|r| r["type"] == request_type)
+            .count()
+    }
+
+    /// Number of WebSocket connections accepted so far.
+    pub fn ws_connections(&self) -> usize {
+        lock(&self.state).ws_connections
+    }
+
+    /// Add fills for `user` while the server is running, as if they had
+    /// just traded. Later `/info` requests include them.
+    pub fn add_fills(&self, user: &str, fills: Vec<Fill>) {
+        let mut state = lock(&self.state);
+        let entry = state.fills.entry(user.to_lowercase()).or_default();
+        entry.extend(fills);
+        entry.sort_by_key(|f| std::cmp::Reverse(f.time));
+    }
+
+    /// Answer every `/info` request with `503 Service Unavailable` until
+    /// called again with `false`. Requests are still logged.
+    pub fn set_unavailable(&self, unavailable: bool) {
+        lock(&self.state).unavailable = unavailable;
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// A plausible BTC fill at `time_ms` with trade ID `tid`.
+///
+/// Fixtures that care about prices or sides should adjust the fields.
+pub fn sample_fill(time_ms: u64, tid: u64) -> Fill {
+    serde_json::from_value(serde_json::json!({
+        "coin": "BTC",
+        "px": "50000.0",
+        "sz": "0.01",
+        "side": "B",
+        "time": time_ms,
+        "startPosition": "0.0",
+        "dir": "Open Long",
+        "closedPnl": "0.0",
+        "hash": format!("0x{:064x}", tid),
+        "oid": tid,
+        "crossed": true,
+        "fee": "0.2",
+        "tid": tid,
+        "feeToken": "USDC",
+    }))
+    .expect("valid fill")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: &str = "0x00000000000000000000000000000000000000A1";
+
+    #[tokio::test]
+    async fn test_info_requests_are_logged() {
+        let server = MockHyperliquid::new()
+            .with_fills(USER, vec![sample_fill(1_000, 1), sample_fill(2_000, 2)])
+            .start()
+            .await
+            .unwrap();
+
+        let fills: Vec<Fill> = reqwest::Client::new()
+            .post(server.url().join("/info").unwrap())
+            .json(&serde_json::json!({ "type": "userFills", "user": USER.to_lowercase() }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+        let tids: Vec<u64> = fills.iter().map(|f| f.tid).collect();
+        assert_eq!(tids, vec![2, 1]);
+        assert_eq!(server.info_request_count("userFills"), 1);
+        assert_eq!(server.info_requests()[0]["user"], USER.to_lowercase());
+    }
+
+    #[tokio::test]
+    async fn test_fills_added_while_running() {
+        let server = MockHyperliquid::new()
+            .with_fills(USER, vec![sample_fill(1_000, 1)])
+            .start()
+            .await
+            .unwrap();
+        server.add_fills(USER, vec![sample_fill(3_000, 3), sample_fill(2_000, 2)]);
+
+        let fills: Vec<Fill> = reqwest::Client::new()
+            .post(server.url().join("/info").unwrap())
+            .json(&serde_json::json!({ "type": "userFills", "user": USER.to_lowercase() }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+        let tids: Vec<u64> = fills.iter().map(|f| f.tid).collect();
+        assert_eq!(tids, vec![3, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_request_is_rejected() {
+        let server = MockHyperliquid::new().start().await.unwrap();
+
+        let response = reqwest::Client::new()
+            .post(server.url().join("/info").unwrap())
+            .json(&serde_json::json!({ "type": "allMids" }))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_unavailable() {
+        let server = MockHyperliquid::new().start().await.unwrap();
+        let request = || {
+            reqwest::Client::new()
+                .post(server.url().join("/info").unwrap())
+                .json(&serde_json::json!({ "type": "userFills", "user": USER }))
+                .send()
+        };
+
+        server.set_unavailable(true);
+        let down = request().await.unwrap();
+        assert_eq!(down.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+
+        server.set_unavailable(false);
+        assert_eq!(request().await.unwrap().status(), reqwest::StatusCode::OK);
+        assert_eq!(server.info_request_count("userFills"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_static_files() {
+        let server = MockHyperliquid::new()
+            .with_file(
+                "/Mainnet/builder_fills/0xabc/20260110.csv.lz4",
+                b"data".to_vec(),
+            )
+            .start()
+            .await
+            .unwrap();
+
+        let url = server.url();
+        let found = reqwest::get(
+            url.join("/Mainnet/builder_fills/0xabc/20260110.csv.lz4")
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(found.status(), reqwest::StatusCode::OK);
+        assert_eq!(found.bytes().await.unwrap().as_ref(), b"data");
+
+        let missing = reqwest::get(
+            url.join("/Mainnet/builder_fills/0xabc/20260111.csv.lz4")
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(missing.status(), reqwest::StatusCode::FORBIDDEN);
+    }
+}
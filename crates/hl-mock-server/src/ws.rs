@@ -0,0 +1,102 @@
+//! The `GET /ws` endpoint.
+//!
+//! Speaks the subset of the Hyperliquid WebSocket protocol the fill
+//! collector uses: `subscribe` / `unsubscribe` (acknowledged with a
+//! `subscriptionResponse`), `ping` (answered with `pong`) and `userFills`
+//! messages: like the real API, a snapshot of the user's recent fills
+//! (`isSnapshot: true`) right after subscribing, then the subscribed user's
+//! script.
+
+use crate::info::{fills_page, retained_fills};
+use crate::{lock, Fill, SharedState};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// A scripted step of a `userFills` subscription.
+#[derive(Debug, Clone)]
+pub enum WsEvent {
+    /// Send a `userFills` message with these fills.
+    Fills(Vec<Fill>),
+    /// Wait before the next event.
+    Delay(Duration),
+    /// Drop the connection without a close frame, like a network failure.
+    Disconnect,
+}
+
+pub(crate) async fn handle(
+    State(state): State<SharedState>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    lock(&state).ws_connections += 1;
+    upgrade.on_upgrade(move |socket| session(socket, state))
+}
+
+async fn session(mut socket: WebSocket, state: SharedState) {
+    while let Some(Ok(message)) = socket.recv().await {
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let Ok(request) = serde_json::from_str::<Value>(&text) else {
+            continue;
+        };
+
+        let reply = match request["method"].as_str() {
+            Some("ping") => json!({ "channel": "pong" }),
+            Some("subscribe") | Some("unsubscribe") => {
+                json!({ "channel": "subscriptionResponse", "data": request })
+            }
+            _ => continue,
+        };
+        if send(&mut socket, reply).await.is_err() {
+            return;
+        }
+
+        let subscription = &request["subscription"];
+        if request["method"] == "subscribe" && subscription["type"] == "userFills" {
+            let user = subscription["user"].as_str().unwrap_or_default();
+            let snapshot = fills_page(retained_fills(&lock(&state), &user.to_lowercase()), 0, None);
+            let message = json!({
+                "channel": "userFills",
+                "data": { "isSnapshot": true, "user": user, "fills": snapshot },
+            });
+            if send(&mut socket, message).await.is_err() {
+                return;
+            }
+            if !play_script(&mut socket, &state, user).await {
+                return;
+            }
+        }
+    }
+}
+
+/// Play `user`'s remaining script. Returns false once the connection is gone.
+async fn play_script(socket: &mut WebSocket, state: &SharedState, user: &str) -> bool {
+    loop {
+        let next = lock(state)
+            .ws_scripts
+            .get_mut(&user.to_lowercase())
+            .and_then(|script| script.pop_front());
+
+        match next {
+            None => return true,
+            Some(WsEvent::Fills(fills)) => {
+                let message = json!({
+                    "channel": "userFills",
+                    "data": { "user": user, "fills": fills },
+                });
+                if send(socket, message).await.is_err() {
+                    return false;
+                }
+            }
+            Some(WsEvent::Delay(delay)) => tokio::time::sleep(delay).await,
+            Some(WsEvent::Disconnect) => return false,
+        }
+    }
+}
+
+async fn send(socket: &mut WebSocket, message: Value) -> Result<(), axum::Error> {
+    socket.send(Message::Text(message.to_string())).await
+}
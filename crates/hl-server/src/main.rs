@@ -2,9 +2,16 @@
 //!
 //! This binary wires together all crates and starts the HTTP server.
 
-use hl_api::{create_router, AppState, CompetitionConfig};
-use hl_indexer::{FillSource, Indexer, IndexerConfig, Network};
+use hl_api::{
+    create_router, spawn_fill_forwarder, spawn_leaderboard_refresher, AppState, CompetitionConfig,
+    RefresherConfig,
+};
+use hl_indexer::{
+    ArchiveCache, ArchiveImporter, BreakerConfig, CacheTtls, Endpoints, FillSource, Indexer,
+    IndexerConfig, Network, Recording,
+};
 use std::sync::Arc;
+use std::time::Duration;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 /// Default port for the server.
@@ -42,6 +49,7 @@ async fn main() -> anyhow::Result<()> {
         .as_str()
     {
         "websocket" | "ws" => FillSource::WebSocket,
+        "builder_archive" | "archive" => FillSource::BuilderArchive,
         _ => FillSource::Api,
     };
 
@@ -62,7 +70,7 @@ async fn main() -> anyhow::Result<()> {
             "Competition configured with {} users",
             competition_config.user_count()
         );
-        if let Some(ref builder) = competition_config.target_builder {
+        for builder in &competition_config.target_builders {
             tracing::info!("Target builder: {}", builder);
         }
         if competition_config.builder_only {
@@ -72,18 +80,154 @@ async fn main() -> anyhow::Result<()> {
         tracing::info!("Competition not configured (set COMPETITION_USERS to enable leaderboard)");
     }
 
+    // Endpoints default to the network's public URLs; a file or env vars
+    // can point them at a mock server or private node
+    let mut endpoints = Endpoints::from_env(network)?;
+    if std::env::var("HL_STATS_URL").is_err() {
+        if let Ok(url) = std::env::var("BUILDER_STATS_URL") {
+            tracing::warn!("BUILDER_STATS_URL is deprecated, use HL_STATS_URL");
+            endpoints = endpoints.with_stats_url(url.parse()?);
+        }
+    }
+    tracing::info!(
+        "Endpoints: info {}, exchange {}, ws {}, stats {}",
+        endpoints.info_url,
+        endpoints.exchange_url,
+        endpoints.ws_url,
+        endpoints.stats_url
+    );
+
     // Create indexer with configured fill source
-    let config = match network {
+    let mut config = match network {
         Network::Mainnet => IndexerConfig::mainnet(),
         Network::Testnet => IndexerConfig::testnet(),
     }
+    .with_endpoints(endpoints)
     .with_fill_source(fill_source);
 
+    // Capture API traffic for later debugging, or serve a capture offline
+    match (
+        std::env::var("HL_RECORD_DIR"),
+        std::env::var("HL_REPLAY_DIR"),
+    ) {
+        (Ok(_), Ok(_)) => anyhow::bail!("HL_RECORD_DIR and HL_REPLAY_DIR are mutually exclusive"),
+        (Ok(dir), Err(_)) => {
+            tracing::info!("Recording API calls to {}", dir);
+            config = config.with_recording(Recording::Record(dir.into()));
+        }
+        (Err(_), Ok(dir)) => {
+            tracing::info!("Replaying API calls from {}", dir);
+            config = config.with_recording(Recording::Replay(dir.into()));
+        }
+        (Err(_), Err(_)) => {}
+    }
+
+    // Reuse API responses across leaderboard refreshes and API requests
+    let cache_enabled = std::env::var("HL_CACHE")
+        .map(|v| v.to_lowercase() != "false")
+        .unwrap_or(true);
+    if cache_enabled {
+        let mut ttls = CacheTtls::default();
+        if let Some(secs) = std::env::var("HL_CACHE_FILLS_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+        {
+            ttls.user_fills = Duration::from_secs(secs);
+        }
+        tracing::info!(
+            "API response cache: fills TTL {}s",
+            ttls.user_fills.as_secs()
+        );
+        config = config.with_cache(ttls);
+    }
+
+    // Stop calling the API while it keeps failing
+    let mut breaker = BreakerConfig::default();
+    if let Some(failures) = std::env::var("HL_BREAKER_FAILURES")
+        .ok()
+        .and_then(|s| s.parse().ok())
+    {
+        breaker.failure_threshold = failures;
+    }
+    if let Some(secs) = std::env::var("HL_BREAKER_COOLDOWN_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+    {
+        breaker.cooldown = Duration::from_secs(secs);
+    }
+    if breaker.failure_threshold > 0 {
+        tracing::info!(
+            "Circuit breaker: open after {} failures for {}s",
+            breaker.failure_threshold,
+            breaker.cooldown.as_secs()
+        );
+    }
+    config = config.with_breaker(breaker);
+
+    // Builder archives are read for the competition's target builders
+    if fill_source == FillSource::BuilderArchive {
+        if competition_config.target_builders.is_empty() {
+            anyhow::bail!("FILL_SOURCE=builder_archive requires TARGET_BUILDER");
+        }
+        config = config.with_builders(&competition_config.target_builders);
+    }
+
+    // Optional on-disk cache for builder fill archives
+    if let Ok(dir) = std::env::var("BUILDER_CACHE_DIR") {
+        // Imported archives are filed under the endpoint clients will use
+        let mut cache = ArchiveCache::new(&dir).with_endpoint(&config.builder_endpoint());
+        if let Some(secs) = std::env::var("BUILDER_CACHE_TODAY_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+        {
+            cache = cache.with_today_ttl(Duration::from_secs(secs));
+        }
+        tracing::info!("Builder fill cache: {}", dir);
+
+        // Seed the cache from local files for offline use
+        if let Ok(import_path) = std::env::var("BUILDER_IMPORT_PATH") {
+            let path = import_path.clone();
+            let archives =
+                tokio::task::spawn_blocking(move || ArchiveImporter::new().import(path)).await??;
+            cache.import(&archives).await;
+            tracing::info!(
+                "Imported {} builder fill archives from {}",
+                archives.len(),
+                import_path
+            );
+        }
+
+        config = config.with_builder_cache(cache);
+    } else if std::env::var("BUILDER_IMPORT_PATH").is_ok() {
+        tracing::warn!("BUILDER_IMPORT_PATH is ignored without BUILDER_CACHE_DIR");
+    }
+
     let indexer = Indexer::new(config);
 
     // Create app state with competition config
     let state = Arc::new(AppState::with_config(indexer, competition_config));
 
+    // Restore leaderboard history persisted by previous runs
+    if let Some(dir) = &state.competition_config.history_dir {
+        let restored = state.leaderboards.load_history().await;
+        tracing::info!(
+            "Restored {} leaderboard history snapshots from {}",
+            restored,
+            dir.display()
+        );
+    }
+
+    // Keep leaderboard snapshots fresh in the background
+    let refresher_config = RefresherConfig::from_env();
+    tracing::info!(
+        "Leaderboard refresh interval: {}s",
+        refresher_config.interval.as_secs()
+    );
+    spawn_leaderboard_refresher(state.clone(), refresher_config);
+
+    // Push collected fills and PnL to streaming clients
+    spawn_fill_forwarder(state.clone());
+
     // Create router
     let app = create_router(state);
 
@@ -97,6 +241,14 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("  GET /v1/trades      - Fetch user trades");
     tracing::info!("  GET /v1/pnl         - Calculate user PnL");
     tracing::info!("  GET /v1/leaderboard - Get competition leaderboard");
+    tracing::info!("  GET /v1/competitions/{{id}}/leaderboard/history - Leaderboard history");
+    tracing::info!("  GET /v1/builder/{{address}}/analytics - Builder revenue analytics");
+    tracing::info!("  GET /v1/stream/trades - Stream new trades (SSE)");
+    tracing::info!("  GET /v1/stream/pnl    - Stream PnL updates (SSE)");
+    tracing::info!("  GET /v1/competitions/{{id}}/leaderboard/stream - Leaderboard updates (SSE)");
+    tracing::info!("  GET /v1/export/{{trades,pnl,roundtrips,leaderboard}} - CSV/NDJSON exports");
+    tracing::info!("  GET /openapi.json   - OpenAPI document");
+    tracing::info!("  GET /docs           - API documentation");
 
     axum::serve(listener, app).await?;
 
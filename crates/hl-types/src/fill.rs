@@ -36,6 +36,70 @@ impl Side {
     }
 }
 
+/// What a fill did to the user's position.
+///
+/// Fills carrying the API's `liquidation` details took part in a
+/// liquidation, on either side, and so do fills labelled
+/// "Auto-Deleveraging". Other fills are classified from their direction
+/// label: Hyperliquid labels fills "Open Long", "Close Short",
+/// "Long > Short" and so on. Spot and settlement fills, and fills without
+/// a label, are [`FillDirection::Other`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FillDirection {
+    /// Opened or increased a position.
+    Open,
+    /// Reduced or closed a position voluntarily.
+    Close,
+    /// Closed a position and opened one on the other side.
+    Flip,
+    /// Took part in a liquidation or auto-deleveraging.
+    Liquidation,
+    /// Anything else.
+    Other,
+}
+
+impl FillDirection {
+    /// Classify a fill from its direction label and whether it carries
+    /// liquidation details.
+    pub fn of(label: &str, liquidation: bool) -> Self {
+        if liquidation || label == "Auto-Deleveraging" {
+            FillDirection::Liquidation
+        } else if label.starts_with("Open") {
+            FillDirection::Open
+        } else if label.starts_with("Close") {
+            FillDirection::Close
+        } else if label.contains(" > ") {
+            FillDirection::Flip
+        } else {
+            FillDirection::Other
+        }
+    }
+
+    /// Get the direction as a string (for API parameters and responses).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FillDirection::Open => "open",
+            FillDirection::Close => "close",
+            FillDirection::Flip => "flip",
+            FillDirection::Liquidation => "liquidation",
+            FillDirection::Other => "other",
+        }
+    }
+
+    /// Parse a direction from a string (case-insensitive).
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "open" => Some(FillDirection::Open),
+            "close" => Some(FillDirection::Close),
+            "flip" => Some(FillDirection::Flip),
+            "liquidation" => Some(FillDirection::Liquidation),
+            "other" => Some(FillDirection::Other),
+            _ => None,
+        }
+    }
+}
+
 /// A fill (trade execution) for a user.
 ///
 /// This struct captures all the information about a single trade execution,
@@ -86,6 +150,11 @@ pub struct UserFill {
 
     /// Direction description (e.g., "Open Long", "Close Short").
     pub direction: String,
+
+    /// Address of the user liquidated, if this fill took part in a
+    /// liquidation. Empty for sources without liquidation details.
+    #[serde(default)]
+    pub liquidated_user: Option<String>,
 }
 
 impl UserFill {
@@ -108,6 +177,11 @@ impl UserFill {
     pub fn net_pnl(&self) -> Decimal {
         self.closed_pnl - self.fee
     }
+
+    /// Classify what the fill did to the user's position.
+    pub fn fill_direction(&self) -> FillDirection {
+        FillDirection::of(&self.direction, self.liquidated_user.is_some())
+    }
 }
 
 #[cfg(test)]
@@ -128,6 +202,7 @@ mod tests {
             order_id: 67890,
             crossed: true,
             direction: "Open Long".to_string(),
+            liquidated_user: None,
         }
     }
 
@@ -154,6 +229,31 @@ mod tests {
         assert_eq!(fill.net_pnl(), dec!(95.8));
     }
 
+    #[test]
+    fn test_fill_direction() {
+        let cases = [
+            ("Open Long", false, FillDirection::Open),
+            ("Close Short", false, FillDirection::Close),
+            ("Long > Short", false, FillDirection::Flip),
+            ("Close Long", true, FillDirection::Liquidation),
+            ("Auto-Deleveraging", false, FillDirection::Liquidation),
+            ("Buy", false, FillDirection::Other),
+            ("", false, FillDirection::Other),
+        ];
+        for (label, liquidation, expected) in cases {
+            assert_eq!(FillDirection::of(label, liquidation), expected, "{label}");
+        }
+        let mut fill = sample_fill();
+        assert_eq!(fill.fill_direction(), FillDirection::Open);
+        fill.liquidated_user = Some("0xabc".to_string());
+        assert_eq!(fill.fill_direction(), FillDirection::Liquidation);
+        assert_eq!(
+            FillDirection::parse("LIQUIDATION"),
+            Some(FillDirection::Liquidation)
+        );
+        assert_eq!(FillDirection::parse("long"), None);
+    }
+
     #[test]
     fn test_timestamp() {
         let fill = sample_fill();
@@ -0,0 +1,378 @@
+//! Synthetic trading histories for tests and benchmarks.
+//!
+//! [`FillGenerator`] produces seeded, reproducible [`UserFill`] streams that
+//! look like a real account's: several assets trading around a random-walk
+//! price, positions that are opened, added to, partially closed, closed and
+//! flipped, maker rebates next to taker fees, and fills delivered slightly
+//! out of order. `closed_pnl` is computed against the average entry price
+//! the way Hyperliquid reports it, so the fills are internally consistent.
+//!
+//! Only available with the `generator` feature.
+//!
+//! # Example
+//!
+//! ```rust
+//! use hl_types::{Asset, FillGenerator, UserPnL};
+//!
+//! let mut generator = FillGenerator::new(7).with_assets([Asset::Btc, Asset::KPepe]);
+//! let fills = generator.generate(500);
+//!
+//! let mut pnl = UserPnL::new("0x1234...".to_string());
+//! pnl.add_fills(fills);
+//! assert_eq!(pnl.calculate_pnl(None).fill_count, 500);
+//! ```
+
+use crate::{Asset, Side, UserFill};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::Decimal;
+
+/// 2026-01-01 00:00:00 UTC in milliseconds.
+const DEFAULT_START_MS: u64 = 1_767_225_600_000;
+
+/// Taker fee rate (0.045%).
+const TAKER_FEE_BPS: i64 = 45;
+
+/// Maker fee rate (0.015%).
+const MAKER_FEE_BPS: i64 = 15;
+
+/// Maker rebate rate (0.002%), paid out as a negative fee.
+const MAKER_REBATE_BPS: i64 = 2;
+
+/// Decimal places for generated fees and realized PnL.
+const USD_DP: u32 = 6;
+
+/// Price and size conventions for one asset.
+#[derive(Debug, Clone)]
+struct Market {
+    asset: Asset,
+    price: Decimal,
+    price_dp: u32,
+    size_dp: u32,
+    /// Signed position size.
+    position: Decimal,
+    /// Average entry price of the open position.
+    entry_price: Decimal,
+}
+
+impl Market {
+    fn new(asset: Asset) -> Self {
+        // Kilo assets are quoted per 1000 tokens but are still far below $1
+        let (price, price_dp, size_dp) = match asset {
+            Asset::Btc => (Decimal::new(60_000, 0), 1, 5),
+            Asset::Eth => (Decimal::new(3_000, 0), 2, 4),
+            Asset::Sol => (Decimal::new(150, 0), 3, 2),
+            Asset::Hype => (Decimal::new(25, 0), 4, 2),
+            _ if asset.is_kilo_asset() => (Decimal::new(12, 3), 7, 0),
+            _ => (Decimal::new(10, 0), 4, 1),
+        };
+        Self {
+            asset,
+            price,
+            price_dp,
+            size_dp,
+            position: Decimal::ZERO,
+            entry_price: Decimal::ZERO,
+        }
+    }
+
+    /// Size worth roughly `notional` USD, at least one lot.
+    fn size_for(&self, notional: Decimal) -> Decimal {
+        let lot = Decimal::new(1, self.size_dp);
+        (notional / self.price).round_dp(self.size_dp).max(lot)
+    }
+}
+
+/// Seeded generator of realistic [`UserFill`] histories.
+///
+/// Successive calls to [`generate`](Self::generate) continue the same
+/// history: positions, prices, timestamps and trade IDs carry over.
+/// Timestamps and trade IDs are unique across the whole history.
+#[derive(Debug, Clone)]
+pub struct FillGenerator {
+    rng: StdRng,
+    markets: Vec<Market>,
+    next_time_ms: u64,
+    next_trade_id: u64,
+    next_order_id: u64,
+    out_of_order: bool,
+}
+
+impl FillGenerator {
+    /// Create a generator trading BTC, ETH, SOL, HYPE, kPEPE and kBONK.
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let next_trade_id = rng.random_range(1..1_000_000_000);
+        Self {
+            rng,
+            markets: Vec::new(),
+            next_time_ms: DEFAULT_START_MS,
+            next_trade_id,
+            next_order_id: next_trade_id * 10,
+            out_of_order: true,
+        }
+        .with_assets([
+            Asset::Btc,
+            Asset::Eth,
+            Asset::Sol,
+            Asset::Hype,
+            Asset::KPepe,
+            Asset::KBonk,
+        ])
+    }
+
+    /// Set the assets to trade, replacing the defaults.
+    ///
+    /// Assets without known price conventions trade around $10, or around
+    /// $0.012 per 1000 tokens for kilo assets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `assets` is empty.
+    pub fn with_assets(mut self, assets: impl IntoIterator<Item = Asset>) -> Self {
+        self.markets = assets.into_iter().map(Market::new).collect();
+        assert!(!self.markets.is_empty(), "at least one asset is required");
+        self
+    }
+
+    /// Set the timestamp of the first fill.
+    pub fn with_start_ms(mut self, start_ms: u64) -> Self {
+        self.next_time_ms = start_ms;
+        self
+    }
+
+    /// Whether to deliver fills slightly out of timestamp order (default
+    /// true), as merged API pages and WebSocket snapshots can.
+    pub fn with_out_of_order(mut self, out_of_order: bool) -> Self {
+        self.out_of_order = out_of_order;
+        self
+    }
+
+    /// Generate the next `count` fills of the history.
+    pub fn generate(&mut self, count: usize) -> Vec<UserFill> {
+        let mut fills: Vec<UserFill> = (0..count).map(|_| self.next_fill()).collect();
+
+        if self.out_of_order {
+            // Displace a fifth of the fills by a few positions
+            for i in 0..fills.len() {
+                if self.rng.random_bool(0.2) {
+                    let j = (i + self.rng.random_range(1..=5)).min(fills.len() - 1);
+                    fills.swap(i, j);
+                }
+            }
+        }
+
+        fills
+    }
+
+    /// Net position in `asset` after the fills generated so far.
+    pub fn position(&self, asset: &Asset) -> Decimal {
+        self.markets
+            .iter()
+            .find(|m| m.asset == *asset)
+            .map_or(Decimal::ZERO, |m| m.position)
+    }
+
+    fn next_fill(&mut self) -> UserFill {
+        let index = self.rng.random_range(0..self.markets.len());
+        let timestamp_ms = self.next_time_ms;
+        self.next_time_ms += self.rng.random_range(1..=60_000);
+        let trade_id = self.next_trade_id;
+        self.next_trade_id += 1;
+        // Roughly one order in four fills in several pieces
+        if self.rng.random_bool(0.75) {
+            self.next_order_id += 1;
+        }
+        let order_id = self.next_order_id;
+
+        let move_bps = self.rng.random_range(-200..=200);
+        let notional = Decimal::from(self.rng.random_range(50..=5_000));
+        let roll: u32 = self.rng.random_range(0..100);
+        let crossed = self.rng.random_bool(0.7);
+        let rebate = !crossed && self.rng.random_bool(0.5);
+        let fraction_pct = self.rng.random_range(10..=90);
+        let go_long = self.rng.random_bool(0.5);
+
+        let market = &mut self.markets[index];
+        let step = Decimal::from(10_000 + move_bps) / Decimal::from(10_000);
+        let tick = Decimal::new(1, market.price_dp);
+        market.price = (market.price * step).round_dp(market.price_dp).max(tick);
+        let price = market.price;
+        let position = market.position;
+        let held = position.abs();
+
+        // Pick the trade: open when flat, otherwise add 30%, partially close
+        // 30%, close 20% or flip 20% of the time
+        let (side, size) = if position.is_zero() {
+            let side = if go_long { Side::Buy } else { Side::Sell };
+            (side, market.size_for(notional))
+        } else {
+            let closing = if position > Decimal::ZERO {
+                Side::Sell
+            } else {
+                Side::Buy
+            };
+            let opening = if closing == Side::Sell {
+                Side::Buy
+            } else {
+                Side::Sell
+            };
+            let partial = (held * Decimal::from(fraction_pct) / Decimal::ONE_HUNDRED)
+                .round_dp(market.size_dp);
+            match roll {
+                0..30 => (opening, market.size_for(notional)),
+                30..60 if !partial.is_zero() && partial < held => (closing, partial),
+                30..80 => (closing, held),
+                _ => (closing, held + market.size_for(notional)),
+            }
+        };
+
+        let new_position = position + size * side.sign();
+        let flipped = (position > Decimal::ZERO) != (new_position > Decimal::ZERO);
+        let closed = if new_position.is_zero() || flipped {
+            held
+        } else if new_position.abs() < held {
+            size
+        } else {
+            Decimal::ZERO
+        };
+        let mut closed_pnl = ((price - market.entry_price) * closed).round_dp(USD_DP);
+        if position < Decimal::ZERO {
+            closed_pnl = -closed_pnl;
+        }
+
+        market.entry_price = if new_position.is_zero() {
+            Decimal::ZERO
+        } else if closed == held {
+            // Opened from flat or flipped through zero
+            price
+        } else if closed.is_zero() {
+            ((market.entry_price * held + price * size) / (held + size)).round_dp(8)
+        } else {
+            market.entry_price
+        };
+        market.position = new_position;
+
+        let direction = direction(position, new_position);
+        let fill_notional = price * size;
+        let fee_bps = if crossed {
+            TAKER_FEE_BPS
+        } else if rebate {
+            -MAKER_REBATE_BPS
+        } else {
+            MAKER_FEE_BPS
+        };
+        let fee = (fill_notional * Decimal::new(fee_bps, 4)).round_dp(USD_DP);
+
+        UserFill {
+            asset: market.asset.clone(),
+            timestamp_ms,
+            price,
+            size,
+            side,
+            fee,
+            closed_pnl,
+            trade_id,
+            order_id,
+            crossed,
+            direction,
+            liquidated_user: None,
+        }
+    }
+}
+
+/// Hyperliquid's direction label for a fill taking `before` to `after`.
+fn direction(before: Decimal, after: Decimal) -> String {
+    let zero = Decimal::ZERO;
+    let label = if before > zero && after < zero {
+        "Long > Short"
+    } else if before < zero && after > zero {
+        "Short > Long"
+    } else if before > zero && after < before {
+        "Close Long"
+    } else if before < zero && after > before {
+        "Close Short"
+    } else if after > before {
+        "Open Long"
+    } else {
+        "Open Short"
+    };
+    label.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn test_same_seed_same_history() {
+        let a = FillGenerator::new(42).generate(200);
+        let b = FillGenerator::new(42).generate(200);
+        let c = FillGenerator::new(43).generate(200);
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_positions_match_fills() {
+        let mut generator = FillGenerator::new(1);
+        let fills = generator.generate(1_000);
+
+        let mut positions: HashMap<Asset, Decimal> = HashMap::new();
+        for fill in &fills {
+            *positions.entry(fill.asset.clone()).or_default() += fill.signed_size();
+        }
+        for (asset, position) in positions {
+            assert_eq!(generator.position(&asset), position, "{}", asset);
+        }
+    }
+
+    #[test]
+    fn test_history_covers_edge_cases() {
+        let fills = FillGenerator::new(3).generate(2_000);
+        let directions: HashSet<&str> = fills.iter().map(|f| f.direction.as_str()).collect();
+
+        for expected in [
+            "Open Long",
+            "Open Short",
+            "Close Long",
+            "Close Short",
+            "Long > Short",
+            "Short > Long",
+        ] {
+            assert!(directions.contains(expected), "no {:?} fill", expected);
+        }
+        assert!(fills.iter().any(|f| f.fee < Decimal::ZERO));
+        assert!(fills.iter().any(|f| f.closed_pnl < Decimal::ZERO));
+        assert!(fills
+            .iter()
+            .any(|f| f.asset.is_kilo_asset() && f.price < Decimal::ONE));
+        assert!(fills
+            .windows(2)
+            .any(|w| w[0].timestamp_ms > w[1].timestamp_ms));
+    }
+
+    #[test]
+    fn test_timestamps_and_trade_ids_unique() {
+        let mut generator = FillGenerator::new(9).with_start_ms(1_000);
+        let mut fills = generator.generate(500);
+        fills.extend(generator.generate(500));
+
+        let times: HashSet<u64> = fills.iter().map(|f| f.timestamp_ms).collect();
+        let ids: HashSet<u64> = fills.iter().map(|f| f.trade_id).collect();
+        assert_eq!(times.len(), 1_000);
+        assert_eq!(ids.len(), 1_000);
+        assert!(fills.iter().all(|f| f.timestamp_ms >= 1_000));
+    }
+
+    #[test]
+    fn test_in_order_when_disabled() {
+        let fills = FillGenerator::new(5).with_out_of_order(false).generate(300);
+
+        assert!(fills
+            .windows(2)
+            .all(|w| w[0].timestamp_ms < w[1].timestamp_ms));
+    }
+}
@@ -6,6 +6,9 @@
 //! - [`UserFill`] - A fill (trade execution) with timestamp
 //! - [`UserPnL`] - PnL tracking with fills partitioned by asset
 //!
+//! With the `generator` feature, `FillGenerator` produces seeded synthetic
+//! trading histories for tests.
+//!
 //! # Example
 //!
 //! ```rust
@@ -25,11 +28,15 @@
 mod asset;
 mod error;
 mod fill;
+#[cfg(feature = "generator")]
+mod generator;
 mod pnl;
 mod position;
 
 pub use asset::Asset;
 pub use error::TypeError;
-pub use fill::{Side, UserFill};
+pub use fill::{FillDirection, Side, UserFill};
+#[cfg(feature = "generator")]
+pub use generator::FillGenerator;
 pub use pnl::{AssetPnL, PnLSummary, UserPnL};
 pub use position::Position;
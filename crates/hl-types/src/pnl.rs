@@ -90,6 +90,30 @@ impl AssetPnL {
             last_fill_ms: None,
         }
     }
+
+    /// Add a fill of this asset to the totals.
+    pub fn add_fill(&mut self, fill: &UserFill) {
+        self.realized_pnl += fill.closed_pnl;
+        self.fees += fill.fee;
+        self.fill_count += 1;
+        self.volume += fill.notional_value();
+
+        // Track time range
+        match self.first_fill_ms {
+            None => self.first_fill_ms = Some(fill.timestamp_ms),
+            Some(first) if fill.timestamp_ms < first => {
+                self.first_fill_ms = Some(fill.timestamp_ms)
+            }
+            _ => {}
+        }
+        match self.last_fill_ms {
+            None => self.last_fill_ms = Some(fill.timestamp_ms),
+            Some(last) if fill.timestamp_ms > last => self.last_fill_ms = Some(fill.timestamp_ms),
+            _ => {}
+        }
+
+        self.net_pnl = self.realized_pnl - self.fees;
+    }
 }
 
 /// Comprehensive PnL tracker for a user.
@@ -240,31 +264,9 @@ impl UserPnL {
     /// Calculate PnL for a single asset's fills.
     fn calculate_asset_pnl(&self, asset: &Asset, fills: &[UserFill]) -> AssetPnL {
         let mut pnl = AssetPnL::new(asset.clone());
-
         for fill in fills {
-            pnl.realized_pnl += fill.closed_pnl;
-            pnl.fees += fill.fee;
-            pnl.fill_count += 1;
-            pnl.volume += fill.notional_value();
-
-            // Track time range
-            match pnl.first_fill_ms {
-                None => pnl.first_fill_ms = Some(fill.timestamp_ms),
-                Some(first) if fill.timestamp_ms < first => {
-                    pnl.first_fill_ms = Some(fill.timestamp_ms)
-                }
-                _ => {}
-            }
-            match pnl.last_fill_ms {
-                None => pnl.last_fill_ms = Some(fill.timestamp_ms),
-                Some(last) if fill.timestamp_ms > last => {
-                    pnl.last_fill_ms = Some(fill.timestamp_ms)
-                }
-                _ => {}
-            }
+            pnl.add_fill(fill);
         }
-
-        pnl.net_pnl = pnl.realized_pnl - pnl.fees;
         pnl
     }
 
@@ -347,6 +349,7 @@ mod tests {
             order_id: timestamp_ms,
             crossed: true,
             direction: "Open Long".to_string(),
+            liquidated_user: None,
         }
     }
 